@@ -0,0 +1,26 @@
+//! Shift cyphers, as used by day 4.
+
+use alloc::string::String;
+
+/// Shift a single ascii letter forward by `shift` places, preserving its case.
+///
+/// Other characters are unchanged.
+pub fn shift_char(mut ch: char, shift: u64) -> char {
+    if !ch.is_ascii_alphabetic() {
+        return ch;
+    }
+    let upper = ch.is_uppercase();
+    ch.make_ascii_lowercase();
+    let ch_idx = ch as u8 - b'a';
+    let shift_idx = ((ch_idx as u64 + shift) % 26) as u8;
+    ch = (shift_idx + b'a') as char;
+    if upper {
+        ch.make_ascii_uppercase();
+    }
+    ch
+}
+
+/// En/decrypt a string using a shift cypher
+pub fn shift_str(encrypted: &str, shift: u64) -> String {
+    encrypted.chars().map(|ch| shift_char(ch, shift)).collect()
+}
@@ -0,0 +1,131 @@
+//! The modified dragon curve and its checksum, as used by day 16.
+
+use alloc::vec::Vec;
+
+/// Perform one step of the dragon curve: `a` becomes `a 0 reverse(!a)`.
+pub fn dragon(data: &[bool]) -> Vec<bool> {
+    let mut next = Vec::with_capacity(2 * data.len() + 1);
+    next.extend_from_slice(data);
+    next.push(false);
+    next.extend(data.iter().rev().map(|bit| !*bit));
+    next
+}
+
+/// Repeat the dragon curve until there are at least `want_bits` bits, then truncate to that length.
+pub fn dragon_fill(data: &[bool], want_bits: usize) -> Vec<bool> {
+    let mut dragon_data = data.to_vec();
+    while dragon_data.len() < want_bits {
+        dragon_data = dragon(&dragon_data);
+    }
+    dragon_data.truncate(want_bits);
+    dragon_data
+}
+
+/// Reduce pairs of bits to a single bit, `1` when they're equal, until the length is odd.
+pub fn checksum(data: &[bool]) -> Vec<bool> {
+    let mut data = data.to_vec();
+    let mut next = Vec::with_capacity(data.len());
+
+    while data.len() & 1 == 0 {
+        next.clear();
+        for pair in data.chunks(2) {
+            next.push(pair[0] == pair[1]);
+        }
+        core::mem::swap(&mut data, &mut next);
+    }
+
+    data
+}
+
+/// The block size reduced to a single bit by `checksum`: the largest power of two dividing `len`.
+pub fn standard_block(len: usize) -> usize {
+    len & len.wrapping_neg()
+}
+
+/// Reduce each block of `block` bits to a single bit, `1` when the block has an even number of
+/// `1`s.
+///
+/// `checksum` is equivalent to this with `block = standard_block(data.len())`: each halving
+/// computes the parity of pairs of parities. Returns `None` unless `block` is non-zero and
+/// divides the length of `data`.
+pub fn checksum_with_block(data: &[bool], block: usize) -> Option<Vec<bool>> {
+    if block == 0 {
+        return None;
+    }
+    let blocks = data.chunks_exact(block);
+    if !blocks.remainder().is_empty() {
+        return None;
+    }
+    Some(
+        blocks
+            .map(|block| block.iter().filter(|bit| **bit).count() & 1 == 0)
+            .collect(),
+    )
+}
+
+/// The `n`th bit (1-based) joining the copies of the original data in a dragon fill.
+///
+/// These follow the regular paperfolding sequence: strip the trailing zeros from `n`, and the bit
+/// is `1` when what remains is `3` mod `4`.
+fn joiner(n: usize) -> bool {
+    let odd = n >> n.trailing_zeros();
+    odd & 3 == 3
+}
+
+/// The same bits as `dragon_fill`, computed one at a time without materializing the fill.
+///
+/// A dragon fill alternates `data` and `reverse(!data)`, with a joiner bit after each copy.
+pub fn dragon_bits(data: &[bool], want_bits: usize) -> impl '_ + Iterator<Item = bool> {
+    let period = data.len() + 1;
+    (0..want_bits).map(move |idx| {
+        let (copy, offset) = (idx / period, idx % period);
+        match data.get(offset) {
+            None => joiner(copy + 1),
+            Some(&bit) if copy & 1 == 0 => bit,
+            Some(_) => !data[data.len() - 1 - offset],
+        }
+    })
+}
+
+/// Computes `checksum` of data supplied one bit at a time, holding only the checksum in memory.
+#[derive(Debug, Clone)]
+pub struct Checksummer {
+    len: usize,
+    block: usize,
+    seen: usize,
+    ones_in_block: usize,
+    checksum: Vec<bool>,
+}
+
+impl Checksummer {
+    /// Prepare to checksum exactly `len` bits.
+    pub fn new(len: usize) -> Checksummer {
+        Checksummer {
+            len,
+            block: standard_block(len),
+            seen: 0,
+            ones_in_block: 0,
+            checksum: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, bit: bool) {
+        self.seen += 1;
+        if self.block <= 1 {
+            // data of odd length is its own checksum; data of zero length must not be pushed at
+            // all, which `finish` reports
+            self.checksum.push(bit);
+            return;
+        }
+        self.ones_in_block += bit as usize;
+        if self.seen % self.block == 0 {
+            self.checksum.push(self.ones_in_block & 1 == 0);
+            self.ones_in_block = 0;
+        }
+    }
+
+    /// The checksum, or `None` if the number of bits pushed was not the length given to `new`.
+    pub fn finish(self) -> Option<Vec<bool>> {
+        (self.seen == self.len).then_some(self.checksum)
+    }
+}
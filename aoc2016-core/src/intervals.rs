@@ -0,0 +1,125 @@
+//! Sets of inclusive `u32` ranges, as used by day 20.
+
+use alloc::vec::Vec;
+use core::ops::{Bound, RangeBounds};
+
+/// Sort inclusive `(low, high)` ranges and merge those which overlap.
+pub fn coalesce(ranges: impl IntoIterator<Item = (u32, u32)>) -> Vec<(u32, u32)> {
+    let mut ranges: Vec<_> = ranges.into_iter().collect();
+    debug_assert!(ranges.iter().all(|(low, high)| low <= high));
+    ranges.sort_unstable();
+
+    let mut coalesced: Vec<(u32, u32)> = Vec::with_capacity(ranges.len());
+    for (next_low, next_high) in ranges {
+        match coalesced.last_mut() {
+            Some((_, prev_high)) if next_low <= *prev_high => {
+                *prev_high = (*prev_high).max(next_high);
+            }
+            _ => coalesced.push((next_low, next_high)),
+        }
+    }
+    coalesced
+}
+
+/// Convert `bounds` to inclusive `(low, high)` form, or `None` if they contain no values.
+fn inclusive_bounds(bounds: impl RangeBounds<u32>) -> Option<(u32, u32)> {
+    let low = match bounds.start_bound() {
+        Bound::Included(v) => *v,
+        Bound::Excluded(v) => v.checked_add(1)?,
+        Bound::Unbounded => 0,
+    };
+    let high = match bounds.end_bound() {
+        Bound::Included(v) => *v,
+        Bound::Excluded(v) => v.checked_sub(1)?,
+        Bound::Unbounded => u32::MAX,
+    };
+    (low <= high).then_some((low, high))
+}
+
+/// Find the lowest value not covered by any of the inclusive `(low, high)` ranges.
+pub fn lowest_uncovered(ranges: impl IntoIterator<Item = (u32, u32)>) -> Option<u32> {
+    lowest_uncovered_in(ranges, ..)
+}
+
+/// Find the lowest value within `bounds` not covered by any of the inclusive `(low, high)` ranges.
+pub fn lowest_uncovered_in(
+    ranges: impl IntoIterator<Item = (u32, u32)>,
+    bounds: impl RangeBounds<u32>,
+) -> Option<u32> {
+    let (low_bound, high_bound) = inclusive_bounds(bounds)?;
+    let mut candidate = low_bound;
+    for (low, high) in coalesce(ranges) {
+        if high < candidate {
+            continue;
+        }
+        if low > candidate {
+            break;
+        }
+        candidate = high.checked_add(1)?;
+    }
+    (candidate <= high_bound).then_some(candidate)
+}
+
+/// Count the values within `bounds` not covered by any of the inclusive `(low, high)` ranges.
+///
+/// The count is a `u64` because the full `u32` space has `2**32` values.
+pub fn count_uncovered_in(
+    ranges: impl IntoIterator<Item = (u32, u32)>,
+    bounds: impl RangeBounds<u32>,
+) -> u64 {
+    let (low_bound, high_bound) = match inclusive_bounds(bounds) {
+        Some(bounds) => bounds,
+        None => return 0,
+    };
+    let mut count = 0;
+    // the lowest value not yet known to be covered
+    let mut next = low_bound as u64;
+    for (low, high) in coalesce(ranges) {
+        if high < low_bound {
+            continue;
+        }
+        if low > high_bound {
+            break;
+        }
+        count += (low as u64).saturating_sub(next);
+        next = next.max(high as u64 + 1);
+    }
+    count + (high_bound as u64 + 1).saturating_sub(next)
+}
+
+/// List the maximal inclusive `(low, high)` runs of values within `bounds` not covered by any
+/// of the inclusive `(low, high)` ranges, in ascending order.
+pub fn uncovered_in(
+    ranges: impl IntoIterator<Item = (u32, u32)>,
+    bounds: impl RangeBounds<u32>,
+) -> Vec<(u32, u32)> {
+    let (low_bound, high_bound) = match inclusive_bounds(bounds) {
+        Some(bounds) => bounds,
+        None => return Vec::new(),
+    };
+    let mut gaps = Vec::new();
+    // the lowest value not yet known to be covered, or `None` once everything is
+    let mut next = Some(low_bound);
+    for (low, high) in coalesce(ranges) {
+        let candidate = match next {
+            Some(candidate) => candidate,
+            None => break,
+        };
+        if high < candidate {
+            continue;
+        }
+        if low > high_bound {
+            break;
+        }
+        if low > candidate {
+            gaps.push((candidate, low - 1));
+        }
+        next = high.checked_add(1);
+    }
+    if let Some(candidate) = next {
+        if candidate <= high_bound {
+            gaps.push((candidate, high_bound));
+        }
+    }
+    gaps
+}
@@ -0,0 +1,140 @@
+//! The Josephus problem and its variant where the elf across the circle is eliminated, as used
+//! by day 19.
+
+use alloc::{boxed::Box, collections::VecDeque, rc::Rc, vec::Vec};
+use core::cell::Cell;
+
+/// Clear the most significant set bit of `n`.
+pub fn clear_leading_one(mut n: u32) -> u32 {
+    if n != 0 {
+        let mask = !(1 << (31 - n.leading_zeros()));
+        n &= mask;
+    }
+    n
+}
+
+/// The survivor of the Josephus problem with `n` players, where each eliminates the next.
+pub fn josephus(n: u32) -> u32 {
+    (clear_leading_one(n) << 1) | 1
+}
+
+/// The survivor when each player eliminates the player across the circle.
+///
+/// This is pretty bad: `O(n**2)`: VecDeque rotation requires `O(n)`
+pub fn josephus_across(n: u32) -> u32 {
+    let mut players: VecDeque<_> = (1..=n).collect();
+    while players.len() > 1 {
+        let shift = players.len() / 2;
+        players.rotate_left(shift);
+        players.pop_front();
+        players.rotate_right(shift - 1);
+    }
+    players[0]
+}
+
+/// The survivor when each player eliminates the player across the circle.
+///
+/// Getting a solution still requires `O(n)`, but that's acceptable, where
+/// the naive implementation isn't.
+pub fn josephus_across_from_iter(n: u32) -> u32 {
+    josephus_across_iter()
+        .nth((n - 1) as usize)
+        .expect("josephus_across_iter never terminates")
+}
+
+pub fn partial_josephus_across_iter(n: u32) -> impl Iterator<Item = u32> {
+    (1..=n).chain((1..=n).map(move |m| 2 * m + n))
+}
+
+/// The survivors of the across-the-circle variant, for `n` in `1..`.
+pub fn josephus_across_iter() -> impl Iterator<Item = u32> {
+    let mut sub_iter: Box<dyn Iterator<Item = u32>> = Box::new(partial_josephus_across_iter(3));
+
+    // this is a bit ugly, but it's forced on us: we're creating two references
+    // to a single `Cell`, which gives us interior mutability. That means that
+    // we can hand one of them to the `from_fn` closure, which can read it, and
+    // another to the `inspect` closure, which can update it.
+    //
+    // We could work around this by implementing Iterator manually on a struct, but
+    // I wanted to do this the lazy way instead.
+    let prev1 = Rc::new(Cell::new(0));
+    let prev2 = prev1.clone();
+
+    [1, 1, 3].iter().copied().chain(
+        core::iter::from_fn(move || {
+            Some(match sub_iter.next() {
+                Some(v) => v,
+                None => {
+                    // set the sub-iterator, but skip its first value; we know that it always
+                    // equals 1
+                    sub_iter = Box::new(partial_josephus_across_iter(prev1.get()).skip(1));
+                    1
+                }
+            })
+        })
+        .inspect(move |v| {
+            prev2.set(*v);
+        }),
+    )
+}
+
+/// Whom each elf takes presents from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variant {
+    /// The next elf to the left, as in part 1.
+    Next,
+    /// The elf across the circle, as in part 2.
+    Across,
+}
+
+/// A single elf taking all of another's presents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elimination {
+    pub thief: u32,
+    pub victim: u32,
+}
+
+/// Simulate the game with `n` elves, returning each elimination in order.
+///
+/// This is `O(n)` for both variants. For `Across`, the circle is split into two halves, with the
+/// current elf at the front of the first; the elf across the circle is then always at the front
+/// of the second.
+pub fn eliminations(n: u32, variant: Variant) -> Vec<Elimination> {
+    let mut out = Vec::with_capacity(n.saturating_sub(1) as usize);
+    match variant {
+        Variant::Next => {
+            let mut circle: VecDeque<_> = (1..=n).collect();
+            while circle.len() > 1 {
+                let thief = circle.pop_front().expect("circle has at least two elves");
+                let victim = circle.pop_front().expect("circle has at least two elves");
+                out.push(Elimination { thief, victim });
+                circle.push_back(thief);
+            }
+        }
+        Variant::Across => {
+            let mut near: VecDeque<_> = (1..=n / 2).collect();
+            let mut far: VecDeque<_> = (n / 2 + 1..=n).collect();
+            while near.len() + far.len() > 1 {
+                // invariant: near holds the first half of the circle, rounded down
+                let thief = near.pop_front().expect("circle has at least two elves");
+                let victim = far.pop_front().expect("far half is never smaller");
+                out.push(Elimination { thief, victim });
+                far.push_back(thief);
+
+                let half = (near.len() + far.len()) / 2;
+                while near.len() < half {
+                    near.push_back(far.pop_front().expect("far half is never smaller"));
+                }
+            }
+        }
+    }
+    out
+}
+
+/// The elves eliminated by the game with `n` elves, in order.
+pub fn elimination_order(n: u32, variant: Variant) -> Vec<u32> {
+    eliminations(n, variant)
+        .into_iter()
+        .map(|elimination| elimination.victim)
+        .collect()
+}
@@ -0,0 +1,104 @@
+//! Walking between the keys of a keypad, as used by day 2.
+
+use alloc::vec::Vec;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Instruction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Instruction {
+    pub fn from_char(ch: char) -> Option<Instruction> {
+        use Instruction::*;
+
+        match ch {
+            'u' | 'U' => Some(Up),
+            'd' | 'D' => Some(Down),
+            'l' | 'L' => Some(Left),
+            'r' | 'R' => Some(Right),
+            _ => None,
+        }
+    }
+
+    /// The character which `from_char` parses into this instruction.
+    pub fn as_char(self) -> char {
+        use Instruction::*;
+
+        match self {
+            Up => 'U',
+            Down => 'D',
+            Left => 'L',
+            Right => 'R',
+        }
+    }
+
+    // disable clippy here because it's too much hassle to come up with a better name
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Vec<Instruction>> {
+        s.trim().chars().map(Instruction::from_char).collect()
+    }
+}
+
+/// A keypad layout: rows from top to bottom, where `None` marks a position without a key.
+pub type Keypad = &'static [&'static [Option<char>]];
+
+/// Represents a key on a keypad
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Key {
+    x: usize,
+    y: usize,
+    pad: Keypad,
+}
+
+impl Key {
+    pub fn center_on(key: char, keypad: Keypad) -> Option<Key> {
+        for (y, row) in keypad.iter().enumerate() {
+            for (x, maybe_key) in row.iter().enumerate() {
+                if *maybe_key == Some(key) {
+                    return Some(Key { x, y, pad: keypad });
+                }
+            }
+        }
+        None
+    }
+
+    pub fn shift(&mut self, inst: Instruction) {
+        use Instruction::*;
+
+        match inst {
+            Up => {
+                if self.y > 0 && self.pad[self.y - 1][self.x].is_some() {
+                    self.y -= 1
+                }
+            }
+            Down => {
+                if self.y < self.pad.len() - 1 && self.pad[self.y + 1][self.x].is_some() {
+                    self.y += 1;
+                }
+            }
+            Left => {
+                if self.x > 0 && self.pad[self.y][self.x - 1].is_some() {
+                    self.x -= 1;
+                }
+            }
+            Right => {
+                if self.x < self.pad[self.y].len() - 1 && self.pad[self.y][self.x + 1].is_some() {
+                    self.x += 1;
+                }
+            }
+        }
+    }
+
+    pub fn shift_many(&mut self, insts: &[Instruction]) {
+        for inst in insts {
+            self.shift(*inst);
+        }
+    }
+
+    pub fn char(&self) -> char {
+        self.pad[self.y][self.x].expect("can't have a key without a char")
+    }
+}
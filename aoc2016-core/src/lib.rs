@@ -0,0 +1,14 @@
+//! Pure algorithmic cores of several puzzles.
+//!
+//! This crate needs only `core` and `alloc`, so it can be used from embedded or wasm targets.
+//! It does no I/O and no parsing of puzzle input; the day crates handle that and call in here.
+
+#![cfg_attr(not(test), no_std)]
+
+extern crate alloc;
+
+pub mod caesar;
+pub mod dragon;
+pub mod intervals;
+pub mod josephus;
+pub mod keypad;
@@ -0,0 +1,113 @@
+//! The answer to a single part of a puzzle.
+
+use std::fmt;
+
+/// A puzzle answer, as returned by every [`Solver`](crate::Solver).
+///
+/// Days compute their answers in whichever integer type suits them, but all integer answers
+/// are normalized to a single type wide enough to hold any of them, so that tooling can
+/// compare answers without knowing how each day computed them. Integers too large even for
+/// that, such as day 9's decompressed lengths, are carried as `Text`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Integer(i128),
+    Text(String),
+    /// One answer per line of the input.
+    Lines(Vec<Answer>),
+}
+
+impl Answer {
+    /// This answer's value, if it is an integer.
+    pub fn as_integer(&self) -> Option<i128> {
+        match self {
+            Answer::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+macro_rules! impl_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Answer {
+                    Answer::Integer(i128::from(value))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(u8, u16, u32, u64, i8, i16, i32, i64, i128);
+
+// `i128` has no `From` impls for the pointer-sized types, but they are at most 64 bits wide on
+// every target Rust supports, so these conversions never truncate
+impl From<usize> for Answer {
+    fn from(value: usize) -> Answer {
+        Answer::Integer(value as i128)
+    }
+}
+
+impl From<isize> for Answer {
+    fn from(value: isize) -> Answer {
+        Answer::Integer(value as i128)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Answer {
+        Answer::Text(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Answer {
+        Answer::Text(value.to_owned())
+    }
+}
+
+impl<T: Into<Answer>> From<Vec<T>> for Answer {
+    fn from(values: Vec<T>) -> Answer {
+        Answer::Lines(values.into_iter().map(Into::into).collect())
+    }
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Integer(value) => write!(f, "{}", value),
+            Answer::Text(text) => write!(f, "{}", text),
+            Answer::Lines(lines) => {
+                for (idx, line) in lines.iter().enumerate() {
+                    if idx > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "{}", line)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integers_are_normalized() {
+        assert_eq!(Answer::from(7_u8), Answer::from(7_i64));
+        assert_eq!(Answer::from(u64::MAX).as_integer(), Some(u64::MAX as i128));
+        assert_eq!(Answer::from(i64::MIN).as_integer(), Some(i64::MIN as i128));
+        assert_eq!(Answer::from(i128::MAX).as_integer(), Some(i128::MAX));
+        assert_eq!(Answer::from(usize::MAX), Answer::from(usize::MAX as u64));
+        assert_eq!(Answer::from("7").as_integer(), None);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Answer::from(vec![1_u32, 22, 333]).to_string(), "1\n22\n333");
+        assert_eq!(Answer::from(-5).to_string(), "-5");
+        assert_eq!(Answer::from("abc").to_string(), "abc");
+    }
+}
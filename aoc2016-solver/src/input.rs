@@ -0,0 +1,191 @@
+//! Puzzle input for the day binaries, from a file or from standard input.
+//!
+//! The day crates read their input from a path, often more than once: each part and each
+//! report rereads it. Standard input can only be read once, so it is spooled to a temporary
+//! file, which then flows through exactly the same parsing as any other input file. The file is
+//! created with a fresh, unpredictable name, and is removed when the `Input` is dropped.
+//!
+//! Every binary reads standard input the same way: it prompts when standard input is a
+//! terminal, reads to end of file, normalizes line endings and trailing whitespace, and
+//! rejects empty input.
+
+use aoclib::{config::Config, website::get_input};
+use std::{
+    io::{self, IsTerminal, Read, Write},
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+use tempfile::TempPath;
+
+const YEAR: u32 = 2016;
+
+/// The input argument which stands for standard input.
+pub const STDIN_ARG: &str = "-";
+
+#[cfg(windows)]
+const EOF_KEY: &str = "Ctrl-Z then Enter";
+#[cfg(not(windows))]
+const EOF_KEY: &str = "Ctrl-D";
+
+/// If standard input is a terminal, tell the user how to end their input.
+///
+/// Piped input needs no prompt, so nothing is printed then.
+pub fn prompt_stdin() {
+    if io::stdin().is_terminal() {
+        eprintln!("reading input from stdin; end it with {}", EOF_KEY);
+    }
+}
+
+/// Normalize input text: line endings become `\n`, and trailing whitespace is trimmed to a
+/// single final newline.
+///
+/// Leading whitespace is kept, since some puzzles align their columns with it.
+pub fn normalize(text: &str) -> String {
+    let mut normalized = text.replace("\r\n", "\n").trim_end().to_owned();
+    if !normalized.is_empty() {
+        normalized.push('\n');
+    }
+    normalized
+}
+
+/// The path of a day's input.
+///
+/// Dereferences to a `Path`, so it can be passed wherever the day crates expect one.
+#[derive(Debug)]
+pub struct Input {
+    path: PathBuf,
+    /// The spooled copy of standard input at `path`, if any, which is removed on drop.
+    spool: Option<TempPath>,
+}
+
+impl Input {
+    /// The input file at `path`.
+    pub fn file(path: impl Into<PathBuf>) -> Input {
+        Input {
+            path: path.into(),
+            spool: None,
+        }
+    }
+
+    /// Read standard input to its end, prompting first if it is a terminal, and spool it to a
+    /// temporary file.
+    pub fn stdin() -> io::Result<Input> {
+        prompt_stdin();
+        Input::from_reader(io::stdin().lock())
+    }
+
+    /// Read `reader` to its end, [normalizing](normalize) it, and spool it to a temporary file.
+    ///
+    /// Input which is empty once normalized is an `UnexpectedEof` error.
+    pub fn from_reader(mut reader: impl Read) -> io::Result<Input> {
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents)?;
+        let contents = normalize(&contents);
+        if contents.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "no input"));
+        }
+        let mut file = tempfile::Builder::new()
+            .prefix("aoc2016-stdin-")
+            .suffix(".txt")
+            .tempfile()?;
+        file.write_all(contents.as_bytes())?;
+        let spool = file.into_temp_path();
+        Ok(Input {
+            path: spool.to_path_buf(),
+            spool: Some(spool),
+        })
+    }
+
+    /// The input named by a command-line argument: standard input if it is `-`, or otherwise
+    /// the file at that path.
+    pub fn from_arg(arg: &Path) -> io::Result<Input> {
+        if arg == Path::new(STDIN_ARG) {
+            Input::stdin()
+        } else {
+            Ok(Input::file(arg))
+        }
+    }
+
+    /// The input a day binary was asked for: standard input if `stdin` is set, the input named
+    /// by `arg` if given, and otherwise the configured input for `day`.
+    pub fn for_day(day: u8, stdin: bool, arg: Option<&Path>) -> Result<Input, Error> {
+        if stdin {
+            return Ok(Input::stdin()?);
+        }
+        match arg {
+            None => {
+                let config = Config::load().map_err(|err| Error::Config(err.into()))?;
+                // this does nothing if the input file already exists, but
+                // simplifies the workflow after cloning the repo on a new computer
+                get_input(&config, YEAR, day).map_err(|err| Error::Download(err.into()))?;
+                Ok(Input::file(config.input_for(YEAR, day)))
+            }
+            Some(path) => Ok(Input::from_arg(path)?),
+        }
+    }
+
+    /// `true` if this input was read from standard input.
+    pub fn is_stdin(&self) -> bool {
+        self.spool.is_some()
+    }
+}
+
+impl Deref for Input {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl AsRef<Path> for Input {
+    fn as_ref(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("loading the configuration")]
+    Config(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("downloading the input")]
+    Download(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_input() {
+        let input = Input::from_arg(Path::new("input.txt")).unwrap();
+        assert!(!input.is_stdin());
+        assert_eq!(&*input, Path::new("input.txt"));
+
+        let input = Input::for_day(1, false, Some(Path::new("input.txt"))).unwrap();
+        assert!(!input.is_stdin());
+        assert_eq!(&*input, Path::new("input.txt"));
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("a\r\nb\r\n\n  \n"), "a\nb\n");
+        assert_eq!(normalize("  4 21 894"), "  4 21 894\n");
+        assert_eq!(normalize(" \n\t"), "");
+    }
+
+    #[test]
+    fn test_spooled_input() {
+        let input = Input::from_reader("abc\r\ndef".as_bytes()).unwrap();
+        assert!(input.is_stdin());
+        let path = input.to_path_buf();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "abc\ndef\n");
+        drop(input);
+        assert!(!path.exists());
+
+        let err = Input::from_reader("\n\n".as_bytes()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
@@ -0,0 +1,200 @@
+//! A common interface to every day's solutions.
+//!
+//! Each day crate implements [`Solver`] and registers itself with a [`Registry`], so that
+//! tooling such as the runner and benchmarks can iterate over every day generically, instead
+//! of hand-wiring each day's `part1` and `part2`.
+
+use std::collections::BTreeMap;
+
+mod answer;
+pub mod input;
+pub mod logging;
+pub mod progress;
+pub use answer::Answer;
+pub use input::Input;
+pub use progress::{Progress, Silent, TerminalProgress};
+
+pub type Error = Box<dyn std::error::Error + Send + Sync>;
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The solutions to a single day.
+///
+/// Each part takes the full text of the puzzle input and returns its answer.
+///
+/// Every day crate implements this on a unit struct named `Solution`, and provides a `register`
+/// function which adds it to a [`Registry`] under that day's number.
+pub trait Solver: Send + Sync {
+    fn part1(&self, input: &str) -> Result<Answer>;
+
+    /// Most days have two parts; those which don't can rely on this default.
+    fn part2(&self, _input: &str) -> Result<Answer> {
+        Err(NoSuchPart(2).into())
+    }
+
+    /// `false` for days which have only one part.
+    fn has_part2(&self) -> bool {
+        true
+    }
+
+    /// The parts of this day, in order.
+    fn parts(&self) -> &'static [u8] {
+        if self.has_part2() {
+            &[1, 2]
+        } else {
+            &[1]
+        }
+    }
+
+    /// The examples given in this day's puzzle text, which the runner's `selftest` checks.
+    ///
+    /// Only examples which the puzzle answers with the same parameters as the real input are
+    /// listed; those which e.g. use a smaller grid are covered by unit tests instead.
+    fn examples(&self) -> &'static [Example] {
+        &[]
+    }
+
+    /// Solve part `part` of this day.
+    fn solve(&self, part: u8, input: &str) -> Result<Answer> {
+        match part {
+            1 => self.part1(input),
+            2 if self.has_part2() => self.part2(input),
+            _ => Err(NoSuchPart(part).into()),
+        }
+    }
+
+    /// Solve part `part` of this day, reporting how far along it is to `progress`.
+    ///
+    /// Only the days which can run for a long time report anything; the rest ignore `progress`.
+    fn solve_reporting(&self, part: u8, input: &str, _progress: &dyn Progress) -> Result<Answer> {
+        self.solve(part, input)
+    }
+}
+
+/// An example input from a puzzle's text, and the answer the text gives for it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Example {
+    pub part: u8,
+    pub input: &'static str,
+    /// The answer as displayed: multi-line answers have one line per input line.
+    pub answer: &'static str,
+}
+
+impl Example {
+    /// Whether `answer` is this example's answer.
+    pub fn matches(&self, answer: &Answer) -> bool {
+        answer.to_string() == self.answer
+    }
+}
+
+/// The requested part doesn't exist for this day.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("no such part: {0}")]
+pub struct NoSuchPart(pub u8);
+
+/// Compute an answer for each of `items`.
+pub fn each_line<T, A: Into<Answer>>(
+    items: impl IntoIterator<Item = T>,
+    mut answer: impl FnMut(T) -> Result<A>,
+) -> Result<Answer> {
+    let answers = items
+        .into_iter()
+        .map(|item| answer(item).map(Into::into))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(Answer::Lines(answers))
+}
+
+/// Every registered day, in order.
+#[derive(Default)]
+pub struct Registry {
+    solvers: BTreeMap<u8, Box<dyn Solver>>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry::default()
+    }
+
+    /// Register the solutions to `day`.
+    ///
+    /// Panics if `day` is already registered.
+    pub fn register(&mut self, day: u8, solver: impl 'static + Solver) {
+        let previous = self.solvers.insert(day, Box::new(solver));
+        assert!(previous.is_none(), "day {} registered twice", day);
+    }
+
+    /// The solutions to `day`, if it is registered.
+    pub fn get(&self, day: u8) -> Option<&dyn Solver> {
+        self.solvers.get(&day).map(AsRef::as_ref)
+    }
+
+    /// Each registered day with its solutions, in ascending order.
+    pub fn iter(&self) -> impl '_ + Iterator<Item = (u8, &dyn Solver)> {
+        self.solvers
+            .iter()
+            .map(|(&day, solver)| (day, solver.as_ref()))
+    }
+
+    pub fn days(&self) -> impl '_ + Iterator<Item = u8> {
+        self.solvers.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.solvers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.solvers.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Lengths;
+
+    impl Solver for Lengths {
+        fn part1(&self, input: &str) -> Result<Answer> {
+            each_line(input.lines(), |line| Ok(line.len()))
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        let mut registry = Registry::new();
+        registry.register(3, Lengths);
+        registry.register(1, Lengths);
+        assert_eq!(registry.days().collect::<Vec<_>>(), vec![1, 3]);
+        assert!(registry.get(2).is_none());
+
+        let solver = registry.get(3).unwrap();
+        assert_eq!(solver.part1("a\nbcd\n").unwrap().to_string(), "1\n3");
+        assert_eq!(
+            solver.solve(1, "ab").unwrap(),
+            Answer::Lines(vec![Answer::Integer(2)])
+        );
+        assert!(solver.solve(3, "ab").is_err());
+        let err = solver.part2("").unwrap_err();
+        assert_eq!(err.downcast_ref::<NoSuchPart>(), Some(&NoSuchPart(2)));
+    }
+
+    #[test]
+    fn test_example_matches() {
+        let example = Example {
+            part: 1,
+            input: "a\nbcd\n",
+            answer: "1\n3",
+        };
+        assert!(example.matches(&Lengths.part1(example.input).unwrap()));
+        assert!(!example.matches(&Answer::from(13)));
+        assert!(Lengths.examples().is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "day 1 registered twice")]
+    fn test_register_twice() {
+        let mut registry = Registry::new();
+        registry.register(1, Lengths);
+        registry.register(1, Lengths);
+    }
+}
@@ -0,0 +1,45 @@
+//! Diagnostic output from the solvers, via `tracing`.
+//!
+//! Solvers emit events at `debug` and `trace` level instead of printing diagnostics directly.
+//! Binaries install a subscriber with [`init`], so that those events can be shown or hidden
+//! without editing code.
+
+use std::io::IsTerminal;
+use tracing_subscriber::{fmt, EnvFilter};
+
+/// The level of events shown at each count of `-v` flags.
+fn level(verbosity: u8) -> &'static str {
+    match verbosity {
+        0 => "warn",
+        1 => "info",
+        2 => "debug",
+        _ => "trace",
+    }
+}
+
+/// Write events to stderr, showing more of them at higher `verbosity`.
+///
+/// When the `RUST_LOG` environment variable is set, it takes precedence over `verbosity`, so
+/// diagnostics can be filtered per day: `RUST_LOG=day22=debug` shows only day 22's.
+pub fn init(verbosity: u8) {
+    let filter =
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level(verbosity)));
+    // a subscriber may already be installed, e.g. by a test harness; keep that one
+    let _ = fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal())
+        .try_init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level() {
+        assert_eq!(level(0), "warn");
+        assert_eq!(level(2), "debug");
+        assert_eq!(level(7), "trace");
+    }
+}
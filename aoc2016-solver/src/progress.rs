@@ -0,0 +1,187 @@
+//! Progress reports from long-running solvers.
+//!
+//! Solvers which may run for minutes accept a `&dyn Progress`, and report to it how many items
+//! they have processed so far. Callers which don't care pass [`Silent`]; the binaries pass a
+//! [`TerminalProgress`].
+
+use std::{
+    io::{IsTerminal, Write},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Receives reports of how far along some work is.
+pub trait Progress: Send + Sync {
+    /// The total number of items, when it is known in advance.
+    fn set_total(&self, _total: u64) {}
+
+    /// `position` items have been processed so far.
+    fn update(&self, position: u64);
+}
+
+/// Discards every report.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Silent;
+
+impl Progress for Silent {
+    fn update(&self, _position: u64) {}
+}
+
+/// Closures receive each position reported.
+impl<F: Fn(u64) + Send + Sync> Progress for F {
+    fn update(&self, position: u64) {
+        self(position)
+    }
+}
+
+/// Minimum time between redraws of a terminal progress bar.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Width of the bar itself, in characters.
+const BAR_WIDTH: usize = 40;
+
+#[derive(Debug)]
+struct BarState {
+    total: Option<u64>,
+    position: u64,
+    started: Instant,
+    last_drawn: Option<Instant>,
+}
+
+/// A progress bar drawn on stderr.
+///
+/// Nothing is drawn when stderr is not a terminal, so piped output stays clean.
+#[derive(Debug)]
+pub struct TerminalProgress {
+    label: String,
+    enabled: bool,
+    state: Mutex<BarState>,
+}
+
+impl TerminalProgress {
+    pub fn new(label: impl Into<String>) -> TerminalProgress {
+        TerminalProgress {
+            label: label.into(),
+            enabled: std::io::stderr().is_terminal(),
+            state: Mutex::new(BarState {
+                total: None,
+                position: 0,
+                started: Instant::now(),
+                last_drawn: None,
+            }),
+        }
+    }
+
+    fn render(&self, state: &BarState, now: Instant) -> String {
+        match state.total {
+            Some(total) => {
+                let fraction = if total == 0 {
+                    1.0
+                } else {
+                    (state.position as f64 / total as f64).min(1.0)
+                };
+                let filled = (fraction * BAR_WIDTH as f64) as usize;
+                format!(
+                    "{} [{}{}] {}/{} ({:.0}%)",
+                    self.label,
+                    "#".repeat(filled),
+                    "-".repeat(BAR_WIDTH - filled),
+                    state.position,
+                    total,
+                    fraction * 100.0
+                )
+            }
+            None => {
+                let elapsed = now.duration_since(state.started).as_secs_f64();
+                let rate = if elapsed > 0.0 {
+                    state.position as f64 / elapsed
+                } else {
+                    0.0
+                };
+                format!("{} {} ({:.0}/s)", self.label, state.position, rate)
+            }
+        }
+    }
+
+    fn draw(&self, state: &mut BarState, now: Instant) {
+        state.last_drawn = Some(now);
+        let mut stderr = std::io::stderr();
+        // progress is best-effort; failing to draw it is no reason to fail the solver
+        let _ = write!(stderr, "\r{}\x1b[K", self.render(state, now));
+        let _ = stderr.flush();
+    }
+
+    /// Draw the final state of the bar, and move to the next line.
+    pub fn finish(&self) {
+        if !self.enabled {
+            return;
+        }
+        let mut state = self.state.lock().expect("progress state is never poisoned");
+        if state.last_drawn.is_some() {
+            self.draw(&mut state, Instant::now());
+            eprintln!();
+        }
+    }
+}
+
+impl Progress for TerminalProgress {
+    fn set_total(&self, total: u64) {
+        let mut state = self.state.lock().expect("progress state is never poisoned");
+        state.total = Some(total);
+    }
+
+    fn update(&self, position: u64) {
+        let mut state = self.state.lock().expect("progress state is never poisoned");
+        state.position = position;
+        if !self.enabled {
+            return;
+        }
+        let now = Instant::now();
+        let due = match state.last_drawn {
+            Some(drawn) => now.duration_since(drawn) >= REDRAW_INTERVAL,
+            None => true,
+        };
+        if due {
+            self.draw(&mut state, now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    #[test]
+    fn test_closure_progress() {
+        let seen = AtomicU64::new(0);
+        let progress = |position| seen.store(position, Ordering::Relaxed);
+        let progress: &dyn Progress = &progress;
+        progress.set_total(10);
+        progress.update(7);
+        assert_eq!(seen.load(Ordering::Relaxed), 7);
+    }
+
+    #[test]
+    fn test_render() {
+        let bar = TerminalProgress::new("keys");
+        let started = Instant::now();
+        let mut state = BarState {
+            total: Some(64),
+            position: 16,
+            started,
+            last_drawn: None,
+        };
+        assert_eq!(
+            bar.render(&state, started),
+            format!("keys [{}{}] 16/64 (25%)", "#".repeat(10), "-".repeat(30))
+        );
+
+        state.total = None;
+        state.position = 300;
+        assert_eq!(
+            bar.render(&state, started + Duration::from_secs(2)),
+            "keys 300 (150/s)"
+        );
+    }
+}
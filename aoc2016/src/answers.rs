@@ -0,0 +1,272 @@
+//! Run every day of the workspace, recording its answers and runtime, and compare those
+//! records against a baseline.
+
+use aoc2016::run::{solve_all, Outcome};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt, path::Path};
+
+/// Runtimes below this many seconds are dominated by noise, and are never flagged as slower.
+const NOISE_FLOOR_SECS: f64 = 0.05;
+
+/// The answers and runtime of a single day.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DayAnswers {
+    pub day: u8,
+    pub success: bool,
+    /// Each line of the day's answers, as `run` prints them.
+    pub answers: Vec<String>,
+    /// The total runtime of the day's parts.
+    pub runtime_secs: f64,
+}
+
+/// The answers of every day in the workspace.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Report {
+    pub days: Vec<DayAnswers>,
+}
+
+impl Report {
+    pub fn load(path: &Path) -> Result<Report, Error> {
+        let file = std::fs::File::open(path)?;
+        Ok(serde_json::from_reader(std::io::BufReader::new(file))?)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let file = std::fs::File::create(path)?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+}
+
+/// Group `outcomes` by day.
+fn report(outcomes: &[Outcome]) -> Report {
+    let mut report = Report::default();
+    for outcome in outcomes {
+        if report.days.last().map(|day| day.day) != Some(outcome.day) {
+            report.days.push(DayAnswers {
+                day: outcome.day,
+                success: true,
+                answers: Vec::new(),
+                runtime_secs: 0.0,
+            });
+        }
+        let day = report.days.last_mut().expect("a day was just pushed");
+        let answer = match &outcome.answer {
+            Ok(answer) => answer.to_string(),
+            Err(err) => {
+                day.success = false;
+                format!("error: {}", err)
+            }
+        };
+        for (idx, line) in answer.lines().enumerate() {
+            day.answers.push(if idx == 0 {
+                format!("part {}: {}", outcome.part, line)
+            } else {
+                line.to_owned()
+            });
+        }
+        day.runtime_secs += outcome.runtime.as_secs_f64();
+    }
+    report
+}
+
+/// Run each day of the workspace in turn, printing a summary line per day.
+///
+/// Days run one at a time, so that their runtimes are comparable between runs.
+pub fn run_all() -> color_eyre::eyre::Result<Report> {
+    let report = report(&solve_all(false)?);
+    for day in &report.days {
+        println!(
+            "day{:02}: {:>8.3}s{}",
+            day.day,
+            day.runtime_secs,
+            if day.success { "" } else { " (failed)" }
+        );
+    }
+    Ok(report)
+}
+
+/// A way in which a day's current run differs from its baseline.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    /// The day is in the baseline but was not run.
+    Missing { day: u8 },
+    /// The day's output or success changed.
+    Answers {
+        baseline: DayAnswers,
+        current: DayAnswers,
+    },
+    /// The day's runtime grew by more than the threshold.
+    Slower {
+        day: u8,
+        baseline_secs: f64,
+        current_secs: f64,
+    },
+}
+
+impl Difference {
+    /// `true` for differences which indicate an incorrect answer, not merely a slow one.
+    pub fn is_answer(&self) -> bool {
+        !matches!(self, Difference::Slower { .. })
+    }
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difference::Missing { day } => write!(f, "!!! day{:02}: missing from this run", day),
+            Difference::Answers { baseline, current } => {
+                write!(f, "!!! day{:02}: ANSWERS CHANGED", baseline.day)?;
+                if baseline.success != current.success {
+                    write!(
+                        f,
+                        "\n    success: {} -> {}",
+                        baseline.success, current.success
+                    )?;
+                }
+                for line in &baseline.answers {
+                    write!(f, "\n    - {}", line)?;
+                }
+                for line in &current.answers {
+                    write!(f, "\n    + {}", line)?;
+                }
+                Ok(())
+            }
+            Difference::Slower {
+                day,
+                baseline_secs,
+                current_secs,
+            } => write!(
+                f,
+                "day{:02}: slower: {:.3}s -> {:.3}s (+{:.0}%)",
+                day,
+                baseline_secs,
+                current_secs,
+                100.0 * (current_secs / baseline_secs - 1.0)
+            ),
+        }
+    }
+}
+
+/// Compare `current` against `baseline`.
+///
+/// A day counts as slower when its runtime grew by more than `threshold`, a fraction of its
+/// baseline runtime. Days new in `current` are not differences.
+pub fn compare(baseline: &Report, current: &Report, threshold: f64) -> Vec<Difference> {
+    let current: BTreeMap<_, _> = current.days.iter().map(|day| (day.day, day)).collect();
+    let mut differences = Vec::new();
+    for baseline in &baseline.days {
+        let current = match current.get(&baseline.day) {
+            Some(current) => *current,
+            None => {
+                differences.push(Difference::Missing { day: baseline.day });
+                continue;
+            }
+        };
+        if baseline.success != current.success || baseline.answers != current.answers {
+            differences.push(Difference::Answers {
+                baseline: baseline.clone(),
+                current: current.clone(),
+            });
+        } else if current.runtime_secs > NOISE_FLOOR_SECS
+            && current.runtime_secs > baseline.runtime_secs * (1.0 + threshold)
+        {
+            differences.push(Difference::Slower {
+                day: baseline.day,
+                baseline_secs: baseline.runtime_secs,
+                current_secs: current.runtime_secs,
+            });
+        }
+    }
+    differences
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("reading or writing answers")]
+    Json(#[from] serde_json::Error),
+    #[error("{0} day(s) changed their answers")]
+    AnswersChanged(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn day(day: u8, answer: &str, runtime_secs: f64) -> DayAnswers {
+        DayAnswers {
+            day,
+            success: true,
+            answers: vec![answer.to_owned()],
+            runtime_secs,
+        }
+    }
+
+    #[test]
+    fn test_report() {
+        let outcome = |day, part, answer| Outcome {
+            day,
+            part,
+            answer,
+            runtime: Duration::from_millis(250),
+        };
+        let report = report(&[
+            outcome(1, 1, Ok(7.into())),
+            outcome(1, 2, Ok(vec![8, 9].into())),
+            outcome(2, 1, Err("no solution".into())),
+        ]);
+        assert_eq!(
+            report,
+            Report {
+                days: vec![
+                    DayAnswers {
+                        day: 1,
+                        success: true,
+                        answers: vec!["part 1: 7".into(), "part 2: 8".into(), "9".into()],
+                        runtime_secs: 0.5,
+                    },
+                    DayAnswers {
+                        day: 2,
+                        success: false,
+                        answers: vec!["part 1: error: no solution".into()],
+                        runtime_secs: 0.25,
+                    },
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn test_compare() {
+        let baseline = Report {
+            days: vec![
+                day(1, "a", 0.5),
+                day(2, "b", 0.5),
+                day(3, "c", 0.5),
+                day(4, "d", 0.01),
+                day(5, "e", 0.5),
+            ],
+        };
+        let current = Report {
+            days: vec![
+                day(1, "a", 0.55),
+                day(2, "B", 0.5),
+                day(3, "c", 1.0),
+                day(4, "d", 0.04),
+                day(6, "f", 0.5),
+            ],
+        };
+        let differences = compare(&baseline, &current, 0.2);
+        assert_eq!(differences.len(), 3);
+        assert!(matches!(
+            &differences[0],
+            Difference::Answers { current, .. } if current.day == 2
+        ));
+        assert!(matches!(differences[1], Difference::Slower { day: 3, .. }));
+        assert_eq!(differences[2], Difference::Missing { day: 5 });
+        assert_eq!(differences.iter().filter(|d| d.is_answer()).count(), 2);
+    }
+}
@@ -0,0 +1,176 @@
+//! Time each day's solutions in-process against the real inputs, counting allocations.
+
+use crate::run::{read_input, registry, select_parts};
+use color_eyre::eyre::{eyre, Result};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+/// Wraps the system allocator, counting every allocation made by any thread.
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+fn count_allocation(size: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_add(size as u64, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn allocation_counts() -> (u64, u64) {
+    (
+        ALLOCATIONS.load(Ordering::Relaxed),
+        ALLOCATED_BYTES.load(Ordering::Relaxed),
+    )
+}
+
+/// Wall time statistics over several runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timing {
+    pub min: Duration,
+    pub mean: Duration,
+    pub max: Duration,
+}
+
+impl Timing {
+    /// Summarize `samples`, or `None` if there are none.
+    pub fn from_samples(samples: &[Duration]) -> Option<Timing> {
+        Some(Timing {
+            min: *samples.iter().min()?,
+            mean: samples.iter().sum::<Duration>() / samples.len() as u32,
+            max: *samples.iter().max()?,
+        })
+    }
+}
+
+/// The measurements of a single part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    pub day: u8,
+    pub part: u8,
+    pub timing: Timing,
+    /// Mean number of allocations per run.
+    pub allocations: u64,
+    /// Mean number of bytes allocated per run.
+    pub allocated_bytes: u64,
+}
+
+impl fmt::Display for Measurement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day{:02} part {}: min {:>9.3?} mean {:>9.3?} max {:>9.3?}  {} allocations ({} bytes)",
+            self.day,
+            self.part,
+            self.timing.min,
+            self.timing.mean,
+            self.timing.max,
+            self.allocations,
+            self.allocated_bytes
+        )
+    }
+}
+
+/// Run `part` of each of `days`, or every part and every day when empty, `iterations` times,
+/// printing each measurement as it completes.
+///
+/// Inputs are read before timing starts, downloading them if necessary.
+pub fn bench(days: &[u8], part: Option<u8>, iterations: u32) -> Result<Vec<Measurement>> {
+    if iterations == 0 {
+        return Err(eyre!("iterations must be positive"));
+    }
+    let registry = registry();
+    let days = if days.is_empty() {
+        registry.days().collect()
+    } else {
+        days.to_vec()
+    };
+
+    let mut measurements = Vec::new();
+    for day in days {
+        let solver = registry
+            .get(day)
+            .ok_or_else(|| eyre!("no such day: {}", day))?;
+        let parts = select_parts(solver, day, part)?;
+        let input = read_input(day, None)?;
+
+        for part in parts {
+            let mut samples = Vec::with_capacity(iterations as usize);
+            let (allocations_before, bytes_before) = allocation_counts();
+            for _ in 0..iterations {
+                let started = Instant::now();
+                solver
+                    .solve(part, &input)
+                    .map_err(|err| eyre!("day {} part {}: {}", day, part, err))?;
+                samples.push(started.elapsed());
+            }
+            let (allocations_after, bytes_after) = allocation_counts();
+
+            let measurement = Measurement {
+                day,
+                part,
+                timing: Timing::from_samples(&samples).expect("iterations is positive"),
+                allocations: (allocations_after - allocations_before) / iterations as u64,
+                allocated_bytes: (bytes_after - bytes_before) / iterations as u64,
+            };
+            println!("{}", measurement);
+            measurements.push(measurement);
+        }
+    }
+    Ok(measurements)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timing() {
+        let samples = [3, 1, 2, 6].map(Duration::from_millis);
+        assert_eq!(
+            Timing::from_samples(&samples),
+            Some(Timing {
+                min: Duration::from_millis(1),
+                mean: Duration::from_millis(3),
+                max: Duration::from_millis(6),
+            })
+        );
+        assert_eq!(Timing::from_samples(&[]), None);
+    }
+
+    #[test]
+    fn test_allocations_are_counted() {
+        let (before, _) = allocation_counts();
+        let boxed = std::hint::black_box(Box::new(17_u64));
+        let (after, _) = allocation_counts();
+        assert!(after > before);
+        drop(boxed);
+    }
+}
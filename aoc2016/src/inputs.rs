@@ -0,0 +1,161 @@
+//! Remember the answers computed from each input file, along with a hash of its contents.
+//!
+//! Each input has a sidecar file beside it recording the input's hash and the answers computed
+//! from it. If the input is later replaced, for example by another account's, the recorded
+//! answers no longer apply: running the day warns about the change before recording new ones.
+
+use aoc2016_solver::Answer;
+use crypto::{digest::Digest, md5::Md5};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// The hash identifying the contents of an input.
+pub fn hash(input: &str) -> String {
+    let mut digest = Md5::new();
+    digest.input_str(input);
+    digest.result_str()
+}
+
+/// Where the answers for the input at `input_path` are recorded.
+pub fn record_path(input_path: &Path) -> PathBuf {
+    let mut name = input_path.file_name().unwrap_or_default().to_owned();
+    name.push(".answers.json");
+    input_path.with_file_name(name)
+}
+
+/// The answers computed from a particular input.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Record {
+    /// The `hash` of the input which produced `answers`.
+    pub input_hash: String,
+    /// Each part's answer.
+    pub answers: BTreeMap<u8, String>,
+}
+
+impl Record {
+    fn new(input: &str) -> Record {
+        Record {
+            input_hash: hash(input),
+            answers: BTreeMap::new(),
+        }
+    }
+
+    /// Load the record for the input at `input_path`, if there is one.
+    pub fn load(input_path: &Path) -> Result<Option<Record>, Error> {
+        let file = match std::fs::File::open(record_path(input_path)) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Some(serde_json::from_reader(std::io::BufReader::new(
+            file,
+        ))?))
+    }
+
+    /// Save this as the record for the input at `input_path`.
+    pub fn save(&self, input_path: &Path) -> Result<(), Error> {
+        let file = std::fs::File::create(record_path(input_path))?;
+        serde_json::to_writer_pretty(file, self)?;
+        Ok(())
+    }
+
+    /// Record `answer` for `part`.
+    pub fn insert(&mut self, part: u8, answer: &Answer) {
+        self.answers.insert(part, answer.to_string());
+    }
+}
+
+/// Load the record for the input at `input_path`, whose contents are `input`.
+///
+/// When answers were recorded for different contents, warns that the input changed and which
+/// answers no longer apply, and returns an empty record for the current contents instead. A
+/// record which can't be read is likewise replaced, with a warning, rather than failing the run.
+pub fn check(day: u8, input_path: &Path, input: &str) -> Record {
+    let current = Record::new(input);
+    let record = match Record::load(input_path) {
+        Ok(Some(record)) => record,
+        Ok(None) => return current,
+        Err(err) => {
+            eprintln!(
+                "warning: ignoring unreadable recorded answers for {}: {}",
+                input_path.display(),
+                err
+            );
+            return current;
+        }
+    };
+    if record.input_hash == current.input_hash {
+        return record;
+    }
+
+    eprintln!(
+        "warning: day {} input {} changed since its answers were recorded ({} -> {})",
+        day,
+        input_path.display(),
+        record.input_hash,
+        current.input_hash,
+    );
+    for (part, answer) in &record.answers {
+        eprintln!("  previously recorded part {}: {}", part, answer);
+    }
+    current
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("reading or writing recorded answers")]
+    Json(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash() {
+        assert_eq!(hash(""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(hash("abc"), "900150983cd24fb0d6963f7d28e17f72");
+    }
+
+    #[test]
+    fn test_record_path() {
+        assert_eq!(
+            record_path(Path::new("inputs/input-01.txt")),
+            Path::new("inputs/input-01.txt.answers.json")
+        );
+    }
+
+    #[test]
+    fn test_check() {
+        let input_path =
+            std::env::temp_dir().join(format!("aoc2016-inputs-test-{}.txt", std::process::id()));
+
+        // nothing recorded yet
+        let mut record = check(1, &input_path, "R2, L3");
+        assert_eq!(record, Record::new("R2, L3"));
+        record.insert(1, &Answer::from(5));
+        record.save(&input_path).unwrap();
+
+        // the same input keeps its answers
+        let loaded = check(1, &input_path, "R2, L3");
+        assert_eq!(loaded, record);
+        assert_eq!(loaded.answers[&1], "5");
+
+        // a changed input discards them
+        let changed = check(1, &input_path, "R5, L5, R5, R3");
+        assert_eq!(changed, Record::new("R5, L5, R5, R3"));
+        assert_ne!(changed.input_hash, record.input_hash);
+
+        // a corrupt record is replaced
+        std::fs::write(record_path(&input_path), "{ not json").unwrap();
+        assert!(Record::load(&input_path).is_err());
+        assert_eq!(check(1, &input_path, "R2, L3"), Record::new("R2, L3"));
+
+        std::fs::remove_file(record_path(&input_path)).unwrap();
+    }
+}
@@ -0,0 +1,4 @@
+//! Running days in-process, shared by the runner and the `tui` dashboard.
+
+pub mod inputs;
+pub mod run;
@@ -0,0 +1,156 @@
+use aoc2016::run;
+use color_eyre::eyre::Result;
+use std::path::PathBuf;
+use structopt::StructOpt;
+
+mod answers;
+mod bench;
+mod scaffold;
+mod selftest;
+
+#[derive(StructOpt, Debug)]
+struct Args {
+    /// show diagnostics from the solvers; repeat for more. `RUST_LOG` takes precedence, e.g.
+    /// `RUST_LOG=day22=debug` shows only day 22's
+    #[structopt(short, long, parse(from_occurrences), global = true)]
+    verbose: u8,
+
+    #[structopt(subcommand)]
+    command: Command,
+}
+
+#[derive(StructOpt, Debug)]
+enum Command {
+    /// run a day's solutions
+    Run {
+        /// day to run
+        #[structopt(long)]
+        day: u8,
+
+        /// run only this part; by default, every part runs
+        #[structopt(long)]
+        part: Option<u8>,
+
+        /// input file; defaults to the configured input for the day, downloading it if necessary
+        #[structopt(long, parse(from_os_str))]
+        input: Option<PathBuf>,
+
+        /// run against the examples from the puzzle text instead of an input file
+        #[structopt(long, conflicts_with = "input")]
+        example: bool,
+    },
+    /// run every part of every day in-process and concurrently, printing a summary table
+    RunAll,
+    /// time each day's solutions in-process against the real inputs
+    Bench {
+        /// day to benchmark; may be repeated. By default, every day is benchmarked
+        #[structopt(long)]
+        day: Vec<u8>,
+
+        /// benchmark only this part; by default, every part is benchmarked
+        #[structopt(long)]
+        part: Option<u8>,
+
+        /// number of times to run each part
+        #[structopt(short = "n", long, default_value = "10")]
+        iterations: u32,
+    },
+    /// check each day's solutions against the examples in its puzzle text
+    Selftest {
+        /// day to check; may be repeated. By default, every day is checked
+        #[structopt(long)]
+        day: Vec<u8>,
+    },
+    /// create a new day crate from the standard template
+    Scaffold {
+        /// day number for the new crate
+        day: u8,
+
+        /// workspace root; defaults to the workspace containing this tool
+        #[structopt(long, parse(from_os_str))]
+        root: Option<PathBuf>,
+    },
+    /// run every day, reporting its answers and runtime
+    Answers {
+        /// write the answers as JSON to this path, for later use as a baseline
+        #[structopt(long, parse(from_os_str))]
+        json: Option<PathBuf>,
+    },
+    /// run every day, comparing its answers and runtime against a baseline
+    Compare {
+        /// answers previously written by `answers --json`
+        #[structopt(long, parse(from_os_str))]
+        baseline: PathBuf,
+
+        /// flag days whose runtime grew by more than this fraction of the baseline
+        #[structopt(long, default_value = "0.2")]
+        threshold: f64,
+    },
+}
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    let args = Args::from_args();
+    aoc2016_solver::logging::init(args.verbose);
+    match args.command {
+        Command::Run {
+            day,
+            part,
+            example: true,
+            ..
+        } => run::run_examples(day, part)?,
+        Command::Run {
+            day, part, input, ..
+        } => run::run(day, part, input)?,
+        Command::RunAll => {
+            run::run_all()?;
+        }
+        Command::Bench {
+            day,
+            part,
+            iterations,
+        } => {
+            bench::bench(&day, part, iterations)?;
+        }
+        Command::Selftest { day } => {
+            selftest::selftest(&day)?;
+        }
+        Command::Scaffold { day, root } => {
+            let root = root.unwrap_or_else(scaffold::default_workspace_root);
+            let crate_dir = scaffold::scaffold(&root, day)?;
+            println!("created {}", crate_dir.display());
+        }
+        Command::Answers { json } => {
+            let report = answers::run_all()?;
+            match json {
+                Some(path) => report.save(&path)?,
+                None => {
+                    for day in &report.days {
+                        for line in &day.answers {
+                            println!("day{:02}: {}", day.day, line);
+                        }
+                    }
+                }
+            }
+        }
+        Command::Compare {
+            baseline,
+            threshold,
+        } => {
+            let baseline = answers::Report::load(&baseline)?;
+            let current = answers::run_all()?;
+            let differences = answers::compare(&baseline, &current, threshold);
+            for difference in &differences {
+                println!("{}", difference);
+            }
+            let changed = differences.iter().filter(|d| d.is_answer()).count();
+            if changed > 0 {
+                return Err(answers::Error::AnswersChanged(changed).into());
+            }
+            if differences.is_empty() {
+                println!("no differences from baseline");
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,318 @@
+//! Run any day's solutions in-process, through its registered `Solver`.
+
+use crate::inputs;
+use aoc2016_solver::{Answer, Registry, Solver};
+use aoclib::{config::Config, website::get_input};
+use color_eyre::eyre::{eyre, Result};
+use rayon::prelude::*;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+const YEAR: u32 = 2016;
+
+/// Every day of the workspace.
+pub fn registry() -> Registry {
+    let mut registry = Registry::new();
+    day01::register(&mut registry);
+    day02::register(&mut registry);
+    day03::register(&mut registry);
+    day04::register(&mut registry);
+    day05::register(&mut registry);
+    day06::register(&mut registry);
+    day07::register(&mut registry);
+    day08::register(&mut registry);
+    day09::register(&mut registry);
+    day10::register(&mut registry);
+    day11::register(&mut registry);
+    day12::register(&mut registry);
+    day13::register(&mut registry);
+    day14::register(&mut registry);
+    day15::register(&mut registry);
+    day16::register(&mut registry);
+    day17::register(&mut registry);
+    day18::register(&mut registry);
+    day19::register(&mut registry);
+    day20::register(&mut registry);
+    day21::register(&mut registry);
+    day22::register(&mut registry);
+    day23::register(&mut registry);
+    day24::register(&mut registry);
+    day25::register(&mut registry);
+    registry
+}
+
+/// The input file for `day`: `input` if given, otherwise the configured input file,
+/// downloading it if necessary.
+pub fn input_path(day: u8, input: Option<PathBuf>) -> Result<PathBuf> {
+    match input {
+        Some(path) => Ok(path),
+        None => {
+            let config = Config::load()?;
+            get_input(&config, YEAR, day)?;
+            Ok(config.input_for(YEAR, day))
+        }
+    }
+}
+
+/// The input for `day`: the contents of `input` if given, otherwise of the configured input
+/// file, downloading it if necessary.
+pub fn read_input(day: u8, input: Option<PathBuf>) -> Result<String> {
+    Ok(std::fs::read_to_string(input_path(day, input)?)?)
+}
+
+/// `part` of `day`, or every part if `part` is `None`.
+pub fn select_parts(solver: &dyn Solver, day: u8, part: Option<u8>) -> Result<Vec<u8>> {
+    match part {
+        None => Ok(solver.parts().to_vec()),
+        Some(part) if solver.parts().contains(&part) => Ok(vec![part]),
+        Some(part) => Err(eyre!("day {} has no part {}", day, part)),
+    }
+}
+
+/// Save `record` as the record for the input at `path`.
+///
+/// Losing a record only means the next run can't warn about a changed input, so failing to
+/// save one is reported rather than failing the run.
+fn save_record(record: &inputs::Record, path: &Path) {
+    if let Err(err) = record.save(path) {
+        eprintln!(
+            "warning: could not record answers for {}: {}",
+            path.display(),
+            err
+        );
+    }
+}
+
+/// Run `part` of `day`, or every part if `part` is `None`, printing each answer.
+///
+/// Answers are recorded when running the configured input, but not an explicit `input` file.
+pub fn run(day: u8, part: Option<u8>, input: Option<PathBuf>) -> Result<()> {
+    let registry = registry();
+    let solver = registry
+        .get(day)
+        .ok_or_else(|| eyre!("no such day: {}", day))?;
+    let parts = select_parts(solver, day, part)?;
+
+    let recording = input.is_none();
+    let path = input_path(day, input)?;
+    let input = std::fs::read_to_string(&path)?;
+    let mut record = if recording {
+        Some(inputs::check(day, &path, &input))
+    } else {
+        None
+    };
+    for part in parts {
+        let answer = solver
+            .solve(part, &input)
+            .map_err(|err| eyre!("day {} part {}: {}", day, part, err))?;
+        println!("part {}: {}", part, answer);
+        if let Some(record) = record.as_mut() {
+            record.insert(part, &answer);
+        }
+    }
+    if let Some(record) = record {
+        save_record(&record, &path);
+    }
+    Ok(())
+}
+
+/// Run `part` of `day`, or every part if `part` is `None`, against each of the day's embedded
+/// examples instead of an input file, printing each answer beside the expected one.
+pub fn run_examples(day: u8, part: Option<u8>) -> Result<()> {
+    let registry = registry();
+    let solver = registry
+        .get(day)
+        .ok_or_else(|| eyre!("no such day: {}", day))?;
+    let parts = select_parts(solver, day, part)?;
+
+    let examples: Vec<_> = solver
+        .examples()
+        .iter()
+        .enumerate()
+        .filter(|(_, example)| parts.contains(&example.part))
+        .collect();
+    if examples.is_empty() {
+        return Err(eyre!("day {} has no examples for the requested parts", day));
+    }
+
+    let mut mismatches = 0;
+    for (index, example) in examples {
+        let answer = solver.solve(example.part, example.input).map_err(|err| {
+            eyre!(
+                "day {} part {} example {}: {}",
+                day,
+                example.part,
+                index,
+                err
+            )
+        })?;
+        let verdict = if example.matches(&answer) {
+            "ok"
+        } else {
+            mismatches += 1;
+            "MISMATCH"
+        };
+        println!(
+            "part {} example {}: {} (expected {}): {}",
+            example.part, index, answer, example.answer, verdict
+        );
+    }
+    if mismatches > 0 {
+        return Err(eyre!("{} example(s) did not match", mismatches));
+    }
+    Ok(())
+}
+
+/// The result of running a single part.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub day: u8,
+    pub part: u8,
+    /// The answer, or the error message if the part failed.
+    pub answer: std::result::Result<Answer, String>,
+    pub runtime: Duration,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let answer = match &self.answer {
+            // multi-line answers are listed on a single row
+            Ok(answer) => answer.to_string().lines().collect::<Vec<_>>().join(", "),
+            Err(err) => format!("error: {}", err),
+        };
+        write!(
+            f,
+            "{:>3} | {:>4} | {:>10.3?} | {}",
+            self.day, self.part, self.runtime, answer
+        )
+    }
+}
+
+/// Run every part of every day, concurrently if `concurrently`, recording each answer.
+///
+/// Inputs are read before any day starts, downloading them if necessary. A part which fails
+/// is reported in its outcome; only failing to read the inputs is an error.
+pub fn solve_all(concurrently: bool) -> Result<Vec<Outcome>> {
+    let registry = registry();
+    let paths = registry
+        .days()
+        .map(|day| input_path(day, None))
+        .collect::<Result<Vec<_>>>()?;
+    let inputs = paths
+        .iter()
+        .map(std::fs::read_to_string)
+        .collect::<std::io::Result<Vec<_>>>()?;
+    let mut records = registry
+        .days()
+        .zip(paths.iter().zip(&inputs))
+        .map(|(day, (path, input))| (day, (path, inputs::check(day, path, input))))
+        .collect::<BTreeMap<_, _>>();
+    let jobs: Vec<_> = registry
+        .iter()
+        .zip(&inputs)
+        .flat_map(|((day, solver), input)| {
+            solver
+                .parts()
+                .iter()
+                .map(move |&part| (day, solver, part, input))
+        })
+        .collect();
+
+    let solve = |(day, solver, part, input): (u8, &dyn Solver, u8, &String)| {
+        let started = Instant::now();
+        let answer = solver.solve(part, input).map_err(|err| err.to_string());
+        Outcome {
+            day,
+            part,
+            answer,
+            runtime: started.elapsed(),
+        }
+    };
+    let outcomes: Vec<Outcome> = if concurrently {
+        jobs.into_par_iter().map(&solve).collect()
+    } else {
+        jobs.into_iter().map(&solve).collect()
+    };
+
+    for outcome in &outcomes {
+        if let Ok(answer) = &outcome.answer {
+            records
+                .get_mut(&outcome.day)
+                .expect("every day has a record")
+                .1
+                .insert(outcome.part, answer);
+        }
+    }
+    for (path, record) in records.values() {
+        save_record(record, path);
+    }
+    Ok(outcomes)
+}
+
+/// Run every part of every day concurrently, printing a summary table once all have finished
+/// and recording each answer.
+pub fn run_all() -> Result<Vec<Outcome>> {
+    let started = Instant::now();
+    let outcomes = solve_all(true)?;
+    let wall_time = started.elapsed();
+
+    println!("day | part |    runtime | answer");
+    println!("----|------|------------|-------");
+    for outcome in &outcomes {
+        println!("{}", outcome);
+    }
+    let cpu_time: Duration = outcomes.iter().map(|outcome| outcome.runtime).sum();
+    println!(
+        "{} parts in {:.3?} ({:.3?} summed over parts)",
+        outcomes.len(),
+        wall_time,
+        cpu_time
+    );
+
+    let failures = outcomes
+        .iter()
+        .filter(|outcome| outcome.answer.is_err())
+        .count();
+    if failures > 0 {
+        return Err(eyre!("{} part(s) failed", failures));
+    }
+    Ok(outcomes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome_row() {
+        let outcome = Outcome {
+            day: 4,
+            part: 2,
+            answer: Ok(vec![17, 42].into()),
+            runtime: Duration::from_millis(12),
+        };
+        assert_eq!(outcome.to_string(), "  4 |    2 |   12.000ms | 17, 42");
+
+        let outcome = Outcome {
+            answer: Err("no solution".into()),
+            ..outcome
+        };
+        assert!(outcome.to_string().ends_with("| error: no solution"));
+    }
+
+    #[test]
+    fn test_every_day_is_registered() {
+        let registry = registry();
+        assert_eq!(
+            registry.days().collect::<Vec<_>>(),
+            (1..=25).collect::<Vec<_>>()
+        );
+        for (day, solver) in registry.iter() {
+            assert_eq!(solver.has_part2(), day != 25, "day {}", day);
+        }
+    }
+}
@@ -0,0 +1,328 @@
+//! Generate a new day crate which follows the conventions of the rest of the workspace.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const CARGO_TEMPLATE: &str = include_str!("../templates/Cargo.toml.in");
+const LIB_TEMPLATE: &str = include_str!("../templates/lib.rs.in");
+const MAIN_TEMPLATE: &str = include_str!("../templates/main.rs.in");
+
+/// The root of the workspace containing this crate.
+pub fn default_workspace_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("this crate lives within the workspace")
+        .to_owned()
+}
+
+fn render(template: &str, crate_name: &str, day: u8) -> String {
+    template
+        .replace("{{crate}}", crate_name)
+        .replace("{{day}}", &day.to_string())
+}
+
+/// Insert `member` into the `members` list of the workspace manifest.
+fn add_workspace_member(manifest: &str, member: &str) -> Result<String, Error> {
+    let quoted = format!("\"{}\"", member);
+    let members_start = manifest
+        .find("members = [")
+        .ok_or(Error::MalformedManifest)?;
+    let members_end = members_start
+        + manifest[members_start..]
+            .find(']')
+            .ok_or(Error::MalformedManifest)?;
+    if manifest[members_start..members_end].contains(&quoted) {
+        return Err(Error::AlreadyMember(member.to_owned()));
+    }
+
+    // the closing bracket is on its own line; insert the new member just before that line
+    let line_start = manifest[..members_end]
+        .rfind('\n')
+        .map(|idx| idx + 1)
+        .unwrap_or(members_end);
+    let mut out = String::with_capacity(manifest.len() + quoted.len() + 7);
+    out.push_str(&manifest[..line_start]);
+    out.push_str("    ");
+    out.push_str(&quoted);
+    out.push_str(",\n");
+    out.push_str(&manifest[line_start..]);
+    Ok(out)
+}
+
+/// Insert a path dependency on the sibling crate `name` into the `[dependencies]` of a manifest,
+/// keeping the dependencies sorted.
+fn add_dependency(manifest: &str, name: &str) -> Result<String, Error> {
+    let section_start = manifest
+        .find("[dependencies]\n")
+        .map(|idx| idx + "[dependencies]\n".len())
+        .ok_or(Error::MalformedManifest)?;
+
+    let mut insert_at = None;
+    let mut after_last = section_start;
+    let mut offset = section_start;
+    for line in manifest[section_start..].split_inclusive('\n') {
+        if line.starts_with('[') {
+            break;
+        }
+        if let Some((key, _)) = line.split_once('=') {
+            let key = key.trim();
+            if key == name {
+                return Err(Error::AlreadyDependency(name.to_owned()));
+            }
+            if insert_at.is_none() && key > name {
+                insert_at = Some(offset);
+            }
+            after_last = offset + line.len();
+        }
+        offset += line.len();
+    }
+    let insert_at = insert_at.unwrap_or(after_last);
+
+    let line = format!("{} = {{ path = \"../{}\" }}\n", name, name);
+    let mut out = String::with_capacity(manifest.len() + line.len());
+    out.push_str(&manifest[..insert_at]);
+    out.push_str(&line);
+    out.push_str(&manifest[insert_at..]);
+    Ok(out)
+}
+
+/// Register the day crate `name` at the end of `run::registry()`.
+fn add_registration(run: &str, name: &str) -> Result<String, Error> {
+    let call = format!("    {}::register(&mut registry);\n", name);
+    let registry_start = run
+        .find("pub fn registry() -> Registry {")
+        .ok_or(Error::MalformedRegistry)?;
+    let registry_end = registry_start
+        + run[registry_start..]
+            .find("    registry\n}")
+            .ok_or(Error::MalformedRegistry)?;
+    if run[registry_start..registry_end].contains(&call) {
+        return Err(Error::AlreadyRegistered(name.to_owned()));
+    }
+
+    let mut out = String::with_capacity(run.len() + call.len());
+    out.push_str(&run[..registry_end]);
+    out.push_str(&call);
+    out.push_str(&run[registry_end..]);
+    Ok(out)
+}
+
+/// Create a new `dayNN` crate in the workspace at `root`, and register it with the runner.
+///
+/// Returns the path to the new crate.
+pub fn scaffold(root: &Path, day: u8) -> Result<PathBuf, Error> {
+    let crate_name = format!("day{:02}", day);
+    let crate_dir = root.join(&crate_name);
+    if crate_dir.exists() {
+        return Err(Error::AlreadyExists(crate_dir));
+    }
+
+    let manifest_path = root.join("Cargo.toml");
+    let manifest = add_workspace_member(&fs::read_to_string(&manifest_path)?, &crate_name)?;
+    let runner_manifest_path = root.join("aoc2016").join("Cargo.toml");
+    let runner_manifest = add_dependency(&fs::read_to_string(&runner_manifest_path)?, &crate_name)?;
+    let registry_path = root.join("aoc2016").join("src").join("run.rs");
+    let registry = add_registration(&fs::read_to_string(&registry_path)?, &crate_name)?;
+
+    fs::create_dir_all(crate_dir.join("src"))?;
+    fs::write(
+        crate_dir.join("Cargo.toml"),
+        render(CARGO_TEMPLATE, &crate_name, day),
+    )?;
+    fs::write(
+        crate_dir.join("src").join("lib.rs"),
+        render(LIB_TEMPLATE, &crate_name, day),
+    )?;
+    fs::write(
+        crate_dir.join("src").join("main.rs"),
+        render(MAIN_TEMPLATE, &crate_name, day),
+    )?;
+    fs::write(manifest_path, manifest)?;
+    fs::write(runner_manifest_path, runner_manifest)?;
+    fs::write(registry_path, registry)?;
+
+    Ok(crate_dir)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("{0} already exists")]
+    AlreadyExists(PathBuf),
+    #[error("{0} is already a workspace member")]
+    AlreadyMember(String),
+    #[error("{0} is already a dependency of the runner")]
+    AlreadyDependency(String),
+    #[error("{0} is already registered with the runner")]
+    AlreadyRegistered(String),
+    #[error("could not find the workspace members or dependencies list")]
+    MalformedManifest,
+    #[error("could not find the runner's registry")]
+    MalformedRegistry,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"[workspace]
+members = [
+    "day01", "day02",
+]
+"#;
+
+    #[test]
+    fn test_add_workspace_member() {
+        assert_eq!(
+            add_workspace_member(MANIFEST, "day03").unwrap(),
+            r#"[workspace]
+members = [
+    "day01", "day02",
+    "day03",
+]
+"#
+        );
+    }
+
+    #[test]
+    fn test_add_existing_member() {
+        assert!(matches!(
+            add_workspace_member(MANIFEST, "day02"),
+            Err(Error::AlreadyMember(_))
+        ));
+    }
+
+    #[test]
+    fn test_add_dependency() {
+        let manifest = r#"[package]
+name = "aoc2016"
+
+[dependencies]
+aoclib = "0.1"
+day01 = { path = "../day01" }
+day02 = { path = "../day02" }
+rayon = "1.5.1"
+
+[dev-dependencies]
+tempfile = "3"
+"#;
+        let expect = r#"[package]
+name = "aoc2016"
+
+[dependencies]
+aoclib = "0.1"
+day01 = { path = "../day01" }
+day02 = { path = "../day02" }
+day03 = { path = "../day03" }
+rayon = "1.5.1"
+
+[dev-dependencies]
+tempfile = "3"
+"#;
+        assert_eq!(add_dependency(manifest, "day03").unwrap(), expect);
+        assert!(matches!(
+            add_dependency(expect, "day03"),
+            Err(Error::AlreadyDependency(_))
+        ));
+        assert_eq!(
+            add_dependency("[dependencies]\naoclib = \"0.1\"\n", "day01").unwrap(),
+            "[dependencies]\naoclib = \"0.1\"\nday01 = { path = \"../day01\" }\n"
+        );
+    }
+
+    #[test]
+    fn test_add_registration() {
+        let run = r#"pub fn registry() -> Registry {
+    let mut registry = Registry::new();
+    day01::register(&mut registry);
+    registry
+}
+"#;
+        let expect = r#"pub fn registry() -> Registry {
+    let mut registry = Registry::new();
+    day01::register(&mut registry);
+    day02::register(&mut registry);
+    registry
+}
+"#;
+        assert_eq!(add_registration(run, "day02").unwrap(), expect);
+        assert!(matches!(
+            add_registration(expect, "day02"),
+            Err(Error::AlreadyRegistered(_))
+        ));
+    }
+
+    /// Copy the parts of the workspace which a new day depends on or edits into `dir`.
+    fn copy_workspace(root: &Path, dir: &Path) {
+        fs::write(
+            dir.join("Cargo.toml"),
+            "[workspace]\nmembers = [\n    \"aoc2016-solver\",\n]\n",
+        )
+        .unwrap();
+        if let Ok(lock) = fs::read(root.join("Cargo.lock")) {
+            fs::write(dir.join("Cargo.lock"), lock).unwrap();
+        }
+        for (krate, files) in &[
+            ("aoc2016", &["Cargo.toml", "src/run.rs"][..]),
+            (
+                "aoc2016-solver",
+                &[
+                    "Cargo.toml",
+                    "src/answer.rs",
+                    "src/input.rs",
+                    "src/lib.rs",
+                    "src/logging.rs",
+                    "src/progress.rs",
+                ][..],
+            ),
+        ] {
+            for file in *files {
+                let to = dir.join(krate).join(file);
+                fs::create_dir_all(to.parent().unwrap()).unwrap();
+                fs::copy(root.join(krate).join(file), to).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_scaffold_checks() {
+        let root = default_workspace_root();
+        let dir = tempfile::tempdir().unwrap();
+        copy_workspace(&root, dir.path());
+
+        let crate_dir = scaffold(dir.path(), 26).unwrap();
+        assert!(fs::read_to_string(dir.path().join("Cargo.toml"))
+            .unwrap()
+            .contains("\"day26\""));
+        assert!(fs::read_to_string(dir.path().join("aoc2016/Cargo.toml"))
+            .unwrap()
+            .contains("day26 = { path = \"../day26\" }"));
+        assert!(fs::read_to_string(dir.path().join("aoc2016/src/run.rs"))
+            .unwrap()
+            .contains("day26::register(&mut registry);"));
+
+        let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+        let status = std::process::Command::new(cargo)
+            .arg("check")
+            .arg("--quiet")
+            .arg("--all-targets")
+            .arg("--manifest-path")
+            .arg(crate_dir.join("Cargo.toml"))
+            .env("CARGO_TARGET_DIR", dir.path().join("target"))
+            .env("RUSTFLAGS", "-D warnings")
+            .status()
+            .unwrap();
+        assert!(status.success(), "the scaffolded crate does not check");
+    }
+
+    #[test]
+    fn test_render() {
+        let main = render(MAIN_TEMPLATE, "day26", 26);
+        assert!(main.contains("use day26::{part1, part2};"));
+        assert!(main.contains("const DAY: u8 = 26;"));
+        assert!(!main.contains("{{"));
+    }
+}
@@ -0,0 +1,291 @@
+//! Check each day's solutions against the examples given in its puzzle text.
+//!
+//! Unlike the unit tests, each example is written to a file, read back with [`read_input`] and
+//! solved by its day's [`Solver`], which is the path `run` takes for a real input. The same file
+//! is then solved by the day's own `part1` or `part2`, which take a path and back its binary.
+//! Day 12's parts take a parsed program instead of a path, so only its `Solver` is checked.
+
+use crate::run::{read_input, registry};
+use aoc2016_solver::{each_line, Answer, Example, Silent, Solver};
+use color_eyre::eyre::{eyre, Result};
+use std::{fmt, io::Write, path::Path};
+use tempfile::TempPath;
+
+/// The result of checking a single example.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub day: u8,
+    pub part: u8,
+    /// The index of this example among its day's examples.
+    pub index: usize,
+    /// `Err` describes why the example failed.
+    pub result: std::result::Result<(), String>,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "day{:02} part {} example {}: ",
+            self.day, self.part, self.index
+        )?;
+        match &self.result {
+            Ok(()) => write!(f, "ok"),
+            Err(err) => write!(f, "FAILED: {}", err),
+        }
+    }
+}
+
+/// A day's own `part1` or `part2`, converting its answer just as its `Solver` does.
+type PathPart = fn(&Path) -> aoc2016_solver::Result<Answer>;
+
+/// The path-based part `part` of `day`, for every day which has examples.
+fn path_part(day: u8, part: u8) -> Option<PathPart> {
+    let path_part: PathPart = match (day, part) {
+        (1, 1) => |path| Ok(day01::part1(path)?.into()),
+        (1, 2) => |path| Ok(day01::part2(path)?.into()),
+        (2, 1) => |path| Ok(day02::part1(path)?.into()),
+        (2, 2) => |path| Ok(day02::part2(path)?.into()),
+        (3, 1) => |path| Ok(day03::part1(path)?.into()),
+        (3, 2) => |path| Ok(day03::part2(path)?.into()),
+        (4, 1) => |path| Ok(day04::part1(path)?.into()),
+        (4, 2) => |path| Ok(day04::part2(path)?.into()),
+        (5, 1) => |path| {
+            each_line(day05::part1(path, &Silent)?, |password| {
+                Ok(password.as_str().to_owned())
+            })
+        },
+        (5, 2) => |path| {
+            each_line(day05::part2(path, &Silent)?, |password| {
+                Ok(password.as_str().to_owned())
+            })
+        },
+        (6, 1) => |path| {
+            Ok(day06::part1(path, day06::RaggedPolicy::Strict)?
+                .message
+                .into())
+        },
+        (6, 2) => |path| {
+            Ok(day06::part2(path, day06::RaggedPolicy::Strict)?
+                .message
+                .into())
+        },
+        (7, 1) => |path| Ok(day07::part1(path)?.into()),
+        (7, 2) => |path| Ok(day07::part2(path)?.into()),
+        (8, 1) => |path| Ok(day08::part1(path, day08::Dialect::Standard)?.into()),
+        (8, 2) => |path| {
+            let reading = day08::part2(path, day08::Dialect::Standard)?.text_or_annotated()?;
+            Ok(if reading.contains('\n') {
+                reading.lines().collect::<Vec<_>>().into()
+            } else {
+                reading.into()
+            })
+        },
+        // examples are compared as displayed, so the lengths needn't fit an `Answer::Integer`
+        (9, 1) => |path| Ok(day09::part1(path)?.into()),
+        (9, 2) => |path| each_line(day09::part2(path)?, |length| Ok(length.to_string())),
+        (14, 1) => |path| {
+            each_line(day14::part1(path, true, &Silent)?, |pad| {
+                Ok(pad.final_index().ok_or(day14::Error::EmptyPad)?)
+            })
+        },
+        (14, 2) => |path| {
+            each_line(day14::part2(path, true, &Silent)?, |pad| {
+                Ok(pad.final_index().ok_or(day14::Error::EmptyPad)?)
+            })
+        },
+        (15, 1) => |path| Ok(day15::part1(path)?.into()),
+        (15, 2) => |path| Ok(day15::part2(path)?.into()),
+        (17, 1) => |path| Ok(day17::part1(path)?.into()),
+        (17, 2) => |path| Ok(day17::part2(path)?.into()),
+        (19, 1) => |path| Ok(day19::part1(path)?.into()),
+        (19, 2) => |path| Ok(day19::part2(path)?.into()),
+        (20, 1) => |path| Ok(day20::part1(path, 0, u32::MAX)?.into()),
+        (20, 2) => |path| Ok(day20::part2(path, 0, u32::MAX)?.into()),
+        (22, 1) => |path| Ok(day22::part1(path)?.into()),
+        (22, 2) => |path| Ok(day22::part2(path)?.len().into()),
+        (23, 1) => |path| Ok(day23::part1(path)?.0.into()),
+        (23, 2) => |path| Ok(day23::part2(path)?.0.into()),
+        (24, 1) => |path| Ok(day24::part1(path)?.0.into()),
+        (24, 2) => |path| Ok(day24::part2(path)?.0.into()),
+        _ => return None,
+    };
+    Some(path_part)
+}
+
+/// `Err` describes how `answer` differs from the one `example` expects.
+fn expect(example: &Example, answer: &Answer) -> std::result::Result<(), String> {
+    if example.matches(answer) {
+        Ok(())
+    } else {
+        Err(format!(
+            "expected {:?}, got {:?}",
+            example.answer,
+            answer.to_string()
+        ))
+    }
+}
+
+/// Write example `index` of `day` to a newly created temporary file, which is removed when the
+/// returned path is dropped.
+fn example_file(day: u8, index: usize, example: &Example) -> std::io::Result<TempPath> {
+    let mut file = tempfile::Builder::new()
+        .prefix(&format!("aoc2016-selftest-day{:02}-{}-", day, index))
+        .suffix(".txt")
+        .tempfile()?;
+    file.write_all(example.input.as_bytes())?;
+    Ok(file.into_temp_path())
+}
+
+/// Write `example` to a file, then solve it from that file, both with `solver` and with
+/// `path_part` if given.
+fn check_example(
+    solver: &dyn Solver,
+    path_part: Option<PathPart>,
+    day: u8,
+    index: usize,
+    example: &Example,
+) -> Check {
+    let check = || {
+        let path = example_file(day, index, example)
+            .map_err(|err| format!("writing the example: {}", err))?;
+        let input = read_input(day, Some(path.to_path_buf())).map_err(|err| err.to_string())?;
+        let answer = solver
+            .solve(example.part, &input)
+            .map_err(|err| err.to_string())?;
+        expect(example, &answer)?;
+        if let Some(path_part) = path_part {
+            path_part(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|answer| expect(example, &answer))
+                .map_err(|err| format!("part{}(path): {}", example.part, err))?;
+        }
+        Ok(())
+    };
+    Check {
+        day,
+        part: example.part,
+        index,
+        result: check(),
+    }
+}
+
+/// Check the examples of each of `days`, or of every day when empty, printing each result as
+/// it completes.
+pub fn selftest(days: &[u8]) -> Result<Vec<Check>> {
+    let registry = registry();
+    let days = if days.is_empty() {
+        registry.days().collect()
+    } else {
+        days.to_vec()
+    };
+
+    let mut checks = Vec::new();
+    for day in days {
+        let solver = registry
+            .get(day)
+            .ok_or_else(|| eyre!("no such day: {}", day))?;
+        for (index, example) in solver.examples().iter().enumerate() {
+            let path_part = path_part(day, example.part);
+            let check = check_example(solver, path_part, day, index, example);
+            println!("{}", check);
+            checks.push(check);
+        }
+    }
+
+    let failures = checks.iter().filter(|check| check.result.is_err()).count();
+    println!("{} examples checked, {} failed", checks.len(), failures);
+    if failures > 0 {
+        return Err(eyre!("{} example(s) failed", failures));
+    }
+    Ok(checks)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoc2016_solver::Answer;
+
+    struct Lengths;
+
+    impl Solver for Lengths {
+        fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+            Ok(input.len().into())
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let example = Example {
+            part: 1,
+            input: "abc",
+            answer: "3",
+        };
+        let check = check_example(&Lengths, None, 1, 0, &example);
+        assert_eq!(check.result, Ok(()));
+        assert_eq!(check.to_string(), "day01 part 1 example 0: ok");
+
+        let example = Example {
+            answer: "4",
+            ..example
+        };
+        let check = check_example(&Lengths, None, 1, 1, &example);
+        assert_eq!(
+            check.to_string(),
+            r#"day01 part 1 example 1: FAILED: expected "4", got "3""#
+        );
+
+        // `Lengths` has no part 2
+        let example = Example { part: 2, ..example };
+        assert!(check_example(&Lengths, None, 1, 2, &example)
+            .result
+            .is_err());
+    }
+
+    #[test]
+    fn test_path_part_is_checked() {
+        fn four(_: &Path) -> aoc2016_solver::Result<Answer> {
+            Ok(4.into())
+        }
+
+        let example = Example {
+            part: 1,
+            input: "abc",
+            answer: "3",
+        };
+        let check = check_example(&Lengths, Some(four), 1, 0, &example);
+        assert_eq!(
+            check.to_string(),
+            r#"day01 part 1 example 0: FAILED: part1(path): expected "3", got "4""#
+        );
+    }
+
+    #[test]
+    fn test_examples_have_path_parts() {
+        let registry = registry();
+        for day in registry.days().filter(|&day| day != 12) {
+            for example in registry.get(day).unwrap().examples() {
+                assert!(
+                    path_part(day, example.part).is_some(),
+                    "day {} part {} has no path-based part",
+                    day,
+                    example.part
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_example_files_are_removed() {
+        let example = Example {
+            part: 1,
+            input: "abc",
+            answer: "3",
+        };
+        let path = example_file(3, 0, &example).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "abc");
+        let kept = path.to_path_buf();
+        drop(path);
+        assert!(!kept.exists());
+    }
+}
@@ -0,0 +1,134 @@
+//! Glyph data for the fonts Advent of Code uses for banner-style answers.
+//!
+//! Each glyph is listed as its rendering, row by row, followed by the letter it represents.
+
+/// The small font: glyphs are 4 pixels wide (`Y` is 5) and 6 tall, on a pitch of 5 columns.
+pub const SMALL: &[(&[&str], char)] = &[
+    (&[".##.", "#..#", "#..#", "####", "#..#", "#..#"], 'A'),
+    (&["###.", "#..#", "###.", "#..#", "#..#", "###."], 'B'),
+    (&[".##.", "#..#", "#...", "#...", "#..#", ".##."], 'C'),
+    (&["####", "#...", "###.", "#...", "#...", "####"], 'E'),
+    (&["####", "#...", "###.", "#...", "#...", "#..."], 'F'),
+    (&[".##.", "#..#", "#...", "#.##", "#..#", ".###"], 'G'),
+    (&["#..#", "#..#", "####", "#..#", "#..#", "#..#"], 'H'),
+    (&[".###", "..#.", "..#.", "..#.", "..#.", ".###"], 'I'),
+    (&["..##", "...#", "...#", "...#", "#..#", ".##."], 'J'),
+    (&["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"], 'K'),
+    (&["#...", "#...", "#...", "#...", "#...", "####"], 'L'),
+    (&[".##.", "#..#", "#..#", "#..#", "#..#", ".##."], 'O'),
+    (&["###.", "#..#", "#..#", "###.", "#...", "#..."], 'P'),
+    (&["###.", "#..#", "#..#", "###.", "#.#.", "#..#"], 'R'),
+    (&[".###", "#...", "#...", ".##.", "...#", "###."], 'S'),
+    (&["#..#", "#..#", "#..#", "#..#", "#..#", ".##."], 'U'),
+    (&["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."], 'Y'),
+    (&["####", "...#", "..#.", ".#..", "#...", "####"], 'Z'),
+];
+
+/// The large font: glyphs are 6 pixels wide and 10 tall, on a pitch of 8 columns.
+pub const LARGE: &[(&[&str], char)] = &[
+    (
+        &[
+            "..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#",
+            "#....#", "#....#",
+        ],
+        'A',
+    ),
+    (
+        &[
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#",
+            "#....#", "#####.",
+        ],
+        'B',
+    ),
+    (
+        &[
+            ".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+            "#....#", ".####.",
+        ],
+        'C',
+    ),
+    (
+        &[
+            "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "######",
+        ],
+        'E',
+    ),
+    (
+        &[
+            "######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "#.....",
+        ],
+        'F',
+    ),
+    (
+        &[
+            ".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#",
+            "#...##", ".###.#",
+        ],
+        'G',
+    ),
+    (
+        &[
+            "#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#",
+            "#....#", "#....#",
+        ],
+        'H',
+    ),
+    (
+        &[
+            "...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.",
+            "#...#.", ".###..",
+        ],
+        'J',
+    ),
+    (
+        &[
+            "#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..",
+            "#...#.", "#....#",
+        ],
+        'K',
+    ),
+    (
+        &[
+            "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....",
+            "#.....", "######",
+        ],
+        'L',
+    ),
+    (
+        &[
+            "#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##",
+            "#...##", "#....#",
+        ],
+        'N',
+    ),
+    (
+        &[
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....",
+            "#.....", "#.....",
+        ],
+        'P',
+    ),
+    (
+        &[
+            "#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.",
+            "#....#", "#....#",
+        ],
+        'R',
+    ),
+    (
+        &[
+            "#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.",
+            "#....#", "#....#",
+        ],
+        'X',
+    ),
+    (
+        &[
+            "######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....",
+            "#.....", "######",
+        ],
+        'Z',
+    ),
+];
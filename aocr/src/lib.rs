@@ -0,0 +1,314 @@
+//! Optical character recognition for Advent of Code's banner-style answers.
+//!
+//! Some puzzles don't produce their answer directly: instead, they produce a grid of pixels
+//! which spell out the answer in large letters. This crate turns such a grid back into text.
+//!
+//! Two fonts are known: the small font is 6 pixels tall, and the large font is 10 pixels tall.
+//! Neither glyph table is known to be complete, so fonts can be extended with new glyphs,
+//! either explicitly or by training them against a rendering whose text is known.
+
+use aoclib::geometry::{tile::Bool, Map};
+use std::collections::HashMap;
+
+mod fonts;
+
+/// `true` when this character of a rendering represents a lit pixel.
+fn is_lit(ch: char) -> bool {
+    ch == '#' || ch == '█'
+}
+
+/// A bitmap font: a table of glyphs of a fixed height, laid out at a fixed pitch.
+///
+/// The pitch is the horizontal distance between the left edges of adjacent glyphs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Font {
+    height: usize,
+    pitch: usize,
+    glyphs: HashMap<Vec<bool>, char>,
+}
+
+impl Font {
+    /// Create a font with no known glyphs.
+    pub fn new(height: usize, pitch: usize) -> Font {
+        Font {
+            height,
+            pitch,
+            glyphs: HashMap::new(),
+        }
+    }
+
+    fn from_table(height: usize, pitch: usize, table: &[(&[&str], char)]) -> Font {
+        let mut font = Font::new(height, pitch);
+        for (rows, letter) in table {
+            font.insert(rows, *letter)
+                .expect("built-in glyph tables are well-formed");
+        }
+        font
+    }
+
+    /// The 6-pixel font, used in most years.
+    pub fn small() -> Font {
+        Font::from_table(6, 5, fonts::SMALL)
+    }
+
+    /// The 10-pixel font, used in 2018.
+    pub fn large() -> Font {
+        Font::from_table(10, 8, fonts::LARGE)
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn pitch(&self) -> usize {
+        self.pitch
+    }
+
+    /// Compute the lookup key for the glyph at `index` within `rows`.
+    ///
+    /// Pixels beyond the end of a row are considered unlit.
+    fn key(&self, rows: &[&str], index: usize) -> Vec<bool> {
+        let mut key = Vec::with_capacity(self.height * self.pitch);
+        for row in rows {
+            let mut pixels = row.chars().skip(index * self.pitch);
+            for _ in 0..self.pitch {
+                key.push(pixels.next().map(is_lit).unwrap_or_default());
+            }
+        }
+        key
+    }
+
+    fn render_key(&self, key: &[bool]) -> String {
+        let mut out = String::with_capacity(key.len() + self.height);
+        for row in key.chunks(self.pitch) {
+            out.extend(row.iter().map(|&lit| if lit { '#' } else { '.' }));
+            out.push('\n');
+        }
+        out
+    }
+
+    fn check_height(&self, rows: &[&str]) -> Result<(), Error> {
+        if rows.len() == self.height {
+            Ok(())
+        } else {
+            Err(Error::WrongHeight {
+                expected: self.height,
+                actual: rows.len(),
+            })
+        }
+    }
+
+    /// Add a glyph to this font, replacing any previous letter for it.
+    ///
+    /// `rows` is the rendering of the glyph, top to bottom. Rows may be narrower than the pitch.
+    pub fn insert(&mut self, rows: &[&str], letter: char) -> Result<(), Error> {
+        self.check_height(rows)?;
+        self.glyphs.insert(self.key(rows, 0), letter);
+        Ok(())
+    }
+
+    /// Learn every glyph of `rendering`, given that it spells `text`.
+    ///
+    /// Spaces in `text` correspond to blank glyphs and are not learned.
+    pub fn train(&mut self, rendering: &str, text: &str) -> Result<(), Error> {
+        let rows = split_rows(rendering);
+        self.check_height(&rows)?;
+        let n_glyphs = self.n_glyphs(&rows);
+        let n_letters = text.chars().count();
+        if n_glyphs != n_letters {
+            return Err(Error::LengthMismatch {
+                glyphs: n_glyphs,
+                letters: n_letters,
+            });
+        }
+        for (index, letter) in text.chars().enumerate() {
+            if letter != ' ' {
+                self.glyphs.insert(self.key(&rows, index), letter);
+            }
+        }
+        Ok(())
+    }
+
+    fn n_glyphs(&self, rows: &[&str]) -> usize {
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or_default();
+        width.div_ceil(self.pitch)
+    }
+
+    /// Read each glyph of `rendering` in turn: its letter, a space for a blank glyph, or `None`
+    /// for a glyph this font doesn't know.
+    pub fn glyphs(&self, rendering: &str) -> Result<Vec<Option<char>>, Error> {
+        let rows = split_rows(rendering);
+        self.check_height(&rows)?;
+        Ok((0..self.n_glyphs(&rows))
+            .map(|index| {
+                let key = self.key(&rows, index);
+                if key.iter().all(|&lit| !lit) {
+                    Some(' ')
+                } else {
+                    self.glyphs.get(&key).copied()
+                }
+            })
+            .collect())
+    }
+
+    /// Read the text spelled out by `rendering`.
+    ///
+    /// Lit pixels are `#` (or `█`); any other character is unlit. Blank glyphs become spaces.
+    pub fn recognize(&self, rendering: &str) -> Result<String, Error> {
+        let mut text = String::new();
+        for (index, letter) in self.glyphs(rendering)?.into_iter().enumerate() {
+            match letter {
+                Some(letter) => text.push(letter),
+                None => {
+                    return Err(Error::UnknownGlyph {
+                        index,
+                        glyph: self.render_key(&self.key(&split_rows(rendering), index)),
+                    })
+                }
+            }
+        }
+        Ok(text.trim_end().to_owned())
+    }
+
+    /// `rendering`, followed by a line with the letter read from each glyph beneath its left
+    /// edge, and `?` beneath each glyph this font doesn't know.
+    ///
+    /// Unlike `recognize`, this doesn't fail on unknown glyphs, so a human can read them instead.
+    pub fn annotate(&self, rendering: &str) -> Result<String, Error> {
+        let glyphs = self.glyphs(rendering)?;
+        let mut annotated = split_rows(rendering).join("\n");
+        annotated.push('\n');
+        let marks: String = glyphs
+            .into_iter()
+            .map(|letter| format!("{:<pitch$}", letter.unwrap_or('?'), pitch = self.pitch))
+            .collect();
+        annotated.push_str(marks.trim_end());
+        Ok(annotated)
+    }
+}
+
+fn split_rows(rendering: &str) -> Vec<&str> {
+    rendering.trim_matches('\n').lines().collect()
+}
+
+/// The font for a rendering of this height.
+fn font_for(rendering: &str) -> Result<Font, Error> {
+    let height = split_rows(rendering).len();
+    match height {
+        6 => Ok(Font::small()),
+        10 => Ok(Font::large()),
+        _ => Err(Error::UnsupportedHeight(height)),
+    }
+}
+
+/// Read the text spelled out by `rendering`, choosing the font by the height of the rendering.
+pub fn recognize(rendering: &str) -> Result<String, Error> {
+    font_for(rendering)?.recognize(rendering)
+}
+
+/// Read the text spelled out by the lit pixels of `map`.
+pub fn recognize_map(map: &Map<Bool>) -> Result<String, Error> {
+    recognize(&map.to_string())
+}
+
+/// Annotate `rendering` with the letters read from it, choosing the font by the height of the
+/// rendering; see [`Font::annotate`].
+pub fn annotate(rendering: &str) -> Result<String, Error> {
+    font_for(rendering)?.annotate(rendering)
+}
+
+/// Annotate a rendering of `map` with the letters read from it; see [`Font::annotate`].
+pub fn annotate_map(map: &Map<Bool>) -> Result<String, Error> {
+    annotate(&map.to_string())
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("no font is known with height {0}")]
+    UnsupportedHeight(usize),
+    #[error("expected a rendering {expected} pixels tall; got {actual}")]
+    WrongHeight { expected: usize, actual: usize },
+    #[error("rendering contains {glyphs} glyphs but text has {letters} letters")]
+    LengthMismatch { glyphs: usize, letters: usize },
+    #[error("unknown glyph at index {index}:\n{glyph}")]
+    UnknownGlyph { index: usize, glyph: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Render `text` in the given font's glyph table, using the font's pitch.
+    fn render(table: &[(&[&str], char)], pitch: usize, text: &str) -> String {
+        let height = table[0].0.len();
+        let mut rows = vec![String::new(); height];
+        for letter in text.chars() {
+            let (glyph, _) = table
+                .iter()
+                .find(|(_, table_letter)| *table_letter == letter)
+                .unwrap();
+            for (row, glyph_row) in rows.iter_mut().zip(glyph.iter()) {
+                row.push_str(&format!("{:.<width$}", glyph_row, width = pitch));
+            }
+        }
+        rows.join("\n")
+    }
+
+    #[test]
+    fn test_small_font() {
+        let text = "ABCEFGHIJKLOPRSUYZ";
+        assert_eq!(recognize(&render(fonts::SMALL, 5, text)).unwrap(), text);
+    }
+
+    #[test]
+    fn test_large_font() {
+        let text = "ABCEFGHJKLNPRXZ";
+        assert_eq!(recognize(&render(fonts::LARGE, 8, text)).unwrap(), text);
+    }
+
+    #[test]
+    fn test_unknown_glyph() {
+        let rendering = "#.#.\n.#..\n#.#.\n....\n....\n....";
+        assert!(matches!(
+            recognize(rendering),
+            Err(Error::UnknownGlyph { index: 0, .. })
+        ));
+    }
+
+    #[test]
+    fn test_annotate() {
+        let rendering = render(fonts::SMALL, 5, "HI")
+            .lines()
+            .zip(&["#.#.", ".#..", "#.#.", "....", "....", "...."])
+            .map(|(known, new)| format!("{}{}", known, new))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert_eq!(
+            Font::small().glyphs(&rendering).unwrap(),
+            vec![Some('H'), Some('I'), None]
+        );
+        let annotated = annotate(&rendering).unwrap();
+        let (screen, marks) = annotated.rsplit_once('\n').unwrap();
+        assert_eq!(screen, rendering);
+        assert_eq!(marks, "H    I    ?");
+    }
+
+    #[test]
+    fn test_train() {
+        let rendering = render(fonts::SMALL, 5, "HI")
+            .lines()
+            .zip(&["#.#.", ".#..", "#.#.", "....", "....", "...."])
+            .map(|(known, new)| format!("{}{}", known, new))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut font = Font::small();
+        assert!(font.recognize(&rendering).is_err());
+        font.train(&rendering, "HIx").unwrap();
+        assert_eq!(font.recognize(&rendering).unwrap(), "HIx");
+    }
+}
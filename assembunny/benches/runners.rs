@@ -0,0 +1,60 @@
+//! Compares the interpreter with the compiled runner on a day 23 program, which spends nearly all
+//! its time in tight arithmetic loops.
+
+use assembunny::{parse_instructions, Computer, Integer, Register};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+/// A day 23 puzzle input. Inputs differ only in the two constants near the end.
+const DAY23: &str = "
+cpy a b
+dec b
+cpy a d
+cpy 0 a
+cpy b c
+inc a
+dec c
+jnz c -2
+dec d
+jnz d -5
+dec b
+cpy b c
+cpy c d
+dec d
+inc c
+jnz d -2
+tgl c
+cpy -16 c
+jnz 1 c
+cpy 94 c
+jnz 80 d
+inc a
+inc d
+jnz d -2
+inc c
+jnz c -5
+";
+
+fn runners(c: &mut Criterion) {
+    let program = parse_instructions(DAY23).unwrap();
+    let computer = |eggs: Integer| {
+        let mut computer = Computer::from_program(program.clone());
+        computer[Register::A] = eggs;
+        computer
+    };
+
+    let mut group = c.benchmark_group("day 23");
+    for eggs in 7..=9 {
+        group.bench_with_input(BenchmarkId::new("try_run", eggs), &eggs, |b, &eggs| {
+            b.iter(|| computer(eggs).try_run().unwrap())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("try_run_compiled", eggs),
+            &eggs,
+            |b, &eggs| b.iter(|| computer(eggs).try_run_compiled().unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, runners);
+criterion_main!(benches);
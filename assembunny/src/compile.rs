@@ -0,0 +1,340 @@
+//! Lowering programs into basic blocks, for faster execution than the interpreter.
+//!
+//! A block is a run of straight-line arithmetic which executes without any dispatch on the
+//! instruction pointer, ending in a direct jump. Instructions with side effects beyond the
+//! registers (`tgl`, `out`, and the memory instructions) end a block and are handed to the
+//! interpreter; when a `tgl` rewrites the program, it is compiled afresh.
+
+use crate::{Computer, CostModel, Error, Instruction, Integer, Register, Value};
+
+/// A block's straight-line arithmetic, with destinations known to be registers.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Op {
+    Copy(Value, Register),
+    Increase(Register),
+    Decrease(Register),
+    Add(Value, Register),
+    Mul(Value, Register),
+}
+
+/// How control leaves a block.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Exit {
+    /// Continue at this address, which starts another block or is past the end of the program.
+    Next(usize),
+    /// The `jnz` at `ip` ends the block.
+    Jnz {
+        ip: usize,
+        condition: Value,
+        offset: Value,
+    },
+    /// The instruction at this address must be interpreted.
+    Interpret(usize),
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+struct Block {
+    /// Each op with the address of the instruction it came from.
+    ///
+    /// Instructions with no effect, such as `nop` or `inc 1`, have no op.
+    ops: Vec<(usize, Op)>,
+    /// The address of the first instruction.
+    start: usize,
+    /// Instructions executed by running the whole block, including a final `jnz`.
+    instructions: u64,
+    /// Cycles consumed by running the whole block, including a final `jnz`.
+    cycles: u64,
+    exit: Exit,
+}
+
+/// A program lowered into basic blocks.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct CompiledProgram {
+    blocks: Vec<Block>,
+    /// For each address, the index of the block starting there, if any.
+    ///
+    /// Register-relative jumps may land within a block; execution then falls back to the
+    /// interpreter until it reaches the start of a block.
+    entries: Vec<Option<usize>>,
+}
+
+/// `true` for instructions which must be interpreted rather than compiled.
+fn interpreted(instruction: Instruction) -> bool {
+    match instruction {
+        Instruction::Toggle(_) | Instruction::Out(_) => true,
+        #[cfg(feature = "memory")]
+        Instruction::Load(..) | Instruction::Store(..) => true,
+        _ => false,
+    }
+}
+
+impl CompiledProgram {
+    pub fn new(program: &[Instruction], cost_model: &CostModel) -> CompiledProgram {
+        let mut leaders = vec![false; program.len() + 1];
+        leaders[0] = true;
+        for (ip, &instruction) in program.iter().enumerate() {
+            match instruction {
+                Instruction::Jnz(_, offset) => {
+                    leaders[ip + 1] = true;
+                    if let Value::Value(offset) = offset {
+                        let target = (ip as Integer).saturating_add(offset);
+                        if (0..program.len() as Integer).contains(&target) {
+                            leaders[target as usize] = true;
+                        }
+                    }
+                }
+                instruction if interpreted(instruction) => {
+                    leaders[ip] = true;
+                    leaders[ip + 1] = true;
+                }
+                _ => {}
+            }
+        }
+
+        let mut blocks = Vec::new();
+        let mut entries = vec![None; program.len()];
+        for start in (0..program.len()).filter(|&ip| leaders[ip]) {
+            entries[start] = Some(blocks.len());
+            let mut block = Block {
+                ops: Vec::new(),
+                start,
+                instructions: 0,
+                cycles: 0,
+                exit: Exit::Next(program.len()),
+            };
+            for ip in start..program.len() {
+                if ip > start && leaders[ip] {
+                    block.exit = Exit::Next(ip);
+                    break;
+                }
+                let instruction = program[ip];
+                if interpreted(instruction) {
+                    block.exit = Exit::Interpret(ip);
+                    break;
+                }
+                block.instructions += 1;
+                block.cycles += cost_model.cost(instruction);
+                let op = match instruction {
+                    Instruction::Jnz(condition, offset) => {
+                        block.exit = Exit::Jnz {
+                            ip,
+                            condition,
+                            offset,
+                        };
+                        break;
+                    }
+                    Instruction::Copy(value, Value::Register(register)) => {
+                        Op::Copy(value, register)
+                    }
+                    Instruction::Increase(Value::Register(register)) => Op::Increase(register),
+                    Instruction::Decrease(Value::Register(register)) => Op::Decrease(register),
+                    Instruction::Add(value, Value::Register(register)) => Op::Add(value, register),
+                    Instruction::Mul(value, Value::Register(register)) => Op::Mul(value, register),
+                    // everything else is a no-op
+                    _ => continue,
+                };
+                block.ops.push((ip, op));
+            }
+            blocks.push(block);
+        }
+
+        CompiledProgram { blocks, entries }
+    }
+
+    /// The number of basic blocks.
+    pub fn blocks(&self) -> usize {
+        self.blocks.len()
+    }
+}
+
+impl Computer {
+    /// Apply `op`, returning the register which overflowed if it fails.
+    fn apply(&mut self, op: Op) -> Result<(), Register> {
+        let (register, result) = match op {
+            Op::Copy(value, register) => (register, Some(self.value(value))),
+            Op::Increase(register) => (register, self[register].checked_add(1)),
+            Op::Decrease(register) => (register, self[register].checked_sub(1)),
+            Op::Add(value, register) => (register, self[register].checked_add(self.value(value))),
+            Op::Mul(value, register) => (register, self[register].checked_mul(self.value(value))),
+        };
+        self[register] = result.ok_or(register)?;
+        Ok(())
+    }
+
+    /// Continue at `ip`, halting if it is outside the program.
+    fn jump(&mut self, ip: Integer) {
+        self.ip = if (0..self.program.len() as Integer).contains(&ip) {
+            ip as usize
+        } else {
+            !0
+        };
+    }
+
+    /// Interpret a single instruction, returning `Ok(false)` if the program halted.
+    fn interpret(&mut self) -> Result<bool, Error> {
+        match self.try_step() {
+            Ok(running) => Ok(running),
+            Err(Error::Halted) | Err(Error::NoOutput { .. }) => Ok(false),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Run `block`, returning `Ok(false)` if the program halted.
+    fn run_block(&mut self, block: &Block) -> Result<bool, Error> {
+        for &(ip, op) in &block.ops {
+            if let Err(register) = self.apply(op) {
+                // account for the work done, including the failed instruction, as the
+                // interpreter would have
+                for instruction in &self.program[block.start..=ip] {
+                    self.profile.instructions += 1;
+                    self.profile.cycles += self.cost_model.cost(*instruction);
+                }
+                self.profile.count(block.start..ip + 1);
+                self.ip = ip;
+                return Err(Error::Overflow {
+                    ip,
+                    line: self.line_of(ip),
+                    register,
+                });
+            }
+        }
+        self.profile.instructions += block.instructions;
+        self.profile.cycles += block.cycles;
+        self.profile
+            .count(block.start..block.start + block.instructions as usize);
+
+        match block.exit {
+            Exit::Next(ip) => self.jump(ip as Integer),
+            Exit::Jnz {
+                ip,
+                condition,
+                offset,
+            } => {
+                let offset = if self.value(condition) != 0 {
+                    self.value(offset)
+                } else {
+                    1
+                };
+                self.jump((ip as Integer).saturating_add(offset));
+            }
+            Exit::Interpret(ip) => {
+                self.ip = ip;
+                return self.interpret();
+            }
+        }
+        Ok(self.ip != !0)
+    }
+
+    /// Run the compiled program until it halts, or until it has executed at least `limit`
+    /// instructions in total.
+    ///
+    /// Returns `true` if the program halted.
+    fn run_compiled(&mut self, limit: Option<u64>) -> Result<bool, Error> {
+        let mut compiled = CompiledProgram::new(&self.program, &self.cost_model);
+        while self.ip < self.program.len() {
+            if matches!(limit, Some(limit) if self.profile.instructions >= limit) {
+                return Ok(false);
+            }
+
+            let ip = self.ip;
+            let toggled = match self.program[ip] {
+                Instruction::Toggle(value) => {
+                    let target = (ip as Integer).saturating_add(self.value(value));
+                    (0..self.program.len() as Integer).contains(&target)
+                }
+                _ => false,
+            };
+            let running = match compiled.entries[ip] {
+                Some(block) if !toggled => self.run_block(&compiled.blocks[block])?,
+                // mid-block entries and toggles are interpreted
+                _ => self.interpret()?,
+            };
+            if toggled {
+                compiled = CompiledProgram::new(&self.program, &self.cost_model);
+            }
+            if !running {
+                break;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Run this computer until the program terminates naturally, or a register overflows,
+    /// executing it as basic blocks.
+    ///
+    /// This behaves exactly like `try_run`, but much faster for programs which spend their time
+    /// in tight loops.
+    pub fn try_run_compiled(&mut self) -> Result<(), Error> {
+        self.run_compiled(None).map(|_| ())
+    }
+
+    /// As `try_run_compiled`, but stop once at least `limit` instructions have executed in
+    /// total, which may be a few more than `limit`.
+    ///
+    /// Returns `true` if the program halted.
+    pub fn try_run_compiled_for(&mut self, limit: u64) -> Result<bool, Error> {
+        self.run_compiled(Some(limit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conformance::{check, Compiled};
+
+    #[test]
+    fn test_compiled_conforms() {
+        let failures = check(&Compiled).unwrap();
+        let report: Vec<_> = failures.iter().map(ToString::to_string).collect();
+        assert!(failures.is_empty(), "{:#?}", report);
+    }
+
+    #[test]
+    fn test_blocks() {
+        let program = crate::parse_instructions(
+            "cpy 41 a
+            inc a
+            jnz a 2
+            dec a
+            tgl c
+            inc b",
+        )
+        .unwrap();
+        let compiled = CompiledProgram::new(&program, &CostModel::default());
+        // `cpy`..`jnz`, `dec`, `tgl`, `inc`
+        assert_eq!(compiled.blocks(), 4);
+        assert_eq!(
+            compiled.entries,
+            vec![Some(0), None, None, Some(1), Some(2), Some(3)]
+        );
+        assert_eq!(compiled.blocks[1].exit, Exit::Next(4));
+        assert_eq!(compiled.blocks[2].exit, Exit::Interpret(4));
+    }
+
+    #[test]
+    fn test_matches_interpreter() {
+        // multiplies a by b through repeated addition, toggling itself partway
+        let source = "cpy 7 a
+            cpy 6 b
+            cpy a c
+            cpy 0 a
+            cpy c d
+            inc a
+            dec d
+            jnz d -2
+            dec b
+            jnz b -5
+            cpy 2 d
+            tgl d
+            inc c
+            dec c";
+        let mut interpreted = Computer::from_source(source).unwrap();
+        interpreted.try_run().unwrap();
+        let mut compiled = Computer::from_source(source).unwrap();
+        compiled.try_run_compiled().unwrap();
+        assert_eq!(compiled.registers(), interpreted.registers());
+        assert_eq!(compiled.registers()[0], 42);
+        assert_eq!(compiled.program(), interpreted.program());
+        assert_eq!(compiled.profile(), interpreted.profile());
+    }
+}
@@ -0,0 +1,431 @@
+//! A conformance suite which every way of executing assembunny must pass.
+//!
+//! Each case is a small program in a `.txt` file in `conformance/` at the root of this crate.
+//! Lines beginning with `#` are directives or comments; everything else is the program itself.
+//! The directives are:
+//!
+//! - `# init: a=7 c=1`: initial register values; unlisted registers start at 0
+//! - `# expect: a=3 b=0`: register values once the program halts; unlisted registers aren't
+//!   checked
+//! - `# output: 0 1 0`: the values the program sends with `out`. Without this directive the
+//!   program runs with no output channel, so the first `out` halts it.
+//! - `# error: overflow`: the program must stop because a register overflows
+//! - `# bits: 64`: the case only applies when [`Integer`] has this many bits, as when it tests
+//!   the limits of a register. The programs of cases which don't apply aren't parsed, since
+//!   their values may not fit.
+//!
+//! Any other comment describes the case. A [`Backend`] runs the programs;
+//! [`Interpreter`] is the reference implementation, and any alternative should be checked
+//! with [`check`] as well.
+
+use crate::{Computer, Error, Instruction, Integer, Register};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    sync::mpsc::{sync_channel, SyncSender},
+};
+
+/// The most values a case may output. Output channels are buffered to this size, so that
+/// backends may run on the caller's thread.
+pub const MAX_OUTPUT: usize = 1024;
+
+/// The most instructions a case may execute before it is deemed not to halt.
+pub const MAX_STEPS: u64 = 1_000_000;
+
+/// Where the conformance cases live.
+pub fn cases_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("conformance")
+}
+
+/// A way of executing assembunny programs.
+pub trait Backend {
+    fn name(&self) -> &str;
+
+    /// Run `program` from the given initial `registers` until it halts, returning its final
+    /// registers, or `None` if it hasn't halted after `max_steps` instructions.
+    ///
+    /// Each `out` instruction sends its value to `output`. If `output` is `None`, the first
+    /// `out` instruction halts the program without executing.
+    fn run(
+        &self,
+        program: Vec<Instruction>,
+        registers: [Integer; 4],
+        output: Option<SyncSender<Integer>>,
+        max_steps: u64,
+    ) -> Result<Option<[Integer; 4]>, Error>;
+}
+
+/// The reference backend: a [`Computer`], stepped one instruction at a time.
+pub struct Interpreter;
+
+impl Backend for Interpreter {
+    fn name(&self) -> &str {
+        "interpreter"
+    }
+
+    fn run(
+        &self,
+        program: Vec<Instruction>,
+        registers: [Integer; 4],
+        output: Option<SyncSender<Integer>>,
+        max_steps: u64,
+    ) -> Result<Option<[Integer; 4]>, Error> {
+        let mut computer = Computer::from_program(program);
+        for (register, value) in REGISTERS.iter().zip(registers.iter()) {
+            computer[*register] = *value;
+        }
+        computer.set_sender(output);
+        for _ in 0..max_steps {
+            match computer.try_step() {
+                Ok(true) => {}
+                Ok(false) | Err(Error::Halted) | Err(Error::NoOutput { .. }) => {
+                    return Ok(Some(computer.registers()))
+                }
+                Err(err) => return Err(err),
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// The compiler from [`crate::compile`], which runs programs as basic blocks.
+pub struct Compiled;
+
+impl Backend for Compiled {
+    fn name(&self) -> &str {
+        "compiled"
+    }
+
+    fn run(
+        &self,
+        program: Vec<Instruction>,
+        registers: [Integer; 4],
+        output: Option<SyncSender<Integer>>,
+        max_steps: u64,
+    ) -> Result<Option<[Integer; 4]>, Error> {
+        let mut computer = Computer::from_program(program);
+        for (register, value) in REGISTERS.iter().zip(registers.iter()) {
+            computer[*register] = *value;
+        }
+        computer.set_sender(output);
+        if computer.try_run_compiled_for(max_steps)? {
+            Ok(Some(computer.registers()))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+const REGISTERS: [Register; 4] = [Register::A, Register::B, Register::C, Register::D];
+
+/// A single conformance case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    /// The file name, without its extension.
+    pub name: String,
+    pub description: String,
+    pub program: Vec<Instruction>,
+    pub init: [Integer; 4],
+    /// The registers to check once the program halts, and their expected values.
+    pub expect: Vec<(Register, Integer)>,
+    /// The values the program must output, if it runs with an output channel.
+    pub output: Option<Vec<Integer>>,
+    /// Whether the program must stop because a register overflows.
+    pub overflow: bool,
+    /// The width of [`Integer`] to which this case is limited, if any.
+    pub bits: Option<u32>,
+}
+
+fn parse_registers(
+    assignments: &str,
+    mut assign: impl FnMut(Register, Integer),
+) -> Result<(), String> {
+    for assignment in assignments.split_whitespace() {
+        let mut parts = assignment.splitn(2, '=');
+        // backends report only the puzzles' four registers
+        let register = parts
+            .next()
+            .and_then(|register| register.parse::<Register>().ok())
+            .filter(|register| register.index() < REGISTERS.len());
+        let value = parts.next().and_then(|value| value.parse().ok());
+        match (register, value) {
+            (Some(register), Some(value)) => assign(register, value),
+            _ => return Err(format!("bad register assignment {:?}", assignment)),
+        }
+    }
+    Ok(())
+}
+
+impl Case {
+    /// Parse a case from the contents of its file.
+    pub fn parse(name: &str, text: &str) -> Result<Case, CaseError> {
+        let invalid = |reason: String| CaseError::Invalid {
+            name: name.to_owned(),
+            reason,
+        };
+        let mut case = Case {
+            name: name.to_owned(),
+            description: String::new(),
+            program: Vec::new(),
+            init: [0; 4],
+            expect: Vec::new(),
+            output: None,
+            overflow: false,
+            bits: None,
+        };
+
+        // directives become blank lines, so that parse errors report the right line numbers
+        let mut source = String::new();
+        for line in text.lines() {
+            if let Some(comment) = line.trim().strip_prefix('#') {
+                let comment = comment.trim();
+                let (key, value) = match comment.find(':') {
+                    Some(idx) => (comment[..idx].trim(), comment[idx + 1..].trim()),
+                    None => ("", comment),
+                };
+                match key {
+                    "init" => parse_registers(value, |register, value| {
+                        case.init[register.index()] = value;
+                    })
+                    .map_err(invalid)?,
+                    "expect" => parse_registers(value, |register, value| {
+                        case.expect.push((register, value))
+                    })
+                    .map_err(invalid)?,
+                    "output" => {
+                        let output = value
+                            .split_whitespace()
+                            .map(|value| value.parse())
+                            .collect::<Result<_, _>>()
+                            .map_err(|_| invalid(format!("bad output {:?}", value)))?;
+                        case.output = Some(output);
+                    }
+                    "error" if value == "overflow" => case.overflow = true,
+                    "error" => return Err(invalid(format!("unknown error {:?}", value))),
+                    "bits" => {
+                        let bits = value
+                            .parse()
+                            .map_err(|_| invalid(format!("bad width {:?}", value)))?;
+                        case.bits = Some(bits);
+                    }
+                    _ => {
+                        if !case.description.is_empty() {
+                            case.description.push(' ');
+                        }
+                        case.description.push_str(comment);
+                    }
+                }
+            } else {
+                source.push_str(line);
+            }
+            source.push('\n');
+        }
+
+        if case.applies() {
+            case.program =
+                crate::parse_instructions(&source).map_err(|err| invalid(err.to_string()))?;
+        }
+        if case.expect.is_empty() && !case.overflow {
+            return Err(invalid("expects nothing".into()));
+        }
+        Ok(case)
+    }
+
+    /// `true` unless this case is limited to another width of [`Integer`].
+    pub fn applies(&self) -> bool {
+        self.bits.iter().all(|&bits| bits == Integer::BITS)
+    }
+
+    /// Run this case on `backend`, checking its results.
+    pub fn check(&self, backend: &dyn Backend) -> Result<(), Failure> {
+        let failure = |reason| Failure {
+            case: self.name.clone(),
+            backend: backend.name().to_owned(),
+            reason,
+        };
+
+        let (sender, receiver) = match self.output {
+            Some(_) => {
+                let (sender, receiver) = sync_channel(MAX_OUTPUT);
+                (Some(sender), Some(receiver))
+            }
+            None => (None, None),
+        };
+        let result = backend.run(self.program.clone(), self.init, sender, MAX_STEPS);
+        let output: Option<Vec<_>> = receiver.map(|receiver| receiver.try_iter().collect());
+
+        let registers = match result {
+            Err(Error::Overflow { .. }) if self.overflow => return Ok(()),
+            Err(err) => return Err(failure(Reason::Error(err.to_string()))),
+            Ok(_) if self.overflow => return Err(failure(Reason::NoOverflow)),
+            Ok(None) => return Err(failure(Reason::StepLimit)),
+            Ok(Some(registers)) => registers,
+        };
+        for &(register, expect) in &self.expect {
+            let actual = registers[register.index()];
+            if actual != expect {
+                return Err(failure(Reason::Register {
+                    register,
+                    expect,
+                    actual,
+                }));
+            }
+        }
+        if output != self.output {
+            return Err(failure(Reason::Output {
+                expect: self.output.clone().unwrap_or_default(),
+                actual: output.unwrap_or_default(),
+            }));
+        }
+        Ok(())
+    }
+}
+
+/// Load every case in `cases_dir`, in order of name.
+pub fn cases() -> Result<Vec<Case>, CaseError> {
+    let mut paths = std::fs::read_dir(cases_dir())?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+    paths.retain(|path| path.extension() == Some("txt".as_ref()));
+    paths.sort();
+    paths
+        .iter()
+        .map(|path| {
+            let name = path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            Case::parse(&name, &std::fs::read_to_string(path)?)
+        })
+        .collect()
+}
+
+/// Run every case which applies on `backend`, returning each failure.
+pub fn check(backend: &dyn Backend) -> Result<Vec<Failure>, CaseError> {
+    Ok(cases()?
+        .iter()
+        .filter(|case| case.applies())
+        .filter_map(|case| case.check(backend).err())
+        .collect())
+}
+
+/// A case which a backend did not pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub case: String,
+    pub backend: String,
+    pub reason: Reason,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} on {}: {}", self.case, self.backend, self.reason)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Reason {
+    #[error("failed: {0}")]
+    Error(String),
+    #[error("expected a register to overflow")]
+    NoOverflow,
+    #[error("did not halt within {} steps", MAX_STEPS)]
+    StepLimit,
+    #[error("register {register} is {actual}, expected {expect}")]
+    Register {
+        register: Register,
+        expect: Integer,
+        actual: Integer,
+    },
+    #[error("output {actual:?}, expected {expect:?}")]
+    Output {
+        expect: Vec<Integer>,
+        actual: Vec<Integer>,
+    },
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CaseError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("case {name}: {reason}")]
+    Invalid { name: String, reason: String },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interpreter_conforms() {
+        let cases = cases().unwrap();
+        assert!(!cases.is_empty());
+        let failures = check(&Interpreter).unwrap();
+        let report: Vec<_> = failures.iter().map(ToString::to_string).collect();
+        assert!(failures.is_empty(), "{:#?}", report);
+    }
+
+    #[test]
+    fn test_parse_case() {
+        let case = Case::parse(
+            "example",
+            "# an example\n# init: a=2\ninc a\n# expect: a=3 b=0\n# output: 1 2\n",
+        )
+        .unwrap();
+        assert_eq!(case.description, "an example");
+        assert_eq!(case.init, [2, 0, 0, 0]);
+        assert_eq!(case.expect, vec![(Register::A, 3), (Register::B, 0)]);
+        assert_eq!(case.output, Some(vec![1, 2]));
+        assert_eq!(case.program.len(), 1);
+        assert!(case.applies());
+
+        let case = Case::parse(
+            "narrow",
+            "# bits: 8
+# error: overflow
+cpy 256 a
+",
+        )
+        .unwrap();
+        assert_eq!(case.bits, Some(8));
+        assert!(!case.applies());
+        assert!(case.program.is_empty());
+
+        assert!(Case::parse("bad", "# expect: e=1\ninc a\n").is_err());
+        assert!(Case::parse("nothing", "inc a\n").is_err());
+        let err = Case::parse("unparsable", "# expect: a=1\n\njmp a b\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "case unparsable: line 3: could not parse \"jmp a b\""
+        );
+    }
+
+    #[test]
+    fn test_failures_are_reported() {
+        // a broken backend which never changes any register
+        struct Idle;
+        impl Backend for Idle {
+            fn name(&self) -> &str {
+                "idle"
+            }
+            fn run(
+                &self,
+                _: Vec<Instruction>,
+                registers: [Integer; 4],
+                _: Option<SyncSender<Integer>>,
+                _: u64,
+            ) -> Result<Option<[Integer; 4]>, Error> {
+                Ok(Some(registers))
+            }
+        }
+
+        let case = Case::parse("inc", "inc a\n# expect: a=1\n").unwrap();
+        let failure = case.check(&Idle).unwrap_err();
+        assert_eq!(
+            failure.to_string(),
+            "inc on idle: register a is 0, expected 1"
+        );
+        assert!(!check(&Idle).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,382 @@
+use crate::{Computer, Error, Instruction, Integer, Register, RegisterFile, StepEvent};
+use std::{fmt, str::FromStr};
+
+/// How a [`Condition`] compares a register with its value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    /// Operators as they are parsed and displayed, longest first so that `<=` is not read as
+    /// `<`.
+    const OPERATORS: [(&'static str, Comparison); 6] = [
+        ("==", Comparison::Equal),
+        ("!=", Comparison::NotEqual),
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ];
+
+    pub fn holds(self, left: Integer, right: Integer) -> bool {
+        match self {
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+        }
+    }
+}
+
+/// A test of a register's value, such as `a == 0` or `c < -1`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Condition {
+    pub register: Register,
+    pub comparison: Comparison,
+    pub value: Integer,
+}
+
+impl Condition {
+    /// Registers missing from `registers` never meet a condition.
+    pub fn holds(&self, registers: &RegisterFile) -> bool {
+        matches!(
+            registers.get(self.register),
+            Some(value) if self.comparison.holds(value, self.value)
+        )
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (operator, _) = Comparison::OPERATORS
+            .iter()
+            .find(|(_, comparison)| *comparison == self.comparison)
+            .expect("every comparison has an operator");
+        write!(f, "{} {} {}", self.register, operator, self.value)
+    }
+}
+
+impl FromStr for Condition {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::Condition(s.to_string());
+        let (index, operator, comparison) = Comparison::OPERATORS
+            .iter()
+            .find_map(|&(operator, comparison)| {
+                s.find(operator).map(|index| (index, operator, comparison))
+            })
+            .ok_or_else(invalid)?;
+        Ok(Condition {
+            register: s[..index].trim().parse().map_err(|_| invalid())?,
+            comparison,
+            value: s[index + operator.len()..]
+                .trim()
+                .parse()
+                .map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// A place or state at which the [`Debugger`] stops, before executing the next instruction.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Breakpoint {
+    /// Stop only before the instruction at this address, if set.
+    pub ip: Option<usize>,
+    /// Stop only while this condition holds, if set.
+    pub condition: Option<Condition>,
+    /// Disabled breakpoints never stop execution.
+    pub enabled: bool,
+    /// How many times this breakpoint has stopped execution.
+    pub hits: usize,
+}
+
+impl Breakpoint {
+    fn triggers(&self, ip: usize, registers: &RegisterFile) -> bool {
+        self.enabled
+            && self.ip.iter().all(|&breakpoint| breakpoint == ip)
+            && self
+                .condition
+                .iter()
+                .all(|condition| condition.holds(registers))
+    }
+}
+
+/// Why the [`Debugger`] stopped.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Stop {
+    /// These breakpoints, by index, triggered before the instruction at the current address.
+    Breakpoint(Vec<usize>),
+    /// The program halted.
+    Halted,
+    /// The step limit was reached.
+    StepLimit,
+}
+
+/// A [`Computer`] which can be stepped through and stopped at breakpoints, for understanding
+/// what a program computes.
+///
+/// ```
+/// # use assembunny::{Computer, Debugger, Register, Stop};
+/// let computer = Computer::from_source("cpy 3 b\ninc a\ndec b\njnz b -2").unwrap();
+/// let mut debugger = Debugger::new(computer);
+/// debugger.break_at(1);
+/// debugger.break_at_when(3, "b == 0".parse().unwrap());
+/// assert_eq!(debugger.resume().unwrap(), Stop::Breakpoint(vec![0]));
+/// assert_eq!(debugger.computer()[Register::B], 3);
+/// debugger.set_enabled(0, false);
+/// assert_eq!(debugger.resume().unwrap(), Stop::Breakpoint(vec![1]));
+/// assert_eq!(debugger.ip(), 3);
+/// assert_eq!(debugger.resume().unwrap(), Stop::Halted);
+/// assert_eq!(debugger.registers(), [3, 0, 0, 0]);
+/// ```
+pub struct Debugger {
+    computer: Computer,
+    breakpoints: Vec<Breakpoint>,
+    /// `true` when stopped at a breakpoint, so that resuming doesn't immediately stop there
+    /// again.
+    at_breakpoint: bool,
+}
+
+impl Debugger {
+    pub fn new(computer: Computer) -> Debugger {
+        Debugger {
+            computer,
+            breakpoints: Vec::new(),
+            at_breakpoint: false,
+        }
+    }
+
+    pub fn computer(&self) -> &Computer {
+        &self.computer
+    }
+
+    /// The computer, for changing registers or memory between steps.
+    pub fn computer_mut(&mut self) -> &mut Computer {
+        &mut self.computer
+    }
+
+    pub fn into_computer(self) -> Computer {
+        self.computer
+    }
+
+    /// The values of registers `a` through `d`; see [`Computer::register_file`] for the rest.
+    pub fn registers(&self) -> [Integer; 4] {
+        self.computer.registers()
+    }
+
+    pub fn ip(&self) -> usize {
+        self.computer.ip()
+    }
+
+    /// The instruction which will execute next, or `None` if the program has halted.
+    pub fn instruction(&self) -> Option<Instruction> {
+        self.computer.program().get(self.ip()).copied()
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.instruction().is_none()
+    }
+
+    fn add_breakpoint(&mut self, ip: Option<usize>, condition: Option<Condition>) -> usize {
+        self.breakpoints.push(Breakpoint {
+            ip,
+            condition,
+            enabled: true,
+            hits: 0,
+        });
+        self.breakpoints.len() - 1
+    }
+
+    /// Stop before the instruction at `ip`, returning the new breakpoint's index.
+    pub fn break_at(&mut self, ip: usize) -> usize {
+        self.add_breakpoint(Some(ip), None)
+    }
+
+    /// Stop before any instruction while `condition` holds, returning the new breakpoint's
+    /// index.
+    pub fn break_when(&mut self, condition: Condition) -> usize {
+        self.add_breakpoint(None, Some(condition))
+    }
+
+    /// Stop before the instruction at `ip` when `condition` holds, returning the new
+    /// breakpoint's index.
+    pub fn break_at_when(&mut self, ip: usize, condition: Condition) -> usize {
+        self.add_breakpoint(Some(ip), Some(condition))
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// Enable or disable the breakpoint at `index`.
+    ///
+    /// Panics if there is no such breakpoint.
+    pub fn set_enabled(&mut self, index: usize, enabled: bool) {
+        self.breakpoints[index].enabled = enabled;
+    }
+
+    /// Execute a single instruction, ignoring breakpoints.
+    ///
+    /// Returns `None` if the program had already halted, or halts without executing an `out`
+    /// instruction because the computer has no output channel.
+    pub fn step(&mut self) -> Result<Option<StepEvent>, Error> {
+        self.at_breakpoint = false;
+        self.computer.steps().next().transpose()
+    }
+
+    /// Run until a breakpoint triggers or the program halts.
+    pub fn resume(&mut self) -> Result<Stop, Error> {
+        self.resume_for(u64::MAX)
+    }
+
+    /// Run until a breakpoint triggers, the program halts, or `max_steps` instructions have
+    /// executed.
+    ///
+    /// Breakpoints are checked before each instruction, except where execution is already
+    /// stopped at a breakpoint.
+    pub fn resume_for(&mut self, max_steps: u64) -> Result<Stop, Error> {
+        let mut steps = 0;
+        loop {
+            if !std::mem::take(&mut self.at_breakpoint) && !self.is_halted() {
+                let (ip, registers) = (self.ip(), self.computer.register_file());
+                let mut triggered = Vec::new();
+                for (index, breakpoint) in self.breakpoints.iter_mut().enumerate() {
+                    if breakpoint.triggers(ip, registers) {
+                        breakpoint.hits += 1;
+                        triggered.push(index);
+                    }
+                }
+                if !triggered.is_empty() {
+                    self.at_breakpoint = true;
+                    return Ok(Stop::Breakpoint(triggered));
+                }
+            }
+            if steps == max_steps {
+                return Ok(Stop::StepLimit);
+            }
+            if self.step()?.is_none() {
+                return Ok(Stop::Halted);
+            }
+            steps += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn countdown() -> Debugger {
+        Debugger::new(
+            Computer::from_source(
+                "cpy 3 b
+                inc a
+                dec b
+                jnz b -2",
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_parse_condition() {
+        let condition: Condition = "c<=-2".parse().unwrap();
+        assert_eq!(
+            condition,
+            Condition {
+                register: Register::C,
+                comparison: Comparison::LessOrEqual,
+                value: -2,
+            }
+        );
+        assert_eq!(condition.to_string(), "c <= -2");
+        assert_eq!(
+            condition.to_string().parse::<Condition>().unwrap(),
+            condition
+        );
+        for invalid in &["a", "a = 1", "A == 1", "a == b", "== 1"] {
+            assert!(
+                matches!(invalid.parse::<Condition>(), Err(Error::Condition(_))),
+                "{}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_breakpoints() {
+        let mut debugger = countdown();
+        let inc = debugger.break_at(1);
+        let low = debugger.break_at_when(3, "b < 1".parse().unwrap());
+
+        for a in 0..3 {
+            assert_eq!(debugger.resume().unwrap(), Stop::Breakpoint(vec![inc]));
+            assert_eq!(debugger.ip(), 1);
+            assert_eq!(debugger.registers(), [a, 3 - a, 0, 0]);
+        }
+        assert_eq!(debugger.resume().unwrap(), Stop::Breakpoint(vec![low]));
+        assert_eq!(debugger.registers(), [3, 0, 0, 0]);
+        assert_eq!(debugger.breakpoints()[inc].hits, 3);
+
+        assert_eq!(debugger.resume().unwrap(), Stop::Halted);
+        assert!(debugger.is_halted());
+        assert_eq!(debugger.resume().unwrap(), Stop::Halted);
+    }
+
+    #[test]
+    fn test_coinciding_breakpoints() {
+        let mut debugger = countdown();
+        debugger.break_at(0);
+        debugger.break_when("a == 0".parse().unwrap());
+        assert_eq!(debugger.resume().unwrap(), Stop::Breakpoint(vec![0, 1]));
+        // the condition still holds before the next instruction
+        assert_eq!(debugger.resume().unwrap(), Stop::Breakpoint(vec![1]));
+        debugger.set_enabled(1, false);
+        assert_eq!(debugger.resume().unwrap(), Stop::Halted);
+    }
+
+    #[test]
+    fn test_step() {
+        let mut debugger = countdown();
+        debugger.break_at(1);
+        let event = debugger.step().unwrap().unwrap();
+        assert_eq!(event.ip, 0);
+        assert_eq!(event.instruction, "cpy 3 b".parse().unwrap());
+        assert_eq!(event.deltas.len(), 1);
+        assert_eq!(debugger.instruction(), Some("inc a".parse().unwrap()));
+
+        assert_eq!(debugger.resume_for(0).unwrap(), Stop::Breakpoint(vec![0]));
+        assert_eq!(debugger.resume_for(3).unwrap(), Stop::Breakpoint(vec![0]));
+        assert_eq!(debugger.resume_for(2).unwrap(), Stop::StepLimit);
+        assert_eq!(debugger.ip(), 3);
+
+        debugger.computer_mut()[Register::B] = 0;
+        assert_eq!(debugger.step().unwrap().unwrap().ip, 3);
+        assert!(debugger.step().unwrap().is_none());
+        assert_eq!(debugger.into_computer().registers(), [2, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_step_overflow() {
+        let mut debugger = Debugger::new(Computer::from_source("inc a\ninc b").unwrap());
+        debugger.computer_mut()[Register::A] = Integer::MAX;
+        assert!(matches!(
+            debugger.step(),
+            Err(Error::Overflow {
+                ip: 0,
+                register: Register::A,
+                ..
+            })
+        ));
+        assert_eq!(debugger.ip(), 0);
+    }
+}
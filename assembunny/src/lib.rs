@@ -1,20 +1,29 @@
 use std::{
+    convert::TryFrom,
     ops::{Index, IndexMut},
+    path::Path,
     thread::JoinHandle,
 };
 
-pub type Integer = i32;
+pub mod compile;
+pub mod conformance;
+mod debugger;
+mod register;
+pub use debugger::{Breakpoint, Comparison, Condition, Debugger, Stop};
+pub use register::{Register, RegisterFile};
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, parse_display::Display, parse_display::FromStr)]
-#[display(style = "lowercase")]
-pub enum Register {
-    A,
-    B,
-    C,
-    D,
-}
+/// The width of the computer's registers.
+///
+/// Arithmetic is checked: a program which overflows a register stops with [`Error::Overflow`]
+/// rather than silently wrapping. Enable the `wide` feature for 128-bit registers.
+#[cfg(not(feature = "wide"))]
+pub type Integer = i64;
+#[cfg(feature = "wide")]
+pub type Integer = i128;
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, parse_display::Display, parse_display::FromStr)]
+#[derive(
+    Clone, Copy, PartialEq, Eq, Hash, Debug, parse_display::Display, parse_display::FromStr,
+)]
 pub enum Value {
     #[display("{0}")]
     Register(Register),
@@ -42,7 +51,9 @@ impl Value {
     }
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, parse_display::Display, parse_display::FromStr)]
+#[derive(
+    Clone, Copy, PartialEq, Eq, Hash, Debug, parse_display::Display, parse_display::FromStr,
+)]
 pub enum Instruction {
     #[display("cpy {0} {1}")]
     Copy(Value, Value),
@@ -56,9 +67,43 @@ pub enum Instruction {
     Toggle(Value),
     #[display("out {0}")]
     Out(Value),
+    /// Add the first argument to the second.
+    #[display("add {0} {1}")]
+    Add(Value, Value),
+    /// Multiply the second argument by the first.
+    #[display("mul {0} {1}")]
+    Mul(Value, Value),
+    #[display("nop")]
+    Nop,
+    /// Load the memory cell addressed by the first argument into the second.
+    #[cfg(feature = "memory")]
+    #[display("ld {0} {1}")]
+    Load(Value, Value),
+    /// Store the first argument into the memory cell addressed by the second.
+    #[cfg(feature = "memory")]
+    #[display("st {0} {1}")]
+    Store(Value, Value),
 }
 
 impl Instruction {
+    /// The instruction's arguments.
+    pub(crate) fn operands(self) -> impl Iterator<Item = Value> {
+        let operands = match self {
+            Self::Increase(value)
+            | Self::Decrease(value)
+            | Self::Toggle(value)
+            | Self::Out(value) => [Some(value), None],
+            Self::Copy(value, qty)
+            | Self::Jnz(value, qty)
+            | Self::Add(value, qty)
+            | Self::Mul(value, qty) => [Some(value), Some(qty)],
+            Self::Nop => [None, None],
+            #[cfg(feature = "memory")]
+            Self::Load(value, qty) | Self::Store(value, qty) => [Some(value), Some(qty)],
+        };
+        IntoIterator::into_iter(operands).flatten()
+    }
+
     fn toggle(&mut self) {
         *self = match *self {
             Self::Increase(value) => Self::Decrease(value),
@@ -66,34 +111,452 @@ impl Instruction {
             Self::Toggle(value) => Self::Increase(value),
             Self::Out(value) => Self::Increase(value),
             Self::Jnz(value, qty) => Self::Copy(value, qty),
-            Self::Copy(value, qty) => Self::Jnz(value, qty),
+            Self::Copy(value, qty) | Self::Add(value, qty) | Self::Mul(value, qty) => {
+                Self::Jnz(value, qty)
+            }
+            // toggling only affects instructions with arguments
+            Self::Nop => Self::Nop,
+            #[cfg(feature = "memory")]
+            Self::Load(value, qty) | Self::Store(value, qty) => Self::Jnz(value, qty),
         }
     }
 }
 
+/// Parse assembunny source, one instruction per line.
+///
+/// Blank lines are skipped. Returns each instruction with its 1-based line number in `source`.
+pub fn parse_program(source: &str) -> Result<Vec<(usize, Instruction)>, Error> {
+    source
+        .lines()
+        .enumerate()
+        .map(|(idx, text)| (idx + 1, text.trim()))
+        .filter(|(_, text)| !text.is_empty())
+        .map(|(line, text)| {
+            text.parse()
+                .map(|instruction| (line, instruction))
+                .map_err(|source| Error::Parse {
+                    line,
+                    text: text.to_owned(),
+                    source,
+                })
+        })
+        .collect()
+}
+
+/// Parse assembunny source, discarding line numbers.
+pub fn parse_instructions(source: &str) -> Result<Vec<Instruction>, Error> {
+    Ok(parse_program(source)?
+        .into_iter()
+        .map(|(_, instruction)| instruction)
+        .collect())
+}
+
+/// Read and parse an assembunny program from a file.
+pub fn read_program(path: &Path) -> Result<Vec<Instruction>, Error> {
+    parse_instructions(&std::fs::read_to_string(path)?)
+}
+
+/// A record of a single `tgl` instruction's execution.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ToggleEvent {
+    /// Address of the `tgl` instruction itself.
+    pub ip: usize,
+    /// Address which the `tgl` instruction targeted. May be out of range.
+    pub target: Integer,
+    /// The targeted instruction before and after toggling, or `None` if the target was
+    /// out of range and nothing happened.
+    pub change: Option<(Instruction, Instruction)>,
+}
+
+/// A change to a single register.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RegisterDelta {
+    pub register: Register,
+    pub before: Integer,
+    pub after: Integer,
+}
+
+/// A record of a single instruction's execution.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct StepEvent {
+    /// Address of the executed instruction.
+    pub ip: usize,
+    /// The instruction, as it was when executed.
+    pub instruction: Instruction,
+    /// Every register changed by the instruction.
+    pub deltas: Vec<RegisterDelta>,
+    /// What the instruction toggled, if it was a `tgl`.
+    pub toggle: Option<ToggleEvent>,
+}
+
+/// Iterator over the execution of a program, one instruction at a time.
+///
+/// Created by [`Computer::steps`]. If an instruction fails, the iterator yields its error and
+/// then ends.
+pub struct Steps<'a> {
+    computer: &'a mut Computer,
+    halted: bool,
+}
+
+impl<'a> Iterator for Steps<'a> {
+    type Item = Result<StepEvent, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.halted || self.computer.ip >= self.computer.program.len() {
+            self.halted = true;
+            return None;
+        }
+
+        let ip = self.computer.ip;
+        let instruction = self.computer.program[ip];
+        let before = self.computer.registers.clone();
+        let toggle_target = self.computer.toggle_target();
+        match self.computer.try_step() {
+            Ok(running) => self.halted = !running,
+            Err(Error::Halted) | Err(Error::NoOutput { .. }) => {
+                self.halted = true;
+                return None;
+            }
+            Err(err) => {
+                self.halted = true;
+                return Some(Err(err));
+            }
+        }
+
+        let program = &self.computer.program;
+        Some(Ok(StepEvent {
+            ip,
+            instruction,
+            deltas: register_deltas(&before, &self.computer.registers),
+            toggle: toggle_target.map(|(target, toggled)| ToggleEvent {
+                ip,
+                target,
+                change: toggled.map(|(index, before)| (before, program[index])),
+            }),
+        }))
+    }
+}
+
+/// The registers which differ between `before` and `after`.
+fn register_deltas(before: &RegisterFile, after: &RegisterFile) -> Vec<RegisterDelta> {
+    before
+        .iter()
+        .zip(after.iter())
+        .filter(|((_, before), (_, after))| before != after)
+        .map(|((register, before), (_, after))| RegisterDelta {
+            register,
+            before,
+            after,
+        })
+        .collect()
+}
+
+impl<'a> std::iter::FusedIterator for Steps<'a> {}
+
+/// The number of cycles each opcode takes to execute.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct CostModel {
+    pub cpy: u64,
+    pub inc: u64,
+    pub dec: u64,
+    pub jnz: u64,
+    pub tgl: u64,
+    pub out: u64,
+    pub add: u64,
+    pub mul: u64,
+    pub nop: u64,
+    #[cfg(feature = "memory")]
+    pub ld: u64,
+    #[cfg(feature = "memory")]
+    pub st: u64,
+}
+
+impl Default for CostModel {
+    /// Every instruction takes a single cycle.
+    fn default() -> Self {
+        Self {
+            cpy: 1,
+            inc: 1,
+            dec: 1,
+            jnz: 1,
+            tgl: 1,
+            out: 1,
+            add: 1,
+            mul: 1,
+            nop: 1,
+            #[cfg(feature = "memory")]
+            ld: 1,
+            #[cfg(feature = "memory")]
+            st: 1,
+        }
+    }
+}
+
+impl CostModel {
+    pub fn cost(&self, instruction: Instruction) -> u64 {
+        match instruction {
+            Instruction::Copy(..) => self.cpy,
+            Instruction::Increase(_) => self.inc,
+            Instruction::Decrease(_) => self.dec,
+            Instruction::Jnz(..) => self.jnz,
+            Instruction::Toggle(_) => self.tgl,
+            Instruction::Out(_) => self.out,
+            Instruction::Add(..) => self.add,
+            Instruction::Mul(..) => self.mul,
+            Instruction::Nop => self.nop,
+            #[cfg(feature = "memory")]
+            Instruction::Load(..) => self.ld,
+            #[cfg(feature = "memory")]
+            Instruction::Store(..) => self.st,
+        }
+    }
+}
+
+/// Summary of the work a computer has done so far.
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct ExecutionProfile {
+    /// Number of instructions executed.
+    pub instructions: u64,
+    /// Number of cycles consumed, according to the computer's cost model.
+    pub cycles: u64,
+    /// Number of times the instruction at each address has executed.
+    ///
+    /// Addresses past the last one executed may be missing.
+    pub counts: Vec<u64>,
+}
+
+impl ExecutionProfile {
+    /// Count one execution of the instructions at each address in `ips`.
+    fn count(&mut self, ips: std::ops::Range<usize>) {
+        if self.counts.len() < ips.end {
+            self.counts.resize(ips.end, 0);
+        }
+        for count in &mut self.counts[ips] {
+            *count += 1;
+        }
+    }
+
+    /// The addresses of the `n` most executed instructions, with their counts, most executed
+    /// first.
+    ///
+    /// Instructions which never executed are omitted.
+    pub fn hottest(&self, n: usize) -> Vec<(usize, u64)> {
+        let mut hottest: Vec<_> = self
+            .counts
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        hottest.sort_by_key(|&(ip, count)| (std::cmp::Reverse(count), ip));
+        hottest.truncate(n);
+        hottest
+    }
+
+    /// Summarize this profile of a run of `program`, listing the `top` most executed
+    /// instructions as `program` gives them.
+    ///
+    /// `program` must be the program which was profiled. Source lines are not known here; see
+    /// [`Computer::profile_report`].
+    pub fn report(&self, program: &[Instruction], top: usize) -> ProfileReport {
+        self.report_with_lines(program, &[], top)
+    }
+
+    fn report_with_lines(
+        &self,
+        program: &[Instruction],
+        lines: &[usize],
+        top: usize,
+    ) -> ProfileReport {
+        ProfileReport {
+            instructions: self.instructions,
+            cycles: self.cycles,
+            hot: self
+                .hottest(top)
+                .into_iter()
+                .map(|(ip, count)| HotInstruction {
+                    ip,
+                    line: lines.get(ip).copied(),
+                    instruction: program[ip],
+                    count,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A frequently executed instruction, as listed in a [`ProfileReport`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct HotInstruction {
+    pub ip: usize,
+    /// The source line of the instruction, if known.
+    pub line: Option<usize>,
+    /// The instruction at `ip` when the report was made.
+    pub instruction: Instruction,
+    /// Number of times the instruction at `ip` has executed.
+    pub count: u64,
+}
+
+/// The total work a computer has done, and the instructions which did most of it.
+///
+/// Created by [`Computer::profile_report`] or [`ExecutionProfile::report`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ProfileReport {
+    pub instructions: u64,
+    pub cycles: u64,
+    /// Most executed first.
+    pub hot: Vec<HotInstruction>,
+}
+
+impl std::fmt::Display for ProfileReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "executed {} instructions in {} cycles",
+            self.instructions, self.cycles
+        )?;
+        for hot in &self.hot {
+            writeln!(
+                f,
+                "{:>5} {:>14} {:>5.1}%  {}{}",
+                hot.ip,
+                hot.count,
+                100.0 * hot.count as f64 / self.instructions as f64,
+                hot.instruction,
+                at_line(&hot.line),
+            )?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Default)]
 pub struct Computer {
-    a: Integer,
-    b: Integer,
-    c: Integer,
-    d: Integer,
+    registers: RegisterFile,
     ip: usize,
     program: Vec<Instruction>,
     sender: Option<std::sync::mpsc::SyncSender<Integer>>,
+    cost_model: CostModel,
+    profile: ExecutionProfile,
+    /// Source line of each instruction, if known.
+    lines: Vec<usize>,
+    #[cfg(feature = "memory")]
+    memory: Vec<Integer>,
 }
 
 impl Computer {
+    /// A computer running `program`, with enough registers for every register it mentions.
     pub fn from_program(program: Vec<Instruction>) -> Self {
         Self {
+            registers: RegisterFile::for_program(&program),
             program,
             ..Self::default()
         }
     }
 
+    /// Parse `source` into a computer which remembers the source line of each instruction.
+    pub fn from_source(source: &str) -> Result<Self, Error> {
+        let (lines, program): (_, Vec<_>) = parse_program(source)?.into_iter().unzip();
+        Ok(Self {
+            registers: RegisterFile::for_program(&program),
+            program,
+            lines,
+            ..Self::default()
+        })
+    }
+
+    /// Provide at least `count` registers, named from `a`, so that more may be set than the
+    /// program mentions.
+    ///
+    /// Panics if `count` exceeds [`Register::COUNT`].
+    pub fn with_registers(mut self, count: usize) -> Self {
+        self.registers.grow(count);
+        self
+    }
+
+    /// The source line of the instruction at `ip`, if the computer was built `from_source`.
+    pub fn line_of(&self, ip: usize) -> Option<usize> {
+        self.lines.get(ip).copied()
+    }
+
     pub fn set_sender(&mut self, sender: impl Into<Option<std::sync::mpsc::SyncSender<Integer>>>) {
         self.sender = sender.into();
     }
 
+    /// Give this computer a zeroed memory of `size` cells.
+    #[cfg(feature = "memory")]
+    pub fn with_memory(mut self, size: usize) -> Self {
+        self.memory = vec![0; size];
+        self
+    }
+
+    #[cfg(feature = "memory")]
+    pub fn memory(&self) -> &[Integer] {
+        &self.memory
+    }
+
+    #[cfg(feature = "memory")]
+    pub fn memory_mut(&mut self) -> &mut [Integer] {
+        &mut self.memory
+    }
+
+    /// Look up the memory cell at `address`, if it is in range.
+    #[cfg(feature = "memory")]
+    fn cell(&mut self, address: Value) -> Option<&mut Integer> {
+        let address = index_in(self.value(address), self.memory.len())?;
+        self.memory.get_mut(address)
+    }
+
+    /// Set the cost model used to count cycles. The default model costs one cycle per instruction.
+    pub fn set_cost_model(&mut self, cost_model: CostModel) {
+        self.cost_model = cost_model;
+    }
+
+    /// Number of cycles consumed so far.
+    pub fn cycles(&self) -> u64 {
+        self.profile.cycles
+    }
+
+    /// Number of instructions executed so far.
+    pub fn instructions(&self) -> u64 {
+        self.profile.instructions
+    }
+
+    pub fn profile(&self) -> ExecutionProfile {
+        self.profile.clone()
+    }
+
+    /// Summarize the work done so far, listing the `top` most executed instructions.
+    pub fn profile_report(&self, top: usize) -> ProfileReport {
+        self.profile
+            .report_with_lines(&self.program, &self.lines, top)
+    }
+
+    /// The current values of the puzzles' registers, in order `a`, `b`, `c`, `d`.
+    pub fn registers(&self) -> [Integer; 4] {
+        [
+            self[Register::A],
+            self[Register::B],
+            self[Register::C],
+            self[Register::D],
+        ]
+    }
+
+    /// The current values of every register.
+    pub fn register_file(&self) -> &RegisterFile {
+        &self.registers
+    }
+
+    /// The instruction pointer.
+    pub fn ip(&self) -> usize {
+        self.ip
+    }
+
+    /// The program, including any modifications made by `tgl`.
+    pub fn program(&self) -> &[Instruction] {
+        &self.program
+    }
+
     pub fn value(&self, value: Value) -> Integer {
         match value {
             Value::Register(register) => self[register],
@@ -102,23 +565,83 @@ impl Computer {
     }
 
     fn instruction_offset(&mut self, value: Value) -> Option<&mut Instruction> {
-        let next_ip = self.ip as Integer + self.value(value);
-        self.program.get_mut(next_ip as usize)
+        let next_ip = (self.ip as Integer).saturating_add(self.value(value));
+        let next_ip = index_in(next_ip, self.program.len())?;
+        self.program.get_mut(next_ip)
     }
 
-    // `true` when the program should continue; `false` when it should halt
-    fn step(&mut self) -> bool {
+    /// If the next instruction is a `tgl`, the address it targets, and that address with the
+    /// instruction there if it is in range.
+    fn toggle_target(&self) -> Option<(Integer, Option<(usize, Instruction)>)> {
+        match self.program.get(self.ip)? {
+            Instruction::Toggle(value) => {
+                let target = (self.ip as Integer).saturating_add(self.value(*value));
+                let toggled =
+                    index_in(target, self.program.len()).map(|index| (index, self.program[index]));
+                Some((target, toggled))
+            }
+            _ => None,
+        }
+    }
+
+    /// Execute a single instruction, or explain why it can't be executed.
+    ///
+    /// Returns `Ok(true)` when the program should continue; `Ok(false)` when it should halt.
+    pub fn try_step(&mut self) -> Result<bool, Error> {
+        if self.ip >= self.program.len() {
+            return Err(Error::Halted);
+        }
+        if let Instruction::Out(_) = self.program[self.ip] {
+            if self.sender.is_none() {
+                return Err(Error::NoOutput {
+                    ip: self.ip,
+                    line: self.line_of(self.ip),
+                });
+            }
+        }
+        self.execute()
+    }
+
+    /// Apply `op` to `register`, if it is a register, failing if the result overflows.
+    fn update(
+        &mut self,
+        register: Value,
+        op: impl FnOnce(Integer) -> Option<Integer>,
+    ) -> Result<(), Error> {
+        if let Value::Register(register) = register {
+            self[register] = op(self[register]).ok_or(Error::Overflow {
+                ip: self.ip,
+                line: self.line_of(self.ip),
+                register,
+            })?;
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self) -> Result<bool, Error> {
+        self.profile.instructions += 1;
+        self.profile.cycles += self.cost_model.cost(self.program[self.ip]);
+        self.profile.count(self.ip..self.ip + 1);
+
         match self.program[self.ip] {
             Instruction::Copy(value, register) => {
                 register.as_register(|register| self[register] = self.value(value));
             }
             Instruction::Increase(register) => {
-                register.as_register(|register| self[register] += 1);
+                self.update(register, |value| value.checked_add(1))?;
             }
             Instruction::Decrease(register) => {
-                register.as_register(|register| self[register] -= 1);
+                self.update(register, |value| value.checked_sub(1))?;
+            }
+            Instruction::Add(value, register) => {
+                let value = self.value(value);
+                self.update(register, |current| current.checked_add(value))?;
+            }
+            Instruction::Mul(value, register) => {
+                let value = self.value(value);
+                self.update(register, |current| current.checked_mul(value))?;
             }
-            Instruction::Jnz(_, _) => {}
+            Instruction::Jnz(_, _) | Instruction::Nop => {}
             Instruction::Toggle(value) => {
                 if let Some(instruction) = self.instruction_offset(value) {
                     instruction.toggle();
@@ -126,63 +649,494 @@ impl Computer {
             }
             Instruction::Out(value) => {
                 let value = self.value(value);
-                let sender = match self.sender.as_mut() {
-                    Some(sender) => sender,
-                    None => return false,
-                };
+                let sender = self
+                    .sender
+                    .as_mut()
+                    .expect("try_step ensures a sender exists");
                 if sender.send(value).is_err() {
-                    return false;
+                    return Ok(false);
+                }
+            }
+            // like other invalid instructions, out-of-range memory accesses are skipped
+            #[cfg(feature = "memory")]
+            Instruction::Load(address, register) => {
+                if let Some(cell) = self.cell(address).copied() {
+                    register.as_register(|register| self[register] = cell);
+                }
+            }
+            #[cfg(feature = "memory")]
+            Instruction::Store(value, address) => {
+                let value = self.value(value);
+                if let Some(cell) = self.cell(address) {
+                    *cell = value;
                 }
             }
         }
 
-        let next_ip = self.ip as Integer
-            + match self.program[self.ip] {
-                Instruction::Jnz(value, distance) if self.value(value) != 0 => self.value(distance),
-                _ => 1,
-            };
-        self.ip = if (0..self.program.len()).contains(&(next_ip as usize)) {
-            next_ip as usize
-        } else {
-            !0
-        };
-        self.ip != !0
+        let next_ip = (self.ip as Integer).saturating_add(match self.program[self.ip] {
+            Instruction::Jnz(value, distance) if self.value(value) != 0 => self.value(distance),
+            _ => 1,
+        });
+        self.ip = index_in(next_ip, self.program.len()).unwrap_or(!0);
+        Ok(self.ip != !0)
     }
 
-    /// Run this computer until the program terminates naturally.
-    pub fn run(&mut self) {
-        while self.step() {}
+    /// Execute the program lazily, yielding an event for each instruction executed.
+    ///
+    /// Each instruction executes only when its event is requested, so `take(n)` limits
+    /// execution to `n` steps. The iterator ends when the program halts; the computer may then
+    /// be inspected, or stepped further if it stopped for lack of an output channel.
+    pub fn steps(&mut self) -> Steps<'_> {
+        Steps {
+            computer: self,
+            halted: false,
+        }
+    }
+
+    /// Run this computer until the program terminates naturally, or a register overflows.
+    ///
+    /// An `out` instruction with no sender set halts the program.
+    pub fn try_run(&mut self) -> Result<(), Error> {
+        loop {
+            match self.try_step() {
+                Ok(true) => {}
+                Ok(false) | Err(Error::Halted) | Err(Error::NoOutput { .. }) => return Ok(()),
+                Err(err) => return Err(err),
+            }
+        }
     }
 
-    /// Run this computer in its own thread until the program terminates naturally.
+    /// Run this computer in its own thread until the program terminates naturally, or a register
+    /// overflows, executing it as basic blocks.
     ///
     /// Note that this consumes `self`. Ensure you've `set_sender` before calling this
-    /// if you want to receive output!
-    pub fn launch(mut self) -> JoinHandle<()> {
-        std::thread::spawn(move || self.run())
+    /// if you want to receive output! The program halts when it sends to a dropped receiver.
+    pub fn launch(mut self) -> JoinHandle<Result<(), Error>> {
+        std::thread::spawn(move || self.try_run_compiled())
+    }
+}
+
+/// `value` as an index into a slice of `len` items, if it is in range.
+///
+/// Unlike a cast, this never wraps a value too wide for a `usize` into range.
+fn index_in(value: Integer, len: usize) -> Option<usize> {
+    usize::try_from(value).ok().filter(|&index| index < len)
+}
+
+/// Describe a source line for an error message, if it is known.
+fn at_line(line: &Option<usize>) -> String {
+    match line {
+        Some(line) => format!(" (line {})", line),
+        None => String::new(),
     }
 }
 
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("line {line}: could not parse {text:?}")]
+    Parse {
+        line: usize,
+        text: String,
+        source: parse_display::ParseError,
+    },
+    #[error("the program has halted")]
+    Halted,
+    #[error("`out` at address {ip}{} has no output channel", at_line(line))]
+    NoOutput { ip: usize, line: Option<usize> },
+    #[error("register {register} overflowed at address {ip}{}", at_line(line))]
+    Overflow {
+        ip: usize,
+        line: Option<usize>,
+        register: Register,
+    },
+    #[error(
+        "invalid condition {0:?}: expected a register, a comparison, and a value, like `a == 0`"
+    )]
+    Condition(String),
+}
+
 impl Index<Register> for Computer {
     type Output = Integer;
 
+    /// Panics if the computer has no such register; see [`Computer::with_registers`].
     fn index(&self, index: Register) -> &Self::Output {
-        match index {
-            Register::A => &self.a,
-            Register::B => &self.b,
-            Register::C => &self.c,
-            Register::D => &self.d,
-        }
+        &self.registers[index]
     }
 }
 
 impl IndexMut<Register> for Computer {
     fn index_mut(&mut self, index: Register) -> &mut Self::Output {
-        match index {
-            Register::A => &mut self.a,
-            Register::B => &mut self.b,
-            Register::C => &mut self.c,
-            Register::D => &mut self.d,
+        &mut self.registers[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(source: &str) -> Vec<Instruction> {
+        source
+            .lines()
+            .map(|line| line.trim().parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let mut computer = Computer::from_program(program(
+            "cpy 6 a
+            cpy 7 b
+            mul b a
+            add a c
+            nop
+            add -2 c",
+        ));
+        computer.try_run().unwrap();
+        assert_eq!(computer.registers(), [42, 7, 40, 0]);
+
+        let mut add = "add a b".parse::<Instruction>().unwrap();
+        add.toggle();
+        assert_eq!(add.to_string(), "jnz a b");
+        let mut nop = Instruction::Nop;
+        nop.toggle();
+        assert_eq!(nop, Instruction::Nop);
+    }
+
+    #[test]
+    fn test_register_file() {
+        let mut computer = Computer::from_source("cpy 7 f\ninc f\ncpy f a").unwrap();
+        assert_eq!(computer.register_file().len(), 6);
+        computer.try_run().unwrap();
+        assert_eq!(computer.registers(), [8, 0, 0, 0]);
+        let f = "f".parse::<Register>().unwrap();
+        assert_eq!(computer[f], 8);
+        assert_eq!(computer.register_file().get(f), Some(8));
+
+        let computer = Computer::from_source("inc a").unwrap().with_registers(26);
+        let z = Register::from_name('z').unwrap();
+        assert_eq!(computer.register_file().iter().last(), Some((z, 0)));
+        assert_eq!(Register::from_index(25), Some(z));
+        assert_eq!(z.to_string(), "z");
+        assert_eq!(Computer::default().register_file().len(), 4);
+
+        for invalid in &["A", "ab", "", "1"] {
+            assert!(invalid.parse::<Register>().is_err(), "{}", invalid);
         }
     }
+
+    #[test]
+    fn test_cycles() {
+        let mut computer = Computer::from_program(program(
+            "cpy 41 a
+            inc a
+            inc a
+            dec a
+            jnz a 2
+            dec a",
+        ));
+        computer.set_cost_model(CostModel {
+            cpy: 2,
+            ..CostModel::default()
+        });
+        computer.try_run().unwrap();
+        assert_eq!(computer[Register::A], 42);
+        assert_eq!(
+            computer.profile(),
+            ExecutionProfile {
+                instructions: 5,
+                cycles: 6,
+                counts: vec![1, 1, 1, 1, 1],
+            }
+        );
+    }
+
+    #[test]
+    fn test_profile_report() {
+        let mut computer = Computer::from_source(
+            "cpy 3 b
+
+            inc a
+            dec b
+            jnz b -2
+            tgl d",
+        )
+        .unwrap();
+        computer.try_run().unwrap();
+        assert_eq!(computer.profile().counts, vec![1, 3, 3, 3, 1]);
+        assert_eq!(computer.profile().hottest(2), vec![(1, 3), (2, 3)]);
+
+        let report = computer.profile_report(2);
+        assert_eq!(report.instructions, 11);
+        assert_eq!(
+            report.hot[0],
+            HotInstruction {
+                ip: 1,
+                line: Some(3),
+                instruction: Instruction::Increase(Register::A.into()),
+                count: 3,
+            }
+        );
+        assert_eq!(
+            report.to_string(),
+            "executed 11 instructions in 11 cycles\n    \
+             1              3  27.3%  inc a (line 3)\n    \
+             2              3  27.3%  dec b (line 4)\n"
+        );
+    }
+
+    #[test]
+    fn test_steps() {
+        let mut computer = Computer::from_program(program(
+            "cpy 2 a
+            dec a
+            jnz a -1
+            inc b",
+        ));
+        let first: Vec<_> = computer.steps().take(2).map(Result::unwrap).collect();
+        assert_eq!(
+            first,
+            vec![
+                StepEvent {
+                    ip: 0,
+                    instruction: Instruction::Copy(2.into(), Register::A.into()),
+                    deltas: vec![RegisterDelta {
+                        register: Register::A,
+                        before: 0,
+                        after: 2,
+                    }],
+                    toggle: None,
+                },
+                StepEvent {
+                    ip: 1,
+                    instruction: Instruction::Decrease(Register::A.into()),
+                    deltas: vec![RegisterDelta {
+                        register: Register::A,
+                        before: 2,
+                        after: 1,
+                    }],
+                    toggle: None,
+                },
+            ]
+        );
+        // stepping was paused after two instructions
+        assert_eq!(computer.ip(), 2);
+
+        let ips: Vec<_> = computer.steps().map(|event| event.unwrap().ip).collect();
+        assert_eq!(ips, vec![2, 1, 2, 3]);
+        assert_eq!(computer.registers(), [0, 1, 0, 0]);
+        assert!(computer.steps().next().is_none());
+    }
+
+    #[test]
+    fn test_steps_overflow() {
+        let mut computer = Computer::from_program(program(
+            "inc a
+inc a
+inc b",
+        ));
+        computer[Register::A] = Integer::MAX - 1;
+        let mut steps = computer.steps();
+        assert_eq!(steps.next().unwrap().unwrap().ip, 0);
+        assert!(matches!(
+            steps.next(),
+            Some(Err(Error::Overflow {
+                ip: 1,
+                register: Register::A,
+                ..
+            }))
+        ));
+        assert!(steps.next().is_none());
+        assert_eq!(computer.registers(), [Integer::MAX, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_launch_overflow() {
+        let mut computer = Computer::from_program(program(
+            "out a
+inc a
+jnz 1 -2",
+        ));
+        let (sender, receiver) = std::sync::mpsc::sync_channel(0);
+        computer.set_sender(sender);
+        computer[Register::A] = Integer::MAX - 2;
+        let handle = computer.launch();
+        let outputs: Vec<_> = receiver.iter().collect();
+        assert_eq!(
+            outputs,
+            vec![Integer::MAX - 2, Integer::MAX - 1, Integer::MAX]
+        );
+        assert!(matches!(
+            handle.join().unwrap(),
+            Err(Error::Overflow { ip: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_toggle_events() {
+        let toggles = |source: &str| {
+            let mut computer = Computer::from_source(source).unwrap();
+            let events: Vec<_> = computer
+                .steps()
+                .filter_map(|event| event.unwrap().toggle)
+                .collect();
+            (events, computer[Register::A])
+        };
+
+        // the example from day 23 toggles itself into a jump past its end
+        let source = "cpy 2 a\ntgl a\ntgl a\ntgl a\ncpy 1 a\ndec a\ndec a";
+        let expected = vec![
+            ToggleEvent {
+                ip: 1,
+                target: 3,
+                change: Some((
+                    Instruction::Toggle(Register::A.into()),
+                    Instruction::Increase(Register::A.into()),
+                )),
+            },
+            ToggleEvent {
+                ip: 2,
+                target: 4,
+                change: Some((
+                    Instruction::Copy(1.into(), Register::A.into()),
+                    Instruction::Jnz(1.into(), Register::A.into()),
+                )),
+            },
+        ];
+        assert_eq!(toggles(source), (expected, 3));
+
+        let (events, _) = toggles("cpy 5 a\ntgl a\ntgl -2");
+        assert_eq!(
+            events,
+            vec![
+                ToggleEvent {
+                    ip: 1,
+                    target: 6,
+                    change: None,
+                },
+                ToggleEvent {
+                    ip: 2,
+                    target: 0,
+                    change: Some((
+                        Instruction::Copy(5.into(), Register::A.into()),
+                        Instruction::Jnz(5.into(), Register::A.into()),
+                    )),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_source_lines() {
+        let computer = Computer::from_source("cpy 1 a\n\n  inc a\n").unwrap();
+        assert_eq!(computer.program().len(), 2);
+        assert_eq!(computer.line_of(0), Some(1));
+        assert_eq!(computer.line_of(1), Some(3));
+        assert_eq!(computer.line_of(2), None);
+
+        let err = Computer::from_source("cpy 1 a\ninc a\njmp a b")
+            .err()
+            .unwrap();
+        assert!(matches!(err, Error::Parse { line: 3, .. }));
+        assert_eq!(err.to_string(), "line 3: could not parse \"jmp a b\"");
+    }
+
+    #[test]
+    fn test_runtime_errors() {
+        let mut computer = Computer::from_source("inc a\n\nout a").unwrap();
+        assert!(matches!(computer.try_step(), Ok(true)));
+        let err = computer.try_step().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`out` at address 1 (line 3) has no output channel"
+        );
+
+        let mut computer = Computer::from_program(program("inc a"));
+        assert!(matches!(computer.try_step(), Ok(false)));
+        assert!(matches!(computer.try_step(), Err(Error::Halted)));
+    }
+
+    #[test]
+    fn test_overflow() {
+        let mut computer = Computer::from_source("inc a\ndec b").unwrap();
+        computer[Register::A] = Integer::MAX - 1;
+        computer.try_run().unwrap();
+        assert_eq!(computer[Register::A], Integer::MAX);
+
+        let mut computer = Computer::from_source("inc a").unwrap();
+        computer[Register::A] = Integer::MAX;
+        let err = computer.try_run().unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Overflow {
+                ip: 0,
+                line: Some(1),
+                register: Register::A
+            }
+        ));
+        assert_eq!(computer[Register::A], Integer::MAX);
+
+        let mut computer = Computer::from_source("dec b").unwrap();
+        computer[Register::B] = Integer::MIN;
+        assert!(computer.try_run().is_err());
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_load_store() {
+        // fill memory[1..=3] with twice the value of memory[0]
+        let program = program(
+            "cpy 1 b
+            ld 0 a
+            cpy a c
+            inc a
+            dec c
+            jnz c -2
+            st a b
+            inc b
+            cpy b d
+            dec d
+            dec d
+            dec d
+            dec d
+            jnz d -12",
+        );
+        let mut computer = Computer::from_program(program).with_memory(4);
+        computer.memory_mut()[0] = 3;
+        computer.try_run().unwrap();
+        assert_eq!(computer.memory(), &[3, 6, 6, 6]);
+    }
+
+    #[cfg(feature = "wide")]
+    #[test]
+    fn test_wide_offsets_are_not_truncated() {
+        // 2^64 + 1 would wrap to 1 if cast to a 64-bit usize
+        let mut computer = Computer::from_source("jnz 1 18446744073709551617\ninc a").unwrap();
+        computer.try_run().unwrap();
+        assert_eq!(computer[Register::A], 0);
+
+        let mut computer = Computer::from_source("tgl 18446744073709551617\ninc a").unwrap();
+        computer.try_run().unwrap();
+        assert_eq!(computer[Register::A], 1);
+    }
+
+    #[cfg(all(feature = "memory", feature = "wide"))]
+    #[test]
+    fn test_wide_addresses_are_not_truncated() {
+        let mut computer =
+            Computer::from_program(program("st 5 18446744073709551616")).with_memory(1);
+        computer.try_run().unwrap();
+        assert_eq!(computer.memory(), &[0]);
+    }
+
+    #[cfg(feature = "memory")]
+    #[test]
+    fn test_out_of_range_is_skipped() {
+        let mut computer =
+            Computer::from_program(program("cpy 5 a\nst a 7\nld -1 a")).with_memory(2);
+        computer.try_run().unwrap();
+        assert_eq!(computer[Register::A], 5);
+        assert_eq!(computer.memory(), &[0, 0]);
+    }
 }
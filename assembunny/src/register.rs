@@ -0,0 +1,148 @@
+use crate::{Instruction, Integer, Value};
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
+
+/// A register, named by a lowercase letter.
+///
+/// Puzzle programs use `a` through `d`; extended programs may use any letter.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Register(u8);
+
+impl Register {
+    pub const A: Register = Register(0);
+    pub const B: Register = Register(1);
+    pub const C: Register = Register(2);
+    pub const D: Register = Register(3);
+
+    /// The number of distinct registers, `a` through `z`.
+    pub const COUNT: usize = 26;
+
+    /// The register at `index` in a register file, if there is one: `0` is `a`, `1` is `b`, and
+    /// so on.
+    pub fn from_index(index: usize) -> Option<Register> {
+        (index < Self::COUNT).then_some(Register(index as u8))
+    }
+
+    /// The register named `name`, if there is one.
+    pub fn from_name(name: char) -> Option<Register> {
+        name.is_ascii_lowercase()
+            .then(|| Register(name as u8 - b'a'))
+    }
+
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+
+    pub fn name(self) -> char {
+        (b'a' + self.0) as char
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Register {
+    type Err = parse_display::ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        match (chars.next(), chars.next()) {
+            (Some(name), None) => Register::from_name(name),
+            _ => None,
+        }
+        .ok_or_else(|| parse_display::ParseError::with_message("registers are named `a` to `z`"))
+    }
+}
+
+/// The values of a computer's registers, indexed by [`Register`].
+///
+/// Holds registers `a` through `d` by default, like the puzzles' computer.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct RegisterFile(Vec<Integer>);
+
+impl Default for RegisterFile {
+    fn default() -> Self {
+        RegisterFile::new(4)
+    }
+}
+
+impl RegisterFile {
+    /// A register file of `count` registers, named from `a`, all zero.
+    ///
+    /// Panics if `count` exceeds [`Register::COUNT`].
+    pub fn new(count: usize) -> RegisterFile {
+        assert!(
+            count <= Register::COUNT,
+            "at most {} registers are supported",
+            Register::COUNT
+        );
+        RegisterFile(vec![0; count])
+    }
+
+    /// The smallest register file, at least the default size, holding every register `program`
+    /// mentions.
+    pub fn for_program(program: &[Instruction]) -> RegisterFile {
+        let count = program
+            .iter()
+            .flat_map(|instruction| instruction.operands())
+            .filter_map(|value| match value {
+                Value::Register(register) => Some(register.index() + 1),
+                Value::Value(_) => None,
+            })
+            .fold(RegisterFile::default().len(), usize::max);
+        RegisterFile::new(count)
+    }
+
+    /// Add registers as needed to hold at least `count`.
+    pub(crate) fn grow(&mut self, count: usize) {
+        assert!(
+            count <= Register::COUNT,
+            "at most {} registers are supported",
+            Register::COUNT
+        );
+        if count > self.len() {
+            self.0.resize(count, 0);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, register: Register) -> Option<Integer> {
+        self.0.get(register.index()).copied()
+    }
+
+    /// Each register and its value, in order from `a`.
+    pub fn iter(&self) -> impl '_ + Iterator<Item = (Register, Integer)> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(index, &value)| (Register(index as u8), value))
+    }
+}
+
+impl Index<Register> for RegisterFile {
+    type Output = Integer;
+
+    /// Panics if the register is beyond the end of this file.
+    fn index(&self, register: Register) -> &Self::Output {
+        &self.0[register.index()]
+    }
+}
+
+impl IndexMut<Register> for RegisterFile {
+    fn index_mut(&mut self, register: Register) -> &mut Self::Output {
+        &mut self.0[register.index()]
+    }
+}
@@ -0,0 +1,198 @@
+//! Export of the walked route, for viewing in external tools.
+
+use crate::{Error, Instruction, Walker};
+use aoclib::geometry::Point;
+use std::io::Write;
+
+/// A file format in which to export a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, parse_display::Display, parse_display::FromStr)]
+#[display(style = "lowercase")]
+pub enum RouteFormat {
+    /// An SVG image of the route as a polyline, north up.
+    Svg,
+    /// A GeoJSON `FeatureCollection` of the route as a `LineString`, in unit grid coordinates.
+    GeoJson,
+}
+
+/// The corners of the walked route, from the origin, and its first revisit if any.
+pub fn trace_route(instructions: &[Instruction]) -> (Vec<Point>, Option<Point>) {
+    let mut walker = Walker::new();
+    let mut corners = vec![walker.location()];
+    for instruction in instructions {
+        if walker.push(*instruction).is_some() {
+            corners.push(walker.location());
+        }
+    }
+    (corners, walker.first_revisit())
+}
+
+/// Write the route walked by following `instructions` to `writer`, marking its first revisit.
+pub fn export_route(
+    instructions: &[Instruction],
+    format: RouteFormat,
+    writer: impl Write,
+) -> Result<(), Error> {
+    let (corners, first_revisit) = trace_route(instructions);
+    match format {
+        RouteFormat::Svg => write_svg(&corners, first_revisit, writer),
+        RouteFormat::GeoJson => write_geojson(&corners, first_revisit, writer),
+    }
+}
+
+fn write_geojson(
+    corners: &[Point],
+    first_revisit: Option<Point>,
+    writer: impl Write,
+) -> Result<(), Error> {
+    let coordinates = |point: Point| serde_json::json!([point.x, point.y]);
+    let mut features = vec![serde_json::json!({
+        "type": "Feature",
+        "properties": { "name": "route" },
+        "geometry": {
+            "type": "LineString",
+            "coordinates": corners.iter().copied().map(coordinates).collect::<Vec<_>>(),
+        },
+    })];
+    if let Some(point) = first_revisit {
+        features.push(serde_json::json!({
+            "type": "Feature",
+            "properties": { "name": "first revisit", "distance": point.manhattan() },
+            "geometry": { "type": "Point", "coordinates": coordinates(point) },
+        }));
+    }
+    let collection = serde_json::json!({
+        "type": "FeatureCollection",
+        "features": features,
+    });
+    serde_json::to_writer_pretty(writer, &collection)?;
+    Ok(())
+}
+
+fn write_svg(
+    corners: &[Point],
+    first_revisit: Option<Point>,
+    mut writer: impl Write,
+) -> Result<(), Error> {
+    // SVG's y axis points down, so north is negated
+    let min_x = corners
+        .iter()
+        .map(|point| point.x)
+        .min()
+        .unwrap_or_default()
+        - 1;
+    let max_x = corners
+        .iter()
+        .map(|point| point.x)
+        .max()
+        .unwrap_or_default()
+        + 1;
+    let min_y = corners
+        .iter()
+        .map(|point| -point.y)
+        .min()
+        .unwrap_or_default()
+        - 1;
+    let max_y = corners
+        .iter()
+        .map(|point| -point.y)
+        .max()
+        .unwrap_or_default()
+        + 1;
+    let radius = f64::from((max_x - min_x).max(max_y - min_y)) / 100.0;
+
+    writeln!(
+        writer,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+        min_x,
+        min_y,
+        max_x - min_x,
+        max_y - min_y
+    )?;
+    let points: Vec<_> = corners
+        .iter()
+        .map(|point| format!("{},{}", point.x, -point.y))
+        .collect();
+    writeln!(
+        writer,
+        r#"  <polyline points="{}" fill="none" stroke="black" stroke-width="1" vector-effect="non-scaling-stroke"/>"#,
+        points.join(" ")
+    )?;
+    let mut mark = |point: Point, color: &str, title: &str| {
+        writeln!(
+            writer,
+            r#"  <circle cx="{}" cy="{}" r="{}" fill="{}"><title>{}</title></circle>"#,
+            point.x, -point.y, radius, color, title
+        )
+    };
+    if let Some(&start) = corners.first() {
+        mark(start, "green", "start")?;
+    }
+    if let Some(&end) = corners.last() {
+        mark(end, "blue", "end")?;
+    }
+    if let Some(point) = first_revisit {
+        mark(point, "red", "first revisit")?;
+    }
+    writeln!(writer, "</svg>")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Turn;
+
+    const CROSSING: &[Instruction] = &[
+        Instruction::new(Turn::Right, 8),
+        Instruction::new(Turn::Right, 4),
+        Instruction::new(Turn::Right, 4),
+        Instruction::new(Turn::Right, 8),
+    ];
+
+    #[test]
+    fn test_geojson() {
+        let mut buffer = Vec::new();
+        export_route(CROSSING, RouteFormat::GeoJson, &mut buffer).unwrap();
+        let collection: serde_json::Value = serde_json::from_slice(&buffer).unwrap();
+
+        let route = &collection["features"][0]["geometry"];
+        assert_eq!(route["type"], "LineString");
+        let coordinates = route["coordinates"].as_array().unwrap();
+        assert_eq!(coordinates.len(), 5);
+        assert_eq!(coordinates[0], serde_json::json!([0, 0]));
+        assert_eq!(coordinates[1], serde_json::json!([8, 0]));
+
+        let revisit = &collection["features"][1];
+        assert_eq!(revisit["properties"]["name"], "first revisit");
+        assert_eq!(
+            revisit["geometry"]["coordinates"],
+            serde_json::json!([4, 0])
+        );
+    }
+
+    #[test]
+    fn test_svg() {
+        let mut buffer = Vec::new();
+        export_route(&CROSSING[..2], RouteFormat::Svg, &mut buffer).unwrap();
+        let svg = String::from_utf8(buffer).unwrap();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(r#"<polyline points="0,0 8,0 "#));
+        assert!(!svg.contains("first revisit"));
+
+        let mut buffer = Vec::new();
+        export_route(CROSSING, RouteFormat::Svg, &mut buffer).unwrap();
+        let svg = String::from_utf8(buffer).unwrap();
+        assert!(svg.contains(r#"<circle cx="4" cy="0" r="0.1" fill="red"><title>first revisit"#));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!("svg".parse::<RouteFormat>().unwrap(), RouteFormat::Svg);
+        assert_eq!(
+            "geojson".parse::<RouteFormat>().unwrap(),
+            RouteFormat::GeoJson
+        );
+        assert!("png".parse::<RouteFormat>().is_err());
+    }
+}
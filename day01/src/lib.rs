@@ -19,6 +19,7 @@
 //! R5, L5, R5, R3 leaves you 12 blocks away.
 //! How many blocks away is Easter Bunny HQ?
 
+use aoc2016_solver::{Answer, Example, Registry, Solver};
 use aoclib::{
     geometry::{
         line::{self, Line},
@@ -27,10 +28,17 @@ use aoclib::{
     },
     parse, CommaSep,
 };
-use std::path::Path;
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::BufRead,
+    path::Path,
+};
+
+mod export;
+pub use export::{export_route, trace_route, RouteFormat};
 
 #[derive(Clone, Copy, Debug, parse_display::Display, parse_display::FromStr)]
-enum Turn {
+pub enum Turn {
     #[display("L")]
     Left,
     #[display("R")]
@@ -40,9 +48,9 @@ enum Turn {
 #[derive(Clone, Copy, Debug, parse_display::Display, parse_display::FromStr)]
 #[display("{turn}{distance}")]
 #[from_str(regex = r" ?(?P<turn>[LR])(?P<distance>\d+)")]
-struct Instruction {
-    turn: Turn,
-    distance: i32,
+pub struct Instruction {
+    pub turn: Turn,
+    pub distance: i32,
 }
 
 #[cfg(test)]
@@ -95,54 +103,384 @@ impl Position {
     }
 
     fn follow_until_duplicate(&mut self, instructions: &[Instruction]) -> Option<Point> {
-        let mut history = Vec::with_capacity(instructions.len());
-        let mut prev_point = self.location;
+        let mut walker = Walker {
+            position: *self,
+            ..Walker::default()
+        };
+        let duplicate =
+            instructions
+                .iter()
+                .find_map(|instruction| match walker.push(*instruction) {
+                    Some(Event::FirstRevisit(point)) => Some(point),
+                    _ => None,
+                });
+        *self = walker.position;
+        duplicate
+    }
+}
 
-        for instruction in instructions {
-            self.follow_instruction(*instruction);
-            let trace = Line::new(prev_point, self.location);
+/// Something noteworthy which happened while following an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// The walker moved to this location.
+    Moved(Point),
+    /// The walker's path crossed itself for the first time, at this point.
+    ///
+    /// The walker's new location is available from `Walker::location`.
+    FirstRevisit(Point),
+}
+
+/// Follow instructions one at a time, as they arrive.
+#[derive(Debug, Default, Clone)]
+pub struct Walker {
+    position: Position,
+    history: Vec<Line>,
+    first_revisit: Option<Point>,
+}
+
+impl Walker {
+    pub fn new() -> Walker {
+        Walker::default()
+    }
+
+    pub fn location(&self) -> Point {
+        self.position.location
+    }
 
-            // O(n**2) still isn't great, but at least we're doing things line-by-line
-            // instead of point-by-point.
+    pub fn first_revisit(&self) -> Option<Point> {
+        self.first_revisit
+    }
+
+    /// Follow a single instruction, reporting what happened.
+    ///
+    /// Returns `None` if the instruction did not move the walker.
+    pub fn push(&mut self, instruction: Instruction) -> Option<Event> {
+        let prev_point = self.position.location;
+        self.position.follow_instruction(instruction);
+        if self.position.location == prev_point {
+            return None;
+        }
+
+        // once we've found the first revisit, there's no need to keep tracking history
+        if self.first_revisit.is_some() {
+            return Some(Event::Moved(self.position.location));
+        }
+
+        let trace = Line::new(prev_point, self.position.location);
 
-            for prev_line in &history {
-                if let Some(intersect) = line::intersect(*prev_line, trace) {
-                    if intersect != prev_point {
-                        return Some(intersect);
-                    }
+        // O(n**2) still isn't great, but at least we're doing things line-by-line
+        // instead of point-by-point.
+        for prev_line in &self.history {
+            if let Some(intersect) = line::intersect(*prev_line, trace) {
+                if intersect != prev_point {
+                    self.first_revisit = Some(intersect);
+                    self.history.clear();
+                    return Some(Event::FirstRevisit(intersect));
                 }
             }
+        }
 
-            prev_point = self.location;
-            history.push(trace);
+        self.history.push(trace);
+        Some(Event::Moved(self.position.location))
+    }
+}
+
+/// The points visited while following a single instruction, as an inclusive bounding box.
+///
+/// Because every walk is along an axis, the box is a line (or a single point).
+#[derive(Debug, Clone, Copy)]
+struct Visited {
+    low: Point,
+    high: Point,
+}
+
+impl Visited {
+    fn intersect(&self, other: &Visited) -> Option<Visited> {
+        let low = Point::new(self.low.x.max(other.low.x), self.low.y.max(other.low.y));
+        let high = Point::new(self.high.x.min(other.high.x), self.high.y.min(other.high.y));
+        (low.x <= high.x && low.y <= high.y).then_some(Visited { low, high })
+    }
+
+    fn points(&self) -> impl Iterator<Item = Point> {
+        let Visited { low, high } = *self;
+        (low.y..=high.y).flat_map(move |y| (low.x..=high.x).map(move |x| Point::new(x, y)))
+    }
+
+    fn is_horizontal(&self) -> bool {
+        self.low.y == self.high.y
+    }
+
+    /// The extent of this walk along its own axis.
+    fn span(&self) -> (i32, i32) {
+        if self.is_horizontal() {
+            (self.low.x, self.high.x)
+        } else {
+            (self.low.y, self.high.y)
+        }
+    }
+}
+
+/// Each instruction which moves the walker, as `(instruction index, first point visited, points
+/// visited)`.
+fn walks(instructions: &[Instruction]) -> Vec<(usize, Point, Visited)> {
+    let mut position = Position::default();
+    let mut walks = Vec::with_capacity(instructions.len());
+    for (idx, instruction) in instructions.iter().enumerate() {
+        let from = position.location;
+        position.follow_instruction(*instruction);
+        let to = position.location;
+        if from == to {
+            continue;
         }
-        None
+        // each walk visits the points after its start; the origin belongs to the first walk
+        let first = if idx == 0 {
+            from
+        } else {
+            from + position.facing
+        };
+        let visited = Visited {
+            low: Point::new(first.x.min(to.x), first.y.min(to.y)),
+            high: Point::new(first.x.max(to.x), first.y.max(to.y)),
+        };
+        walks.push((idx, first, visited));
+    }
+    walks
+}
+
+/// What happens to a walk as the sweep line reaches it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Sweep {
+    /// A horizontal walk becomes active.
+    Start,
+    /// A vertical walk crosses every active horizontal walk within its span.
+    Cross,
+    /// A horizontal walk is no longer active.
+    End,
+}
+
+/// Every pair of walks, by position in `walks`, which visit a point in common.
+///
+/// Walks along the same line are sorted along it, so each is compared only with the earlier
+/// ones it overlaps. Crossings are found by sweeping a vertical line across the map, keeping the
+/// horizontal walks it currently meets ordered by `y`. Either way, the work done is proportional
+/// to the number of pairs found, not the number of pairs of walks, up to a logarithmic factor.
+fn overlapping_walks(walks: &[(usize, Point, Visited)]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+
+    let mut lines: HashMap<(bool, i32), Vec<usize>> = HashMap::new();
+    for (walk, (_, _, visited)) in walks.iter().enumerate() {
+        let line = if visited.is_horizontal() {
+            (true, visited.low.y)
+        } else {
+            (false, visited.low.x)
+        };
+        lines.entry(line).or_default().push(walk);
+    }
+    for mut line in lines.into_values() {
+        line.sort_unstable_by_key(|&walk| walks[walk].2.span());
+        let mut active: Vec<usize> = Vec::new();
+        for walk in line {
+            let (low, _) = walks[walk].2.span();
+            active.retain(|&other| walks[other].2.span().1 >= low);
+            pairs.extend(active.iter().map(|&other| (other, walk)));
+            active.push(walk);
+        }
+    }
+
+    let mut events = Vec::with_capacity(2 * walks.len());
+    for (walk, (_, _, visited)) in walks.iter().enumerate() {
+        if visited.is_horizontal() {
+            events.push((visited.low.x, Sweep::Start, walk));
+            events.push((visited.high.x, Sweep::End, walk));
+        } else {
+            events.push((visited.low.x, Sweep::Cross, walk));
+        }
+    }
+    events.sort_unstable();
+    let mut active = BTreeSet::new();
+    for (_, sweep, walk) in events {
+        let visited = walks[walk].2;
+        match sweep {
+            Sweep::Start => {
+                active.insert((visited.low.y, walk));
+            }
+            Sweep::Cross => pairs.extend(
+                active
+                    .range((visited.low.y, 0)..=(visited.high.y, usize::MAX))
+                    .map(|&(_, horizontal)| (horizontal, walk)),
+            ),
+            Sweep::End => {
+                active.remove(&(visited.low.y, walk));
+            }
+        }
+    }
+
+    pairs
+}
+
+/// List every point at which the path revisits itself.
+///
+/// Each entry is `(point, earlier, later)`, where `earlier` and `later` are the indices of
+/// the instructions whose walks both pass through `point`. A point visited several times
+/// appears once for each earlier visit. Entries are in the order in which the later visits
+/// happen, so the first entry is the first revisit.
+///
+/// The starting point counts as visited by instruction 0.
+pub fn all_revisits(instructions: &[Instruction]) -> Vec<(Point, usize, usize)> {
+    let walks = walks(instructions);
+    let mut revisits: Vec<_> = overlapping_walks(&walks)
+        .into_iter()
+        .flat_map(|(a, b)| {
+            let (earlier, later) = if walks[a].0 < walks[b].0 {
+                (walks[a], walks[b])
+            } else {
+                (walks[b], walks[a])
+            };
+            let (earlier, _, earlier_visited) = earlier;
+            let (later, first, later_visited) = later;
+            later_visited
+                .intersect(&earlier_visited)
+                .into_iter()
+                .flat_map(|overlap| overlap.points())
+                .map(move |point| (later, (point - first).manhattan(), earlier, point))
+        })
+        .collect();
+    revisits.sort_unstable_by_key(|&(later, steps, earlier, _)| (later, steps, earlier));
+    revisits
+        .into_iter()
+        .map(|(later, _, earlier, point)| (point, earlier, later))
+        .collect()
+}
+
+pub fn print_revisits(path: &Path) -> Result<(), Error> {
+    let instructions = parse::<CommaSep<Instruction>>(path)?
+        .flatten()
+        .collect::<Vec<_>>();
+    for (point, earlier, later) in all_revisits(&instructions) {
+        println!(
+            "({}, {}): instructions {} and {}; distance {}",
+            point.x,
+            point.y,
+            earlier,
+            later,
+            point.manhattan()
+        );
     }
+    Ok(())
 }
 
-pub fn part1(path: &Path) -> Result<(), Error> {
+/// Write the route walked by following the instructions at `path` to `out_path`.
+pub fn write_route(path: &Path, format: RouteFormat, out_path: &Path) -> Result<(), Error> {
     let instructions = parse::<CommaSep<Instruction>>(path)?
         .flatten()
         .collect::<Vec<_>>();
+    let writer = std::io::BufWriter::new(std::fs::File::create(out_path)?);
+    export_route(&instructions, format, writer)
+}
+
+/// Follow comma-separated instructions from `reader` as they arrive, printing each event.
+pub fn stream(reader: impl BufRead) -> Result<(), Error> {
+    let mut walker = Walker::new();
+    for token in reader.split(b',') {
+        let token = String::from_utf8_lossy(&token?).trim().to_owned();
+        if token.is_empty() {
+            continue;
+        }
+        let instruction: Instruction = token.parse().map_err(|_| Error::Parse(token))?;
+        match walker.push(instruction) {
+            Some(Event::Moved(location)) => println!(
+                "{}: at ({}, {}); distance {}",
+                instruction,
+                location.x,
+                location.y,
+                location.manhattan()
+            ),
+            Some(Event::FirstRevisit(point)) => println!(
+                "{}: first revisit at ({}, {}); distance {}",
+                instruction,
+                point.x,
+                point.y,
+                point.manhattan()
+            ),
+            None => println!("{}: no movement", instruction),
+        }
+    }
+    Ok(())
+}
+
+/// The Manhattan distance to Easter Bunny HQ.
+pub fn part1(path: &Path) -> Result<i32, Error> {
+    part1_from_str(&std::fs::read_to_string(path)?)
+}
+
+/// The Manhattan distance to Easter Bunny HQ, given the instructions as text.
+pub fn part1_from_str(input: &str) -> Result<i32, Error> {
+    let instructions = aoclib::input::parse_str::<CommaSep<Instruction>>(input)?
+        .flatten()
+        .collect::<Vec<_>>();
     let mut position = Position::default();
     position.follow(&instructions);
-    println!("hq manhattan: {}", position.location.manhattan());
-    Ok(())
+    Ok(position.location.manhattan())
 }
 
-pub fn part2(path: &Path) -> Result<(), Error> {
-    let instructions = parse::<CommaSep<Instruction>>(path)?
+/// The Manhattan distance to the first location visited twice.
+pub fn part2(path: &Path) -> Result<i32, Error> {
+    part2_from_str(&std::fs::read_to_string(path)?)
+}
+
+/// The Manhattan distance to the first location visited twice, given the instructions as text.
+pub fn part2_from_str(input: &str) -> Result<i32, Error> {
+    let instructions = aoclib::input::parse_str::<CommaSep<Instruction>>(input)?
         .flatten()
         .collect::<Vec<_>>();
     let mut position = Position::default();
     let intersection = position
         .follow_until_duplicate(&instructions)
         .ok_or(Error::NoIntersection)?;
-    println!(
-        "dist of first duplicate point: {}",
-        intersection.manhattan()
-    );
-    Ok(())
+    Ok(intersection.manhattan())
+}
+
+const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: "R2, L3",
+        answer: "5",
+    },
+    Example {
+        part: 1,
+        input: "R2, R2, R2",
+        answer: "2",
+    },
+    Example {
+        part: 1,
+        input: "R5, L5, R5, R3",
+        answer: "12",
+    },
+    Example {
+        part: 2,
+        input: "R8, R4, R4, R8",
+        answer: "4",
+    },
+];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part1_from_str(input)?.into())
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part2_from_str(input)?.into())
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(1, Solution);
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -151,6 +489,10 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("no intersection found")]
     NoIntersection,
+    #[error("could not parse instruction: {0:?}")]
+    Parse(String),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
 }
 
 #[cfg(test)]
@@ -225,4 +567,101 @@ mod tests {
         assert_eq!(dupe, Point::new(4, 0));
         assert_eq!(dupe.manhattan(), 4);
     }
+
+    #[test]
+    fn test_walker_events() {
+        let mut walker = Walker::new();
+        let events: Vec<_> = FOURTH_CASE
+            .iter()
+            .map(|instruction| walker.push(*instruction))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                Some(Event::Moved(Point::new(8, 0))),
+                Some(Event::Moved(Point::new(8, -4))),
+                Some(Event::Moved(Point::new(4, -4))),
+                Some(Event::FirstRevisit(Point::new(4, 0))),
+            ]
+        );
+        assert_eq!(walker.location(), Point::new(4, 4));
+        assert_eq!(walker.first_revisit(), Some(Point::new(4, 0)));
+        assert_eq!(
+            walker.push(Instruction::new(Turn::Left, 0)),
+            None,
+            "turning in place is not movement"
+        );
+    }
+
+    #[test]
+    fn test_all_revisits() {
+        let revisits = all_revisits(FOURTH_CASE);
+        assert_eq!(revisits, vec![(Point::new(4, 0), 0, 3)]);
+
+        let mut position = Position::default();
+        assert_eq!(
+            position.follow_until_duplicate(FOURTH_CASE),
+            Some(revisits[0].0)
+        );
+    }
+
+    #[test]
+    fn test_all_revisits_matches_pairwise() {
+        // a long, tangled path from a fixed pseudo-random sequence
+        let mut state = 2016_u32;
+        let instructions: Vec<_> = (0..300)
+            .map(|_| {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                let turn = if state & 0x100 == 0 {
+                    Turn::Left
+                } else {
+                    Turn::Right
+                };
+                Instruction::new(turn, (state >> 16) as i32 % 12)
+            })
+            .collect();
+
+        let walks = walks(&instructions);
+        let mut pairwise = Vec::new();
+        for (later_idx, &(later, first, later_visited)) in walks.iter().enumerate() {
+            for &(earlier, _, earlier_visited) in &walks[..later_idx] {
+                if let Some(overlap) = later_visited.intersect(&earlier_visited) {
+                    pairwise.extend(overlap.points().map(|point| {
+                        let steps = (point - first).manhattan();
+                        (later, steps, earlier, point)
+                    }));
+                }
+            }
+        }
+        pairwise.sort_unstable_by_key(|&(later, steps, earlier, _)| (later, steps, earlier));
+        let pairwise: Vec<_> = pairwise
+            .into_iter()
+            .map(|(later, _, earlier, point)| (point, earlier, later))
+            .collect();
+
+        assert!(pairwise.len() > 100, "too few revisits to be a useful test");
+        assert_eq!(all_revisits(&instructions), pairwise);
+    }
+
+    #[test]
+    fn test_all_revisits_doubling_back() {
+        let instructions = [
+            Instruction::new(Turn::Right, 3),
+            Instruction::new(Turn::Right, 0),
+            Instruction::new(Turn::Right, 3),
+            Instruction::new(Turn::Left, 1),
+            Instruction::new(Turn::Left, 1),
+            Instruction::new(Turn::Left, 2),
+        ];
+        assert_eq!(
+            all_revisits(&instructions),
+            vec![
+                (Point::new(2, 0), 0, 2),
+                (Point::new(1, 0), 0, 2),
+                (Point::new(0, 0), 0, 2),
+                (Point::new(1, 0), 0, 5),
+                (Point::new(1, 0), 2, 5),
+            ]
+        );
+    }
 }
@@ -1,19 +1,26 @@
-use aoclib::{config::Config, website::get_input};
-use day01::{part1, part2};
+use aoc2016_solver::{input::prompt_stdin, Input};
+use day01::{part1, part2, print_revisits, stream, write_route, RouteFormat};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 1;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
+    /// follow instructions from stdin as they arrive, instead of solving the puzzle
+    #[structopt(long)]
+    stream: bool,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -21,33 +28,41 @@ struct RunArgs {
     /// run part 2
     #[structopt(long)]
     part2: bool,
-}
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
-    }
+    /// list every point at which the path crosses itself
+    #[structopt(long)]
+    all_revisits: bool,
+
+    /// write the walked route to this path
+    #[structopt(long, parse(from_os_str))]
+    export_route: Option<PathBuf>,
+
+    /// format of the exported route: svg or geojson
+    #[structopt(long, default_value = "svg")]
+    route_format: RouteFormat,
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    if args.stream {
+        prompt_stdin();
+        stream(std::io::stdin().lock())?;
+        return Ok(());
+    }
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
 
     if !args.no_part1 {
-        part1(&input_path)?;
+        println!("hq manhattan: {}", part1(&input_path)?);
     }
     if args.part2 {
-        part2(&input_path)?;
+        println!("dist of first duplicate point: {}", part2(&input_path)?);
+    }
+    if args.all_revisits {
+        print_revisits(&input_path)?;
+    }
+    if let Some(ref out_path) = args.export_route {
+        write_route(&input_path, args.route_format, out_path)?;
     }
     Ok(())
 }
@@ -49,42 +49,15 @@
 //! Your puzzle input is the instructions from the document you found at the front desk.
 //! What is the bathroom code?
 
+pub use aoc2016_core::keypad::{Instruction, Key, Keypad};
+use aoc2016_solver::{Answer, Example, Registry, Solver};
 use std::{
+    collections::{HashSet, VecDeque},
     fs::File,
     io::{BufRead, BufReader},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum Instruction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-impl Instruction {
-    pub fn from_char(ch: char) -> Option<Instruction> {
-        use Instruction::*;
-
-        match ch {
-            'u' | 'U' => Some(Up),
-            'd' | 'D' => Some(Down),
-            'l' | 'L' => Some(Left),
-            'r' | 'R' => Some(Right),
-            _ => None,
-        }
-    }
-
-    // disable clippy here because it's too much hassle to come up with a better name
-    #[allow(clippy::should_implement_trait)]
-    pub fn from_str(s: &str) -> Option<Vec<Instruction>> {
-        s.trim().chars().map(Instruction::from_char).collect()
-    }
-}
-
-pub type Keypad = &'static [&'static [Option<char>]];
-
 const KEYPAD_ORTHO: Keypad = &[
     &[Some('1'), Some('2'), Some('3')],
     &[Some('4'), Some('5'), Some('6')],
@@ -99,67 +72,10 @@ const KEYPAD_DIAG: Keypad = &[
     &[None, None, Some('D'), None, None],
 ];
 
-/// Represents a key on a keypad
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub struct Key {
-    x: usize,
-    y: usize,
-    pad: Keypad,
-}
-
-impl Key {
-    pub fn center_on(key: char, keypad: Keypad) -> Option<Key> {
-        for (y, row) in keypad.iter().enumerate() {
-            for (x, maybe_key) in row.iter().enumerate() {
-                if *maybe_key == Some(key) {
-                    return Some(Key { x, y, pad: keypad });
-                }
-            }
-        }
-        None
-    }
-
-    pub fn shift(&mut self, inst: Instruction) {
-        use Instruction::*;
-
-        match inst {
-            Up => {
-                if self.y > 0 && self.pad[self.y - 1][self.x].is_some() {
-                    self.y -= 1
-                }
-            }
-            Down => {
-                if self.y < self.pad.len() - 1 && self.pad[self.y + 1][self.x].is_some() {
-                    self.y += 1;
-                }
-            }
-            Left => {
-                if self.x > 0 && self.pad[self.y][self.x - 1].is_some() {
-                    self.x -= 1;
-                }
-            }
-            Right => {
-                if self.x < self.pad[self.y].len() - 1 && self.pad[self.y][self.x + 1].is_some() {
-                    self.x += 1;
-                }
-            }
-        }
-    }
-
-    pub fn shift_many(&mut self, insts: &[Instruction]) {
-        for inst in insts {
-            self.shift(*inst);
-        }
-    }
-
-    pub fn char(&self) -> char {
-        self.pad[self.y][self.x].expect("can't have a key without a char")
-    }
-}
-
-/// Parse a number of lines into a code.
-pub fn decode_on(reader: impl BufRead, keypad: Keypad) -> Result<String, Error> {
-    let mut key = Key::center_on('5', keypad).ok_or(Error::BadKeypad)?;
+/// Parse a number of lines into a code, starting from the given key.
+///
+/// Return the code and the key on which decoding finished.
+pub fn decode_from(reader: impl BufRead, mut key: Key) -> Result<(String, Key), Error> {
     let mut out = String::new();
 
     for line in reader.lines() {
@@ -169,25 +85,166 @@ pub fn decode_on(reader: impl BufRead, keypad: Keypad) -> Result<String, Error>
         out.push(key.char());
     }
 
-    Ok(out)
+    Ok((out, key))
+}
+
+/// Parse a number of lines into a code, starting from the '5' key.
+///
+/// Return the code and the key on which decoding finished.
+pub fn decode_on(reader: impl BufRead, keypad: Keypad) -> Result<(String, Key), Error> {
+    let key = Key::center_on('5', keypad).ok_or(Error::BadKeypad)?;
+    decode_from(reader, key)
+}
+
+/// Decode several documents in sequence.
+///
+/// The first document starts from the '5' key; each subsequent document starts from
+/// the key on which the previous one finished.
+pub fn decode_many<R: BufRead>(
+    docs: impl IntoIterator<Item = R>,
+    keypad: Keypad,
+) -> Result<Vec<String>, Error> {
+    let mut key = Key::center_on('5', keypad).ok_or(Error::BadKeypad)?;
+    let mut codes = Vec::new();
+    for doc in docs {
+        let (code, end) = decode_from(doc, key)?;
+        codes.push(code);
+        key = end;
+    }
+    Ok(codes)
 }
 
-pub fn part1(path: &Path) -> Result<(), Error> {
+fn decode_path(path: &Path, keypad: Keypad) -> Result<String, Error> {
     let file = File::open(path)?;
     let reader = BufReader::new(file);
-    let code = decode_on(reader, KEYPAD_ORTHO)?;
-    println!("code on ortho keys: {}", code);
+    decode_on(reader, keypad).map(|(code, _)| code)
+}
+
+/// The bathroom code on the ordinary keypad.
+pub fn part1(path: &Path) -> Result<String, Error> {
+    decode_path(path, KEYPAD_ORTHO)
+}
+
+/// The bathroom code on the ordinary keypad, given the instructions as text.
+pub fn part1_from_str(input: &str) -> Result<String, Error> {
+    decode_on(input.as_bytes(), KEYPAD_ORTHO).map(|(code, _)| code)
+}
+
+/// The bathroom code on the diamond-shaped keypad.
+pub fn part2(path: &Path) -> Result<String, Error> {
+    decode_path(path, KEYPAD_DIAG)
+}
+
+/// The bathroom code on the diamond-shaped keypad, given the instructions as text.
+pub fn part2_from_str(input: &str) -> Result<String, Error> {
+    decode_on(input.as_bytes(), KEYPAD_DIAG).map(|(code, _)| code)
+}
+
+/// Decode a chain of documents on both keypads, each starting where the previous left off.
+pub fn chain(paths: &[PathBuf]) -> Result<(), Error> {
+    for (name, keypad) in &[("ortho", KEYPAD_ORTHO), ("diag", KEYPAD_DIAG)] {
+        let docs = paths
+            .iter()
+            .map(|path| File::open(path).map(BufReader::new))
+            .collect::<Result<Vec<_>, _>>()?;
+        let codes = decode_many(docs, keypad)?;
+        println!("chained codes on {} keys: {}", name, codes.join(", "));
+    }
     Ok(())
 }
 
-pub fn part2(path: &Path) -> Result<(), Error> {
-    let file = File::open(path)?;
-    let reader = BufReader::new(file);
-    let code = decode_on(reader, KEYPAD_DIAG)?;
-    println!("code on diag keys: {}", code);
+/// Find a shortest instruction line leading from `from` to the key labeled `to`.
+fn shortest_line(from: Key, to: char) -> Option<String> {
+    const INSTRUCTIONS: [Instruction; 4] = [
+        Instruction::Up,
+        Instruction::Down,
+        Instruction::Left,
+        Instruction::Right,
+    ];
+
+    let mut visited = HashSet::new();
+    visited.insert(from.char());
+    let mut queue = VecDeque::new();
+    queue.push_back((from, String::new()));
+
+    while let Some((key, line)) = queue.pop_front() {
+        if key.char() == to {
+            return Some(line);
+        }
+        for instruction in INSTRUCTIONS {
+            let mut next = key;
+            next.shift(instruction);
+            if visited.insert(next.char()) {
+                let mut line = line.clone();
+                line.push(instruction.as_char());
+                queue.push_back((next, line));
+            }
+        }
+    }
+
+    None
+}
+
+/// Produce instructions which decode to `code` on `keypad`: the inverse of `decode_on`.
+///
+/// Each line is a shortest sequence of moves from the previous key, starting from the '5' key.
+/// A line is empty when a key repeats.
+pub fn derive_instructions(code: &str, keypad: Keypad) -> Result<Vec<String>, Error> {
+    let mut key = Key::center_on('5', keypad).ok_or(Error::BadKeypad)?;
+    code.chars()
+        .map(|ch| {
+            let line = shortest_line(key, ch).ok_or(Error::UnknownKey(ch))?;
+            key = Key::center_on(ch, keypad).ok_or(Error::UnknownKey(ch))?;
+            Ok(line)
+        })
+        .collect()
+}
+
+/// Print instructions which produce `code` on the ortho keypad, or the diag keypad if `diag`.
+pub fn print_derived(code: &str, diag: bool) -> Result<(), Error> {
+    let keypad = if diag { KEYPAD_DIAG } else { KEYPAD_ORTHO };
+    for line in derive_instructions(code, keypad)? {
+        println!("{}", line);
+    }
     Ok(())
 }
 
+/// The instructions from the puzzle text.
+const EXAMPLE: &str = "ULL\nRRDDD\nLURDL\nUUUUD\n";
+
+const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        answer: "1985",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        answer: "5DB3",
+    },
+];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part1_from_str(input)?.into())
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part2_from_str(input)?.into())
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(2, Solution);
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
@@ -196,6 +253,8 @@ pub enum Error {
     UnknownInstruction,
     #[error("bad keypad")]
     BadKeypad,
+    #[error("no reachable key '{0}' on keypad")]
+    UnknownKey(char),
 }
 
 #[cfg(test)]
@@ -204,8 +263,6 @@ mod tests {
 
     use super::*;
 
-    const EXAMPLE: &str = "ULL\nRRDDD\nLURDL\nUUUUD\n";
-
     #[test]
     /// From the example:
     ///
@@ -218,15 +275,60 @@ mod tests {
     ///
     /// produces "5DB3"
     fn test_decode_diag() {
-        let result = decode_on(Cursor::new(EXAMPLE), KEYPAD_DIAG)
+        let (result, _) = decode_on(Cursor::new(EXAMPLE), KEYPAD_DIAG)
             .expect("Decoding failed when it shouldn't");
         assert_eq!(result, "5DB3");
     }
 
     #[test]
     fn test_decode_ortho() {
-        let result = decode_on(Cursor::new(EXAMPLE), KEYPAD_ORTHO)
+        let (result, end) = decode_on(Cursor::new(EXAMPLE), KEYPAD_ORTHO)
             .expect("Decoding failed when it shouldn't");
         assert_eq!(result, "1985");
+        assert_eq!(end.char(), '5');
+    }
+
+    #[test]
+    fn test_decode_many() {
+        let docs = vec![
+            Cursor::new("ULL\n"),
+            Cursor::new("RRDDD\nLURDL\n"),
+            Cursor::new("UUUUD\n"),
+        ];
+        let codes = decode_many(docs, KEYPAD_ORTHO).unwrap();
+        assert_eq!(codes, vec!["1", "98", "5"]);
+    }
+
+    #[test]
+    fn test_derive_instructions() {
+        let lines = derive_instructions("1985", KEYPAD_ORTHO).unwrap();
+        assert_eq!(
+            lines.iter().map(String::len).collect::<Vec<_>>(),
+            [2, 4, 1, 1]
+        );
+        let (code, _) = decode_on(Cursor::new(lines.join("\n")), KEYPAD_ORTHO).unwrap();
+        assert_eq!(code, "1985");
+
+        for keypad in [KEYPAD_ORTHO, KEYPAD_DIAG] {
+            let code = if keypad == KEYPAD_ORTHO {
+                "5123456789"
+            } else {
+                "D1A9C55B"
+            };
+            let lines = derive_instructions(code, keypad).unwrap();
+            let (decoded, _) = decode_on(Cursor::new(lines.join("\n")), keypad).unwrap();
+            assert_eq!(decoded, code);
+        }
+
+        assert!(matches!(
+            derive_instructions("1A", KEYPAD_ORTHO),
+            Err(Error::UnknownKey('A'))
+        ));
+    }
+
+    #[test]
+    fn test_solution() {
+        assert_eq!(Solution.part1(EXAMPLE).unwrap(), Answer::from("1985"));
+        assert_eq!(Solution.part2(EXAMPLE).unwrap(), Answer::from("5DB3"));
     }
 }
@@ -1,19 +1,26 @@
-use aoclib::{config::Config, website::get_input};
-use day02::{part1, part2};
+use aoc2016_solver::Input;
+use day02::{chain, part1, part2, print_derived};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 2;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
+    /// decode these documents after the input, each starting from the previous final key
+    #[structopt(long, parse(from_os_str))]
+    chain: Vec<PathBuf>,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -21,33 +28,35 @@ struct RunArgs {
     /// run part 2
     #[structopt(long)]
     part2: bool,
-}
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
-    }
+    /// print instructions which produce this code, instead of decoding the input
+    #[structopt(long)]
+    derive: Option<String>,
+
+    /// derive instructions for the diagonal keypad from part 2
+    #[structopt(long)]
+    diag: bool,
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    if let Some(ref code) = args.derive {
+        print_derived(code, args.diag)?;
+        return Ok(());
+    }
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
 
+    if !args.chain.is_empty() {
+        let mut paths = vec![input_path.to_path_buf()];
+        paths.extend(args.chain.iter().cloned());
+        chain(&paths)?;
+    }
     if !args.no_part1 {
-        part1(&input_path)?;
+        println!("code on ortho keys: {}", part1(&input_path)?);
     }
     if args.part2 {
-        part2(&input_path)?;
+        println!("code on diag keys: {}", part2(&input_path)?);
     }
     Ok(())
 }
@@ -0,0 +1,30 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use day03::{count_possible, Triangle};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+fn triangles() -> Vec<Triangle> {
+    let mut rng = StdRng::seed_from_u64(2016);
+    (0..1_000_000)
+        .map(|_| {
+            Triangle::new(
+                rng.gen_range(1..1000),
+                rng.gen_range(1..1000),
+                rng.gen_range(1..1000),
+            )
+        })
+        .collect()
+}
+
+fn count(c: &mut Criterion) {
+    let triangles = triangles();
+
+    let mut group = c.benchmark_group("count 1M triangles");
+    group.bench_function("per triangle", |b| {
+        b.iter(|| triangles.iter().filter(|t| t.is_possible()).count())
+    });
+    group.bench_function("bulk", |b| b.iter(|| count_possible(&triangles)));
+    group.finish();
+}
+
+criterion_group!(benches, count);
+criterion_main!(benches);
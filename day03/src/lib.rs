@@ -16,27 +16,46 @@
 //!
 //! In your puzzle input, how many of the listed triangles are possible?
 
-use aoclib::parse;
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+
+use aoc2016_solver::{Answer, Example, Registry, Solver};
+use aoclib::input::parse_str;
 use std::path::Path;
 
-#[derive(Debug, parse_display::Display, parse_display::FromStr)]
+#[derive(Debug, Clone, Copy, parse_display::Display, parse_display::FromStr)]
 #[display("{0:>3} {1:>3} {2:>3}")]
 #[from_str(regex = r"(?P<0>\d+)\s+(?P<1>\d+)\s+(?P<2>\d+)")]
-struct Triangle(u64, u64, u64);
+pub struct Triangle(u64, u64, u64);
 
 impl Triangle {
+    pub fn new(a: u64, b: u64, c: u64) -> Triangle {
+        Triangle(a, b, c)
+    }
+
     fn as_array(&self) -> [u64; 3] {
         [self.0, self.1, self.2]
     }
 
-    fn is_possible(&self) -> bool {
+    pub fn is_possible(&self) -> bool {
         let mut array = self.as_array();
         array.sort_unstable();
         array[0] + array[1] > array[2]
     }
+
+    /// Equivalent to `is_possible`, but sorts the sides with `min` and `max`, which compile to
+    /// branchless instructions.
+    ///
+    /// The comparison is arranged so that it can't overflow.
+    fn is_possible_branchless(&self) -> bool {
+        let Triangle(a, b, c) = *self;
+        let (low, high) = (a.min(b), a.max(b));
+        let min = low.min(c);
+        let max = high.max(c);
+        let mid = low.max(high.min(c));
+        min > max - mid
+    }
 }
 
-#[cfg(test)]
 impl From<[u64; 3]> for Triangle {
     fn from([a, b, c]: [u64; 3]) -> Self {
         Triangle(a, b, c)
@@ -57,18 +76,158 @@ fn reorient(triangles: &[Triangle]) -> Vec<Triangle> {
     vertical
 }
 
-pub fn part1(path: &Path) -> Result<(), Error> {
-    let possible_triangles = parse::<Triangle>(path)?.filter(|t| t.is_possible()).count();
-    println!("possible triangles: {}", possible_triangles);
-    Ok(())
+/// Whether triangles are read from the rows of the input, as in part 1, or down its columns,
+/// as in part 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, parse_display::Display)]
+#[display(style = "lowercase")]
+pub enum Orientation {
+    Horizontal,
+    Vertical,
+}
+
+impl Orientation {
+    /// The triangles read from `rows` in this orientation.
+    pub fn read(self, rows: &[Triangle]) -> Vec<Triangle> {
+        match self {
+            Orientation::Horizontal => rows.to_vec(),
+            Orientation::Vertical => reorient(rows),
+        }
+    }
+}
+
+/// Reading vertically must find at least this fraction of all triangles more possible than
+/// reading horizontally before an input is considered vertical.
+const VERTICAL_MARGIN: f64 = 0.1;
+
+/// Guess which way the rows of an input are best read: the orientation in which clearly more
+/// triangles are possible.
+///
+/// This is only a hint. Both parts of the puzzle read the same input, and real inputs give
+/// noticeably more possible triangles when read vertically, so they are reported as vertical
+/// even for part 1. Inputs whose rows can't be grouped in threes can only be read horizontally;
+/// inputs without a clear margin between the readings also go to horizontal.
+pub fn detect_orientation(triangles: &[Triangle]) -> Orientation {
+    if triangles.is_empty() || triangles.len() % 3 != 0 {
+        return Orientation::Horizontal;
+    }
+    // both readings hold the same number of triangles, so their counts compare directly
+    let horizontal = count_possible(triangles);
+    let vertical = count_possible(&reorient(triangles));
+    let margin = (triangles.len() as f64 * VERTICAL_MARGIN).ceil() as usize;
+    if vertical >= horizontal + margin.max(1) {
+        Orientation::Vertical
+    } else {
+        Orientation::Horizontal
+    }
+}
+
+/// Count the possible triangles.
+///
+/// Triangles are checked in fixed-size chunks with branchless comparisons, which lets the
+/// compiler vectorize the loop. With the `simd` feature (nightly only), the chunks are
+/// checked with explicit SIMD instructions instead.
+pub fn count_possible(triangles: &[Triangle]) -> usize {
+    const CHUNK: usize = 8;
+
+    let chunks = triangles.chunks_exact(CHUNK);
+    let remainder = chunks.remainder();
+    let mut count = 0;
+    for chunk in chunks {
+        count += count_chunk(chunk);
+    }
+    count
+        + remainder
+            .iter()
+            .filter(|t| t.is_possible_branchless())
+            .count()
+}
+
+#[cfg(not(feature = "simd"))]
+fn count_chunk(chunk: &[Triangle]) -> usize {
+    chunk
+        .iter()
+        .map(|t| t.is_possible_branchless() as usize)
+        .sum()
+}
+
+#[cfg(feature = "simd")]
+fn count_chunk(chunk: &[Triangle]) -> usize {
+    use std::simd::{
+        cmp::{SimdOrd, SimdPartialOrd},
+        u64x8,
+    };
+
+    let a = u64x8::from_array(std::array::from_fn(|idx| chunk[idx].0));
+    let b = u64x8::from_array(std::array::from_fn(|idx| chunk[idx].1));
+    let c = u64x8::from_array(std::array::from_fn(|idx| chunk[idx].2));
+
+    let (low, high) = (a.simd_min(b), a.simd_max(b));
+    let min = low.simd_min(c);
+    let max = high.simd_max(c);
+    let mid = low.simd_max(high.simd_min(c));
+    min.simd_gt(max - mid).to_bitmask().count_ones() as usize
+}
+
+/// The number of possible triangles, reading each row as a triangle.
+pub fn part1(path: &Path) -> Result<usize, Error> {
+    part1_from_str(&std::fs::read_to_string(path)?)
+}
+
+/// The number of possible triangles, reading each row of `input` as a triangle.
+pub fn part1_from_str(input: &str) -> Result<usize, Error> {
+    let triangles: Vec<Triangle> = parse_str(input)?.collect();
+    Ok(count_possible(&triangles))
+}
+
+/// The number of possible triangles, reading triangles vertically.
+pub fn part2(path: &Path) -> Result<usize, Error> {
+    part2_from_str(&std::fs::read_to_string(path)?)
+}
+
+/// The number of possible triangles, reading triangles vertically from `input`.
+pub fn part2_from_str(input: &str) -> Result<usize, Error> {
+    let triangles: Vec<Triangle> = parse_str(input)?.collect();
+    Ok(count_possible(&reorient(&triangles)))
+}
+
+/// The number of possible triangles, reading triangles in the orientation `detect_orientation`
+/// picks, and that orientation.
+pub fn count_valid_auto(path: &Path) -> Result<(Orientation, usize), Error> {
+    count_valid_auto_from_str(&std::fs::read_to_string(path)?)
+}
+
+/// The number of possible triangles in `input`, reading triangles in the orientation
+/// `detect_orientation` picks, and that orientation.
+pub fn count_valid_auto_from_str(input: &str) -> Result<(Orientation, usize), Error> {
+    let triangles: Vec<Triangle> = parse_str(input)?.collect();
+    let orientation = detect_orientation(&triangles);
+    Ok((orientation, count_possible(&orientation.read(&triangles))))
+}
+
+const EXAMPLES: &[Example] = &[Example {
+    part: 1,
+    input: "5 10 25\n",
+    answer: "0",
+}];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part1_from_str(input)?.into())
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part2_from_str(input)?.into())
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
 }
 
-pub fn part2(path: &Path) -> Result<(), Error> {
-    let triangles: Vec<Triangle> = parse(path)?.collect();
-    let triangles = reorient(&triangles);
-    let possible = triangles.iter().filter(|t| t.is_possible()).count();
-    println!("possible triangles (vertical orient): {}", possible);
-    Ok(())
+pub fn register(registry: &mut Registry) {
+    registry.register(3, Solution);
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -101,6 +260,77 @@ mod tests {
         assert!([t1, t2, t3].iter().all(|t| t.is_possible()));
     }
 
+    #[test]
+    fn test_count_possible() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(3);
+        let triangles: Vec<Triangle> = (0..1003)
+            .map(|_| {
+                Triangle(
+                    rng.gen_range(1..100),
+                    rng.gen_range(1..100),
+                    rng.gen_range(1..100),
+                )
+            })
+            .collect();
+        for len in [0, 1, 7, 8, 9, 1003] {
+            let triangles = &triangles[..len];
+            assert_eq!(
+                count_possible(triangles),
+                triangles.iter().filter(|t| t.is_possible()).count()
+            );
+        }
+    }
+
+    #[test]
+    fn test_count_possible_large_sides() {
+        let triangles = [
+            Triangle(u64::MAX, u64::MAX, u64::MAX),
+            Triangle(u64::MAX, u64::MAX, 1),
+            Triangle(u64::MAX, 1, 1),
+            Triangle(u64::MAX, u64::MAX - 1, 1),
+        ];
+        assert_eq!(count_possible(&triangles), 2);
+    }
+
+    #[test]
+    fn test_detect_orientation() {
+        let vertical = "101 301 501\n102 302 502\n103 303 503\n\
+                        201 401 901\n202 402 902\n203 403 903\n";
+        assert_eq!(
+            count_valid_auto_from_str(vertical).unwrap(),
+            (Orientation::Vertical, 6)
+        );
+        assert_eq!(part1_from_str(vertical).unwrap(), 0);
+
+        let horizontal = "3 4 5\n100 1 1\n1 100 1\n";
+        assert_eq!(
+            count_valid_auto_from_str(horizontal).unwrap(),
+            (Orientation::Horizontal, 1)
+        );
+        assert_eq!(part2_from_str(horizontal).unwrap(), 0);
+
+        // without complete groups of three rows, only the horizontal reading makes sense
+        let ragged = &vertical[..vertical.len() - "203 403 903\n".len()];
+        assert_eq!(
+            count_valid_auto_from_str(ragged).unwrap(),
+            (Orientation::Horizontal, 0)
+        );
+        assert_eq!(detect_orientation(&[]), Orientation::Horizontal);
+
+        // one more possible triangle in 30 isn't a clear margin; three more is
+        let rows = |last: [Triangle; 3]| {
+            let mut rows = vec![Triangle(3, 4, 5); 27];
+            rows.extend(last);
+            rows
+        };
+        let one_more = [Triangle(1, 1, 1), Triangle(1, 1, 3), Triangle(2, 1, 3)];
+        assert_eq!(detect_orientation(&rows(one_more)), Orientation::Horizontal);
+        let three_more = [Triangle(2, 1, 1), Triangle(2, 1, 1), Triangle(3, 1, 1)];
+        assert_eq!(detect_orientation(&rows(three_more)), Orientation::Vertical);
+    }
+
     #[test]
     fn test_parse_vertical() {
         let input = "101 301 501\n102 302 502\n103 303 503\n\
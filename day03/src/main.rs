@@ -1,19 +1,22 @@
-use aoclib::{config::Config, website::get_input};
-use day03::{part1, part2};
+use aoc2016_solver::Input;
+use day03::{count_valid_auto, part1, part2, Orientation};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 3;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -21,33 +24,46 @@ struct RunArgs {
     /// run part 2
     #[structopt(long)]
     part2: bool,
-}
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
-    }
+    /// also count triangles in the orientation which finds clearly more of them possible, noting
+    /// parts which read the input the other way
+    #[structopt(long)]
+    auto: bool,
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
+
+    if args.auto {
+        let (orientation, count) = count_valid_auto(&input_path)?;
+        println!(
+            "possible triangles (detected {} orient): {}",
+            orientation, count
+        );
+        let hint = |part, reads| {
+            eprintln!(
+                "hint: part {} reads triangles {}ly; more are possible read {}ly",
+                part, reads, orientation
+            )
+        };
+        if !args.no_part1 && orientation != Orientation::Horizontal {
+            hint(1, Orientation::Horizontal);
+        }
+        if args.part2 && orientation != Orientation::Vertical {
+            hint(2, Orientation::Vertical);
+        }
+    }
 
     if !args.no_part1 {
-        part1(&input_path)?;
+        println!("possible triangles: {}", part1(&input_path)?);
     }
     if args.part2 {
-        part2(&input_path)?;
+        println!(
+            "possible triangles (vertical orient): {}",
+            part2(&input_path)?
+        );
     }
     Ok(())
 }
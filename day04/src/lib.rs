@@ -23,6 +23,8 @@
 //!
 //! What is the sum of the sector IDs of the real rooms?
 
+pub use aoc2016_core::caesar::shift_str;
+use aoc2016_solver::{each_line, Answer, Example, Registry, Solver};
 use aoclib::parse;
 use counter::Counter;
 use lazy_static::lazy_static;
@@ -43,7 +45,7 @@ lazy_static! {
 
 #[derive(Debug, parse_display::Display)]
 #[display("{name}-{sector}[{checksum}]")]
-struct Room {
+pub struct Room {
     name: String,
     sector: u64,
     checksum: String,
@@ -63,6 +65,18 @@ impl FromStr for Room {
 }
 
 impl Room {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn sector(&self) -> u64 {
+        self.sector
+    }
+
+    pub fn checksum(&self) -> &str {
+        &self.checksum
+    }
+
     /// Construct a checksum per the Santa Rules
     fn make_checksum(&self) -> String {
         let mut counter = Counter::<_, usize>::init(self.name.chars());
@@ -76,15 +90,31 @@ impl Room {
     }
 
     /// `true` if this room is valid
-    fn is_valid(&self) -> bool {
+    pub fn is_valid(&self) -> bool {
         self.make_checksum() == self.checksum
     }
 
+    /// Suggest a corrected checksum for an invalid room.
+    ///
+    /// Returns `None` when the room is already valid.
+    pub fn suggest_checksum(&self) -> Option<String> {
+        let checksum = self.make_checksum();
+        (checksum != self.checksum).then_some(checksum)
+    }
+
+    /// The edit distance between this room's checksum and the correct checksum.
+    ///
+    /// Transcription errors in hand-copied input tend to be a character or two off, where
+    /// decoy rooms usually differ entirely.
+    pub fn checksum_distance(&self) -> usize {
+        edit_distance(&self.checksum, &self.make_checksum())
+    }
+
     /// Decrypt a room code according to Santa Rules
     ///
     /// 1. shift every char by sector number
     /// 2. dashes become spaces
-    fn decrypt(&self) -> String {
+    pub fn decrypt(&self) -> String {
         shift_str(&self.name, self.sector).replace("-", " ")
     }
 
@@ -96,50 +126,127 @@ impl Room {
     }
 }
 
-fn shift_char(mut ch: char, shift: u64) -> char {
-    if !ch.is_ascii_alphabetic() {
-        return ch;
-    }
-    let upper = ch.is_uppercase();
-    ch.make_ascii_lowercase();
-    let ch_idx = ch as u8 - b'a';
-    let shift_idx = ((ch_idx as u64 + shift) % 26) as u8;
-    ch = (shift_idx + b'a') as char;
-    if upper {
-        ch.make_ascii_uppercase();
+/// Compute the Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut row = vec![0; b.len() + 1];
+    for (i, a_ch) in a.chars().enumerate() {
+        row[0] = i + 1;
+        for (j, b_ch) in b.iter().enumerate() {
+            let substitute = prev[j] + (a_ch != *b_ch) as usize;
+            row[j + 1] = substitute.min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut row);
     }
-    ch
+    prev[b.len()]
+}
+
+/// Iterate over every room listed in the input file.
+pub fn rooms(path: &Path) -> Result<impl Iterator<Item = Room>, Error> {
+    parse::<Room>(path).map_err(Into::into)
+}
+
+/// Iterate over every room listed in `input`.
+pub fn rooms_from_str(input: &str) -> Result<impl '_ + Iterator<Item = Room>, Error> {
+    aoclib::input::parse_str::<Room>(input).map_err(Into::into)
 }
 
-/// En/decrypt a string using a shift cypher
-pub fn shift_str(encrypted: &str, shift: u64) -> String {
-    encrypted.chars().map(|ch| shift_char(ch, shift)).collect()
+/// The sum of the sector IDs of the real rooms.
+pub fn part1(path: &Path) -> Result<u64, Error> {
+    part1_from_str(&std::fs::read_to_string(path)?)
 }
 
-pub fn part1(path: &Path) -> Result<(), Error> {
-    let valid_sector_sum: u64 = parse::<Room>(path)?
+/// The sum of the sector IDs of the real rooms listed in `input`.
+pub fn part1_from_str(input: &str) -> Result<u64, Error> {
+    Ok(rooms_from_str(input)?
         .filter(|room| room.is_valid())
         .map(|room| room.sector)
-        .sum();
-    println!("valid count: {}", valid_sector_sum);
+        .sum())
+}
+
+/// The sector IDs of the rooms where North Pole objects are stored.
+pub fn part2(path: &Path) -> Result<Vec<u64>, Error> {
+    part2_from_str(&std::fs::read_to_string(path)?)
+}
+
+/// The sector IDs of the rooms listed in `input` where North Pole objects are stored.
+pub fn part2_from_str(input: &str) -> Result<Vec<u64>, Error> {
+    Ok(rooms_from_str(input)?
+        .filter(|room| room.has_north_pole())
+        .map(|room| room.sector)
+        .collect())
+}
+
+pub fn list_decrypted(path: &Path) -> Result<(), Error> {
+    for room in rooms(path)? {
+        println!("{}", room.decrypt());
+    }
     Ok(())
 }
 
-pub fn part2(path: &Path) -> Result<(), Error> {
-    println!("rooms with north pole:");
-    for room in parse::<Room>(path)?.filter(|room| room.has_north_pole()) {
-        println!("  {}", room.sector);
+/// Find the valid rooms whose decrypted names match `pattern`.
+pub fn grep_rooms<'a>(
+    rooms: impl 'a + Iterator<Item = Room>,
+    pattern: &'a Regex,
+) -> impl 'a + Iterator<Item = Room> {
+    rooms.filter(move |room| room.is_valid() && pattern.is_match(&room.decrypt()))
+}
+
+/// Print the sector and encrypted line of each valid room whose decrypted name matches `pattern`.
+pub fn grep(path: &Path, pattern: &str) -> Result<(), Error> {
+    let pattern = Regex::new(pattern)?;
+    for room in grep_rooms(rooms(path)?, &pattern) {
+        println!("{}\t{}", room.sector, room);
     }
     Ok(())
 }
 
-pub fn list_decrypted(path: &Path) -> Result<(), Error> {
-    for room in parse::<Room>(path)? {
-        println!("{}", room.decrypt());
+/// For each invalid room, print the room, the correct checksum, and the edit distance between
+/// the two checksums.
+pub fn suggest_repairs(path: &Path) -> Result<(), Error> {
+    for room in rooms(path)? {
+        if let Some(suggestion) = room.suggest_checksum() {
+            println!(
+                "{}\t{}\t{}",
+                room,
+                suggestion,
+                edit_distance(&room.checksum, &suggestion)
+            );
+        }
     }
     Ok(())
 }
 
+/// The rooms from the puzzle text, of which all but the last are real.
+const EXAMPLE: &str = "aaaaa-bbb-z-y-x-123[abxyz]\na-b-c-d-e-f-g-h-987[abcde]\nnot-a-real-room-404[oarel]\ntotally-real-room-200[decoy]\n";
+
+const EXAMPLES: &[Example] = &[Example {
+    part: 1,
+    input: EXAMPLE,
+    answer: "1514",
+}];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part1_from_str(input)?.into())
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        each_line(part2_from_str(input)?, Ok)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(4, Solution);
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
@@ -148,35 +255,36 @@ pub enum Error {
     NoMatch,
     #[error("parsing sector")]
     ParseSector(#[from] ParseIntError),
+    #[error("invalid pattern")]
+    Pattern(#[from] regex::Error),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const EXAMPLES: &[(&'static str, bool)] = &[
-        ("aaaaa-bbb-z-y-x-123[abxyz]", true),
-        ("a-b-c-d-e-f-g-h-987[abcde]", true),
-        ("not-a-real-room-404[oarel]", true),
-        ("totally-real-room-200[decoy]", false),
-        ("doesn't match the regex", false),
-    ];
+    /// The example rooms, and whether each is real.
+    fn examples() -> impl Iterator<Item = (&'static str, bool)> {
+        EXAMPLE
+            .lines()
+            .zip([true, true, true, false].iter().copied())
+            .chain(std::iter::once(("doesn't match the regex", false)))
+    }
 
     #[test]
     fn test_validate() {
-        for (room, expected) in EXAMPLES {
+        for (room, expected) in examples() {
             let validity = room
                 .parse::<Room>()
                 .map(|room| room.is_valid())
                 .unwrap_or_default();
-            assert_eq!(validity, *expected);
+            assert_eq!(validity, expected);
         }
     }
 
     #[test]
     fn test_sum_valid_sectors() {
-        let sum_valid: u64 = EXAMPLES
-            .iter()
+        let sum_valid: u64 = examples()
             .filter_map(|(room, _)| room.parse::<Room>().ok())
             .filter(|room| room.is_valid())
             .map(|room| room.sector)
@@ -192,4 +300,45 @@ mod tests {
         let room: Room = encrypted.parse().unwrap();
         assert_eq!(room.decrypt(), "very encrypted name");
     }
+
+    #[test]
+    fn test_grep_rooms() {
+        let pattern = Regex::new("^[a-z]+ [a-z]+ [a-z]+ [a-z]+$").unwrap();
+        // a decoy whose name matches the pattern, so only its checksum keeps it out
+        let decoy: Room = "qzmt-zixmtkozy-ivhz-abcd-343[decoy]".parse().unwrap();
+        assert_eq!(decoy.decrypt(), "very encrypted name fghi");
+        assert!(pattern.is_match(&decoy.decrypt()));
+        assert!(!decoy.is_valid());
+
+        let rooms = examples()
+            .filter_map(|(room, _)| room.parse::<Room>().ok())
+            .chain(std::iter::once(decoy));
+        let matches: Vec<_> = grep_rooms(rooms, &pattern)
+            .map(|room| room.to_string())
+            .collect();
+        assert_eq!(matches, vec!["not-a-real-room-404[oarel]"]);
+    }
+
+    #[test]
+    fn test_suggest_checksum() {
+        let valid: Room = "not-a-real-room-404[oarel]".parse().unwrap();
+        assert_eq!(valid.suggest_checksum(), None);
+        assert_eq!(valid.checksum_distance(), 0);
+
+        let typo: Room = "not-a-real-room-404[oaerl]".parse().unwrap();
+        assert_eq!(typo.suggest_checksum().as_deref(), Some("oarel"));
+        assert_eq!(typo.checksum_distance(), 2);
+
+        let decoy: Room = "totally-real-room-200[decoy]".parse().unwrap();
+        assert_eq!(decoy.suggest_checksum().as_deref(), Some("loart"));
+        assert_eq!(decoy.checksum_distance(), 5);
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("abcde", "abcde"), 0);
+        assert_eq!(edit_distance("abcde", "abxde"), 1);
+        assert_eq!(edit_distance("abcde", "bcdef"), 2);
+    }
 }
@@ -1,19 +1,22 @@
-use aoclib::{config::Config, website::get_input};
-use day04::{list_decrypted, part1, part2};
+use aoc2016_solver::Input;
+use day04::{grep, list_decrypted, part1, part2, suggest_repairs};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 4;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -24,33 +27,43 @@ struct RunArgs {
 
     #[structopt(long)]
     list_decrypted: bool,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
 }
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
-    }
+#[derive(StructOpt, Debug)]
+enum Command {
+    /// print the sector and encrypted line of each valid room whose decrypted name matches
+    Grep {
+        /// regular expression to search for in the decrypted names
+        pattern: String,
+    },
+    /// print the correct checksum and its edit distance from the given one for each invalid room
+    Repair,
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
 
+    if let Some(Command::Grep { ref pattern }) = args.command {
+        grep(&input_path, pattern)?;
+        return Ok(());
+    }
+    if let Some(Command::Repair) = args.command {
+        suggest_repairs(&input_path)?;
+        return Ok(());
+    }
     if !args.no_part1 {
-        part1(&input_path)?;
+        println!("valid count: {}", part1(&input_path)?);
     }
     if args.part2 {
-        part2(&input_path)?;
+        println!("rooms with north pole:");
+        for sector in part2(&input_path)? {
+            println!("  {}", sector);
+        }
     }
     if args.list_decrypted {
         list_decrypted(&input_path)?;
@@ -29,14 +29,30 @@
 //!
 //! Given the actual Door ID, what is the password?
 
-use aoclib::parse;
+use aoc2016_solver::{each_line, Answer, Example, Progress, Registry, Silent, Solver};
+use aoclib::input::parse_str;
 use crypto::digest::Digest;
 use crypto::md5::Md5;
-use std::{borrow::Borrow, path::Path};
+use std::{
+    borrow::Borrow,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    ops::Range,
+    path::Path,
+    str::FromStr,
+};
 
 #[cfg(feature = "parallelism")]
 use rayon::prelude::*;
 
+#[cfg(feature = "async")]
+mod stream;
+#[cfg(feature = "async")]
+pub use stream::{crack_password_stream, PasswordEvent};
+
+/// Number of suffixes searched at a time.
+const CHUNK_SIZE: u64 = 1 << 16;
+
 fn make_hash_for(prefix: &str) -> impl '_ + Fn(u64) -> (u64, String) {
     let key = prefix.as_bytes();
     move |suffix| {
@@ -69,30 +85,52 @@ fn zero_five_six(tuple: impl Borrow<(u64, String)>) -> Option<(u64, char, char)>
     }
 }
 
-/// Return the tuple `(suffix, five, six)`.
+/// Return every tuple `(suffix, five, six)` for suffixes within `suffixes`, in ascending order.
 #[cfg(feature = "parallelism")]
-fn next_valid_suffix(prefix: &str, initial_suffix: u64) -> Option<(u64, char, char)> {
+fn valid_suffixes_in(prefix: &str, suffixes: Range<u64>) -> Vec<(u64, char, char)> {
     let hash_for = make_hash_for(prefix);
-    (initial_suffix..=u64::MAX)
+    suffixes
         .into_par_iter()
         .map(hash_for)
-        .find_map_first(zero_five_six)
+        .filter_map(zero_five_six)
+        .collect()
 }
 
-/// Return the tuple `(suffix, five, six)`.
+/// Return every tuple `(suffix, five, six)` for suffixes within `suffixes`, in ascending order.
 #[cfg(not(feature = "parallelism"))]
-fn next_valid_suffix(prefix: &str, initial_suffix: u64) -> Option<(u64, char, char)> {
+fn valid_suffixes_in(prefix: &str, suffixes: Range<u64>) -> Vec<(u64, char, char)> {
     let hash_for = make_hash_for(prefix);
-    (initial_suffix..=u64::MAX)
-        .map(hash_for)
-        .find_map(zero_five_six)
+    suffixes.map(hash_for).filter_map(zero_five_six).collect()
+}
+
+/// Return the tuple `(suffix, five, six)`.
+///
+/// Suffixes are searched one chunk at a time, reporting the number of suffixes searched so far
+/// to `progress` after each.
+fn next_valid_suffix(
+    prefix: &str,
+    initial_suffix: u64,
+    progress: &dyn Progress,
+) -> Option<(u64, char, char)> {
+    let mut start = initial_suffix;
+    loop {
+        progress.update(start);
+        let end = start.saturating_add(CHUNK_SIZE);
+        if start == end {
+            return None;
+        }
+        if let Some(&found) = valid_suffixes_in(prefix, start..end).first() {
+            return Some(found);
+        }
+        start = end;
+    }
 }
 
-struct SuffixIter<'a>(&'a str, u64);
+struct SuffixIter<'a>(&'a str, u64, &'a dyn Progress);
 
 impl<'a> SuffixIter<'a> {
-    fn new(prefix: &'a str) -> SuffixIter<'a> {
-        SuffixIter(prefix, 0)
+    fn new(prefix: &'a str, progress: &'a dyn Progress) -> SuffixIter<'a> {
+        SuffixIter(prefix, 0, progress)
     }
 }
 
@@ -100,50 +138,417 @@ impl<'a> Iterator for SuffixIter<'a> {
     type Item = (char, char);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (suffix, five, six) = next_valid_suffix(self.0, self.1)?;
+        let (suffix, five, six) = next_valid_suffix(self.0, self.1, self.2)?;
         self.1 = suffix + 1;
         Some((five, six))
     }
 }
 
-fn make_password_simple(prefix: &str) -> Option<String> {
-    let mut password = String::with_capacity(8);
-    password.extend(SuffixIter::new(prefix).take(8).map(|(five, _)| five));
-    (password.len() == 8).then(move || password)
+/// The interesting hashes found so far for a door: each suffix whose hash starts with five
+/// zeroes, with the hash's sixth and seventh characters.
+///
+/// Both passwords are built from the same sequence of interesting hashes, so building the second
+/// from a `HashHits` which built the first repeats none of the search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashHits {
+    prefix: String,
+    hits: BTreeMap<u64, (char, char)>,
+    /// Every suffix below this has been searched, and each hit among them recorded.
+    searched: u64,
+}
+
+impl HashHits {
+    pub fn new(prefix: impl Into<String>) -> HashHits {
+        HashHits {
+            prefix: prefix.into(),
+            hits: BTreeMap::new(),
+            searched: 0,
+        }
+    }
+
+    /// The number of suffixes searched so far.
+    pub fn searched(&self) -> u64 {
+        self.searched
+    }
+
+    /// Each hit found so far, in ascending order of suffix.
+    pub fn hits(&self) -> impl '_ + Iterator<Item = (u64, char, char)> {
+        self.hits
+            .iter()
+            .map(|(&suffix, &(five, six))| (suffix, five, six))
+    }
+
+    /// Find the password according to `policy`, using the hits already found and searching
+    /// further only when they run out.
+    ///
+    /// Any search reports the number of suffixes searched to `progress`.
+    pub fn make_password(
+        &mut self,
+        policy: &impl PasswordPolicy,
+        progress: &dyn Progress,
+    ) -> Option<Password> {
+        fill_password(
+            policy,
+            HitIter {
+                hits: self,
+                next_suffix: 0,
+                progress,
+            },
+        )
+    }
+}
+
+/// Iterator over a door's interesting hashes, first those already found, then new ones.
+struct HitIter<'a> {
+    hits: &'a mut HashHits,
+    next_suffix: u64,
+    progress: &'a dyn Progress,
+}
+
+impl<'a> Iterator for HitIter<'a> {
+    type Item = (char, char);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((&suffix, &found)) = self.hits.hits.range(self.next_suffix..).next() {
+            self.next_suffix = suffix + 1;
+            return Some(found);
+        }
+        // every hit below `searched` is recorded, so the next one can't be below it
+        let (suffix, five, six) =
+            next_valid_suffix(&self.hits.prefix, self.hits.searched, self.progress)?;
+        self.hits.hits.insert(suffix, (five, six));
+        self.hits.searched = suffix + 1;
+        self.next_suffix = suffix + 1;
+        Some((five, six))
+    }
+}
+
+/// A door password.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Password(String);
+
+impl Password {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Render this password in the requested style.
+    pub fn render(&self, style: &RenderStyle) -> String {
+        match style {
+            RenderStyle::Raw => self.0.clone(),
+            RenderStyle::Substituted(substitutions) => substitutions.apply(&self.0),
+        }
+    }
+}
+
+impl fmt::Display for Password {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PartialEq<str> for Password {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Password {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+/// A character substitution table for rendering passwords.
+///
+/// The default table maps hex digits to their "leet" lookalike letters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Substitutions(HashMap<char, char>);
+
+impl Default for Substitutions {
+    fn default() -> Self {
+        [
+            ('0', 'o'),
+            ('1', 'l'),
+            ('2', 'z'),
+            ('3', 'e'),
+            ('4', 'a'),
+            ('5', 's'),
+            ('6', 'b'),
+            ('7', 't'),
+            ('8', 'b'),
+            ('9', 'g'),
+        ]
+        .iter()
+        .copied()
+        .collect()
+    }
+}
+
+impl std::iter::FromIterator<(char, char)> for Substitutions {
+    fn from_iter<T: IntoIterator<Item = (char, char)>>(iter: T) -> Self {
+        Substitutions(iter.into_iter().collect())
+    }
+}
+
+impl Substitutions {
+    fn apply(&self, s: &str) -> String {
+        s.chars()
+            .map(|ch| self.0.get(&ch).copied().unwrap_or(ch))
+            .collect()
+    }
+}
+
+/// Parse a substitution table of the form `0=o,1=l`.
+impl FromStr for Substitutions {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|pair| {
+                let mut chars = pair.trim().chars();
+                match (chars.next(), chars.next(), chars.next(), chars.next()) {
+                    (Some(from), Some('='), Some(to), None) => Ok((from, to)),
+                    _ => Err(Error::MalformedSubstitution(pair.to_owned())),
+                }
+            })
+            .collect()
+    }
+}
+
+/// How to present a password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RenderStyle {
+    /// The password exactly as computed.
+    Raw,
+    /// The password passed through a substitution table.
+    Substituted(Substitutions),
+}
+
+/// Number of characters in a door password.
+const PASSWORD_LEN: usize = 8;
+
+/// Decides how interesting hashes fill in a password.
+///
+/// Each time a hash starting with five zeroes is found, the policy may fill in some positions
+/// of the password from the hash's sixth and seventh characters. The search ends once every
+/// position is filled.
+pub trait PasswordPolicy {
+    /// Fill in `password` from an interesting hash whose sixth and seventh characters are `five`
+    /// and `six`.
+    ///
+    /// Returns each position newly filled.
+    fn fill(&self, password: &mut [Option<char>], five: char, six: char) -> Vec<usize>;
+}
+
+/// Part 1: each hash's sixth character fills the next empty position.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sequential;
+
+impl PasswordPolicy for Sequential {
+    fn fill(&self, password: &mut [Option<char>], five: char, _six: char) -> Vec<usize> {
+        match password.iter().position(Option::is_none) {
+            Some(position) => {
+                password[position] = Some(five);
+                vec![position]
+            }
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Part 2: each hash's sixth character designates a position, which its seventh character
+/// fills unless already filled.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Positional;
+
+impl PasswordPolicy for Positional {
+    fn fill(&self, password: &mut [Option<char>], five: char, six: char) -> Vec<usize> {
+        match fancy_position(five) {
+            Some(position) if password[position].is_none() => {
+                password[position] = Some(six);
+                vec![position]
+            }
+            _ => Vec::new(),
+        }
+    }
 }
 
-fn make_password_fancy(prefix: &str) -> Option<String> {
-    let mut password = vec![None; 8];
-    let mut iter = SuffixIter::new(prefix);
-    while password.iter().any(|maybe_char| maybe_char.is_none()) {
-        let (five, six) = iter.next()?;
-        let idx = match (five as u8).checked_sub(b'0') {
-            Some(idx) if idx < 8 => idx as usize,
-            _ => continue,
-        };
-        if password[idx].is_none() {
-            password[idx] = Some(six);
+/// Wraps another policy, ignoring any hash which would repeat a character already in the password.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Unique<P>(pub P);
+
+impl<P: PasswordPolicy> PasswordPolicy for Unique<P> {
+    fn fill(&self, password: &mut [Option<char>], five: char, six: char) -> Vec<usize> {
+        let before = password.to_vec();
+        let filled = self.0.fill(password, five, six);
+        let repeats = filled.iter().any(|&position| {
+            password
+                .iter()
+                .enumerate()
+                .any(|(idx, ch)| idx != position && *ch == password[position])
+        });
+        if repeats {
+            password.copy_from_slice(&before);
+            Vec::new()
+        } else {
+            filled
         }
     }
-    password.into_iter().collect()
 }
 
-pub fn part1(path: &Path) -> Result<(), Error> {
-    for door_input in parse::<String>(path)? {
-        let password =
-            make_password_simple(&door_input).ok_or_else(|| Error::NotFound(door_input.clone()))?;
-        println!("simple password for {}: {}", door_input, password);
+/// Fill a password according to `policy` from a sequence of interesting hashes' sixth and
+/// seventh characters.
+fn fill_password(
+    policy: &impl PasswordPolicy,
+    hashes: impl Iterator<Item = (char, char)>,
+) -> Option<Password> {
+    let mut password = vec![None; PASSWORD_LEN];
+    let mut hashes = hashes;
+    while password.iter().any(Option::is_none) {
+        let (five, six) = hashes.next()?;
+        policy.fill(&mut password, five, six);
     }
-    Ok(())
+    password.into_iter().collect::<Option<_>>().map(Password)
 }
 
-pub fn part2(path: &Path) -> Result<(), Error> {
-    for door_input in parse::<String>(path)? {
-        let password =
-            make_password_fancy(&door_input).ok_or_else(|| Error::NotFound(door_input.clone()))?;
-        println!("fancy password for {}: {}", door_input, password);
+/// Find the password for the door `prefix` according to `policy`.
+pub fn make_password(prefix: &str, policy: &impl PasswordPolicy) -> Option<Password> {
+    make_password_with(prefix, policy, &Silent)
+}
+
+/// Find the password for the door `prefix` according to `policy`, reporting the number of
+/// suffixes searched to `progress`.
+pub fn make_password_with(
+    prefix: &str,
+    policy: &impl PasswordPolicy,
+    progress: &dyn Progress,
+) -> Option<Password> {
+    fill_password(policy, SuffixIter::new(prefix, progress))
+}
+
+pub fn make_password_simple(prefix: &str) -> Option<Password> {
+    make_password(prefix, &Sequential)
+}
+
+/// The position in the fancy password which `five` designates, if any.
+fn fancy_position(five: char) -> Option<usize> {
+    match (five as u8).checked_sub(b'0') {
+        Some(idx) if (idx as usize) < PASSWORD_LEN => Some(idx as usize),
+        _ => None,
+    }
+}
+
+pub fn make_password_fancy(prefix: &str) -> Option<Password> {
+    make_password(prefix, &Positional)
+}
+
+/// The password for each door ID in the input, found one character at a time.
+pub fn part1(path: &Path, progress: &dyn Progress) -> Result<Vec<Password>, Error> {
+    part1_from_str(&std::fs::read_to_string(path)?, progress)
+}
+
+/// The password for each door ID in `input`, found one character at a time.
+pub fn part1_from_str(input: &str, progress: &dyn Progress) -> Result<Vec<Password>, Error> {
+    parse_str::<String>(input)?
+        .map(|door_input| {
+            make_password_with(&door_input, &Sequential, progress)
+                .ok_or(Error::NotFound(door_input))
+        })
+        .collect()
+}
+
+/// The password for each door ID in the input, found one position at a time.
+pub fn part2(path: &Path, progress: &dyn Progress) -> Result<Vec<Password>, Error> {
+    part2_from_str(&std::fs::read_to_string(path)?, progress)
+}
+
+/// The password for each door ID in `input`, found one position at a time.
+pub fn part2_from_str(input: &str, progress: &dyn Progress) -> Result<Vec<Password>, Error> {
+    parse_str::<String>(input)?
+        .map(|door_input| {
+            make_password_with(&door_input, &Positional, progress)
+                .ok_or(Error::NotFound(door_input))
+        })
+        .collect()
+}
+
+/// Both passwords for each door ID in the input, sharing one search between them.
+pub fn solve_both(
+    path: &Path,
+    progress: &dyn Progress,
+) -> Result<(Vec<Password>, Vec<Password>), Error> {
+    solve_both_from_str(&std::fs::read_to_string(path)?, progress)
+}
+
+/// Both passwords for each door ID in `input`, sharing one search between them.
+///
+/// This hashes each suffix once, where running `part1` and then `part2` hashes most of them
+/// twice.
+pub fn solve_both_from_str(
+    input: &str,
+    progress: &dyn Progress,
+) -> Result<(Vec<Password>, Vec<Password>), Error> {
+    let mut simple = Vec::new();
+    let mut fancy = Vec::new();
+    for door_input in parse_str::<String>(input)? {
+        let mut hits = HashHits::new(door_input.as_str());
+        simple.push(
+            hits.make_password(&Sequential, progress)
+                .ok_or_else(|| Error::NotFound(door_input.clone()))?,
+        );
+        fancy.push(
+            hits.make_password(&Positional, progress)
+                .ok_or(Error::NotFound(door_input))?,
+        );
+    }
+    Ok((simple, fancy))
+}
+
+const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: "abc",
+        answer: "18f47a30",
+    },
+    Example {
+        part: 2,
+        input: "abc",
+        answer: "05ace8e3",
+    },
+];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        self.solve_reporting(1, input, &Silent)
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        self.solve_reporting(2, input, &Silent)
     }
-    Ok(())
+
+    fn solve_reporting(
+        &self,
+        part: u8,
+        input: &str,
+        progress: &dyn Progress,
+    ) -> aoc2016_solver::Result<Answer> {
+        match part {
+            1 => each_line(part1_from_str(input, progress)?, |password| Ok(password.0)),
+            2 => each_line(part2_from_str(input, progress)?, |password| Ok(password.0)),
+            _ => self.solve(part, input),
+        }
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(5, Solution);
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -152,6 +557,8 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("could not determine a password for \"{0}\"")]
     NotFound(String),
+    #[error("malformed substitution \"{0}\": expected \"from=to\"")]
+    MalformedSubstitution(String),
 }
 
 #[cfg(test)]
@@ -168,11 +575,11 @@ mod tests {
         let should_work = 3231929;
 
         assert!(matches!(
-            next_valid_suffix(prefix, should_work),
+            next_valid_suffix(prefix, should_work, &Silent),
             Some((suffix, '1', _)) if suffix == should_work,
         ));
         assert!(matches!(
-            next_valid_suffix(prefix, should_work - 1),
+            next_valid_suffix(prefix, should_work - 1, &Silent),
             Some((suffix, '1', _)) if suffix == should_work,
         ));
     }
@@ -181,16 +588,25 @@ mod tests {
     /// Test function which gets next passing number.
     fn test_get_next() {
         let prefix = "abc";
-        let result = next_valid_suffix(prefix, 0);
+        let result = next_valid_suffix(prefix, 0, &Silent);
         assert!(matches!(result, Some((3231929, '1', _))));
 
-        let result = next_valid_suffix(prefix, 3231930);
+        let result = next_valid_suffix(prefix, 3231930, &Silent);
         assert!(matches!(result, Some((5017308, '8', _))));
 
-        let result = next_valid_suffix(prefix, 5017309);
+        let result = next_valid_suffix(prefix, 5017309, &Silent);
         assert!(matches!(result, Some((5278568, 'f', _))));
     }
 
+    #[test]
+    fn test_valid_suffixes_in() {
+        let found = valid_suffixes_in("abc", 3_000_000..5_100_000);
+        assert_eq!(
+            found.iter().map(|(suffix, ..)| *suffix).collect::<Vec<_>>(),
+            vec![3231929, 5017308]
+        );
+    }
+
     #[test]
     fn test_get_first_eight() {
         let result = make_password_simple("abc").unwrap();
@@ -199,7 +615,7 @@ mod tests {
 
     #[test]
     fn test_suffix_iter() {
-        let mut iter = SuffixIter::new("abc");
+        let mut iter = SuffixIter::new("abc", &Silent);
 
         assert_eq!(iter.next(), Some(('1', '5')));
         assert_eq!(iter.next(), Some(('8', 'f')));
@@ -211,4 +627,91 @@ mod tests {
     fn test_password_fancy() {
         assert_eq!(make_password_fancy("abc").unwrap(), "05ace8e3");
     }
+
+    #[test]
+    fn test_hash_hits_shared() {
+        let mut hits = HashHits::new("abc");
+        assert_eq!(
+            hits.make_password(&Sequential, &Silent).unwrap(),
+            "18f47a30"
+        );
+        let searched = hits.searched();
+        let found = hits.hits().count();
+        assert_eq!(found, 8);
+
+        assert_eq!(
+            hits.make_password(&Positional, &Silent).unwrap(),
+            "05ace8e3"
+        );
+        // the fancy password needs more hits than the simple one, but reuses the first eight
+        assert!(hits.searched() > searched);
+        assert!(hits.hits().count() > found);
+        assert_eq!(
+            hits.hits()
+                .take(3)
+                .map(|(suffix, ..)| suffix)
+                .collect::<Vec<_>>(),
+            vec![3231929, 5017308, 5278568]
+        );
+
+        // once every hit is known, building a password again searches nothing
+        let searched = hits.searched();
+        assert_eq!(
+            hits.make_password(&Sequential, &Silent).unwrap(),
+            "18f47a30"
+        );
+        assert_eq!(hits.searched(), searched);
+    }
+
+    #[test]
+    fn test_policies() {
+        let hashes = [('1', 'a'), ('9', 'b'), ('1', 'c'), ('0', 'd'), ('3', 'e')];
+        let mut simple = vec![None; 3];
+        for &(five, six) in &hashes {
+            Sequential.fill(&mut simple, five, six);
+        }
+        assert_eq!(simple, vec![Some('1'), Some('9'), Some('1')]);
+
+        let mut unique = vec![None; 3];
+        for &(five, six) in &hashes {
+            Unique(Sequential).fill(&mut unique, five, six);
+        }
+        assert_eq!(unique, vec![Some('1'), Some('9'), Some('0')]);
+
+        let mut positional = vec![None; 8];
+        for &(five, six) in &hashes {
+            Positional.fill(&mut positional, five, six);
+        }
+        assert_eq!(positional[..4], [Some('d'), Some('a'), None, Some('e')]);
+    }
+
+    #[test]
+    fn test_fill_password() {
+        let hashes = "0a1b2c3d4e5f6g7h8i".chars().zip("zyxwvutsrq".chars());
+        assert_eq!(
+            fill_password(&Sequential, hashes.clone()).unwrap(),
+            "0a1b2c3d"
+        );
+        assert_eq!(fill_password(&Positional, hashes.clone()), None);
+    }
+
+    #[test]
+    fn test_render() {
+        let password = Password("18f47a30".into());
+        assert_eq!(password.render(&RenderStyle::Raw), "18f47a30");
+        assert_eq!(
+            password.render(&RenderStyle::Substituted(Substitutions::default())),
+            "lbfataeo"
+        );
+        assert_eq!(
+            password.render(&RenderStyle::Substituted("1=i, f=F".parse().unwrap())),
+            "i8F47a30"
+        );
+    }
+
+    #[test]
+    fn test_malformed_substitution() {
+        assert!("1=il".parse::<Substitutions>().is_err());
+        assert!("1-i".parse::<Substitutions>().is_err());
+    }
 }
@@ -1,19 +1,30 @@
-use aoclib::{config::Config, website::get_input};
-use day05::{part1, part2};
+use aoc2016_solver::{Input, TerminalProgress};
+use day05::{part1, part2, solve_both, Password, RenderStyle, Substitutions};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 5;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
+    /// also render passwords through a substitution table
+    #[structopt(long)]
+    leet: bool,
+
+    /// substitution table to use with --leet, as "0=o,1=l,..."
+    #[structopt(long)]
+    substitutions: Option<Substitutions>,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -24,30 +35,43 @@ struct RunArgs {
 }
 
 impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
+    fn style(&self) -> RenderStyle {
+        if self.leet {
+            RenderStyle::Substituted(self.substitutions.clone().unwrap_or_default())
+        } else {
+            RenderStyle::Raw
         }
     }
 }
 
+fn show(password: &Password, style: &RenderStyle) -> String {
+    match style {
+        RenderStyle::Raw => password.to_string(),
+        _ => format!("{} (rendered: {})", password, password.render(style)),
+    }
+}
+
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
+    let style = args.style();
+
+    let progress = TerminalProgress::new("suffixes searched");
+    // when running both parts, one search serves both
+    let (simple, fancy) = match (!args.no_part1, args.part2) {
+        (true, true) => solve_both(&input_path, &progress)?,
+        (true, false) => (part1(&input_path, &progress)?, Vec::new()),
+        (false, true) => (Vec::new(), part2(&input_path, &progress)?),
+        (false, false) => (Vec::new(), Vec::new()),
+    };
+    progress.finish();
 
-    if !args.no_part1 {
-        part1(&input_path)?;
+    for password in simple {
+        println!("simple password: {}", show(&password, &style));
     }
-    if args.part2 {
-        part2(&input_path)?;
+    for password in fancy {
+        println!("fancy password: {}", show(&password, &style));
     }
     Ok(())
 }
@@ -0,0 +1,116 @@
+//! Live password cracking, for frontends which want to display progress.
+
+use crate::{
+    valid_suffixes_in, Password, PasswordPolicy, Positional, Sequential, CHUNK_SIZE, PASSWORD_LEN,
+};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream};
+
+/// Something which happened while cracking a password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordEvent {
+    /// A character of the simple (part 1) password was found.
+    Simple {
+        position: usize,
+        ch: char,
+        suffix: u64,
+    },
+    /// A character of the fancy (part 2) password was found.
+    Fancy {
+        position: usize,
+        ch: char,
+        suffix: u64,
+    },
+    /// Another chunk of suffixes was searched.
+    Progress { hashes: u64 },
+    /// Both passwords are complete. This is always the final event.
+    Done { simple: Password, fancy: Password },
+}
+
+/// Crack both passwords for `prefix`, yielding each character as it is found.
+///
+/// The search runs on tokio's blocking thread pool, one chunk of suffixes at a time, with a
+/// progress event after each chunk; with the `parallelism` feature, each chunk is searched in
+/// parallel. Dropping the stream stops the search after the current chunk.
+///
+/// This must be called from within a tokio runtime.
+pub fn crack_password_stream(prefix: &str) -> impl Stream<Item = PasswordEvent> {
+    let prefix = prefix.to_owned();
+    let (sender, receiver) = mpsc::channel(64);
+    tokio::task::spawn_blocking(move || search(&prefix, sender));
+    ReceiverStream::new(receiver)
+}
+
+fn search(prefix: &str, sender: mpsc::Sender<PasswordEvent>) {
+    let mut simple = vec![None; PASSWORD_LEN];
+    let mut fancy = vec![None; PASSWORD_LEN];
+    let mut start: u64 = 0;
+
+    while simple.iter().any(Option::is_none) || fancy.iter().any(Option::is_none) {
+        let end = start.saturating_add(CHUNK_SIZE);
+        if start == end {
+            // the suffix space is exhausted
+            return;
+        }
+        let mut events = Vec::new();
+        for (suffix, five, six) in valid_suffixes_in(prefix, start..end) {
+            for position in Sequential.fill(&mut simple, five, six) {
+                events.push(PasswordEvent::Simple {
+                    position,
+                    ch: simple[position].expect("policy filled this position"),
+                    suffix,
+                });
+            }
+            for position in Positional.fill(&mut fancy, five, six) {
+                events.push(PasswordEvent::Fancy {
+                    position,
+                    ch: fancy[position].expect("policy filled this position"),
+                    suffix,
+                });
+            }
+        }
+        events.push(PasswordEvent::Progress { hashes: end });
+        start = end;
+
+        for event in events {
+            if sender.blocking_send(event).is_err() {
+                // nobody is listening anymore
+                return;
+            }
+        }
+    }
+
+    let simple = simple.into_iter().collect::<Option<String>>().map(Password);
+    let fancy = fancy.into_iter().collect::<Option<String>>().map(Password);
+    if let (Some(simple), Some(fancy)) = (simple, fancy) {
+        let _ = sender.blocking_send(PasswordEvent::Done { simple, fancy });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn test_stream_first_character() {
+        let events: Vec<_> = crack_password_stream("abc")
+            .take_while(|event| matches!(event, PasswordEvent::Progress { .. }))
+            .collect()
+            .await;
+        assert_eq!(events.len() as u64, 3231929 / CHUNK_SIZE);
+
+        let first = crack_password_stream("abc")
+            .filter(|event| !matches!(event, PasswordEvent::Progress { .. }))
+            .next()
+            .await;
+        assert_eq!(
+            first,
+            Some(PasswordEvent::Simple {
+                position: 0,
+                ch: '1',
+                suffix: 3231929
+            })
+        );
+    }
+}
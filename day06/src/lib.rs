@@ -38,78 +38,282 @@
 //! Given the recording in your puzzle input, what is the error-corrected version of
 //! the message being sent?
 
-use aoclib::parse;
+use aoc2016_solver::{Answer, Example, Registry, Solver};
+use aoclib::input::parse_str;
 use counter::Counter;
 use std::path::Path;
 
+/// How to handle input lines of differing lengths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, parse_display::Display, parse_display::FromStr)]
+#[display(style = "kebab-case")]
+pub enum RaggedPolicy {
+    /// Every line must have the same length.
+    Strict,
+    /// Decode as many columns as the longest line; short lines don't participate in the
+    /// columns they lack.
+    PadShort,
+    /// Decode only as many columns as the shortest line.
+    TruncateToShortest,
+}
+
+/// A decoded message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoded {
+    pub message: String,
+    /// For each column of the message, the number of input lines which participated.
+    pub participation: Vec<usize>,
+    /// For each column of the message, the number of input lines bearing its character.
+    pub winning_counts: Vec<usize>,
+}
+
+impl Decoded {
+    /// Each column's winning character, with the number of input lines bearing it.
+    pub fn winners(&self) -> impl '_ + Iterator<Item = (char, usize)> {
+        self.message
+            .chars()
+            .zip(self.winning_counts.iter().copied())
+    }
+
+    /// `true` when some columns drew on fewer input lines than others.
+    pub fn is_ragged(&self) -> bool {
+        self.participation
+            .windows(2)
+            .any(|window| window[0] != window[1])
+    }
+}
+
 /// Compose a string in which each character is the most or least common from the input lines.
 ///
-/// Every input line must have an equal number of characters for this to work right.
-fn count_frequent<Lines>(lines: Lines, want_greatest: bool) -> Option<String>
-where
-    Lines: IntoIterator<Item = String>,
-    <Lines as IntoIterator>::IntoIter: Clone,
-{
-    let iter = lines.into_iter();
-    let width = iter.clone().next()?.len();
-
-    let mut output = String::with_capacity(width);
+/// Lines of differing lengths are handled according to `policy`.
+fn count_frequent(
+    lines: &[String],
+    want_greatest: bool,
+    policy: RaggedPolicy,
+) -> Result<Decoded, Error> {
+    let lengths = lines.iter().map(|line| line.len());
+    let width = match policy {
+        RaggedPolicy::Strict => {
+            let width = lines.first().ok_or(Error::SuperlativeProblem)?.len();
+            if let Some((line, actual)) = lengths.enumerate().find(|(_, length)| *length != width) {
+                return Err(Error::Ragged {
+                    line,
+                    expected: width,
+                    actual,
+                });
+            }
+            width
+        }
+        RaggedPolicy::PadShort => lengths.max().ok_or(Error::SuperlativeProblem)?,
+        RaggedPolicy::TruncateToShortest => lengths.min().ok_or(Error::SuperlativeProblem)?,
+    };
+
+    let mut message = String::with_capacity(width);
+    let mut participation = Vec::with_capacity(width);
+    let mut winning_counts = Vec::with_capacity(width);
     for idx in 0..width {
-        let counter: Counter<u8> = iter.clone().map(|str| str.as_bytes()[idx]).collect();
+        let counter: Counter<u8> = lines
+            .iter()
+            .filter_map(|line| line.as_bytes().get(idx).copied())
+            .collect();
         let ordering = counter.most_common_ordered();
-        let (superlative, _) = if want_greatest {
-            ordering.first()?
+        let (superlative, count) = if want_greatest {
+            ordering.first()
         } else {
-            ordering.last()?
-        };
-        output.push(*superlative as char);
+            ordering.last()
+        }
+        .ok_or(Error::SuperlativeProblem)?;
+        message.push(*superlative as char);
+        participation.push(counter.values().sum());
+        winning_counts.push(*count);
     }
 
-    Some(output)
+    Ok(Decoded {
+        message,
+        participation,
+        winning_counts,
+    })
 }
 
 /// Compose a string in which each character is the most common from the input lines.
-///
-/// Every input line must have an equal number of characters for this to work right.
-fn count_most_frequent<Lines>(lines: Lines) -> Option<String>
-where
-    Lines: IntoIterator<Item = String>,
-    <Lines as IntoIterator>::IntoIter: Clone,
-{
-    count_frequent(lines, true)
+pub fn count_most_frequent(lines: &[String], policy: RaggedPolicy) -> Result<Decoded, Error> {
+    count_frequent(lines, true, policy)
 }
 
 /// Compose a string in which each character is the least common from the input lines.
-///
-/// Every input line must have an equal number of characters for this to work right.
-fn count_least_frequent<Lines>(lines: Lines) -> Option<String>
-where
-    Lines: IntoIterator<Item = String>,
-    <Lines as IntoIterator>::IntoIter: Clone,
-{
-    count_frequent(lines, false)
+pub fn count_least_frequent(lines: &[String], policy: RaggedPolicy) -> Result<Decoded, Error> {
+    count_frequent(lines, false, policy)
 }
 
-pub fn part1(path: &Path) -> Result<(), Error> {
-    let signals: Vec<String> = parse(path)?.collect();
+fn ascii_signals(signals: impl Iterator<Item = String>) -> Result<Vec<String>, Error> {
+    let signals: Vec<String> = signals.collect();
     if signals.iter().any(|signal| !signal.is_ascii()) {
         return Err(Error::NotAscii);
     }
-    let message = count_most_frequent(signals).ok_or(Error::SuperlativeProblem)?;
-    println!("message (most frequent): {}", message);
-    Ok(())
+    Ok(signals)
 }
 
-pub fn part2(path: &Path) -> Result<(), Error> {
-    let signals: Vec<String> = parse(path)?.collect();
-    if signals.iter().any(|signal| !signal.is_ascii()) {
-        return Err(Error::NotAscii);
+/// The message formed from the most frequent character in each column.
+pub fn part1(path: &Path, policy: RaggedPolicy) -> Result<Decoded, Error> {
+    part1_from_str(&std::fs::read_to_string(path)?, policy)
+}
+
+/// The message formed from the most frequent character in each column of `input`.
+pub fn part1_from_str(input: &str, policy: RaggedPolicy) -> Result<Decoded, Error> {
+    count_most_frequent(&ascii_signals(parse_str(input)?)?, policy)
+}
+
+/// The message formed from the least frequent character in each column.
+pub fn part2(path: &Path, policy: RaggedPolicy) -> Result<Decoded, Error> {
+    part2_from_str(&std::fs::read_to_string(path)?, policy)
+}
+
+/// The message formed from the least frequent character in each column of `input`.
+pub fn part2_from_str(input: &str, policy: RaggedPolicy) -> Result<Decoded, Error> {
+    count_least_frequent(&ascii_signals(parse_str(input)?)?, policy)
+}
+
+/// The Hamming distance from each line to `reference`.
+///
+/// Where a line and the reference differ in length, each character which only one of them has
+/// counts as a difference.
+pub fn score_against(lines: &[String], reference: &str) -> Vec<usize> {
+    lines
+        .iter()
+        .map(|line| {
+            let differing = line
+                .bytes()
+                .zip(reference.bytes())
+                .filter(|(a, b)| a != b)
+                .count();
+            differing + line.len().max(reference.len()) - line.len().min(reference.len())
+        })
+        .collect()
+}
+
+/// How far each recording strays from a reference message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoiseReport {
+    pub reference: String,
+    /// The Hamming distance from each recording to the reference, in input order.
+    pub distances: Vec<usize>,
+}
+
+impl NoiseReport {
+    pub fn new(lines: &[String], reference: &str) -> NoiseReport {
+        NoiseReport {
+            reference: reference.to_owned(),
+            distances: score_against(lines, reference),
+        }
+    }
+
+    /// The mean distance of a recording from the reference.
+    pub fn mean(&self) -> f64 {
+        if self.distances.is_empty() {
+            return 0.0;
+        }
+        self.distances.iter().sum::<usize>() as f64 / self.distances.len() as f64
+    }
+
+    /// The standard deviation of the recordings' distances from the reference.
+    pub fn std_dev(&self) -> f64 {
+        if self.distances.is_empty() {
+            return 0.0;
+        }
+        let mean = self.mean();
+        let variance = self
+            .distances
+            .iter()
+            .map(|&distance| (distance as f64 - mean).powi(2))
+            .sum::<f64>()
+            / self.distances.len() as f64;
+        variance.sqrt()
+    }
+
+    /// The `n` noisiest recordings as `(line index, distance)`, noisiest first.
+    ///
+    /// Recordings which are equally noisy are listed in input order.
+    pub fn noisiest(&self, n: usize) -> Vec<(usize, usize)> {
+        let mut ranked: Vec<_> = self.distances.iter().copied().enumerate().collect();
+        ranked.sort_by_key(|&(line, distance)| (std::cmp::Reverse(distance), line));
+        ranked.truncate(n);
+        ranked
+    }
+
+    /// The recordings more than `sigmas` standard deviations noisier than the mean, as
+    /// `(line index, distance)` in input order.
+    pub fn outliers(&self, sigmas: f64) -> Vec<(usize, usize)> {
+        let threshold = self.mean() + sigmas * self.std_dev();
+        self.distances
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, distance)| distance as f64 > threshold)
+            .collect()
+    }
+}
+
+/// Decode the most frequent message, then print the `n` recordings which stray furthest from
+/// it, and any which are more than two standard deviations noisier than the mean.
+pub fn print_noise_report(path: &Path, policy: RaggedPolicy, n: usize) -> Result<(), Error> {
+    let lines = ascii_signals(parse_str(&std::fs::read_to_string(path)?)?)?;
+    let decoded = count_most_frequent(&lines, policy)?;
+    let report = NoiseReport::new(&lines, &decoded.message);
+
+    println!(
+        "distance from {:?}: mean {:.2}, std dev {:.2}",
+        report.reference,
+        report.mean(),
+        report.std_dev()
+    );
+    for (line, distance) in report.noisiest(n) {
+        println!("  line {:>4}: {} ({})", line + 1, lines[line], distance);
+    }
+    let outliers = report.outliers(2.0);
+    if !outliers.is_empty() {
+        println!("outliers beyond 2 std devs:");
+        for (line, distance) in outliers {
+            println!("  line {:>4}: {} ({})", line + 1, lines[line], distance);
+        }
     }
-    let message = count_least_frequent(signals).ok_or(Error::SuperlativeProblem)?;
-    println!("message (least frequent): {}", message);
     Ok(())
 }
 
+/// The recording from the puzzle text.
+const EXAMPLE: &str = "eedadn\ndrvtee\neandsr\nraavrd\natevrs\ntsrnev\nsdttsa\nrasrtv\nnssdts\nntnada\nsvetve\ntesnvt\nvntsnd\nvrdear\ndvrsen\nenarar\n";
+
+const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: EXAMPLE,
+        answer: "easter",
+    },
+    Example {
+        part: 2,
+        input: EXAMPLE,
+        answer: "advent",
+    },
+];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part1_from_str(input, RaggedPolicy::Strict)?.message.into())
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part2_from_str(input, RaggedPolicy::Strict)?.message.into())
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(6, Solution);
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
@@ -118,4 +322,83 @@ pub enum Error {
     NotAscii,
     #[error("problem creating a word from the superlative frequencies of the input")]
     SuperlativeProblem,
+    #[error("line {line} has length {actual}; expected {expected}")]
+    Ragged {
+        line: usize,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Vec<String> {
+        EXAMPLE.lines().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_example() {
+        let signals = example();
+        let most = count_most_frequent(&signals, RaggedPolicy::Strict).unwrap();
+        assert_eq!(most.message, "easter");
+        assert_eq!(most.participation, vec![16; 6]);
+        let least = count_least_frequent(&signals, RaggedPolicy::Strict).unwrap();
+        assert_eq!(least.message, "advent");
+        assert_eq!(
+            most.winners().collect::<Vec<_>>(),
+            vec![('e', 3), ('a', 3), ('s', 3), ('t', 3), ('e', 3), ('r', 3)]
+        );
+    }
+
+    #[test]
+    fn test_score_against() {
+        let signals = example();
+        let scores = score_against(&signals, "easter");
+        assert_eq!(scores.len(), signals.len());
+        // "eandsr" differs from "easter" in its 3rd, 4th and 5th characters
+        assert_eq!(scores[2], 3);
+        assert_eq!(
+            score_against(&["east".to_string(), "eastern".to_string()], "easter"),
+            vec![2, 1]
+        );
+
+        let report = NoiseReport::new(&signals, "easter");
+        let noisiest = report.noisiest(3);
+        assert_eq!(noisiest.len(), 3);
+        assert!(noisiest.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert!(report
+            .distances
+            .iter()
+            .all(|&distance| distance <= noisiest[0].1));
+        assert!(report
+            .outliers(0.0)
+            .iter()
+            .all(|&(_, d)| d as f64 > report.mean()));
+    }
+
+    #[test]
+    fn test_ragged() {
+        let mut signals = example();
+        signals[3].truncate(4);
+        signals[7].truncate(5);
+
+        assert!(matches!(
+            count_most_frequent(&signals, RaggedPolicy::Strict),
+            Err(Error::Ragged {
+                line: 3,
+                expected: 6,
+                actual: 4
+            })
+        ));
+
+        let padded = count_most_frequent(&signals, RaggedPolicy::PadShort).unwrap();
+        assert_eq!(padded.message, "easter");
+        assert_eq!(padded.participation, vec![16, 16, 16, 16, 15, 14]);
+
+        let truncated = count_most_frequent(&signals, RaggedPolicy::TruncateToShortest).unwrap();
+        assert_eq!(truncated.message, "east");
+        assert_eq!(truncated.participation, vec![16; 4]);
+    }
 }
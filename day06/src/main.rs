@@ -1,19 +1,26 @@
-use aoclib::{config::Config, website::get_input};
-use day06::{part1, part2};
+use aoc2016_solver::Input;
+use day06::{part1, part2, print_noise_report, Decoded, RaggedPolicy};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 6;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
+    /// how to handle lines of differing lengths: strict, pad-short, or truncate-to-shortest
+    #[structopt(long, default_value = "strict")]
+    ragged: RaggedPolicy,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -21,33 +28,32 @@ struct RunArgs {
     /// run part 2
     #[structopt(long)]
     part2: bool,
+
+    /// list this many of the recordings which differ most from the decoded message
+    #[structopt(long)]
+    noisiest: Option<usize>,
 }
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
+fn show(description: &str, decoded: &Decoded) {
+    println!("message ({}): {}", description, decoded.message);
+    if decoded.is_ragged() {
+        println!("  participation per column: {:?}", decoded.participation);
     }
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
 
     if !args.no_part1 {
-        part1(&input_path)?;
+        show("most frequent", &part1(&input_path, args.ragged)?);
     }
     if args.part2 {
-        part2(&input_path)?;
+        show("least frequent", &part2(&input_path, args.ragged)?);
+    }
+    if let Some(n) = args.noisiest {
+        print_noise_report(&input_path, args.ragged, n)?;
     }
     Ok(())
 }
@@ -0,0 +1,39 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use day07::{gen_ipv7, supports_ssl, supports_tls, GenConfig};
+use rand::{rngs::StdRng, SeedableRng};
+
+fn addresses() -> Vec<String> {
+    let mut rng = StdRng::seed_from_u64(2016);
+    (0..1000)
+        .map(|_| {
+            let config = GenConfig::random(&mut rng);
+            gen_ipv7(&mut rng, &config)
+        })
+        .collect()
+}
+
+fn classify(c: &mut Criterion) {
+    let addresses = addresses();
+
+    let mut group = c.benchmark_group("classify 1000 addresses");
+    group.bench_function("tls", |b| {
+        b.iter(|| {
+            addresses
+                .iter()
+                .filter(|address| supports_tls(address))
+                .count()
+        })
+    });
+    group.bench_function("ssl", |b| {
+        b.iter(|| {
+            addresses
+                .iter()
+                .filter(|address| supports_ssl(address))
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, classify);
+criterion_main!(benches);
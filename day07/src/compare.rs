@@ -0,0 +1,253 @@
+//! Comparing this crate's verdicts with another solver's, to track down mismatched answers.
+
+use crate::{classify, contained_abas, find_abba, open, split_brackets, Classification, Error};
+use std::{collections::HashMap, fmt, io::BufRead, path::Path};
+
+/// The substrings which justify an address's classification.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Witness {
+    /// An ABBA outside every hypernet, which TLS requires.
+    pub abba: Option<String>,
+    /// An ABBA within a hypernet, which rules out TLS.
+    pub hypernet_abba: Option<String>,
+    /// An ABA outside every hypernet and its BAB within one, which together give SSL.
+    pub aba_bab: Option<(String, String)>,
+}
+
+fn first_abba<'a>(sections: &[(&'a str, bool)]) -> Option<&'a str> {
+    sections.iter().find_map(|(section, _)| find_abba(section))
+}
+
+impl Witness {
+    /// Find the witnesses in `ipv7`. Malformed addresses have none.
+    pub fn new(ipv7: &str) -> Witness {
+        let sections = split_brackets(ipv7).unwrap_or_default();
+        let (hypernets, supernets): (Vec<_>, Vec<_>) = sections
+            .into_iter()
+            .partition(|&(_, is_hypernet)| is_hypernet);
+
+        let aba_bab = supernets
+            .iter()
+            .flat_map(|(supernet, _)| contained_abas(supernet))
+            .find_map(|aba| {
+                let aba = aba.as_bytes();
+                let bab = String::from_utf8(vec![aba[1], aba[0], aba[1]]).ok()?;
+                hypernets
+                    .iter()
+                    .any(|(hypernet, _)| hypernet.contains(&bab))
+                    .then(|| (String::from_utf8_lossy(aba).into_owned(), bab))
+            });
+
+        Witness {
+            abba: first_abba(&supernets).map(Into::into),
+            hypernet_abba: first_abba(&hypernets).map(Into::into),
+            aba_bab,
+        }
+    }
+}
+
+impl fmt::Display for Witness {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.abba {
+            Some(ref abba) => write!(f, "abba `{}`", abba)?,
+            None => write!(f, "no abba")?,
+        }
+        match self.hypernet_abba {
+            Some(ref abba) => write!(f, ", hypernet abba `{}`", abba)?,
+            None => write!(f, ", no hypernet abba")?,
+        }
+        match self.aba_bab {
+            Some((ref aba, ref bab)) => write!(f, ", aba `{}` with bab `{}`", aba, bab),
+            None => write!(f, ", no aba with a matching bab"),
+        }
+    }
+}
+
+/// An address on which another solver's verdict differs from ours.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disagreement {
+    /// The line of the puzzle input holding the address, counting from 1.
+    pub line: usize,
+    pub address: String,
+    pub ours: Classification,
+    /// `None` when the other results don't mention this address.
+    pub theirs: Option<Classification>,
+    pub witness: Witness,
+}
+
+impl fmt::Display for Disagreement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}: ", self.line, self.address)?;
+        let theirs = match self.theirs {
+            Some(theirs) => theirs,
+            None => return write!(f, "missing from the other results"),
+        };
+        let mut differences = Vec::new();
+        if self.ours.tls != theirs.tls {
+            differences.push(format!("tls ours {} theirs {}", self.ours.tls, theirs.tls));
+        }
+        if self.ours.ssl != theirs.ssl {
+            differences.push(format!("ssl ours {} theirs {}", self.ours.ssl, theirs.ssl));
+        }
+        write!(f, "{} ({})", differences.join(", "), self.witness)
+    }
+}
+
+fn parse_verdict(field: &str) -> Option<bool> {
+    match field.trim().to_ascii_lowercase().as_str() {
+        "true" | "yes" | "1" => Some(true),
+        "false" | "no" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Read another solver's verdicts: CSV lines of `address,tls,ssl`.
+///
+/// Verdicts may be `true`/`false`, `yes`/`no`, or `1`/`0`. Blank lines and a leading header
+/// line starting with `address` are skipped.
+pub fn read_classifications(
+    reader: impl BufRead,
+) -> Result<HashMap<String, Classification>, Error> {
+    let mut classifications = HashMap::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let line = line.trim();
+        if line.is_empty() || (index == 0 && line.to_ascii_lowercase().starts_with("address")) {
+            continue;
+        }
+        let line_no = index + 1;
+        let error = |reason| Error::Results {
+            line: line_no,
+            reason,
+        };
+
+        let fields: Vec<_> = line.split(',').collect();
+        let (address, tls, ssl) = match fields.as_slice() {
+            [address, tls, ssl] => (address.trim(), tls, ssl),
+            _ => return Err(error("expected `address,tls,ssl`")),
+        };
+        let classification = Classification {
+            tls: parse_verdict(tls).ok_or_else(|| error("invalid tls verdict"))?,
+            ssl: parse_verdict(ssl).ok_or_else(|| error("invalid ssl verdict"))?,
+        };
+        if classifications
+            .insert(address.to_owned(), classification)
+            .is_some()
+        {
+            return Err(error("duplicate address"));
+        }
+    }
+    Ok(classifications)
+}
+
+/// Compare our verdict on each address in `input` with those in `other_results`, as read by
+/// [`read_classifications`].
+///
+/// Addresses which only appear in `other_results` are ignored.
+pub fn compare_classifications_from_readers(
+    input: impl BufRead,
+    other_results: impl BufRead,
+) -> Result<Vec<Disagreement>, Error> {
+    let theirs = read_classifications(other_results)?;
+    let mut disagreements = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let line = line?;
+        let address = line.trim();
+        if address.is_empty() {
+            continue;
+        }
+        let ours = classify(address);
+        let theirs = theirs.get(address).copied();
+        if theirs != Some(ours) {
+            disagreements.push(Disagreement {
+                line: index + 1,
+                address: address.to_owned(),
+                ours,
+                theirs,
+                witness: Witness::new(address),
+            });
+        }
+    }
+    Ok(disagreements)
+}
+
+/// Compare our verdict on each address in the puzzle input at `path` with another solver's
+/// verdicts in the CSV file at `other_results_path`.
+pub fn compare_classifications(
+    path: &Path,
+    other_results_path: &Path,
+) -> Result<Vec<Disagreement>, Error> {
+    compare_classifications_from_readers(open(path)?, open(other_results_path)?)
+}
+
+/// Print each disagreement between our verdicts and another solver's, with its witnesses.
+pub fn print_comparison(path: &Path, other_results_path: &Path) -> Result<(), Error> {
+    let disagreements = compare_classifications(path, other_results_path)?;
+    for disagreement in &disagreements {
+        println!("{}", disagreement);
+    }
+    println!("{} disagreements", disagreements.len());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_witness() {
+        let witness = Witness::new("abcd[bddb]xyyx");
+        assert_eq!(witness.abba.as_deref(), Some("xyyx"));
+        assert_eq!(witness.hypernet_abba.as_deref(), Some("bddb"));
+        assert_eq!(witness.aba_bab, None);
+
+        let witness = Witness::new("zazbz[bzb]cdb");
+        assert_eq!(witness.abba, None);
+        assert_eq!(
+            witness.aba_bab,
+            Some(("zbz".to_string(), "bzb".to_string()))
+        );
+        assert_eq!(Witness::new("ab]cd"), Witness::default());
+    }
+
+    #[test]
+    fn test_compare() {
+        let input = "abba[mnop]qrst\nabcd[bddb]xyyx\n\naba[bab]xyz\nioxxoj[asdfgh]zxcvbn\n";
+        let other = "Address,TLS,SSL\n\
+                     abba[mnop]qrst,1,0\n\
+                     abcd[bddb]xyyx, true, false\n\
+                     aba[bab]xyz,no,no\n\
+                     unrelated,yes,yes\n";
+        let disagreements =
+            compare_classifications_from_readers(input.as_bytes(), other.as_bytes()).unwrap();
+        assert_eq!(disagreements.len(), 3);
+
+        assert_eq!(disagreements[0].line, 2);
+        assert_eq!(
+            disagreements[0].to_string(),
+            "line 2: abcd[bddb]xyyx: tls ours false theirs true \
+             (abba `xyyx`, hypernet abba `bddb`, no aba with a matching bab)"
+        );
+        assert_eq!(disagreements[1].line, 4);
+        assert_eq!(disagreements[1].theirs, Some(Classification::default()));
+        assert!(disagreements[1].ours.ssl);
+        assert_eq!(
+            disagreements[2].to_string(),
+            "line 5: ioxxoj[asdfgh]zxcvbn: missing from the other results"
+        );
+    }
+
+    #[test]
+    fn test_read_classifications_errors() {
+        for (other, line) in &[
+            ("a,true\n", 1),
+            ("address,tls,ssl\na,true,maybe\n", 2),
+            ("a,1,1\n\na,1,1\n", 3),
+        ] {
+            match read_classifications(other.as_bytes()) {
+                Err(Error::Results { line: found, .. }) => assert_eq!(found, *line, "{}", other),
+                result => panic!("{}: {:?}", other, result),
+            }
+        }
+    }
+}
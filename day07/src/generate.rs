@@ -0,0 +1,185 @@
+use rand::Rng;
+use std::ops::RangeInclusive;
+
+const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+/// Configuration for `gen_ipv7`.
+///
+/// Sections alternate between supernets and hypernets, so the counts may differ by at most one.
+/// When there are more hypernets than supernets, the address begins with a hypernet.
+///
+/// Filler text never contains an ABBA or an ABA, so an address's classification is determined
+/// entirely by which patterns are planted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GenConfig {
+    pub supernets: usize,
+    pub hypernets: usize,
+    /// Number of filler characters in each section, excluding planted patterns.
+    pub filler_len: RangeInclusive<usize>,
+    /// Plant an ABBA in some supernet.
+    pub supernet_abba: bool,
+    /// Plant an ABBA in some hypernet.
+    pub hypernet_abba: bool,
+    /// Plant an ABA in some supernet.
+    pub supernet_aba: bool,
+    /// Plant a BAB in some hypernet. If `supernet_aba` is also set, this corresponds to it;
+    /// otherwise it is a BAB without any corresponding ABA.
+    pub hypernet_bab: bool,
+}
+
+impl Default for GenConfig {
+    fn default() -> Self {
+        GenConfig {
+            supernets: 3,
+            hypernets: 2,
+            filler_len: 4..=12,
+            supernet_abba: false,
+            hypernet_abba: false,
+            supernet_aba: false,
+            hypernet_bab: false,
+        }
+    }
+}
+
+impl GenConfig {
+    /// Generate a random valid configuration.
+    pub fn random<R: Rng>(rng: &mut R) -> GenConfig {
+        let hypernets = rng.gen_range(0..=4);
+        let supernets = rng.gen_range(hypernets.max(2) - 1..=hypernets + 1);
+        GenConfig {
+            supernets,
+            hypernets,
+            filler_len: 0..=rng.gen_range(0..16),
+            supernet_abba: rng.gen(),
+            hypernet_abba: hypernets > 0 && rng.gen(),
+            supernet_aba: rng.gen(),
+            hypernet_bab: hypernets > 0 && rng.gen(),
+        }
+    }
+
+    /// `true` if addresses generated from this configuration support TLS.
+    pub fn expect_tls(&self) -> bool {
+        self.supernet_abba && !self.hypernet_abba
+    }
+
+    /// `true` if addresses generated from this configuration support SSL.
+    pub fn expect_ssl(&self) -> bool {
+        self.supernet_aba && self.hypernet_bab
+    }
+}
+
+/// Choose `n` distinct letters.
+fn letters<R: Rng>(rng: &mut R, n: usize) -> Vec<u8> {
+    rand::seq::index::sample(rng, ALPHABET.len(), n)
+        .into_iter()
+        .map(|idx| ALPHABET[idx])
+        .collect()
+}
+
+/// Append `len` filler characters to `section`.
+///
+/// Each filler character differs from the three characters before it, and from every letter of
+/// the `upcoming` pattern, so no window of up to four characters touching filler can form an
+/// ABA or an ABBA.
+fn push_filler<R: Rng>(rng: &mut R, section: &mut Vec<u8>, len: usize, upcoming: &[u8]) {
+    for _ in 0..len {
+        let recent = &section[section.len().saturating_sub(3)..];
+        let ch = loop {
+            let ch = ALPHABET[rng.gen_range(0..ALPHABET.len())];
+            if !recent.contains(&ch) && !upcoming.contains(&ch) {
+                break ch;
+            }
+        };
+        section.push(ch);
+    }
+}
+
+/// Add `pattern` to the plants of a random section.
+fn plant<R: Rng>(rng: &mut R, sections: &mut [Vec<Vec<u8>>], pattern: Vec<u8>) {
+    let idx = rng.gen_range(0..sections.len());
+    sections[idx].push(pattern);
+}
+
+/// Generate a single section containing the given planted patterns, separated by filler.
+fn gen_section<R: Rng>(
+    rng: &mut R,
+    filler_len: &RangeInclusive<usize>,
+    plants: &[Vec<u8>],
+) -> String {
+    // patterns are separated by at least three filler characters, so that no window can span two
+    let mut gaps = vec![0; plants.len() + 1];
+    for gap in gaps[1..plants.len().max(1)].iter_mut() {
+        *gap = 3;
+    }
+    for _ in 0..rng.gen_range(filler_len.clone()) {
+        let idx = rng.gen_range(0..gaps.len());
+        gaps[idx] += 1;
+    }
+
+    let mut section = Vec::new();
+    for (plant, gap) in plants.iter().zip(&gaps) {
+        push_filler(rng, &mut section, *gap, plant);
+        section.extend_from_slice(plant);
+    }
+    push_filler(rng, &mut section, gaps[plants.len()], &[]);
+    if section.is_empty() {
+        push_filler(rng, &mut section, 1, &[]);
+    }
+
+    String::from_utf8(section).expect("alphabet is ascii")
+}
+
+/// Generate a random IPv7 address according to `config`.
+///
+/// Panics if the section counts can't alternate, or if a pattern is to be planted in a hypernet
+/// but there are none.
+pub fn gen_ipv7<R: Rng>(rng: &mut R, config: &GenConfig) -> String {
+    assert!(
+        config.supernets >= 1,
+        "an address needs at least one supernet"
+    );
+    assert!(
+        config.supernets + 1 >= config.hypernets && config.hypernets + 1 >= config.supernets,
+        "supernets and hypernets must alternate"
+    );
+    assert!(
+        config.hypernets > 0 || !(config.hypernet_abba || config.hypernet_bab),
+        "cannot plant in a hypernet without hypernets"
+    );
+
+    let mut supernets = vec![Vec::new(); config.supernets];
+    let mut hypernets = vec![Vec::new(); config.hypernets];
+
+    if config.supernet_abba {
+        let ab = letters(rng, 2);
+        plant(rng, &mut supernets, vec![ab[0], ab[1], ab[1], ab[0]]);
+    }
+    if config.hypernet_abba {
+        let ab = letters(rng, 2);
+        plant(rng, &mut hypernets, vec![ab[0], ab[1], ab[1], ab[0]]);
+    }
+    let ab = letters(rng, 2);
+    if config.supernet_aba {
+        plant(rng, &mut supernets, vec![ab[0], ab[1], ab[0]]);
+    }
+    if config.hypernet_bab {
+        plant(rng, &mut hypernets, vec![ab[1], ab[0], ab[1]]);
+    }
+
+    let hypernet_first = config.hypernets > config.supernets;
+    let mut supernets = supernets.into_iter();
+    let mut hypernets = hypernets.into_iter();
+    let mut address = String::new();
+    for idx in 0..config.supernets + config.hypernets {
+        if (idx % 2 == 1) != hypernet_first {
+            let plants = hypernets.next().expect("hypernet count is consistent");
+            address.push('[');
+            address.push_str(&gen_section(rng, &config.filler_len, &plants));
+            address.push(']');
+        } else {
+            let plants = supernets.next().expect("supernet count is consistent");
+            address.push_str(&gen_section(rng, &config.filler_len, &plants));
+        }
+    }
+    address
+}
@@ -23,8 +23,20 @@
 //!
 //! How many IPs in your puzzle input support TLS?
 
-use aoclib::parse;
-use std::path::Path;
+use aoc2016_solver::{Answer, Example, Registry, Solver};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+mod compare;
+mod generate;
+pub use compare::{
+    compare_classifications, compare_classifications_from_readers, print_comparison,
+    read_classifications, Disagreement, Witness,
+};
+pub use generate::{gen_ipv7, GenConfig};
 
 /// Assert that this let pattern is irrefutable.
 macro_rules! assert_irrefutable {
@@ -97,20 +109,33 @@ pub fn split_brackets(input: &str) -> Result<Vec<(&str, bool)>, Error> {
 }
 
 pub fn contains_abba(input: &str) -> bool {
+    find_abba(input).is_some()
+}
+
+/// The first ABBA in `input`, if any.
+pub fn find_abba(input: &str) -> Option<&str> {
     if input.len() < 4 {
-        return false;
+        return None;
     }
 
     // to avoid reallocating everything as a vector of chars,
-    // we have to look at it as bytes instead. This of course
-    // means that we're vulnerable to errors if we encounter some unicode,
-    // but we _shouldn't_ encounter that for this problem.
+    // we have to look at it as bytes instead. A window which doesn't
+    // start and end on char boundaries is part of some multi-byte
+    // character, and never an ABBA.
     let bytes = input.as_bytes();
 
-    bytes.windows(4).any(|window| {
-        assert_irrefutable!(let [a1, b1, b2, a2] = window);
-        a1 != b1 && a1 == a2 && b1 == b2
-    })
+    bytes
+        .windows(4)
+        .enumerate()
+        .position(|(start, window)| {
+            assert_irrefutable!(let [a1, b1, b2, a2] = window);
+            a1 != b1
+                && a1 == a2
+                && b1 == b2
+                && input.is_char_boundary(start)
+                && input.is_char_boundary(start + 4)
+        })
+        .map(|start| &input[start..start + 4])
 }
 
 pub fn supports_tls(ipv7: &str) -> bool {
@@ -135,7 +160,8 @@ pub fn contained_abas(input: &str) -> Vec<&str> {
 
     for (start, window) in bytes.windows(3).enumerate() {
         assert_irrefutable!(let [a1, b, a2] = window);
-        if a1 != b && a1 == a2 {
+        if a1 != b && a1 == a2 && input.is_char_boundary(start) && input.is_char_boundary(start + 3)
+        {
             abas.push(&input[start..start + 3]);
         }
     }
@@ -178,22 +204,123 @@ pub fn supports_ssl(ipv7: &str) -> bool {
         .unwrap_or_default()
 }
 
-pub fn part1(path: &Path) -> Result<(), Error> {
-    let supports_tls = parse::<String>(path)?
-        .filter(|ipv7| supports_tls(ipv7))
-        .count();
-    println!("supports tls: {}", supports_tls);
-    Ok(())
+/// Which protocols an address supports.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Classification {
+    pub tls: bool,
+    pub ssl: bool,
+}
+
+pub fn classify(ipv7: &str) -> Classification {
+    Classification {
+        tls: supports_tls(ipv7),
+        ssl: supports_ssl(ipv7),
+    }
+}
+
+/// Classify each address in `reader`, one per line, as it is read.
+///
+/// Only a single line is held in memory at a time. Surrounding whitespace is trimmed, and
+/// blank lines are skipped.
+pub fn classify_reader<R: BufRead>(
+    reader: R,
+) -> impl Iterator<Item = Result<(String, Classification), Error>> {
+    reader.lines().filter_map(|line| match line {
+        Ok(line) => {
+            let address = line.trim();
+            (!address.is_empty()).then(|| {
+                let classification = classify(address);
+                Ok((address.to_owned(), classification))
+            })
+        }
+        Err(err) => Some(Err(err.into())),
+    })
 }
 
-pub fn part2(path: &Path) -> Result<(), Error> {
-    let supports_ssl = parse::<String>(path)?
-        .filter(|ipv7| supports_ssl(ipv7))
-        .count();
-    println!("supports ssl: {}", supports_ssl);
+/// Count the addresses in `reader` which support TLS and SSL, respectively.
+pub fn count_reader<R: BufRead>(reader: R) -> Result<(usize, usize), Error> {
+    classify_reader(reader).try_fold((0, 0), |(tls, ssl), item| {
+        let (_, classification) = item?;
+        Ok((
+            tls + classification.tls as usize,
+            ssl + classification.ssl as usize,
+        ))
+    })
+}
+
+/// Print the number of addresses in `reader` which support TLS and SSL.
+pub fn print_counts<R: BufRead>(reader: R) -> Result<(), Error> {
+    let (tls, ssl) = count_reader(reader)?;
+    println!("supports tls: {}", tls);
+    println!("supports ssl: {}", ssl);
     Ok(())
 }
 
+fn open(path: &Path) -> Result<BufReader<File>, Error> {
+    Ok(BufReader::new(File::open(path)?))
+}
+
+/// The number of addresses which support TLS.
+pub fn part1(path: &Path) -> Result<usize, Error> {
+    part1_from_reader(open(path)?)
+}
+
+/// The number of addresses in `reader` which support TLS.
+pub fn part1_from_reader(reader: impl BufRead) -> Result<usize, Error> {
+    let (supports_tls, _) = count_reader(reader)?;
+    Ok(supports_tls)
+}
+
+/// The number of addresses which support SSL.
+pub fn part2(path: &Path) -> Result<usize, Error> {
+    part2_from_reader(open(path)?)
+}
+
+/// The number of addresses in `reader` which support SSL.
+pub fn part2_from_reader(reader: impl BufRead) -> Result<usize, Error> {
+    let (_, supports_ssl) = count_reader(reader)?;
+    Ok(supports_ssl)
+}
+
+/// The addresses from the puzzle text for part 1, of which the first and last support TLS.
+const TLS_EXAMPLE: &str = "abba[mnop]qrst\nabcd[bddb]xyyx\naaaa[qwer]tyui\nioxxoj[asdfgh]zxcvbn\n";
+
+/// The addresses from the puzzle text for part 2, of which all but the second support SSL.
+const SSL_EXAMPLE: &str = "aba[bab]xyz\nxyx[xyx]xyx\naaa[kek]eke\nzazbz[bzb]cdb\n";
+
+const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: TLS_EXAMPLE,
+        answer: "2",
+    },
+    Example {
+        part: 2,
+        input: SSL_EXAMPLE,
+        answer: "3",
+    },
+];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part1_from_reader(input.as_bytes())?.into())
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part2_from_reader(input.as_bytes())?.into())
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(7, Solution);
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
@@ -204,19 +331,14 @@ pub enum Error {
     NestedBrackets(String),
     #[error("reversed brackets: \"{0}\"")]
     ReversedBrackets(String),
+    #[error("line {line} of the other results: {reason}")]
+    Results { line: usize, reason: &'static str },
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const EXAMPLES: &[&str] = &[
-        "abba[mnop]qrst",
-        "abcd[bddb]xyyx",
-        "aaaa[qwer]tyui",
-        "ioxxoj[asdfgh]zxcvbn",
-    ];
-
     #[test]
     fn test_split_brackets_happy() {
         let expected = vec![
@@ -226,7 +348,7 @@ mod tests {
             vec![("ioxxoj", false), ("asdfgh", true), ("zxcvbn", false)],
         ];
 
-        for (example, expect) in EXAMPLES.iter().zip(expected) {
+        for (example, expect) in TLS_EXAMPLE.lines().zip(expected) {
             assert!(split_brackets(&example).unwrap() == expect);
         }
     }
@@ -263,7 +385,7 @@ mod tests {
 
     #[test]
     fn test_contains_abba() {
-        for (case, expect) in EXAMPLES.iter().zip([true, true, false, true].iter()) {
+        for (case, expect) in TLS_EXAMPLE.lines().zip([true, true, false, true].iter()) {
             assert!(contains_abba(case) == *expect);
         }
         assert!(contains_abba("abba") == true);
@@ -272,9 +394,27 @@ mod tests {
         assert!(contains_abba("aaaa") == false);
     }
 
+    #[test]
+    fn test_non_ascii() {
+        // U+2000 is E2 80 80: two of them together contain the bytes of an ABBA
+        let spaces = "\u{2000}\u{2000}";
+        assert_eq!(find_abba(spaces), None);
+        assert_eq!(find_abba("\u{2000}xyyx\u{2000}"), Some("xyyx"));
+        // 80 E2 80 within the same pair
+        assert!(contained_abas(spaces).is_empty());
+        assert_eq!(
+            classify(&format!("{}[{}]abba", spaces, spaces)),
+            Classification {
+                tls: true,
+                ssl: false
+            }
+        );
+        assert_eq!(compare::Witness::new(spaces), compare::Witness::default());
+    }
+
     #[test]
     fn test_supports_tls() {
-        for (case, expect) in EXAMPLES.iter().zip([true, false, false, true].iter()) {
+        for (case, expect) in TLS_EXAMPLE.lines().zip([true, false, false, true].iter()) {
             println!(
                 "Case '{}': expect {} found {}",
                 case,
@@ -287,13 +427,10 @@ mod tests {
 
     #[test]
     fn test_supports_ssl() {
-        let cases = vec![
-            ("aba[bab]xyz", true),
-            ("xyx[xyx]xyx", false),
-            ("aaa[kek]eke", true),
-            ("zazbz[bzb]cdb", true),
-        ];
-        for (case, expect) in cases {
+        for (case, expect) in SSL_EXAMPLE
+            .lines()
+            .zip([true, false, true, true].iter().copied())
+        {
             println!(
                 "Case '{}': expect {} found {}",
                 case,
@@ -303,4 +440,70 @@ mod tests {
             assert_eq!(supports_ssl(case), expect);
         }
     }
+
+    #[test]
+    fn test_classify_reader() {
+        let input = "abba[mnop]qrst\n\n  aba[bab]xyz  \naaaa[qwer]tyui\n";
+        let classified: Vec<_> = classify_reader(input.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            classified,
+            vec![
+                (
+                    "abba[mnop]qrst".to_string(),
+                    Classification {
+                        tls: true,
+                        ssl: false
+                    }
+                ),
+                (
+                    "aba[bab]xyz".to_string(),
+                    Classification {
+                        tls: false,
+                        ssl: true
+                    }
+                ),
+                ("aaaa[qwer]tyui".to_string(), Classification::default()),
+            ]
+        );
+        assert_eq!(count_reader(input.as_bytes()).unwrap(), (1, 1));
+    }
+
+    #[test]
+    fn test_generated_classification() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..2000 {
+            let config = GenConfig::random(&mut rng);
+            let address = gen_ipv7(&mut rng, &config);
+            assert_eq!(
+                supports_tls(&address),
+                config.expect_tls(),
+                "{:?}: {}",
+                config,
+                address
+            );
+            assert_eq!(
+                supports_ssl(&address),
+                config.expect_ssl(),
+                "{:?}: {}",
+                config,
+                address
+            );
+
+            let sections = split_brackets(&address).unwrap();
+            let hypernets = sections
+                .iter()
+                .filter(|(_, is_hypernet)| *is_hypernet)
+                .count();
+            let supernets = sections
+                .iter()
+                .filter(|(section, is_hypernet)| !is_hypernet && !section.is_empty())
+                .count();
+            assert_eq!(hypernets, config.hypernets, "{}", address);
+            assert_eq!(supernets, config.supernets, "{}", address);
+        }
+    }
 }
@@ -1,19 +1,22 @@
-use aoclib::{config::Config, website::get_input};
-use day07::{part1, part2};
+use aoc2016_solver::{input::prompt_stdin, Input};
+use day07::{part1, part2, print_comparison, print_counts};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 7;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -21,33 +24,36 @@ struct RunArgs {
     /// run part 2
     #[structopt(long)]
     part2: bool,
-}
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
-    }
+    /// count addresses from stdin as they arrive, holding only one line in memory at a time
+    #[structopt(long, conflicts_with_all = &["input", "stdin"])]
+    stream: bool,
+
+    /// report where another solver's verdicts, as CSV lines of `address,tls,ssl`, disagree
+    /// with ours
+    #[structopt(long, parse(from_os_str))]
+    compare: Option<PathBuf>,
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    if args.stream {
+        prompt_stdin();
+        print_counts(std::io::stdin().lock())?;
+        return Ok(());
+    }
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
+    if let Some(ref other_results) = args.compare {
+        print_comparison(&input_path, other_results)?;
+        return Ok(());
+    }
 
     if !args.no_part1 {
-        part1(&input_path)?;
+        println!("supports tls: {}", part1(&input_path)?);
     }
     if args.part2 {
-        part2(&input_path)?;
+        println!("supports ssl: {}", part2(&input_path)?);
     }
     Ok(())
 }
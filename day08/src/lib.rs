@@ -64,20 +64,95 @@
 //! There seems to be an intermediate check of the voltage used by the display: after you swipe
 //! your card, if the screen did work, how many pixels should be lit?
 
-use aoclib::{
-    geometry::{tile::Bool, Map, Point},
-    parse,
-};
-use std::{collections::VecDeque, path::Path};
+use aoc2016_solver::{Answer, Example, Registry, Solver};
+use aoclib::geometry::{tile::Bool, Map, Point};
+#[cfg(feature = "fs")]
+use aoclib::parse;
+use std::collections::VecDeque;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+/// Which set of instructions a screen program may use.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, parse_display::Display, parse_display::FromStr)]
+#[display(style = "lowercase")]
+pub enum Dialect {
+    /// Only the instructions from the puzzle.
+    Standard,
+    /// Additionally, leftward and upward rotations, and clearing and inverting rectangles.
+    Extended,
+}
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, parse_display::Display, parse_display::FromStr)]
 pub enum Instruction {
     #[display("rect {0}x{1}")]
     Rect(usize, usize),
     #[display("rotate row y={0} by {1}")]
+    #[from_str(regex = r"rotate row y=(?P<0>\d+) by (?P<1>\d+)")]
     RotateRow(usize, usize),
     #[display("rotate column x={0} by {1}")]
+    #[from_str(regex = r"rotate column x=(?P<0>\d+) by (?P<1>\d+)")]
     RotateCol(usize, usize),
+    #[display("rotate row y={0} left by {1}")]
+    RotateRowLeft(usize, usize),
+    #[display("rotate column x={0} up by {1}")]
+    RotateColUp(usize, usize),
+    #[display("clear {0}x{1}")]
+    Clear(usize, usize),
+    #[display("invert {0}x{1}")]
+    Invert(usize, usize),
+}
+
+impl Instruction {
+    /// The least dialect in which this instruction is available.
+    pub fn dialect(self) -> Dialect {
+        match self {
+            Instruction::Rect(..) | Instruction::RotateRow(..) | Instruction::RotateCol(..) => {
+                Dialect::Standard
+            }
+            _ => Dialect::Extended,
+        }
+    }
+
+    /// The instruction which undoes this one, if any.
+    ///
+    /// `rect` and `clear` discard the previous contents of their rectangle, so they have no inverse.
+    pub fn inverse(self) -> Option<Instruction> {
+        match self {
+            Instruction::Rect(..) | Instruction::Clear(..) => None,
+            Instruction::RotateRow(row, by) => Some(Instruction::RotateRowLeft(row, by)),
+            Instruction::RotateCol(col, by) => Some(Instruction::RotateColUp(col, by)),
+            Instruction::RotateRowLeft(row, by) => Some(Instruction::RotateRow(row, by)),
+            Instruction::RotateColUp(col, by) => Some(Instruction::RotateCol(col, by)),
+            Instruction::Invert(..) => Some(self),
+        }
+    }
+}
+
+fn check_dialect(instruction: Instruction, dialect: Dialect) -> Result<Instruction, Error> {
+    if dialect == Dialect::Standard && instruction.dialect() == Dialect::Extended {
+        Err(Error::UnsupportedInstruction(instruction, dialect))
+    } else {
+        Ok(instruction)
+    }
+}
+
+/// Parse the instructions in `path`, ensuring that each is permitted in `dialect`.
+#[cfg(feature = "fs")]
+pub fn read_instructions(path: &Path, dialect: Dialect) -> Result<Vec<Instruction>, Error> {
+    parse::<Instruction>(path)?
+        .map(|instruction| check_dialect(instruction, dialect))
+        .collect()
+}
+
+/// Parse one instruction per non-blank line of `text`, ensuring that each is permitted in `dialect`.
+///
+/// This needs no file system, so it is usable from the browser.
+pub fn parse_instructions(text: &str, dialect: Dialect) -> Result<Vec<Instruction>, Error> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| check_dialect(line.parse()?, dialect))
+        .collect()
 }
 
 pub struct Screen(Map<Bool>);
@@ -89,17 +164,26 @@ impl Screen {
 
     pub fn apply(&mut self, instruction: Instruction) {
         match instruction {
-            Instruction::Rect(width, height) => self.rect(width, height),
+            Instruction::Rect(width, height) => self.fill(width, height, |_| true),
             Instruction::RotateCol(col, by) => self.rotate_col(col, by),
             Instruction::RotateRow(row, by) => self.rotate_row(row, by),
+            Instruction::RotateColUp(col, by) => {
+                self.rotate_col(col, self.0.height() - by % self.0.height())
+            }
+            Instruction::RotateRowLeft(row, by) => {
+                self.rotate_row(row, self.0.width() - by % self.0.width())
+            }
+            Instruction::Clear(width, height) => self.fill(width, height, |_| false),
+            Instruction::Invert(width, height) => self.fill(width, height, |lit| !lit),
         }
     }
 
-    fn rect(&mut self, width: usize, height: usize) {
+    /// Update each pixel in a rectangle at the top left of the screen.
+    fn fill(&mut self, width: usize, height: usize, update: impl Fn(bool) -> bool) {
         // we have to fill in the top left; origin is on the bottom left
         for y in (self.0.height() - height)..self.0.height() {
             for x in 0..width {
-                self.0[(x, y)] = true.into();
+                self.0[(x, y)] = update(self.0[(x, y)].into()).into();
             }
         }
     }
@@ -113,7 +197,7 @@ impl Screen {
             .map(|point| self.0[point])
             .collect();
         // since we started at the bottom, this rotates the row down
-        col.rotate_left(by);
+        col.rotate_left(by % col.len());
 
         for (y, value) in col.into_iter().enumerate() {
             self.0[(x, y)] = value;
@@ -128,16 +212,102 @@ impl Screen {
             .project(Point::new(0, y as i32), 1, 0)
             .map(|point| self.0[point])
             .collect();
-        row.rotate_right(by);
+        row.rotate_right(by % row.len());
 
         for (x, value) in row.into_iter().enumerate() {
             self.0[(x, y)] = value;
         }
     }
 
+    /// Whether the pixel at `(x, y)` is lit, where `y` counts rows down from the top.
+    ///
+    /// `None` when the point is off the screen.
+    fn pixel(&self, x: usize, y: usize) -> Option<bool> {
+        (x < self.0.width() && y < self.0.height())
+            .then(|| self.0[(x, self.0.height() - y - 1)].into())
+    }
+
+    /// Serialize this screen as `WxH:` followed by its pixels, row-major from the top left,
+    /// with `1` for lit and `0` for unlit.
+    ///
+    /// Unlike `Display`, this fits on one line, so it is suitable for snapshots.
+    pub fn to_compact_string(&self) -> String {
+        let (width, height) = (self.0.width(), self.0.height());
+        let mut compact = format!("{}x{}:", width, height);
+        compact.reserve(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                compact.push(if self.pixel(x, y) == Some(true) {
+                    '1'
+                } else {
+                    '0'
+                });
+            }
+        }
+        compact
+    }
+
+    /// Parse a screen serialized by [`Screen::to_compact_string`].
+    pub fn from_compact_string(compact: &str) -> Result<Screen, Error> {
+        let invalid = || Error::InvalidCompact(compact.to_string());
+        let (dimensions, pixels) = compact.trim().split_once(':').ok_or_else(invalid)?;
+        let (width, height) = dimensions.split_once('x').ok_or_else(invalid)?;
+        let width: usize = width.parse().map_err(|_| invalid())?;
+        let height: usize = height.parse().map_err(|_| invalid())?;
+        if width == 0 || height == 0 || pixels.len() != width * height {
+            return Err(invalid());
+        }
+
+        let mut screen = Screen::new(width, height);
+        for (idx, pixel) in pixels.chars().enumerate() {
+            let lit = match pixel {
+                '1' => true,
+                '0' => false,
+                _ => return Err(invalid()),
+            };
+            let (x, y) = (idx % width, idx / width);
+            screen.0[(x, height - y - 1)] = lit.into();
+        }
+        Ok(screen)
+    }
+
+    /// List the pixels which differ between this screen and `other`, row-major from the top
+    /// left.
+    ///
+    /// Points use the same coordinates as the instructions: `y` counts rows down from the top.
+    /// When the screens' sizes differ, every pixel present on only one of them differs.
+    pub fn diff(&self, other: &Screen) -> Vec<Point> {
+        let width = self.0.width().max(other.0.width());
+        let height = self.0.height().max(other.0.height());
+        (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.pixel(x, y) != other.pixel(x, y))
+            .map(|(x, y)| Point::new(x as i32, y as i32))
+            .collect()
+    }
+
     fn num_pixels_lit(&self) -> usize {
         self.0.iter().filter(|pixel| (**pixel).into()).count()
     }
+
+    /// Read the text displayed on this screen.
+    pub fn text(&self) -> Result<String, aocr::Error> {
+        aocr::recognize_map(&self.0)
+    }
+
+    /// Render this screen with the text read from it beneath, marking unknown glyphs with `?`.
+    pub fn annotated(&self) -> Result<String, aocr::Error> {
+        aocr::annotate_map(&self.0)
+    }
+
+    /// The text displayed on this screen, or, if some of its glyphs are unknown, the annotated
+    /// screen for a human to read.
+    pub fn text_or_annotated(&self) -> Result<String, aocr::Error> {
+        match self.text() {
+            Err(aocr::Error::UnknownGlyph { .. }) => self.annotated(),
+            result => result,
+        }
+    }
 }
 
 impl Default for Screen {
@@ -152,40 +322,100 @@ impl std::fmt::Display for Screen {
     }
 }
 
-pub fn part1(path: &Path) -> Result<(), Error> {
+/// Run the program in `path` on a default-sized screen and render the result.
+#[cfg(feature = "fs")]
+pub fn render_screen(path: &Path, dialect: Dialect) -> Result<String, Error> {
     let mut screen = Screen::default();
-    for instruction in parse::<Instruction>(path)? {
+    for instruction in read_instructions(path, dialect)? {
         screen.apply(instruction);
     }
-    println!("num pixels lit: {}", screen.num_pixels_lit());
-    Ok(())
+    Ok(screen.to_string())
+}
+
+/// The number of pixels lit once every instruction has been applied.
+#[cfg(feature = "fs")]
+pub fn part1(path: &Path, dialect: Dialect) -> Result<usize, Error> {
+    part1_from_str(&std::fs::read_to_string(path)?, dialect)
+}
+
+/// The number of pixels lit once every instruction in `input` has been applied.
+pub fn part1_from_str(input: &str, dialect: Dialect) -> Result<usize, Error> {
+    Ok(part2_from_str(input, dialect)?.num_pixels_lit())
+}
+
+/// The screen once every instruction has been applied.
+#[cfg(feature = "fs")]
+pub fn part2(path: &Path, dialect: Dialect) -> Result<Screen, Error> {
+    part2_from_str(&std::fs::read_to_string(path)?, dialect)
 }
 
-pub fn part2(path: &Path) -> Result<(), Error> {
+/// The screen once every instruction in `input` has been applied.
+pub fn part2_from_str(input: &str, dialect: Dialect) -> Result<Screen, Error> {
     let mut screen = Screen::default();
-    for instruction in parse::<Instruction>(path)? {
+    for instruction in parse_instructions(input, dialect)? {
         screen.apply(instruction);
     }
-    println!("screen:\n{}", screen);
-    Ok(())
+    Ok(screen)
+}
+
+/// The instructions from the puzzle text, for a 7x3 screen.
+const EXAMPLE: &str =
+    "rect 3x2\nrotate column x=1 by 1\nrotate row y=0 by 4\nrotate column x=1 by 1\n";
+
+// the puzzle's example uses a smaller screen, but rotations don't change how many pixels are lit
+const EXAMPLES: &[Example] = &[Example {
+    part: 1,
+    input: EXAMPLE,
+    answer: "6",
+}];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part1_from_str(input, Dialect::Standard)?.into())
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        let reading = part2_from_str(input, Dialect::Standard)?.text_or_annotated()?;
+        // an annotated screen spans several lines
+        Ok(if reading.contains('\n') {
+            reading.lines().collect::<Vec<_>>().into()
+        } else {
+            reading.into()
+        })
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(8, Solution);
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error("reading screen")]
+    Ocr(#[from] aocr::Error),
+    #[error("parsing instruction")]
+    Parse(#[from] parse_display::ParseError),
+    #[error("instruction `{0}` is not supported in the {1} dialect")]
+    UnsupportedInstruction(Instruction, Dialect),
+    #[error("not a compact screen: {0:?}")]
+    InvalidCompact(String),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    pub const EXAMPLE: &[&str] = &[
-        "rect 3x2",
-        "rotate column x=1 by 1",
-        "rotate row y=0 by 4",
-        "rotate column x=1 by 1",
-    ];
+    fn example() -> Vec<&'static str> {
+        EXAMPLE.lines().collect()
+    }
 
     #[test]
     /// rect 3x2 creates a small rectangle in the top-left corner:
@@ -227,7 +457,7 @@ mod tests {
             ".#..#.#\n#.#....\n.#.....\n",
         ];
         let mut ts = Screen::new(7, 3);
-        for (instruction, expect) in EXAMPLE
+        for (instruction, expect) in example()
             .iter()
             .map(|instruction| instruction.parse::<Instruction>().unwrap())
             .zip(expected)
@@ -237,6 +467,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_unknown_glyph() {
+        let screen = part2_from_str("rect 1x1\n", Dialect::Standard).unwrap();
+        assert!(screen.text().is_err());
+        let annotated = screen.text_or_annotated().unwrap();
+        assert!(annotated.starts_with(&screen.to_string()));
+        assert_eq!(annotated.lines().last(), Some("?"));
+
+        let answer = Solution.part2("rect 1x1\n").unwrap();
+        assert_eq!(answer.to_string(), annotated);
+    }
+
     #[test]
     fn test_parse_instructions() {
         let expected = vec![
@@ -246,8 +488,101 @@ mod tests {
             Instruction::RotateCol(1, 1),
         ];
 
-        for (line, expect) in EXAMPLE.iter().zip(expected) {
+        for (line, expect) in example().iter().zip(expected) {
             assert_eq!(line.parse::<Instruction>().unwrap(), expect);
         }
     }
+
+    #[test]
+    fn test_extended_dialect() {
+        let expected = &[
+            ("rotate row y=0 left by 4", "#.#....\n###....\n.#.....\n"),
+            ("rotate column x=1 up by 1", "###....\n###....\n.......\n"),
+            ("invert 2x3", "..#....\n..#....\n##.....\n"),
+            ("clear 3x1", ".......\n..#....\n##.....\n"),
+        ];
+        let mut ts = Screen::new(7, 3);
+        for instruction in example().iter().take(3) {
+            ts.apply(instruction.parse().unwrap());
+        }
+        for (instruction, expect) in expected {
+            let instruction: Instruction = instruction.parse().unwrap();
+            assert_eq!(instruction.dialect(), Dialect::Extended);
+            ts.apply(instruction);
+            assert_eq!(&ts.to_string(), expect);
+        }
+    }
+
+    #[test]
+    fn test_inverse() {
+        let mut ts = Screen::new(7, 3);
+        ts.apply(Instruction::Rect(3, 2));
+        let before = ts.to_string();
+
+        let instructions = [
+            Instruction::RotateCol(1, 1),
+            Instruction::RotateRow(0, 4),
+            Instruction::Invert(5, 2),
+            Instruction::RotateRowLeft(2, 9),
+            Instruction::RotateColUp(6, 2),
+        ];
+        for instruction in instructions.iter() {
+            ts.apply(*instruction);
+        }
+        for instruction in instructions.iter().rev() {
+            ts.apply(instruction.inverse().unwrap());
+        }
+        assert_eq!(ts.to_string(), before);
+        assert_eq!(Instruction::Clear(1, 1).inverse(), None);
+    }
+
+    #[test]
+    fn test_compact_string() {
+        let mut ts = Screen::new(7, 3);
+        for instruction in example() {
+            ts.apply(instruction.parse().unwrap());
+        }
+        let compact = ts.to_compact_string();
+        assert_eq!(compact, "7x3:010010110100000100000");
+
+        let restored = Screen::from_compact_string(&compact).unwrap();
+        assert_eq!(restored.to_string(), ts.to_string());
+        assert!(restored.diff(&ts).is_empty());
+
+        for invalid in [
+            "7x3:0100",
+            "7x3:01001011010000010000x",
+            "7:0",
+            "0x0:",
+            "x3:000",
+        ] {
+            assert!(
+                matches!(
+                    Screen::from_compact_string(invalid),
+                    Err(Error::InvalidCompact(_))
+                ),
+                "{:?}",
+                invalid
+            );
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut ts = Screen::new(7, 3);
+        for instruction in example().iter().take(3) {
+            ts.apply(instruction.parse().unwrap());
+        }
+        let before = Screen::from_compact_string(&ts.to_compact_string()).unwrap();
+        ts.apply(example()[3].parse().unwrap());
+        // the last step rotates column 1 down: row 2's pixel wraps to row 0 and row 1's moves to
+        // row 2, so only rows 0 and 1 change
+        assert_eq!(before.diff(&ts), vec![Point::new(1, 0), Point::new(1, 1)]);
+
+        let wider = Screen::new(8, 3);
+        assert_eq!(
+            Screen::new(7, 3).diff(&wider),
+            vec![Point::new(7, 0), Point::new(7, 1), Point::new(7, 2)]
+        );
+    }
 }
@@ -1,19 +1,26 @@
-use aoclib::{config::Config, website::get_input};
-use day08::{part1, part2};
+use aoc2016_solver::Input;
+use day08::{part1, part2, Dialect};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 8;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
+    /// instruction dialect: standard or extended
+    #[structopt(long, default_value = "standard")]
+    dialect: Dialect,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -23,31 +30,18 @@ struct RunArgs {
     part2: bool,
 }
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
-    }
-}
-
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
 
     if !args.no_part1 {
-        part1(&input_path)?;
+        println!("num pixels lit: {}", part1(&input_path, args.dialect)?);
     }
     if args.part2 {
-        part2(&input_path)?;
+        let screen = part2(&input_path, args.dialect)?;
+        println!("screen:\n{}", screen);
+        println!("screen reads: {}", screen.text_or_annotated()?);
     }
     Ok(())
 }
@@ -34,11 +34,15 @@
 //!
 //! What is the decompressed length of the file (your puzzle input)? Don't count whitespace.
 
-use aoclib::parse;
+use aoc2016_solver::{each_line, Answer, Example, Registry, Solver};
+use aoclib::input::parse_str;
 use num_bigint::BigUint;
-use num_traits::{cast::FromPrimitive, Zero};
+use num_traits::{ToPrimitive, Zero};
 use std::path::Path;
 
+#[cfg(feature = "parallelism")]
+use rayon::prelude::*;
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum State {
     Normal,
@@ -122,18 +126,74 @@ fn handle_char(state: State, input: char) -> (State, Option<String>) {
     }
 }
 
-/// Decompress the given input according to Santa Rules
+/// Bounds on how far `decompress_with` will expand its input.
+///
+/// Small inputs such as `(999999999x999999999)...` would otherwise demand far more memory than
+/// any machine has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// The most bytes of decompressed output to produce.
+    pub max_output_bytes: usize,
+    /// The largest repetition count to accept in a marker.
+    pub max_marker_repeat: usize,
+}
+
+impl Default for Limits {
+    /// Generous enough for any real puzzle input, whose output is a few hundred kilobytes.
+    fn default() -> Limits {
+        Limits {
+            max_output_bytes: 1 << 28,
+            max_marker_repeat: 1 << 16,
+        }
+    }
+}
+
+impl Limits {
+    fn check(value: usize, limit: usize, what: &'static str) -> Result<(), Error> {
+        if value > limit {
+            Err(Error::LimitExceeded { what, value, limit })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Fail if emitting `count` repetitions of `marked` after `output` would exceed the limits.
+    ///
+    /// This is checked before the repetitions are built, so nothing excessive is allocated.
+    fn check_expansion(&self, output: &str, count: usize, marked: &str) -> Result<(), Error> {
+        let expanded = count
+            .saturating_mul(marked.len())
+            .saturating_add(output.len());
+        Limits::check(expanded, self.max_output_bytes, "output bytes")
+    }
+}
+
+/// Decompress the given input according to Santa Rules, within the default `Limits`
 pub fn decompress(input: &str) -> Result<String, Error> {
+    decompress_with(input, Limits::default())
+}
+
+/// Decompress the given input according to Santa Rules, failing with `Error::LimitExceeded`
+/// rather than expanding it beyond `limits`.
+pub fn decompress_with(input: &str, limits: Limits) -> Result<String, Error> {
     let mut state = State::default();
-    let mut output = String::with_capacity(input.len());
+    let mut output = String::with_capacity(input.len().min(limits.max_output_bytes));
 
     for ch in input.chars() {
+        // completing a marked section emits all its repetitions at once
+        if let State::ReadingMarked(0, count, ref marked) = state {
+            limits.check_expansion(&output, count, marked)?;
+        }
         let result_tuple = handle_char(state, ch);
         state = result_tuple.0;
         state.check_error()?;
+        if let State::ReadingMarked(_, count, _) = state {
+            Limits::check(count, limits.max_marker_repeat, "marker repeat")?;
+        }
 
         if let Some(intermediate) = result_tuple.1 {
             output.push_str(&intermediate);
+            Limits::check(output.len(), limits.max_output_bytes, "output bytes")?;
         }
     }
     // we may not have actually emitted any output, if the last character read was one
@@ -141,6 +201,7 @@ pub fn decompress(input: &str) -> Result<String, Error> {
     match state {
         // if we ended just as the marking ended, we still need to write our output
         State::ReadingMarked(length, count, ref marked) if length == 0 => {
+            limits.check_expansion(&output, count, marked)?;
             for _ in 0..count {
                 output.push_str(marked);
             }
@@ -153,30 +214,44 @@ pub fn decompress(input: &str) -> Result<String, Error> {
     Ok(output)
 }
 
+/// Parse a marker whose opening paren has just been consumed, returning the index of its
+/// closing paren along with its length and count.
+///
+/// Fails if the input ends before the marker is closed.
 fn parse_marker<I>(input: &mut I) -> Result<(usize, usize, usize), Error>
 where
     I: Iterator<Item = (usize, char)>,
 {
-    let length_str = input
-        .by_ref()
-        .map(|(_, c)| c)
-        .take_while(|c| *c != 'x')
-        .collect::<String>();
-    let length = length_str.parse::<usize>();
-    let (index, count_str): (Vec<usize>, String) =
-        input.by_ref().take_while(|&(_, c)| c != ')').unzip();
-    // index is last updated on the character preceding the close paren.
-    // What we want to return is the index of the close paren
-    // Therefore, simply add one.
-    let index = *index.last().unwrap() + 1;
-    let count = count_str.parse::<usize>();
-
-    match (length, count) {
-        (Ok(length), Ok(count)) => Ok((index, length, count)),
-        _ => Err(Error::ParseMarker(format!(
+    let mut length_str = String::new();
+    let mut count_str = String::new();
+    let mut reading_count = false;
+    let mut close = None;
+    for (index, ch) in input {
+        match ch {
+            'x' if !reading_count => reading_count = true,
+            ')' if reading_count => {
+                close = Some(index);
+                break;
+            }
+            _ if reading_count => count_str.push(ch),
+            _ => length_str.push(ch),
+        }
+    }
+
+    match (
+        close,
+        length_str.parse::<usize>(),
+        count_str.parse::<usize>(),
+    ) {
+        (Some(index), Ok(length), Ok(count)) => Ok((index, length, count)),
+        (Some(_), _, _) => Err(Error::ParseMarker(format!(
             "({}x{})",
             length_str, count_str
         ))),
+        (None, _, _) if reading_count => {
+            Err(Error::ParseMarker(format!("({}x{}", length_str, count_str)))
+        }
+        (None, _, _) => Err(Error::ParseMarker(format!("({}", length_str))),
     }
 }
 
@@ -224,32 +299,160 @@ where
         // if this was an open paren, parse that
         if ch == '(' {
             let (index, length, count) = parse_marker(&mut enumerated.by_ref())?;
-            multipliers.push((index + length, count));
+            multipliers.push((index.saturating_add(length), count));
         } else {
-            let multiplicand = multipliers
+            // nested counts can overflow any fixed-width integer
+            let multiplicand: BigUint = multipliers
                 .iter()
-                .map(|&(_, multiplicand)| multiplicand as u64)
+                .map(|&(_, multiplicand)| BigUint::from(multiplicand))
                 .product();
-            total += BigUint::from_u64(multiplicand).unwrap();
+            total += multiplicand;
         }
     }
     Ok(total)
 }
 
-pub fn part1(path: &Path) -> Result<(), Error> {
-    for input in parse::<String>(path)? {
-        let decompressed = decompress(&input)?;
-        println!("decompressed len: {}", decompressed.len());
+/// Split the input into segments whose v2 decompressed lengths are independent.
+///
+/// Each top-level marker, together with all the data it covers, forms one segment; each run of
+/// unmarked characters between them forms another. The v2 length of the input is the sum of
+/// the v2 lengths of its segments.
+///
+/// Markers are found exactly as `count_decompressed_v2` finds them, so a segment extends over
+/// any marker which begins within the data it covers, and over all the data that marker covers
+/// in turn.
+pub fn top_level_segments(input: &str) -> Result<Vec<&str>, Error> {
+    let chars: Vec<(usize, char)> = input.char_indices().collect();
+    let byte_offset = |idx: usize| {
+        chars
+            .get(idx)
+            .map(|&(offset, _)| offset)
+            .unwrap_or_else(|| input.len())
+    };
+
+    let mut segments = Vec::new();
+    let mut start = 0;
+    // the last index covered by the markers of the current segment, if it has any
+    let mut reach: Option<usize> = None;
+    let mut idx = 0;
+    while idx < chars.len() {
+        let is_marker = chars[idx].1 == '(';
+        let covered = matches!(reach, Some(reach) if idx <= reach);
+        // a segment ends where marked data gives way to unmarked, or unmarked to a marker
+        if !covered && (is_marker || reach.is_some()) {
+            if start < idx {
+                segments.push(&input[byte_offset(start)..byte_offset(idx)]);
+            }
+            start = idx;
+            reach = None;
+        }
+        if is_marker {
+            let mut marker = chars.iter().map(|&(_, ch)| ch).enumerate().skip(idx + 1);
+            let (close, length, _) = parse_marker(&mut marker)?;
+            let end = close.saturating_add(length);
+            reach = Some(reach.map_or(end, |reach| reach.max(end)));
+            idx = close + 1;
+        } else {
+            idx += 1;
+        }
+    }
+    if start < chars.len() {
+        segments.push(&input[byte_offset(start)..]);
     }
-    Ok(())
+
+    Ok(segments)
+}
+
+/// Return the length of the decompressed data, computing independent segments in parallel.
+///
+/// This produces the same results as `count_decompressed_v2`.
+#[cfg(feature = "parallelism")]
+pub fn count_decompressed_v2_segmented(input: &str) -> Result<BigUint, Error> {
+    let lengths = top_level_segments(input)?
+        .par_iter()
+        .map(|segment| count_decompressed_v2(&mut segment.chars()))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(lengths.into_iter().sum())
+}
+
+/// Return the length of the decompressed data, computing independent segments one at a time.
+///
+/// This produces the same results as `count_decompressed_v2`.
+#[cfg(not(feature = "parallelism"))]
+pub fn count_decompressed_v2_segmented(input: &str) -> Result<BigUint, Error> {
+    top_level_segments(input)?
+        .iter()
+        .map(|segment| count_decompressed_v2(&mut segment.chars()))
+        .sum()
+}
+
+/// The decompressed length of each line of the input.
+pub fn part1(path: &Path) -> Result<Vec<usize>, Error> {
+    part1_from_str(&std::fs::read_to_string(path)?)
+}
+
+/// The decompressed length of each line of `input`.
+pub fn part1_from_str(input: &str) -> Result<Vec<usize>, Error> {
+    parse_str::<String>(input)?
+        .map(|input| decompress(&input).map(|decompressed| decompressed.len()))
+        .collect()
+}
+
+/// The decompressed length of each line of the input, by the improved format.
+pub fn part2(path: &Path) -> Result<Vec<BigUint>, Error> {
+    part2_from_str(&std::fs::read_to_string(path)?)
 }
 
-pub fn part2(path: &Path) -> Result<(), Error> {
-    for input in parse::<String>(path)? {
-        let decompressed_len = count_decompressed_v2(&mut input.chars())?;
-        println!("decompressed len (v2): {}", decompressed_len);
+/// The decompressed length of each line of `input`, by the improved format.
+pub fn part2_from_str(input: &str) -> Result<Vec<BigUint>, Error> {
+    parse_str::<String>(input)?
+        .map(|input| count_decompressed_v2_segmented(&input))
+        .collect()
+}
+
+/// The compressed files from the puzzle text for part 1, one per line.
+const V1_EXAMPLE: &str =
+    "ADVENT\nA(1x5)BC\n(3x3)XYZ\nA(2x2)BCD(2x2)EFG\n(6x1)(1x3)A\nX(8x2)(3x3)ABCY\n";
+
+/// The compressed files from the puzzle text for part 2, one per line.
+const V2_EXAMPLE: &str = "(3x3)XYZ\nX(8x2)(3x3)ABCY\n(27x12)(20x12)(13x14)(7x10)(1x12)A\n(25x3)(3x3)ABC(2x3)XY(5x2)PQRSTX(18x9)(3x2)TWO(5x7)SEVEN\n";
+
+const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: V1_EXAMPLE,
+        answer: "6\n7\n9\n11\n6\n18",
+    },
+    Example {
+        part: 2,
+        input: V2_EXAMPLE,
+        answer: "9\n20\n241920\n445",
+    },
+];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        each_line(part1_from_str(input)?, Ok)
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        each_line(part2_from_str(input)?, |length| {
+            // only adversarial inputs decompress to more than an `Answer::Integer` can hold
+            Ok(length
+                .to_i128()
+                .map_or_else(|| Answer::Text(length.to_string()), Answer::Integer))
+        })
     }
-    Ok(())
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(9, Solution);
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -262,6 +465,12 @@ pub enum Error {
     UnexpectedState(State),
     #[error("failed to parse as marker: \"{0}\"")]
     ParseMarker(String),
+    #[error("{what} of {value} exceeds the limit of {limit}")]
+    LimitExceeded {
+        what: &'static str,
+        value: usize,
+        limit: usize,
+    },
 }
 
 #[cfg(test)]
@@ -273,14 +482,7 @@ mod tests {
     use num_traits::cast::FromPrimitive;
 
     fn get_examples() -> Vec<&'static str> {
-        vec![
-            "ADVENT",
-            "A(1x5)BC",
-            "(3x3)XYZ",
-            "A(2x2)BCD(2x2)EFG",
-            "(6x1)(1x3)A",
-            "X(8x2)(3x3)ABCY",
-        ]
+        V1_EXAMPLE.lines().collect()
     }
 
     #[test]
@@ -309,15 +511,7 @@ mod tests {
 
     #[test]
     fn test_count_v2() {
-        let expected = vec![
-            ("(3x3)XYZ", 9),
-            ("X(8x2)(3x3)ABCY", 20),
-            ("(27x12)(20x12)(13x14)(7x10)(1x12)A", 241920),
-            (
-                "(25x3)(3x3)ABC(2x3)XY(5x2)PQRSTX(18x9)(3x2)TWO(5x7)SEVEN",
-                445,
-            ),
-        ];
+        let expected = V2_EXAMPLE.lines().zip([9, 20, 241920, 445].iter().copied());
         for (case, ex_len) in expected {
             println!("Decompressing: {}", case);
             let length = count_decompressed_v2(&mut case.chars());
@@ -325,4 +519,133 @@ mod tests {
             assert_eq!(length.unwrap(), BigUint::from_u64(ex_len).unwrap());
         }
     }
+
+    #[test]
+    fn test_limits() {
+        assert!(matches!(
+            decompress("(999999999x999999999)A"),
+            Err(Error::LimitExceeded {
+                what: "marker repeat",
+                value: 999999999,
+                ..
+            })
+        ));
+
+        let limits = Limits {
+            max_output_bytes: 500,
+            max_marker_repeat: 100,
+        };
+        // the marked section completes at the end of the input
+        assert!(matches!(
+            decompress_with("(10x100)ABCDEFGHIJ", limits),
+            Err(Error::LimitExceeded {
+                what: "output bytes",
+                value: 1000,
+                ..
+            })
+        ));
+        // the marked section completes before more input
+        assert!(matches!(
+            decompress_with("(10x100)ABCDEFGHIJK", limits),
+            Err(Error::LimitExceeded {
+                what: "output bytes",
+                value: 1000,
+                ..
+            })
+        ));
+        assert!(matches!(
+            decompress_with(&"A".repeat(501), limits),
+            Err(Error::LimitExceeded {
+                what: "output bytes",
+                value: 501,
+                ..
+            })
+        ));
+        assert_eq!(
+            decompress_with("(10x50)ABCDEFGHIJ", limits).unwrap().len(),
+            500
+        );
+    }
+
+    #[test]
+    fn test_malformed_markers() {
+        for case in &["(3x", "(3", "A(", "(3xA", "(x3)AB", "(3x)ABC", "(3y3)ABC"] {
+            assert!(
+                count_decompressed_v2(&mut case.chars()).is_err(),
+                "v2 accepted {:?}",
+                case
+            );
+            assert!(top_level_segments(case).is_err(), "split {:?}", case);
+        }
+        for case in &["(3x", "(3", "A(", "(x3)AB", "(3x)ABC", "(3x3)"] {
+            assert!(decompress(case).is_err(), "decompressed {:?}", case);
+        }
+        assert!(matches!(
+            count_decompressed_v2(&mut "(3x3".chars()),
+            Err(Error::ParseMarker(marker)) if marker == "(3x3"
+        ));
+    }
+
+    #[test]
+    fn test_count_v2_overflow() {
+        // more repetitions than fit in a u64, but only a few characters of input
+        let input = format!("{}A", "(99x4294967295)".repeat(3));
+        let expect = BigUint::from(4294967295_u64).pow(3);
+        assert_eq!(count_decompressed_v2(&mut input.chars()).unwrap(), expect);
+        assert_eq!(count_decompressed_v2_segmented(&input).unwrap(), expect);
+        let huge = format!("({}x2)A", usize::MAX);
+        assert_eq!(
+            count_decompressed_v2(&mut huge.chars()).unwrap(),
+            BigUint::from(2_u8)
+        );
+    }
+
+    #[test]
+    fn test_top_level_segments() {
+        assert_eq!(
+            top_level_segments("X(8x2)(3x3)ABCY").unwrap(),
+            vec!["X", "(8x2)(3x3)ABC", "Y"]
+        );
+        assert_eq!(
+            top_level_segments("A(2x2)BCD(2x2)EFG").unwrap(),
+            vec!["A", "(2x2)BC", "D", "(2x2)EF", "G"]
+        );
+        assert_eq!(top_level_segments("(3x3)XY").unwrap(), vec!["(3x3)XY"]);
+        // the first marker's data ends partway through the second marker
+        assert_eq!(
+            top_level_segments("(3x2)(1x5)AB").unwrap(),
+            vec!["(3x2)(1x5)A", "B"]
+        );
+    }
+
+    #[test]
+    fn test_marker_cut_by_repeated_section() {
+        let input = "(3x2)(1x5)A";
+        assert_eq!(
+            count_decompressed_v2(&mut input.chars()).unwrap(),
+            BigUint::from(5_u8)
+        );
+        assert_eq!(
+            count_decompressed_v2_segmented(input).unwrap(),
+            BigUint::from(5_u8)
+        );
+        assert_eq!(part2_from_str(input).unwrap(), vec![BigUint::from(5_u8)]);
+    }
+
+    #[test]
+    fn test_segmented_matches_sequential() {
+        let mut input = String::new();
+        for case in get_examples().iter().cycle().take(60) {
+            input.push_str(case);
+            for v2_case in V2_EXAMPLE.lines().skip(2) {
+                input.push_str(v2_case);
+            }
+            assert_eq!(
+                count_decompressed_v2_segmented(&input).unwrap(),
+                count_decompressed_v2(&mut input.chars()).unwrap(),
+                "input: {}",
+                input
+            );
+        }
+    }
 }
@@ -43,10 +43,11 @@
 //! Based on your instructions, what is the number of the bot that is responsible for
 //! comparing value-61 microchips with value-17 microchips?
 
+use aoc2016_solver::{Answer, Registry, Solver};
 use aoclib::parse;
 use std::{
     array,
-    collections::{hash_map::Entry, HashMap, VecDeque},
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
     path::Path,
 };
 
@@ -131,6 +132,33 @@ impl Instruction {
             high_dest,
         }
     }
+
+    /// Orders `Get`s before `Transfer`s, then each by bot and value.
+    fn normal_order(&self) -> (u8, Id, Value) {
+        match *self {
+            Instruction::Get { bot_id, value } => (0, bot_id, value),
+            Instruction::Transfer { bot_id, .. } => (1, bot_id, 0),
+        }
+    }
+}
+
+/// Serialize `instructions` in the puzzle's format, one per line.
+///
+/// Parsing the result produces `instructions` again.
+pub fn to_lines(instructions: &[Instruction]) -> String {
+    instructions
+        .iter()
+        .map(|instruction| format!("{}\n", instruction))
+        .collect()
+}
+
+/// Sort `instructions` into a canonical order: every `Get` before every `Transfer`, each sorted
+/// by bot id.
+///
+/// The order of the instructions doesn't affect how they're processed, so tools which emit
+/// instructions normalize them first to produce reproducible output.
+pub fn normalize(instructions: &mut [Instruction]) {
+    instructions.sort_by_key(Instruction::normal_order);
 }
 
 /// Process a list of instructions.
@@ -211,23 +239,241 @@ pub fn find_bot_handling(bots: &Bots, mut low: Value, mut high: Value) -> Result
         .ok_or(Error::NoBotFound(low, high))
 }
 
-pub fn part1(path: &Path) -> Result<(), Error> {
+/// The schedule on which bots fire, if every ready bot fires simultaneously.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScheduleAnalysis {
+    /// The bots which fire in each round, in ascending order of id.
+    pub rounds: Vec<Vec<Id>>,
+}
+
+impl ScheduleAnalysis {
+    /// The dependency depth of the bot network: the number of rounds needed.
+    pub fn depth(&self) -> usize {
+        self.rounds.len()
+    }
+
+    /// The number of bots which fire in each round.
+    pub fn widths(&self) -> impl '_ + Iterator<Item = usize> {
+        self.rounds.iter().map(|round| round.len())
+    }
+
+    /// The maximum number of bots which fire simultaneously.
+    pub fn max_width(&self) -> usize {
+        self.widths().max().unwrap_or_default()
+    }
+}
+
+/// Analyze the schedule of a list of instructions.
+///
+/// In each round, every bot which holds two chips fires at once, handing its chips onward.
+/// A bot which receives its second chip during a round fires in the next round.
+pub fn analyze_schedule(instructions: &[Instruction]) -> Result<ScheduleAnalysis, Error> {
+    let mut holdings: HashMap<Id, Vec<Value>> = HashMap::new();
+    let mut rules = HashMap::new();
+    for instruction in instructions {
+        match *instruction {
+            Instruction::Get { bot_id, value } => holdings.entry(bot_id).or_default().push(value),
+            Instruction::Transfer {
+                bot_id,
+                low_dest,
+                high_dest,
+            } => {
+                rules.insert(bot_id, (low_dest, high_dest));
+            }
+        }
+    }
+
+    let mut fired = HashSet::new();
+    let mut rounds = Vec::new();
+    loop {
+        let mut ready: Vec<Id> = holdings
+            .iter()
+            .filter(|(id, chips)| {
+                chips.len() == 2 && rules.contains_key(*id) && !fired.contains(*id)
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        if ready.is_empty() {
+            break;
+        }
+        ready.sort_unstable();
+
+        // collect every delivery before making any, so that chips handed over
+        // this round can't trigger a bot until the next
+        let mut deliveries = Vec::with_capacity(2 * ready.len());
+        for id in &ready {
+            let chips = &holdings[id];
+            let (low, high) = (chips[0].min(chips[1]), chips[0].max(chips[1]));
+            let (low_dest, high_dest) = rules[id];
+            deliveries.push((low, low_dest));
+            deliveries.push((high, high_dest));
+            fired.insert(*id);
+        }
+        for (value, receiver) in deliveries {
+            if let Receiver::Bot(id) = receiver {
+                let chips = holdings.entry(id).or_default();
+                if chips.len() >= 2 {
+                    return Err(Error::BotInsert(value, id));
+                }
+                chips.push(value);
+            }
+        }
+
+        rounds.push(ready);
+    }
+
+    Ok(ScheduleAnalysis { rounds })
+}
+
+pub fn print_schedule(path: &Path) -> Result<(), Error> {
     let instructions: Vec<Instruction> = parse(path)?.collect();
-    let (bots, _) = process(&instructions)?;
-    let bot = find_bot_handling(&bots, 61, 17)?;
-    println!("Bot handling (61, 17): {}", bot);
+    let analysis = analyze_schedule(&instructions)?;
+    println!(
+        "schedule depth: {} rounds; max width: {} bots",
+        analysis.depth(),
+        analysis.max_width()
+    );
+    for (idx, width) in analysis.widths().enumerate() {
+        println!("  round {:>3}: {:>3} bots", idx + 1, width);
+    }
     Ok(())
 }
 
-pub fn part2(path: &Path) -> Result<(), Error> {
+/// One link in the chain of custody of a chip.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, parse_display::Display)]
+pub enum Step {
+    /// A `Get` instruction put the chip into a bot.
+    #[display("value {value} goes to bot {bot_id}")]
+    Get { bot_id: Id, value: Value },
+    /// A bot gave the chip away, as its low chip or its high chip.
+    #[display("bot {from} gives {which} to {to}")]
+    Give {
+        from: Id,
+        which: Which,
+        to: Receiver,
+    },
+}
+
+/// Which of a bot's two chips was given.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, parse_display::Display)]
+#[display(style = "lowercase")]
+pub enum Which {
+    Low,
+    High,
+}
+
+/// Trace the chip with `value` from its `Get` instruction to its final location.
+///
+/// The final location is the destination of the last `Give`, or the bot from the `Get` if the
+/// chip never moved.
+pub fn provenance(instructions: &[Instruction], value: Value) -> Result<Vec<Step>, Error> {
+    let mut bot_id = instructions
+        .iter()
+        .find_map(|instruction| match *instruction {
+            Instruction::Get { bot_id, value: v } if v == value => Some(bot_id),
+            _ => None,
+        })
+        .ok_or(Error::UnknownValue(value))?;
+    let rules: HashMap<_, _> = instructions
+        .iter()
+        .filter_map(|instruction| match *instruction {
+            Instruction::Transfer {
+                bot_id,
+                low_dest,
+                high_dest,
+            } => Some((bot_id, (low_dest, high_dest))),
+            _ => None,
+        })
+        .collect();
+    let (bots, _) = process(instructions)?;
+
+    let mut steps = vec![Step::Get { bot_id, value }];
+    // each bot fires at most once, so a chip visits each bot at most once
+    for _ in 0..bots.len() {
+        let (bot, (low_dest, high_dest)) = match (bots.get(&bot_id), rules.get(&bot_id)) {
+            (Some(bot), Some(rule)) if bot.is_full() => (bot, *rule),
+            _ => break,
+        };
+        let (which, to) = if bot.high == Some(value) {
+            (Which::High, high_dest)
+        } else {
+            (Which::Low, low_dest)
+        };
+        steps.push(Step::Give {
+            from: bot_id,
+            which,
+            to,
+        });
+        match to {
+            Receiver::Bot(id) => bot_id = id,
+            Receiver::Output(_) => break,
+        }
+    }
+
+    Ok(steps)
+}
+
+/// Trace the chip which ends up in output `output_id` back to its `Get` instruction.
+pub fn output_provenance(instructions: &[Instruction], output_id: Id) -> Result<Vec<Step>, Error> {
+    let (_, outputs) = process(instructions)?;
+    let value = *outputs
+        .get(&output_id)
+        .ok_or(Error::NoChipFound(output_id))?;
+    provenance(instructions, value)
+}
+
+pub fn print_provenance(path: &Path, output_id: Id) -> Result<(), Error> {
     let instructions: Vec<Instruction> = parse(path)?.collect();
+    let steps = output_provenance(&instructions, output_id)?;
+    println!("provenance of the chip in output {}:", output_id);
+    for step in steps {
+        println!("  {}", step);
+    }
+    Ok(())
+}
+
+/// The bot which compares value-61 microchips with value-17 microchips.
+pub fn part1(path: &Path) -> Result<Id, Error> {
+    part1_from_str(&std::fs::read_to_string(path)?)
+}
+
+/// The bot which compares value-61 microchips with value-17 microchips, given the instructions
+/// as text.
+pub fn part1_from_str(input: &str) -> Result<Id, Error> {
+    let instructions: Vec<Instruction> = aoclib::input::parse_str(input)?.collect();
+    let (bots, _) = process(&instructions)?;
+    find_bot_handling(&bots, 61, 17)
+}
+
+/// The product of the values of the chips in outputs 0, 1, and 2.
+pub fn part2(path: &Path) -> Result<Value, Error> {
+    part2_from_str(&std::fs::read_to_string(path)?)
+}
+
+/// The product of the values of the chips in outputs 0, 1, and 2, given the instructions as text.
+pub fn part2_from_str(input: &str) -> Result<Value, Error> {
+    let instructions: Vec<Instruction> = aoclib::input::parse_str(input)?.collect();
     let (_, outputs) = process(&instructions)?;
     let chips = array::IntoIter::new([0, 1, 2])
         .map(|id| outputs.get(&id).ok_or(Error::NoChipFound(id)))
         .collect::<Result<Vec<_>, _>>()?;
-    let chip_product: Value = chips.into_iter().product();
-    println!("Product of chips (0, 1, 2): {}", chip_product);
-    Ok(())
+    Ok(chips.into_iter().product())
+}
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part1_from_str(input)?.into())
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part2_from_str(input)?.into())
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(10, Solution);
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -242,6 +488,8 @@ pub enum Error {
     OutputInsert(Id, Value, Value),
     #[error("could not find a chip output {0}")]
     NoChipFound(Id),
+    #[error("no instruction introduces a chip with value {0}")]
+    UnknownValue(Value),
 }
 
 #[cfg(test)]
@@ -287,6 +535,69 @@ mod tests {
         assert_eq!(find_bot_handling(&bots, 5, 2).unwrap(), 2);
     }
 
+    #[test]
+    fn test_schedule() {
+        let analysis = analyze_schedule(EXAMPLE_INSTRUCTIONS).unwrap();
+        assert_eq!(analysis.rounds, vec![vec![2], vec![1], vec![0]]);
+        assert_eq!(analysis.depth(), 3);
+        assert_eq!(analysis.max_width(), 1);
+    }
+
+    #[test]
+    fn test_schedule_width() {
+        let instructions = [
+            Instruction::get(0, 1),
+            Instruction::get(0, 2),
+            Instruction::get(1, 3),
+            Instruction::get(1, 4),
+            Instruction::transfer(0, Receiver::Bot(2), Receiver::Output(0)),
+            Instruction::transfer(1, Receiver::Bot(2), Receiver::Output(1)),
+            Instruction::transfer(2, Receiver::Output(2), Receiver::Output(3)),
+        ];
+        let analysis = analyze_schedule(&instructions).unwrap();
+        assert_eq!(analysis.rounds, vec![vec![0, 1], vec![2]]);
+        assert_eq!(analysis.widths().collect::<Vec<_>>(), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_provenance() {
+        let steps = output_provenance(EXAMPLE_INSTRUCTIONS, 0).unwrap();
+        assert_eq!(
+            steps,
+            vec![
+                Step::Get {
+                    bot_id: 2,
+                    value: 5
+                },
+                Step::Give {
+                    from: 2,
+                    which: Which::High,
+                    to: Receiver::Bot(0)
+                },
+                Step::Give {
+                    from: 0,
+                    which: Which::High,
+                    to: Receiver::Output(0)
+                },
+            ]
+        );
+        let printed: Vec<_> = steps.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            printed,
+            vec![
+                "value 5 goes to bot 2",
+                "bot 2 gives high to bot 0",
+                "bot 0 gives high to output 0",
+            ]
+        );
+
+        assert_eq!(provenance(EXAMPLE_INSTRUCTIONS, 2).unwrap().len(), 3);
+        assert!(matches!(
+            provenance(EXAMPLE_INSTRUCTIONS, 4),
+            Err(Error::UnknownValue(4))
+        ));
+    }
+
     #[test]
     fn test_parse() {
         for (raw, parsed) in EXAMPLE_INSTRUCTIONS_STR
@@ -298,4 +609,72 @@ mod tests {
             assert_eq!(got, *parsed);
         }
     }
+
+    fn round_trip(instructions: &[Instruction]) -> Vec<Instruction> {
+        aoclib::input::parse_str(&to_lines(instructions))
+            .unwrap()
+            .collect()
+    }
+
+    #[test]
+    fn test_to_lines() {
+        let mut expected = EXAMPLE_INSTRUCTIONS_STR.join("\n");
+        expected.push('\n');
+        assert_eq!(to_lines(EXAMPLE_INSTRUCTIONS), expected);
+        assert_eq!(round_trip(EXAMPLE_INSTRUCTIONS), EXAMPLE_INSTRUCTIONS);
+    }
+
+    #[test]
+    fn test_round_trip_random() {
+        use rand::{rngs::StdRng, Rng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(10);
+        let receiver = |rng: &mut StdRng| {
+            let id = rng.gen_range(0..1000);
+            if rng.gen() {
+                Receiver::Bot(id)
+            } else {
+                Receiver::Output(id)
+            }
+        };
+        for _ in 0..100 {
+            let len = rng.gen_range(0..50);
+            let instructions: Vec<_> = (0..len)
+                .map(|_| {
+                    let bot_id = rng.gen_range(0..1000);
+                    if rng.gen() {
+                        Instruction::get(bot_id, rng.gen())
+                    } else {
+                        Instruction::transfer(bot_id, receiver(&mut rng), receiver(&mut rng))
+                    }
+                })
+                .collect();
+            assert_eq!(round_trip(&instructions), instructions);
+        }
+    }
+
+    #[test]
+    fn test_normalize() {
+        let mut normalized = EXAMPLE_INSTRUCTIONS.to_vec();
+        normalize(&mut normalized);
+        assert_eq!(
+            normalized,
+            vec![
+                Instruction::get(1, 3),
+                Instruction::get(2, 2),
+                Instruction::get(2, 5),
+                Instruction::transfer(0, Receiver::Output(2), Receiver::Output(0)),
+                Instruction::transfer(1, Receiver::Output(1), Receiver::Bot(0)),
+                Instruction::transfer(2, Receiver::Bot(1), Receiver::Bot(0)),
+            ]
+        );
+        assert_eq!(
+            process(&normalized).unwrap().1,
+            process(EXAMPLE_INSTRUCTIONS).unwrap().1
+        );
+
+        let mut reversed: Vec<_> = normalized.iter().rev().copied().collect();
+        normalize(&mut reversed);
+        assert_eq!(reversed, normalized);
+    }
 }
@@ -0,0 +1,47 @@
+//! Breadth-first search is the only solver, so this compares it with and without pruning, over
+//! random states of increasing size.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use day11::{breadth_first_search, exhaustive_search, gen_state, GenConfig};
+use rand::{rngs::StdRng, SeedableRng};
+
+/// Beyond this many pairs, a single run of the exhaustive search takes seconds.
+const MAX_EXHAUSTIVE_PAIRS: usize = 3;
+
+fn search(c: &mut Criterion) {
+    let mut rng = StdRng::seed_from_u64(2016);
+
+    let mut group = c.benchmark_group("search 10 random states");
+    for pairs in 1..=4 {
+        let config = GenConfig::with_pairs(pairs);
+        let states: Vec<_> = (0..10)
+            .map(|_| gen_state(&mut rng, &config).unwrap())
+            .collect();
+        group.bench_with_input(BenchmarkId::new("bfs", pairs), &states, |b, states| {
+            b.iter(|| {
+                states
+                    .iter()
+                    .map(|state| breadth_first_search(state.clone()).map(|goal| goal.steps()))
+                    .collect::<Vec<_>>()
+            })
+        });
+        if pairs <= MAX_EXHAUSTIVE_PAIRS {
+            group.bench_with_input(
+                BenchmarkId::new("exhaustive", pairs),
+                &states,
+                |b, states| {
+                    b.iter(|| {
+                        states
+                            .iter()
+                            .map(|state| exhaustive_search(state.clone()).map(|goal| goal.steps()))
+                            .collect::<Vec<_>>()
+                    })
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, search);
+criterion_main!(benches);
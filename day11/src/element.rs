@@ -14,6 +14,19 @@ pub enum Element {
     Ruthenium,
 }
 
+impl Element {
+    /// Every element, in order.
+    pub const ALL: [Element; 7] = [
+        Self::Cobalt,
+        Self::Curium,
+        Self::Hydrogen,
+        Self::Lithium,
+        Self::Plutonium,
+        Self::Promethium,
+        Self::Ruthenium,
+    ];
+}
+
 impl fmt::Display for Element {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
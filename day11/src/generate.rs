@@ -0,0 +1,115 @@
+use crate::{state::FLOORS, Device, Element, Error, State};
+use rand::{seq::SliceRandom, Rng};
+
+/// Configuration for `gen_state`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GenConfig {
+    /// Number of elements, each of which has a generator and a microchip.
+    pub pairs: usize,
+    /// Devices are placed only on this many of the lowest floors.
+    pub floors: usize,
+}
+
+impl Default for GenConfig {
+    /// Two pairs spread over the lowest three floors, like the example.
+    fn default() -> Self {
+        GenConfig {
+            pairs: 2,
+            floors: FLOORS - 1,
+        }
+    }
+}
+
+impl GenConfig {
+    pub fn with_pairs(pairs: usize) -> GenConfig {
+        GenConfig {
+            pairs,
+            ..GenConfig::default()
+        }
+    }
+}
+
+/// Generate a random safe initial state.
+///
+/// Each generator is placed on a random floor. Each microchip then goes either on its
+/// generator's floor or on a floor with no generators, so no microchip is ever fried. The
+/// elevator starts on the lowest floor holding any device.
+pub fn gen_state<R: Rng>(rng: &mut R, config: &GenConfig) -> Result<State, Error> {
+    if config.pairs > Element::ALL.len() {
+        return Err(Error::TooManyPairs(config.pairs));
+    }
+    if !(1..=FLOORS).contains(&config.floors) {
+        return Err(Error::Floors(config.floors));
+    }
+
+    let elements: Vec<_> = Element::ALL
+        .choose_multiple(rng, config.pairs)
+        .copied()
+        .collect();
+    let generator_floors: Vec<_> = elements
+        .iter()
+        .map(|_| rng.gen_range(0..config.floors))
+        .collect();
+    let unpowered: Vec<_> = (0..config.floors)
+        .filter(|floor| !generator_floors.contains(floor))
+        .collect();
+
+    let mut state = State::default();
+    for (&element, &generator_floor) in elements.iter().zip(&generator_floors) {
+        let microchip_floor = match rng.gen_range(0..=unpowered.len()) {
+            0 => generator_floor,
+            idx => unpowered[idx - 1],
+        };
+        state.add_device(generator_floor, Device::generator(element));
+        state.add_device(microchip_floor, Device::microchip(element));
+    }
+
+    let lowest = (0..FLOORS as u8)
+        .find(|&floor| !state[floor].is_empty())
+        .unwrap_or_default();
+    state.set_elevator(lowest);
+    debug_assert!(state.is_safe());
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn test_generated_states_are_safe() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for pairs in 0..=Element::ALL.len() {
+            for floors in 1..=FLOORS {
+                let config = GenConfig { pairs, floors };
+                for _ in 0..50 {
+                    let state = gen_state(&mut rng, &config).unwrap();
+                    assert!(state.is_safe(), "unsafe:\n{}", state);
+
+                    let occupied: Vec<_> = (0..FLOORS as u8)
+                        .filter(|&floor| !state[floor].is_empty())
+                        .collect();
+                    let devices: usize = occupied
+                        .iter()
+                        .map(|&floor| state[floor].devices().count())
+                        .sum();
+                    assert_eq!(devices, 2 * pairs);
+                    assert!(occupied.iter().all(|&floor| (floor as usize) < floors));
+                    assert_eq!(state.elevator(), occupied.first().copied().unwrap_or(0));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let mut rng = StdRng::seed_from_u64(11);
+        assert!(gen_state(&mut rng, &GenConfig::with_pairs(8)).is_err());
+        let config = GenConfig {
+            pairs: 1,
+            floors: 0,
+        };
+        assert!(gen_state(&mut rng, &config).is_err());
+    }
+}
@@ -1,14 +1,31 @@
+use aoc2016_solver::{Answer, Progress, Registry, Silent, Solver};
 use std::collections::{HashSet, VecDeque};
 
 mod device;
 mod element;
 mod floor;
 mod gadget;
+mod generate;
 mod state;
 
+pub use generate::{gen_state, GenConfig};
+pub use state::PruneOptions;
 pub(crate) use {device::Device, element::Element, floor::Floor, gadget::Gadget, state::State};
 
 pub fn breadth_first_search(initial: State) -> Result<State, Error> {
+    breadth_first_search_with(initial, &PruneOptions::default())
+}
+
+pub fn breadth_first_search_with(initial: State, prune: &PruneOptions) -> Result<State, Error> {
+    breadth_first_search_reporting(initial, prune, &Silent)
+}
+
+/// Search as `breadth_first_search_with`, reporting the number of states visited to `progress`.
+pub fn breadth_first_search_reporting(
+    initial: State,
+    prune: &PruneOptions,
+    progress: &dyn Progress,
+) -> Result<State, Error> {
     let mut visited = HashSet::new();
     let mut queue = VecDeque::new();
     queue.push_front(initial);
@@ -22,16 +39,62 @@ pub fn breadth_first_search(initial: State) -> Result<State, Error> {
             return Ok(state);
         }
 
-        for child in state.children(&visited) {
+        for child in state.children(&visited, prune) {
             queue.push_back(child);
         }
 
         visited.insert(state);
+        progress.update(visited.len() as u64);
+    }
+
+    Err(Error::NoSolution)
+}
+
+/// Search breadth-first without any pruning, visiting every state which isn't `equivalent` to
+/// one already visited.
+///
+/// Much slower than `breadth_first_search`, but the visited check is exact rather than by
+/// isomorph, so its solution is certainly optimal.
+pub fn exhaustive_search(initial: State) -> Result<State, Error> {
+    let mut visited = HashSet::new();
+    let mut queue = VecDeque::new();
+    let nothing = HashSet::new();
+    queue.push_back(initial);
+
+    while let Some(state) = queue.pop_front() {
+        if !visited.insert(state.canonical()) {
+            continue;
+        }
+        if state.is_goal() {
+            return Ok(state);
+        }
+        queue.extend(state.children(&nothing, &PruneOptions::none()));
     }
 
     Err(Error::NoSolution)
 }
 
+/// A state for which the pruned search's answer differs from the exhaustive search's.
+#[derive(Debug, Clone)]
+pub struct PruningViolation {
+    pub initial: State,
+    /// Steps in each search's solution, or `None` if it found none.
+    pub pruned: Option<usize>,
+    pub exhaustive: Option<usize>,
+}
+
+/// Check that searching from `initial` with `prune` finds an optimal solution.
+pub fn check_pruning(initial: &State, prune: &PruneOptions) -> Option<PruningViolation> {
+    let steps = |result: Result<State, Error>| result.ok().map(|goal| goal.steps());
+    let pruned = steps(breadth_first_search_with(initial.clone(), prune));
+    let exhaustive = steps(exhaustive_search(initial.clone()));
+    (pruned != exhaustive).then(|| PruningViolation {
+        initial: initial.clone(),
+        pruned,
+        exhaustive,
+    })
+}
+
 pub fn input() -> State {
     use Element::*;
 
@@ -66,18 +129,44 @@ pub fn input_part2() -> State {
     s
 }
 
-pub fn part1() -> Result<(), Error> {
-    let state = input();
-    let steps = breadth_first_search(state)?.steps();
-    println!("part1 solution in {} steps", steps);
-    Ok(())
+/// The minimum number of steps to bring everything to the fourth floor.
+pub fn part1(prune: &PruneOptions, progress: &dyn Progress) -> Result<usize, Error> {
+    Ok(breadth_first_search_reporting(input(), prune, progress)?.steps())
 }
 
-pub fn part2() -> Result<(), Error> {
-    let state = input_part2();
-    let steps = breadth_first_search(state)?.steps();
-    println!("part2 solution in {} steps", steps);
-    Ok(())
+/// The minimum number of steps to bring everything to the fourth floor, with the extra parts.
+pub fn part2(prune: &PruneOptions, progress: &dyn Progress) -> Result<usize, Error> {
+    Ok(breadth_first_search_reporting(input_part2(), prune, progress)?.steps())
+}
+
+pub struct Solution;
+
+// the input is compiled in
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        self.solve_reporting(1, input, &Silent)
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        self.solve_reporting(2, input, &Silent)
+    }
+
+    fn solve_reporting(
+        &self,
+        part: u8,
+        input: &str,
+        progress: &dyn Progress,
+    ) -> aoc2016_solver::Result<Answer> {
+        match part {
+            1 => Ok(part1(&PruneOptions::default(), progress)?.into()),
+            2 => Ok(part2(&PruneOptions::default(), progress)?.into()),
+            _ => self.solve(part, input),
+        }
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(11, Solution);
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -86,6 +175,13 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("could not determine a solution")]
     NoSolution,
+    #[error("{0} pairs requested, but there are only {} elements", Element::ALL.len())]
+    TooManyPairs(usize),
+    #[error(
+        "devices must be placed on between 1 and {} floors, not {0}",
+        state::FLOORS
+    )]
+    Floors(usize),
 }
 
 #[cfg(test)]
@@ -119,4 +215,42 @@ mod tests {
         show_path_to(&goal);
         assert_eq!(goal.steps(), 11);
     }
+
+    #[test]
+    fn test_example_unpruned() {
+        let goal = breadth_first_search_with(example(), &PruneOptions::none()).unwrap();
+        assert_eq!(goal.steps(), 11);
+    }
+
+    #[test]
+    fn test_exhaustive_search() {
+        assert_eq!(exhaustive_search(example()).unwrap().steps(), 11);
+        assert!(check_pruning(&example(), &PruneOptions::default()).is_none());
+    }
+
+    #[test]
+    fn test_random_states_preserve_optimality() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..20 {
+            let state = gen_state(&mut rng, &GenConfig::default()).unwrap();
+            if let Some(violation) = check_pruning(&state, &PruneOptions::default()) {
+                panic!("{:?}", violation);
+            }
+        }
+    }
+
+    #[test]
+    fn test_pruning_preserves_optimality() {
+        use Element::*;
+
+        let mut s = example();
+        s.add_device(1, Device::generator(Plutonium));
+        s.add_device(0, Device::microchip(Plutonium));
+
+        let pruned = breadth_first_search(s.clone()).unwrap();
+        let unpruned = breadth_first_search_with(s, &PruneOptions::none()).unwrap();
+        assert_eq!(pruned.steps(), unpruned.steps());
+    }
 }
@@ -1,6 +1,11 @@
-use day11::{part1, part2};
+use aoc2016_solver::TerminalProgress;
+use day11::{
+    breadth_first_search_with, check_pruning, gen_state, part1, part2, GenConfig, PruneOptions,
+};
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{bail, Result};
+use rand::{rngs::StdRng, SeedableRng};
+use std::time::Instant;
 use structopt::StructOpt;
 
 #[derive(StructOpt, Debug)]
@@ -17,17 +22,84 @@ struct RunArgs {
     /// run part 2
     #[structopt(long)]
     part2: bool,
+
+    /// disable all heuristic pruning, searching exhaustively
+    #[structopt(long)]
+    no_prune: bool,
+
+    /// instead of the puzzle, search this many random starting states
+    #[structopt(long)]
+    random: Option<usize>,
+
+    /// element pairs in each random state
+    #[structopt(long, default_value = "2")]
+    pairs: usize,
+
+    /// seed for the random states, to reproduce a run
+    #[structopt(long)]
+    seed: Option<u64>,
+
+    /// check each random state's solution against an exhaustive search
+    #[structopt(long)]
+    check_pruning: bool,
+}
+
+fn search_random(args: &RunArgs, count: usize, prune: &PruneOptions) -> Result<()> {
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let config = GenConfig::with_pairs(args.pairs);
+    let mut violations = 0;
+    for idx in 0..count {
+        let state = gen_state(&mut rng, &config)?;
+        if args.check_pruning {
+            if let Some(violation) = check_pruning(&state, prune) {
+                violations += 1;
+                println!(
+                    "state {}: pruned search found {:?} steps, exhaustive search {:?}:\n{}",
+                    idx, violation.pruned, violation.exhaustive, violation.initial
+                );
+            }
+        } else {
+            let started = Instant::now();
+            let steps = breadth_first_search_with(state, prune).map(|goal| goal.steps());
+            println!("state {}: {:?} in {:.3?}", idx, steps, started.elapsed());
+        }
+    }
+    if violations > 0 {
+        bail!(
+            "pruning lost optimality for {} of {} states",
+            violations,
+            count
+        );
+    }
+    Ok(())
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
+    let prune = if args.no_prune {
+        PruneOptions::none()
+    } else {
+        PruneOptions::default()
+    };
+    if let Some(count) = args.random {
+        return search_random(&args, count, &prune);
+    }
 
     if !args.no_part1 {
-        part1()?;
+        let progress = TerminalProgress::new("states visited");
+        let steps = part1(&prune, &progress)?;
+        progress.finish();
+        println!("part1 solution in {} steps", steps);
     }
     if args.part2 {
-        part2()?;
+        let progress = TerminalProgress::new("states visited");
+        let steps = part2(&prune, &progress)?;
+        progress.finish();
+        println!("part2 solution in {} steps", steps);
     }
     Ok(())
 }
@@ -1,9 +1,8 @@
-use crate::{Device, Floor};
+use crate::{Device, Floor, Gadget};
 use itertools::Itertools;
 use std::{
-    array,
     borrow::Borrow,
-    collections::HashSet,
+    collections::{HashMap, HashSet},
     fmt,
     hash::{Hash, Hasher},
     ops::{Index, IndexMut},
@@ -12,6 +11,121 @@ use std::{
 
 pub const FLOORS: usize = 4;
 
+/// Heuristic pruning rules applied when generating the children of a state.
+///
+/// None of these rules are known to be necessary for correctness; they just shrink the search
+/// space. Disabling them all makes the search exhaustive, which is useful on small instances for
+/// checking that the heuristics don't lose optimality.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PruneOptions {
+    /// If all floors below the current floor are empty, don't move anything down.
+    pub skip_down_when_below_empty: bool,
+    /// If it's possible to move a pair upstairs, don't bother bringing single items upstairs.
+    pub prefer_pairs_up: bool,
+    /// If it's possible to move a single item downstairs, don't bother bringing pairs downstairs.
+    pub prefer_singles_down: bool,
+}
+
+impl Default for PruneOptions {
+    fn default() -> Self {
+        PruneOptions {
+            skip_down_when_below_empty: true,
+            prefer_pairs_up: true,
+            prefer_singles_down: true,
+        }
+    }
+}
+
+impl PruneOptions {
+    /// Disable all pruning.
+    pub fn none() -> Self {
+        PruneOptions {
+            skip_down_when_below_empty: false,
+            prefer_pairs_up: false,
+            prefer_singles_down: false,
+        }
+    }
+}
+
+/// A device described without naming its element.
+///
+/// Renaming elements never changes the components on a floor, so two states are equivalent
+/// exactly when their elevators agree and each floor has the same components.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Component {
+    pub gadget: Gadget,
+    /// Floor of the other device of the same element, if present.
+    pub partner_floor: Option<u8>,
+}
+
+impl fmt::Display for Component {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.partner_floor {
+            Some(floor) => write!(f, "{}[F{}]", self.gadget, floor + 1),
+            None => write!(f, "{}[-]", self.gadget),
+        }
+    }
+}
+
+/// The components of a single floor which differ between two states.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FloorDiff {
+    pub floor: u8,
+    pub only_left: Vec<Component>,
+    pub only_right: Vec<Component>,
+}
+
+/// The differences between two states, after accounting for element renaming.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct StateDiff {
+    /// `(left, right)` elevator floors, if they differ.
+    pub elevator: Option<(u8, u8)>,
+    pub floors: Vec<FloorDiff>,
+}
+
+impl StateDiff {
+    /// `true` if the states are equivalent.
+    pub fn is_empty(&self) -> bool {
+        self.elevator.is_none() && self.floors.is_empty()
+    }
+}
+
+impl fmt::Display for StateDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "equivalent");
+        }
+        if let Some((left, right)) = self.elevator {
+            writeln!(f, "elevator: F{} vs F{}", left + 1, right + 1)?;
+        }
+        for floor in &self.floors {
+            write!(f, "F{}: left only:", floor.floor + 1)?;
+            for component in &floor.only_left {
+                write!(f, " {}", component)?;
+            }
+            write!(f, "; right only:")?;
+            for component in &floor.only_right {
+                write!(f, " {}", component)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Remove the items of sorted `right` from sorted `left`, as multisets.
+fn multiset_difference(left: &[Component], right: &[Component]) -> Vec<Component> {
+    let mut right = right.iter().peekable();
+    let mut out = Vec::new();
+    for item in left {
+        while right.next_if(|other| *other < item).is_some() {}
+        if right.next_if(|other| *other == item).is_none() {
+            out.push(*item);
+        }
+    }
+    out
+}
+
 #[derive(Default, Debug, Clone, Eq)]
 pub struct State {
     parent: Option<Rc<State>>,
@@ -72,6 +186,15 @@ impl State {
         self.floors[floor].add_device(device);
     }
 
+    pub fn elevator(&self) -> u8 {
+        self.elevator
+    }
+
+    pub fn set_elevator(&mut self, floor: u8) {
+        debug_assert!((floor as usize) < FLOORS);
+        self.elevator = floor;
+    }
+
     pub fn steps(&self) -> usize {
         if let Some(ref parent) = self.parent {
             1 + parent.steps()
@@ -85,11 +208,10 @@ impl State {
     // Follows these rules:
     //
     // - don't include unsafe children
-    // - if all floors below the current floor are empty, don't move anything down
-    // - if possible to move a pair upstairs, don't bother bringing single items upstairs
-    // - if possible to move a single item downstairs, don't bother bringing pairs downstairs
     // - exclude child states isomorphic to visited states
-    pub fn children(&self, visited: &HashSet<State>) -> Vec<State> {
+    //
+    // Additional heuristic rules are applied according to `prune`; see `PruneOptions`.
+    pub fn children(&self, visited: &HashSet<State>, prune: &PruneOptions) -> Vec<State> {
         let parent = Some(Rc::new(self.clone()));
         let mut children = Vec::new();
 
@@ -114,7 +236,7 @@ impl State {
 
             for (a, b) in pairs.clone() {
                 let mut child = make_child();
-                for device in array::IntoIter::new([a, b]) {
+                for device in [a, b] {
                     move_device(&mut child, device);
                 }
                 if !visited.contains(&child) && child.is_safe() {
@@ -124,7 +246,7 @@ impl State {
             }
 
             // only move single items up if we didn't manage to move a pair
-            if !moved_pair {
+            if !(prune.prefer_pairs_up && moved_pair) {
                 for device in self[self.elevator].devices() {
                     let mut child = make_child();
                     move_device(&mut child, device);
@@ -138,9 +260,10 @@ impl State {
 
         // consider moving single items or pairs downstairs
         if self.elevator > 0
-            && !self
-                .floors_below()
-                .all(|floor| floor.devices().next().is_none())
+            && !(prune.skip_down_when_below_empty
+                && self
+                    .floors_below()
+                    .all(|floor| floor.devices().next().is_none()))
         {
             let mut moved_single = false;
 
@@ -165,10 +288,10 @@ impl State {
             }
 
             // only move pairs down if we didn't manage to move a single
-            if !moved_single {
+            if !(prune.prefer_singles_down && moved_single) {
                 for (a, b) in pairs {
                     let mut child = make_child();
-                    for device in array::IntoIter::new([a, b]) {
+                    for device in [a, b] {
                         move_device(&mut child, device);
                     }
 
@@ -182,6 +305,65 @@ impl State {
         children
     }
 
+    /// Compute the components of each floor, sorted.
+    fn components(&self) -> [Vec<Component>; FLOORS] {
+        let locations: HashMap<Device, u8> = (0..FLOORS as u8)
+            .flat_map(|floor| self[floor].devices().map(move |device| (device, floor)))
+            .collect();
+
+        let mut components: [Vec<Component>; FLOORS] = Default::default();
+        for (device, floor) in locations.iter() {
+            let partner = Device::new(
+                device.element,
+                match device.gadget {
+                    Gadget::Generator => Gadget::Microchip,
+                    Gadget::Microchip => Gadget::Generator,
+                },
+            );
+            components[*floor as usize].push(Component {
+                gadget: device.gadget,
+                partner_floor: locations.get(&partner).copied(),
+            });
+        }
+        for floor in components.iter_mut() {
+            floor.sort_unstable();
+        }
+        components
+    }
+
+    /// `true` if `other` is identical to this state after some renaming of elements.
+    ///
+    /// Unlike the isomorph used for hashing, this is exact.
+    pub fn equivalent(&self, other: &State) -> bool {
+        self.canonical() == other.canonical()
+    }
+
+    /// A value which is equal for two states exactly when they are `equivalent`.
+    pub fn canonical(&self) -> (u8, [Vec<Component>; FLOORS]) {
+        (self.elevator, self.components())
+    }
+
+    /// Explain which floors differ between this state and `other`, after accounting for
+    /// element renaming.
+    ///
+    /// The diff is empty exactly when the states are `equivalent`.
+    pub fn diff(&self, other: &State) -> StateDiff {
+        let elevator = (self.elevator != other.elevator).then_some((self.elevator, other.elevator));
+        let floors = self
+            .components()
+            .iter()
+            .zip(other.components().iter())
+            .enumerate()
+            .filter(|(_, (left, right))| left != right)
+            .map(|(floor, (left, right))| FloorDiff {
+                floor: floor as u8,
+                only_left: multiset_difference(left, right),
+                only_right: multiset_difference(right, left),
+            })
+            .collect();
+        StateDiff { elevator, floors }
+    }
+
     /// Compute a single value corresponding to the distribution of devices among
     /// the floors of this state.
     ///
@@ -281,3 +463,95 @@ mod isomorph_tests {
         }
     }
 }
+
+#[cfg(test)]
+mod equivalence_tests {
+    use super::*;
+    use crate::Element::{self, *};
+
+    fn example() -> State {
+        let mut s = State::default();
+        s.add_device(0, Device::microchip(Hydrogen));
+        s.add_device(0, Device::microchip(Lithium));
+        s.add_device(1, Device::generator(Hydrogen));
+        s.add_device(2, Device::generator(Lithium));
+
+        s
+    }
+
+    fn rename(state: &State, rename: impl Fn(Element) -> Element) -> State {
+        let mut renamed = State {
+            elevator: state.elevator,
+            ..State::default()
+        };
+        for floor in 0..FLOORS as u8 {
+            for device in state[floor].devices() {
+                renamed[floor].add_device(Device::new(rename(device.element), device.gadget));
+            }
+        }
+        renamed
+    }
+
+    #[test]
+    fn test_renamed_states_are_equivalent() {
+        let state = example();
+        let renamed = rename(&state, |element| match element {
+            Hydrogen => Lithium,
+            Lithium => Hydrogen,
+            other => other,
+        });
+        assert!(state.equivalent(&renamed));
+        assert!(state.diff(&renamed).is_empty());
+        assert_eq!(state.isomorph(), renamed.isomorph());
+    }
+
+    #[test]
+    fn test_elevator_diff() {
+        let state = example();
+        let mut moved = example();
+        moved.elevator = 1;
+        assert!(!state.equivalent(&moved));
+        let diff = state.diff(&moved);
+        assert_eq!(diff.elevator, Some((0, 1)));
+        assert!(diff.floors.is_empty());
+    }
+
+    #[test]
+    fn test_floor_diff() {
+        // each floor holds one unpaired device in both states, but the pairings differ
+        let mut left = State::default();
+        left.add_device(0, Device::microchip(Hydrogen));
+        left.add_device(1, Device::generator(Hydrogen));
+        left.add_device(2, Device::microchip(Lithium));
+        left.add_device(3, Device::generator(Lithium));
+
+        let mut right = State::default();
+        right.add_device(0, Device::microchip(Hydrogen));
+        right.add_device(1, Device::generator(Lithium));
+        right.add_device(2, Device::microchip(Lithium));
+        right.add_device(3, Device::generator(Hydrogen));
+
+        assert!(!left.equivalent(&right));
+        let diff = left.diff(&right);
+        assert_eq!(diff.elevator, None);
+        assert_eq!(diff.floors.len(), 4);
+        assert_eq!(
+            diff.floors[0],
+            FloorDiff {
+                floor: 0,
+                only_left: vec![Component {
+                    gadget: Gadget::Microchip,
+                    partner_floor: Some(1),
+                }],
+                only_right: vec![Component {
+                    gadget: Gadget::Microchip,
+                    partner_floor: Some(3),
+                }],
+            }
+        );
+        assert_eq!(
+            diff.to_string().lines().next(),
+            Some("F1: left only: M[F2]; right only: M[F4]")
+        );
+    }
+}
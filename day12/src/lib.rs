@@ -1,27 +1,160 @@
-use aoclib::parse;
-use assembunny::{Computer, Instruction, Register};
+use aoc2016_solver::{Answer, Example, Registry, Solver};
+use assembunny::{Computer, ExecutionProfile, Instruction, Integer, Register};
 
 use std::path::Path;
 
-pub fn part1(input: &Path) -> Result<(), Error> {
-    let program: Vec<Instruction> = parse(input)?.collect();
-    let mut computer = Computer::from_program(program);
-    computer.run();
-    println!("value in a after termination: {}", computer[Register::A]);
-    Ok(())
+/// Parse the program in `input`.
+pub fn read_program(input: &Path) -> Result<Vec<Instruction>, Error> {
+    Ok(assembunny::read_program(input)?)
 }
 
-pub fn part2(input: &Path) -> Result<(), Error> {
-    let program: Vec<Instruction> = parse(input)?.collect();
-    let mut computer = Computer::from_program(program);
-    computer[Register::C] = 1;
-    computer.run();
-    println!("value in a after termination: {}", computer[Register::A]);
-    Ok(())
+/// Parse the program in the text `source`.
+pub fn parse_program(source: &str) -> Result<Vec<Instruction>, Error> {
+    Ok(assembunny::parse_instructions(source)?)
+}
+
+/// Run `program` to termination, with each register in `inits` set to its value beforehand.
+///
+/// Returns the final register file, in order `a`, `b`, `c`, `d`, or an error if a register
+/// overflows.
+pub fn run_with(
+    program: &[Instruction],
+    inits: &[(Register, Integer)],
+) -> Result<[Integer; 4], Error> {
+    Ok(execute(program, inits)?.registers())
+}
+
+fn execute(program: &[Instruction], inits: &[(Register, Integer)]) -> Result<Computer, Error> {
+    let mut computer = Computer::from_program(program.to_vec());
+    for &(register, value) in inits {
+        computer[register] = value;
+    }
+    computer.try_run_compiled()?;
+    Ok(computer)
+}
+
+/// Run `program` to termination as `run_with` does, also returning the work done to get there.
+pub fn profile_with(
+    program: &[Instruction],
+    inits: &[(Register, Integer)],
+) -> Result<([Integer; 4], ExecutionProfile), Error> {
+    let computer = execute(program, inits)?;
+    Ok((computer.registers(), computer.profile()))
+}
+
+/// The registers initialized for part 2.
+pub const PART2_INITS: &[(Register, Integer)] = &[(Register::C, 1)];
+
+/// The value in register `a` after the program terminates, and the work done to get there.
+pub fn part1(program: &[Instruction]) -> Result<(Integer, ExecutionProfile), Error> {
+    let ([a, ..], profile) = profile_with(program, &[])?;
+    Ok((a, profile))
+}
+
+/// The value in register `a` after the program terminates, with `c` initialized to 1, and the
+/// work done to get there.
+pub fn part2(program: &[Instruction]) -> Result<(Integer, ExecutionProfile), Error> {
+    let ([a, ..], profile) = profile_with(program, PART2_INITS)?;
+    Ok((a, profile))
+}
+
+/// The program from the puzzle text.
+const EXAMPLE: &str = "cpy 41 a\ninc a\ninc a\ndec a\njnz a 2\ndec a\n";
+
+const EXAMPLES: &[Example] = &[Example {
+    part: 1,
+    input: EXAMPLE,
+    answer: "42",
+}];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part1(&parse_program(input)?)?.0.into())
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part2(&parse_program(input)?)?.0.into())
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(12, Solution);
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Program(#[from] assembunny::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn program(text: &str) -> Vec<Instruction> {
+        text.lines()
+            .map(|line| line.trim().parse().unwrap())
+            .collect()
+    }
+
+    /// Copies `c` to `a`, then adds 10 to `a` unless `b` is 0.
+    const CONDITIONAL: &str = "cpy c a
+    jnz b 2
+    jnz 1 2
+    cpy 10 d
+    jnz d 2
+    jnz 1 4
+    inc a
+    dec d
+    jnz d -2";
+
+    /// `(program, inits, expected registers)`
+    type Case = (&'static str, &'static [(Register, Integer)], [Integer; 4]);
+
+    #[test]
+    fn test_profile_with() {
+        let program = program(CONDITIONAL);
+        let (registers, profile) = profile_with(&program, &[(Register::B, 1)]).unwrap();
+        assert_eq!(registers, [10, 1, 0, 0]);
+        let report = profile.report(&program, 3);
+        assert_eq!(report.instructions, 34);
+        assert_eq!(report.hot[0].instruction.to_string(), "inc a");
+        let hot: Vec<_> = report.hot.iter().map(|hot| (hot.ip, hot.count)).collect();
+        assert_eq!(hot, vec![(6, 10), (7, 10), (8, 10)]);
+    }
+
+    #[test]
+    fn test_run_with() {
+        let cases: &[Case] = &[
+            (EXAMPLE, &[], [42, 0, 0, 0]),
+            (
+                EXAMPLE,
+                &[(Register::A, 7), (Register::D, 3)],
+                [42, 0, 0, 3],
+            ),
+            (CONDITIONAL, &[], [0, 0, 0, 0]),
+            (CONDITIONAL, &[(Register::C, 5)], [5, 0, 5, 0]),
+            (
+                CONDITIONAL,
+                &[(Register::B, 1), (Register::C, 5)],
+                [15, 1, 5, 0],
+            ),
+        ];
+        for (text, inits, expect) in cases {
+            assert_eq!(
+                run_with(&program(text), inits).unwrap(),
+                *expect,
+                "inits: {:?}",
+                inits
+            );
+        }
+    }
 }
@@ -0,0 +1,21 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use day13::{Office, INITIAL, PART1_GOAL};
+
+// an arbitrary favorite number with a path from the initial position to the part 1 goal
+const FAVORITE_NUMBER: i32 = 1358;
+
+fn navigation(c: &mut Criterion) {
+    let office = Office::new(FAVORITE_NUMBER);
+
+    let mut group = c.benchmark_group("part1 goal");
+    group.bench_function("astar", |b| {
+        b.iter(|| office.shortest_path(INITIAL, PART1_GOAL))
+    });
+    group.bench_function("map navigate", |b| {
+        b.iter(|| office.map(64).navigate(INITIAL, PART1_GOAL))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, navigation);
+criterion_main!(benches);
@@ -0,0 +1,226 @@
+use std::{fmt, str::FromStr};
+
+/// Computes the magic number of each position, from which the office's walls are derived.
+///
+/// The favorite number is added afterwards; a position is a wall when the sum has an odd number
+/// of bits set. Closures taking `(x, y)` are formulas too.
+pub trait Formula {
+    fn magic(&self, x: i32, y: i32) -> i32;
+}
+
+impl<F: Fn(i32, i32) -> i32> Formula for F {
+    fn magic(&self, x: i32, y: i32) -> i32 {
+        self(x, y)
+    }
+}
+
+/// The designer's formula from the puzzle: `x*x + 3*x + 2*x*y + y + y*y`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Puzzle;
+
+impl Formula for Puzzle {
+    fn magic(&self, x: i32, y: i32) -> i32 {
+        x * x + 3 * x + 2 * x * y + y + y * y
+    }
+}
+
+/// A polynomial-ish expression in `x` and `y`, for experimenting with other mazes.
+///
+/// Parses from expressions such as `x*x + 3x + 2xy + y + y*y`: integers, `x`, `y`, `+`, `-`,
+/// `*`, `^` (xor), and parentheses, where adjacent factors are multiplied. Arithmetic wraps
+/// rather than overflowing.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum Expr {
+    X,
+    Y,
+    Const(i32),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// The puzzle's formula, as an expression.
+    pub fn puzzle() -> Expr {
+        "x*x + 3x + 2xy + y + y*y"
+            .parse()
+            .expect("the puzzle's formula is valid")
+    }
+
+    pub fn eval(&self, x: i32, y: i32) -> i32 {
+        match self {
+            Expr::X => x,
+            Expr::Y => y,
+            Expr::Const(n) => *n,
+            Expr::Add(a, b) => a.eval(x, y).wrapping_add(b.eval(x, y)),
+            Expr::Sub(a, b) => a.eval(x, y).wrapping_sub(b.eval(x, y)),
+            Expr::Mul(a, b) => a.eval(x, y).wrapping_mul(b.eval(x, y)),
+            Expr::Xor(a, b) => a.eval(x, y) ^ b.eval(x, y),
+        }
+    }
+}
+
+impl Formula for Expr {
+    fn magic(&self, x: i32, y: i32) -> i32 {
+        self.eval(x, y)
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::X => write!(f, "x"),
+            Expr::Y => write!(f, "y"),
+            Expr::Const(n) => write!(f, "{}", n),
+            Expr::Add(a, b) => write!(f, "({} + {})", a, b),
+            Expr::Sub(a, b) => write!(f, "({} - {})", a, b),
+            Expr::Mul(a, b) => write!(f, "{}*{}", a, b),
+            Expr::Xor(a, b) => write!(f, "({} ^ {})", a, b),
+        }
+    }
+}
+
+/// A recursive-descent parser over the bytes of an expression, ignoring whitespace.
+struct Parser<'a> {
+    source: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&mut self) -> Option<u8> {
+        while self.source.get(self.position) == Some(&b' ') {
+            self.position += 1;
+        }
+        self.source.get(self.position).copied()
+    }
+
+    fn error(&self) -> ParseError {
+        ParseError(self.position)
+    }
+
+    /// `xor := sum ('^' sum)*`
+    fn xor(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.sum()?;
+        while self.peek() == Some(b'^') {
+            self.position += 1;
+            expr = Expr::Xor(Box::new(expr), Box::new(self.sum()?));
+        }
+        Ok(expr)
+    }
+
+    /// `sum := product (('+' | '-') product)*`
+    fn sum(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.product()?;
+        loop {
+            match self.peek() {
+                Some(b'+') => {
+                    self.position += 1;
+                    expr = Expr::Add(Box::new(expr), Box::new(self.product()?));
+                }
+                Some(b'-') => {
+                    self.position += 1;
+                    expr = Expr::Sub(Box::new(expr), Box::new(self.product()?));
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    /// `product := factor ('*'? factor)*`
+    fn product(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.factor()?;
+        loop {
+            match self.peek() {
+                Some(b'*') => self.position += 1,
+                Some(b'x') | Some(b'y') | Some(b'(') => {}
+                Some(c) if c.is_ascii_digit() => {}
+                _ => return Ok(expr),
+            }
+            expr = Expr::Mul(Box::new(expr), Box::new(self.factor()?));
+        }
+    }
+
+    /// `factor := integer | 'x' | 'y' | '(' xor ')'`
+    fn factor(&mut self) -> Result<Expr, ParseError> {
+        match self.peek() {
+            Some(b'x') => {
+                self.position += 1;
+                Ok(Expr::X)
+            }
+            Some(b'y') => {
+                self.position += 1;
+                Ok(Expr::Y)
+            }
+            Some(b'(') => {
+                self.position += 1;
+                let expr = self.xor()?;
+                if self.peek() != Some(b')') {
+                    return Err(self.error());
+                }
+                self.position += 1;
+                Ok(expr)
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let start = self.position;
+                while matches!(self.source.get(self.position), Some(c) if c.is_ascii_digit()) {
+                    self.position += 1;
+                }
+                std::str::from_utf8(&self.source[start..self.position])
+                    .ok()
+                    .and_then(|digits| digits.parse().ok())
+                    .map(Expr::Const)
+                    .ok_or(ParseError(start))
+            }
+            _ => Err(self.error()),
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            source: s.as_bytes(),
+            position: 0,
+        };
+        let expr = parser.xor()?;
+        match parser.peek() {
+            None => Ok(expr),
+            Some(_) => Err(parser.error()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("invalid formula at byte {0}")]
+pub struct ParseError(pub usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_puzzle_expr_matches_puzzle() {
+        let expr = Expr::puzzle();
+        for y in 0..50 {
+            for x in 0..50 {
+                assert_eq!(expr.magic(x, y), Puzzle.magic(x, y), "at ({}, {})", x, y);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse() {
+        let expr: Expr = "2(x - 1)y ^ 7".parse().unwrap();
+        assert_eq!(expr.eval(3, 5), (2 * (3 - 1) * 5) ^ 7);
+        assert_eq!(expr.to_string(), "(2*(x - 1)*y ^ 7)");
+        assert_eq!(expr.to_string().parse::<Expr>().unwrap(), expr);
+
+        assert_eq!("x +".parse::<Expr>(), Err(ParseError(3)));
+        assert_eq!("(x".parse::<Expr>(), Err(ParseError(2)));
+        assert_eq!("x z".parse::<Expr>(), Err(ParseError(2)));
+        assert!("99999999999".parse::<Expr>().is_err());
+    }
+}
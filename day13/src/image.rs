@@ -0,0 +1,69 @@
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+pub type Rgb = [u8; 3];
+
+/// A simple RGB raster image.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// Create a black image of the given dimensions.
+    pub fn new(width: usize, height: usize) -> Image {
+        Image {
+            width,
+            height,
+            pixels: vec![[0; 3]; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The pixel at `(x, y)`, where `(0, 0)` is the top left.
+    ///
+    /// Panics if the pixel is out of bounds.
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        assert!(x < self.width && y < self.height, "pixel out of bounds");
+        self.pixels[y * self.width + x]
+    }
+
+    /// Set the pixel at `(x, y)`, where `(0, 0)` is the top left.
+    ///
+    /// Panics if the pixel is out of bounds.
+    pub fn set(&mut self, x: usize, y: usize, color: Rgb) {
+        assert!(x < self.width && y < self.height, "pixel out of bounds");
+        self.pixels[y * self.width + x] = color;
+    }
+
+    /// Enlarge this image so that each pixel becomes a `factor` by `factor` square.
+    pub fn scaled(&self, factor: usize) -> Image {
+        let mut out = Image::new(self.width * factor, self.height * factor);
+        for y in 0..out.height {
+            for x in 0..out.width {
+                out.set(x, y, self.get(x / factor, y / factor));
+            }
+        }
+        out
+    }
+
+    /// Write this image to `path` as a PNG.
+    #[cfg(feature = "fs")]
+    pub fn write_png(&self, path: &Path) -> Result<(), png::EncodingError> {
+        let writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let data: Vec<u8> = self.pixels.iter().flatten().copied().collect();
+        encoder.write_header()?.write_image_data(&data)
+    }
+}
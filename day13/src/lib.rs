@@ -1,17 +1,24 @@
-use aoclib::{
-    geometry::{
-        map::{ContextFrom, ContextInto, Map, Traversable},
-        Point,
-    },
-    parse,
+use aoc2016_solver::{each_line, Answer, Registry, Solver};
+use aoclib::geometry::{
+    map::{ContextFrom, Map, Traversable},
+    Direction, Point,
 };
+#[cfg(feature = "fs")]
+use aoclib::parse;
+#[cfg(feature = "fs")]
+use std::path::Path;
 use std::{
-    collections::{HashSet, VecDeque},
-    path::Path,
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
 };
 
+mod formula;
+mod image;
+pub use formula::{Expr, Formula, ParseError, Puzzle};
+pub use image::{Image, Rgb};
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
-struct Tile(bool);
+pub struct Tile(bool);
 
 impl ContextFrom<Tile> for Traversable {
     type Context = ();
@@ -24,68 +31,462 @@ impl ContextFrom<Tile> for Traversable {
     }
 }
 
-fn make_cubical_design(favorite_number: i32) -> impl Fn(Point) -> Tile {
-    move |Point { x, y }: Point| -> Tile {
-        let mut magic = x * x + 3 * x + 2 * x * y + y + y * y;
-        magic += favorite_number;
-        Tile(magic.count_ones() % 2 != 0)
-    }
+/// The office building, whose layout is determined by the designer's favorite number.
+///
+/// The building extends infinitely in the positive x and y directions; tiles are computed
+/// on demand.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Office<F = Puzzle> {
+    favorite_number: i32,
+    formula: F,
 }
 
-fn make_map(edge_size: usize, favorite_number: i32) -> Map<Tile> {
-    Map::procedural(edge_size, edge_size, make_cubical_design(favorite_number))
+impl Office {
+    pub fn new(favorite_number: i32) -> Office {
+        Office::with_formula(favorite_number, Puzzle)
+    }
 }
 
-const INITIAL: Point = Point::new(1, 1);
-const PART1_GOAL: Point = Point::new(31, 39);
+impl<F: Formula> Office<F> {
+    /// An office laid out by some other formula than the puzzle's.
+    pub fn with_formula(favorite_number: i32, formula: F) -> Office<F> {
+        Office {
+            favorite_number,
+            formula,
+        }
+    }
 
-pub fn part1(input: &Path) -> Result<(), Error> {
-    for favorite_number in parse::<i32>(input)? {
-        let map = make_map(64, favorite_number);
-        let path_len = map
-            .navigate(INITIAL, PART1_GOAL)
-            .ok_or(Error::NoPath(INITIAL, PART1_GOAL))?
-            .len();
-        println!("number of steps from initial to goal: {}", path_len);
+    pub fn tile(&self, Point { x, y }: Point) -> Tile {
+        let magic = self.formula.magic(x, y).wrapping_add(self.favorite_number);
+        Tile(magic.count_ones() % 2 != 0)
     }
-    Ok(())
-}
 
-pub fn part2(input: &Path) -> Result<(), Error> {
-    for favorite_number in parse::<i32>(input)? {
-        let map = make_map(64, favorite_number);
+    /// `true` if `point` is outside the building or a wall.
+    pub fn is_wall(&self, point: Point) -> bool {
+        point.x < 0 || point.y < 0 || self.tile(point).0
+    }
 
+    /// Compute a square map of the building, anchored at the origin.
+    pub fn map(&self, edge_size: usize) -> Map<Tile> {
+        Map::procedural(edge_size, edge_size, |point| self.tile(point))
+    }
+
+    /// Find a shortest path from `from` to `to`, using A* with the manhattan distance heuristic.
+    ///
+    /// Unlike `Map::navigate`, this never computes tiles it doesn't need to visit.
+    pub fn shortest_path(&self, from: Point, to: Point) -> Option<Vec<Direction>> {
+        let heuristic = |point: Point| (to - point).manhattan();
+
+        let mut came_from: HashMap<Point, Direction> = HashMap::new();
+        let mut cost = HashMap::new();
+        cost.insert(from, 0);
+        let mut open = BinaryHeap::new();
+        open.push((Reverse(heuristic(from)), from.x, from.y));
+
+        while let Some((_, x, y)) = open.pop() {
+            let position = Point::new(x, y);
+            if position == to {
+                let mut path = Vec::new();
+                let mut position = to;
+                while let Some(&direction) = came_from.get(&position) {
+                    path.push(direction);
+                    position += direction.reverse();
+                }
+                path.reverse();
+                return Some(path);
+            }
+
+            let steps = cost[&position] + 1;
+            for direction in Direction::iter() {
+                let next = position + direction;
+                if self.is_wall(next) || matches!(cost.get(&next), Some(&prev) if prev <= steps) {
+                    continue;
+                }
+                cost.insert(next, steps);
+                came_from.insert(next, direction);
+                open.push((Reverse(steps + heuristic(next)), next.x, next.y));
+            }
+        }
+
+        None
+    }
+
+    /// Compute the cumulative number of cells reachable from `start` at each step count.
+    ///
+    /// `curve[n]` is the number of distinct open cells reachable in at most `n` steps, so
+    /// `curve[0]` is `1` for an open `start`. The curve has `max_steps + 1` entries.
+    pub fn growth_curve(&self, start: Point, max_steps: usize) -> Vec<usize> {
+        let mut new_per_step = vec![0; max_steps + 1];
         let mut visited = HashSet::new();
-        visited.insert(INITIAL);
         let mut queue = VecDeque::new();
-        queue.push_front((0, INITIAL));
+        if !self.is_wall(start) {
+            visited.insert(start);
+            queue.push_back((0, start));
+        }
 
         while let Some((steps, position)) = queue.pop_front() {
-            if steps > 50 {
+            new_per_step[steps] += 1;
+            if steps == max_steps {
                 continue;
             }
+            for direction in Direction::iter() {
+                let next = position + direction;
+                if !self.is_wall(next) && visited.insert(next) {
+                    queue.push_back((steps + 1, next));
+                }
+            }
+        }
 
-            map.orthogonal_adjacencies(position)
-                .filter(|adj| {
-                    !visited.contains(adj) && {
-                        let traversable: Traversable = map[*adj].ctx_into(&());
-                        traversable == Traversable::Free
-                    }
-                })
-                .for_each(|adj| queue.push_back((steps + 1, adj)));
+        new_per_step
+            .into_iter()
+            .scan(0, |total, new| {
+                *total += new;
+                Some(*total)
+            })
+            .collect()
+    }
 
-            visited.insert(position);
+    /// Render the top-left `edge` by `edge` corner of the building, marking the shortest path
+    /// from `INITIAL` to `goal` with `O`.
+    ///
+    /// Walls are `#` and open space is `.`. As in the puzzle, `y` increases downwards.
+    /// Returns `None` if `goal` is unreachable.
+    pub fn render_path(&self, edge: usize, goal: Point) -> Option<String> {
+        let mut on_path = HashSet::new();
+        let mut position = INITIAL;
+        on_path.insert(position);
+        for direction in self.shortest_path(INITIAL, goal)? {
+            position += direction;
+            on_path.insert(position);
+        }
+
+        let mut out = String::with_capacity(edge * (edge + 1));
+        for y in 0..edge as i32 {
+            for x in 0..edge as i32 {
+                let point = Point::new(x, y);
+                out.push(if on_path.contains(&point) {
+                    'O'
+                } else if self.is_wall(point) {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            out.push('\n');
         }
+        Some(out)
+    }
+}
+
+const WALL_COLOR: Rgb = [0, 0, 0];
+const START_COLOR: Rgb = [255, 255, 255];
+const UNREACHABLE_COLOR: Rgb = [64, 64, 64];
+
+/// Colors for increasing distance from the start; intermediate distances are interpolated.
+const GRADIENT: &[Rgb] = &[[253, 231, 37], [53, 183, 121], [49, 104, 142], [68, 1, 84]];
+
+/// The color for a cell `fraction` of the way from the start to the farthest cell.
+fn gradient(fraction: f64) -> Rgb {
+    let scaled = fraction.clamp(0.0, 1.0) * (GRADIENT.len() - 1) as f64;
+    let low = (scaled.floor() as usize).min(GRADIENT.len() - 2);
+    let t = scaled - low as f64;
+    let mut color = [0; 3];
+    for (channel, (a, b)) in color
+        .iter_mut()
+        .zip(GRADIENT[low].iter().zip(GRADIENT[low + 1].iter()))
+    {
+        *channel = (*a as f64 + t * (*b as f64 - *a as f64)).round() as u8;
+    }
+    color
+}
+
+/// Render the `width` by `height` region of the building at the origin, with one pixel per cell.
+///
+/// Each open cell is colored by its distance from `start`, from yellow when near to purple at
+/// the farthest. Paths are confined to the region. The start is white, walls are black, and
+/// open cells which can't be reached are gray. As in the puzzle, `y` increases downwards.
+pub fn render_distance_field<F: Formula>(
+    office: &Office<F>,
+    (width, height): (usize, usize),
+    start: Point,
+) -> Image {
+    let in_bounds = |point: Point| {
+        !office.is_wall(point) && (point.x as usize) < width && (point.y as usize) < height
+    };
+
+    let mut distances = HashMap::new();
+    let mut queue = VecDeque::new();
+    if in_bounds(start) {
+        distances.insert(start, 0);
+        queue.push_back(start);
+    }
+    while let Some(position) = queue.pop_front() {
+        let steps = distances[&position] + 1;
+        for direction in Direction::iter() {
+            let next = position + direction;
+            if in_bounds(next) && !distances.contains_key(&next) {
+                distances.insert(next, steps);
+                queue.push_back(next);
+            }
+        }
+    }
+    let max_distance = distances.values().copied().max().unwrap_or_default().max(1);
+
+    let mut image = Image::new(width, height);
+    for y in 0..height {
+        for x in 0..width {
+            let point = Point::new(x as i32, y as i32);
+            let color = if point == start && in_bounds(point) {
+                START_COLOR
+            } else if office.is_wall(point) {
+                WALL_COLOR
+            } else {
+                match distances.get(&point) {
+                    Some(&distance) => gradient(distance as f64 / max_distance as f64),
+                    None => UNREACHABLE_COLOR,
+                }
+            };
+            image.set(x, y, color);
+        }
+    }
+    image
+}
+
+/// Each cell of a distance field PNG is a square this many pixels wide.
+pub const PNG_CELL_SIZE: usize = 8;
 
-        println!("reachable positions in 50 steps: {}", visited.len());
+/// Write the distance field of the `edge` by `edge` region at the origin from `INITIAL` as a
+/// PNG, for each favorite number in the input, laying out the office with `formula`.
+///
+/// With several inputs, each image's path is suffixed with its favorite number.
+#[cfg(feature = "fs")]
+pub fn write_distance_field(
+    input: &Path,
+    edge: usize,
+    path: &Path,
+    formula: &(impl Formula + Clone),
+) -> Result<(), Error> {
+    let favorite_numbers: Vec<i32> = parse(input)?.collect();
+    for &favorite_number in &favorite_numbers {
+        let path = if favorite_numbers.len() == 1 {
+            path.to_owned()
+        } else {
+            let mut name = path.file_stem().unwrap_or_default().to_owned();
+            name.push(format!("-{}.png", favorite_number));
+            path.with_file_name(name)
+        };
+        render_distance_field(
+            &Office::with_formula(favorite_number, formula.clone()),
+            (edge, edge),
+            INITIAL,
+        )
+        .scaled(PNG_CELL_SIZE)
+        .write_png(&path)?;
+        println!("wrote {}", path.display());
     }
     Ok(())
 }
 
+pub const INITIAL: Point = Point::new(1, 1);
+pub const PART1_GOAL: Point = Point::new(31, 39);
+
+/// The length of the shortest path to the goal, for each favorite number in the input.
+#[cfg(feature = "fs")]
+pub fn part1(input: &Path) -> Result<Vec<usize>, Error> {
+    part1_from_str(&std::fs::read_to_string(input)?)
+}
+
+/// The length of the shortest path to the goal, for each favorite number in `input`.
+pub fn part1_from_str(input: &str) -> Result<Vec<usize>, Error> {
+    part1_with_formula(input, &Puzzle)
+}
+
+/// As `part1_from_str`, but laying out the office with `formula`.
+pub fn part1_with_formula(
+    input: &str,
+    formula: &(impl Formula + Clone),
+) -> Result<Vec<usize>, Error> {
+    aoclib::input::parse_str::<i32>(input)?
+        .map(|favorite_number| {
+            Office::with_formula(favorite_number, formula.clone())
+                .shortest_path(INITIAL, PART1_GOAL)
+                .map(|path| path.len())
+                .ok_or(Error::NoPath(INITIAL, PART1_GOAL))
+        })
+        .collect()
+}
+
+pub const PART2_STEPS: usize = 50;
+
+/// The number of positions reachable within `PART2_STEPS`, for each favorite number in the input.
+#[cfg(feature = "fs")]
+pub fn part2(input: &Path) -> Result<Vec<usize>, Error> {
+    part2_from_str(&std::fs::read_to_string(input)?)
+}
+
+/// The number of positions reachable within `PART2_STEPS`, for each favorite number in `input`.
+pub fn part2_from_str(input: &str) -> Result<Vec<usize>, Error> {
+    part2_with_formula(input, &Puzzle)
+}
+
+/// As `part2_from_str`, but laying out the office with `formula`.
+pub fn part2_with_formula(
+    input: &str,
+    formula: &(impl Formula + Clone),
+) -> Result<Vec<usize>, Error> {
+    Ok(aoclib::input::parse_str::<i32>(input)?
+        .map(|favorite_number| {
+            Office::with_formula(favorite_number, formula.clone())
+                .growth_curve(INITIAL, PART2_STEPS)[PART2_STEPS]
+        })
+        .collect())
+}
+
+/// Print the number of reachable positions at each step count, one per line, laying out the
+/// office with `formula`.
+#[cfg(feature = "fs")]
+pub fn print_growth_curve(
+    input: &Path,
+    max_steps: usize,
+    formula: &(impl Formula + Clone),
+) -> Result<(), Error> {
+    for favorite_number in parse::<i32>(input)? {
+        let curve =
+            Office::with_formula(favorite_number, formula.clone()).growth_curve(INITIAL, max_steps);
+        for (steps, reachable) in curve.iter().enumerate() {
+            println!("{}\t{}", steps, reachable);
+        }
+    }
+    Ok(())
+}
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        each_line(part1_from_str(input)?, Ok)
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        each_line(part2_from_str(input)?, Ok)
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(13, Solution);
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
     #[error("no path found from {0:?} to {1:?}")]
     NoPath(Point, Point),
+    #[error("writing png")]
+    Png(#[from] png::EncodingError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        let office = Office::new(10);
+        let goal = Point::new(7, 4);
+        let path = office.shortest_path(INITIAL, goal).unwrap();
+        assert_eq!(path.len(), 11);
+
+        let mut position = INITIAL;
+        for direction in path {
+            position += direction;
+            assert!(!office.is_wall(position));
+        }
+        assert_eq!(position, goal);
+    }
+
+    #[test]
+    fn test_matches_navigate() {
+        let office = Office::new(10);
+        let map = office.map(32);
+        for goal in map.points().filter(|&point| !office.is_wall(point)) {
+            assert_eq!(
+                office.shortest_path(INITIAL, goal).map(|path| path.len()),
+                map.navigate(INITIAL, goal).map(|path| path.len()),
+                "path lengths to {:?} differ",
+                goal,
+            );
+        }
+    }
+
+    #[test]
+    fn test_distance_field() {
+        let office = Office::new(10);
+        let image = render_distance_field(&office, (10, 7), INITIAL);
+        assert_eq!((image.width(), image.height()), (10, 7));
+        assert_eq!(image.get(1, 1), START_COLOR);
+        assert_eq!(image.get(0, 0), gradient(2.0 / 11.0));
+        for y in 0..7 {
+            for x in 0..10 {
+                if office.is_wall(Point::new(x as i32, y as i32)) {
+                    assert_eq!(image.get(x, y), WALL_COLOR);
+                }
+            }
+        }
+        // the goal of the example is 11 steps away, which is as far as the region goes
+        assert_eq!(image.get(7, 4), gradient(1.0));
+
+        let scaled = image.scaled(3);
+        assert_eq!((scaled.width(), scaled.height()), (30, 21));
+        assert_eq!(scaled.get(5, 4), START_COLOR);
+    }
+
+    #[test]
+    fn test_formulas() {
+        let puzzle = Office::new(10);
+        let expr = Office::with_formula(10, Expr::puzzle());
+        let closure =
+            Office::with_formula(10, |x: i32, y: i32| x * x + 3 * x + 2 * x * y + y + y * y);
+        let map = puzzle.map(32);
+        assert_eq!(expr.map(32), map);
+        assert_eq!(closure.map(32), map);
+
+        // with favorite number 1, the main diagonal of an xor of the coordinates is all walls
+        let xor = Office::with_formula(1, |x: i32, y: i32| x ^ y);
+        assert!((0..10).all(|n| xor.is_wall(Point::new(n, n))));
+        assert!(!xor.is_wall(Point::new(1, 3)));
+        assert_eq!(
+            part1_with_formula("1358", &Expr::puzzle()).unwrap(),
+            part1_from_str("1358").unwrap()
+        );
+        assert_eq!(
+            part2_with_formula("10", &Expr::puzzle()).unwrap(),
+            part2_from_str("10").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_growth_curve_matches_shortest_path() {
+        let office = Office::new(10);
+        let max_steps = 12;
+        let curve = office.growth_curve(INITIAL, max_steps);
+        assert_eq!(curve.len(), max_steps + 1);
+        assert_eq!(curve[0], 1);
+
+        let edge = (INITIAL.x as usize) + max_steps + 1;
+        let map = office.map(edge);
+        for (steps, &reachable) in curve.iter().enumerate() {
+            let expect = map
+                .points()
+                .filter(|&point| {
+                    !office.is_wall(point)
+                        && matches!(
+                            office.shortest_path(INITIAL, point),
+                            Some(path) if path.len() <= steps
+                        )
+                })
+                .count();
+            assert_eq!(reachable, expect, "reachable in {} steps", steps);
+        }
+    }
 }
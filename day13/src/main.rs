@@ -1,19 +1,25 @@
-use aoclib::{config::Config, website::get_input};
-use day13::{part1, part2};
+use aoc2016_solver::Input;
+use day13::{
+    part1_with_formula, part2_with_formula, print_growth_curve, write_distance_field, Expr,
+    PART2_STEPS,
+};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 13;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -21,33 +27,51 @@ struct RunArgs {
     /// run part 2
     #[structopt(long)]
     part2: bool,
-}
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
-    }
+    /// print the number of reachable positions for each step count up to this many steps
+    #[structopt(long)]
+    growth_curve: Option<usize>,
+
+    /// write a PNG of each open cell's distance from the start to this path
+    #[structopt(long, parse(from_os_str))]
+    distance_png: Option<PathBuf>,
+
+    /// size of the region drawn by --distance-png
+    #[structopt(long, default_value = "50")]
+    png_edge: usize,
+
+    /// lay out the office with this formula in x and y instead of the puzzle's,
+    /// e.g. "x*x + 3x + 2xy + y + y*y"
+    #[structopt(long)]
+    formula: Option<Expr>,
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
+    let formula = args.formula.clone().unwrap_or_else(Expr::puzzle);
 
     if !args.no_part1 {
-        part1(&input_path)?;
+        let input = std::fs::read_to_string(&*input_path)?;
+        for steps in part1_with_formula(&input, &formula)? {
+            println!("number of steps from initial to goal: {}", steps);
+        }
     }
     if args.part2 {
-        part2(&input_path)?;
+        let input = std::fs::read_to_string(&*input_path)?;
+        for reachable in part2_with_formula(&input, &formula)? {
+            println!(
+                "reachable positions in {} steps: {}",
+                PART2_STEPS, reachable
+            );
+        }
+    }
+    if let Some(max_steps) = args.growth_curve {
+        print_growth_curve(&input_path, max_steps, &formula)?;
+    }
+    if let Some(ref path) = args.distance_png {
+        write_distance_field(&input_path, args.png_edge, path, &formula)?;
     }
     Ok(())
 }
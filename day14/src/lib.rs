@@ -2,14 +2,24 @@
 //!
 //! Note: part2 is slow, consider testing in release mode.
 
+use aoc2016_solver::{each_line, Answer, Example, Progress, Registry, Silent, Solver};
 use aoclib::parse;
 use crypto::{digest::Digest, md5::Md5};
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::VecDeque,
+    collections::{BTreeMap, VecDeque},
+    fmt,
+    io::{BufRead, Write},
     ops::{Index, IndexMut},
     path::Path,
 };
 
+/// A triplet is a key if a matching quintuplet appears within this many subsequent hashes.
+const WINDOW: usize = 1000;
+
+/// The number of keys in a onetime pad.
+const PAD_LEN: usize = 64;
+
 /// `State` keeps track of potential keys.
 ///
 /// A key is added to the potential keys at a certain position
@@ -69,7 +79,7 @@ impl State {
         for queue in self.0.iter_mut() {
             while queue
                 .front()
-                .map(|&insert_idx| idx - insert_idx > 1000)
+                .map(|&insert_idx| idx - insert_idx > WINDOW)
                 .unwrap_or_default()
             {
                 queue.pop_front();
@@ -151,54 +161,567 @@ fn quintuplets_in(hash: &str) -> impl '_ + Iterator<Item = char> {
         .map(|window| window[0] as char)
 }
 
-/// Generate a onetime pad using the specified hash-maker.
+/// Generate the 64 keys of a onetime pad using the specified hash-maker.
+///
+/// Each key is `(insert, key)`, where `insert` is the index of its triplet. Keys are sorted by index.
+///
+/// Keys activate in the order of their quintuplets, not their triplets, so the first 64 keys
+/// to activate are not necessarily the first 64 keys. When `strict`, scanning continues until
+/// no pending triplet could sort before the 64th key. Otherwise, scanning stops as soon as 64
+/// keys have activated, which is faster but may produce the wrong pad.
 ///
-/// Return the pad and the index which produced its 64th character.
-fn generate_onetime_pad(make_hash: impl Fn(usize) -> String) -> (String, usize) {
+/// The number of keys activated so far is reported to `progress`.
+///
+/// Returns `None` if `make_hash` runs out of hashes before the search ends.
+fn generate_keys(
+    mut make_hash: impl FnMut(usize) -> Option<String>,
+    strict: bool,
+    progress: &dyn Progress,
+) -> Option<Vec<(usize, char)>> {
+    progress.set_total(PAD_LEN as u64);
     let mut state = State::default();
-    let mut keys = Vec::with_capacity(64);
+    let mut keys: Vec<(usize, char)> = Vec::with_capacity(PAD_LEN);
 
     let mut idx = 0;
-    while keys.len() < 64 {
-        let hash = make_hash(idx);
+    loop {
+        if keys.len() >= PAD_LEN {
+            if !strict {
+                keys.truncate(PAD_LEN);
+                break;
+            }
+            // a triplet still pending at `idx` was inserted after `idx - WINDOW`
+            keys.sort_unstable();
+            keys.truncate(PAD_LEN);
+            if idx > keys[PAD_LEN - 1].0 + WINDOW {
+                break;
+            }
+        }
+        let hash = make_hash(idx)?;
         keys.extend(state.update(idx, first_triplet_in(&hash), quintuplets_in(&hash)));
+        progress.update(keys.len().min(PAD_LEN) as u64);
         idx += 1;
     }
 
-    keys.truncate(64);
     keys.sort_unstable();
-    let (final_insert, _) = *keys.last().unwrap();
-    let pad = keys.into_iter().map(|(_, key)| key).collect();
+    Some(keys)
+}
+
+/// A single key of a onetime pad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Key {
+    /// The index of the hash containing this key's triplet.
+    pub index: usize,
+    pub ch: char,
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "'{}' at {}", self.ch, self.index)
+    }
+}
+
+/// A onetime pad: 64 keys, sorted by index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pad {
+    keys: Vec<Key>,
+}
 
-    (pad, final_insert)
+/// A position at which two pads disagree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyDiff {
+    pub position: usize,
+    pub ours: Option<Key>,
+    pub theirs: Option<Key>,
 }
 
-pub fn part1(input: &Path, show_pad: bool) -> Result<(), Error> {
+impl fmt::Display for KeyDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show =
+            |key: Option<Key>| key.map_or_else(|| "nothing".to_string(), |key| key.to_string());
+        write!(
+            f,
+            "key {:>2}: {} vs {}",
+            self.position,
+            show(self.ours),
+            show(self.theirs)
+        )
+    }
+}
+
+impl Pad {
+    fn from_keys(keys: Vec<(usize, char)>) -> Pad {
+        let keys = keys
+            .into_iter()
+            .map(|(index, ch)| Key { index, ch })
+            .collect();
+        Pad { keys }
+    }
+
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    /// The index which produced the final key of this pad.
+    pub fn final_index(&self) -> Option<usize> {
+        self.keys.last().map(|key| key.index)
+    }
+
+    /// Every position at which this pad and `other` have different keys.
+    pub fn diff(&self, other: &Pad) -> Vec<KeyDiff> {
+        (0..self.keys.len().max(other.keys.len()))
+            .filter_map(|position| {
+                let ours = self.keys.get(position).copied();
+                let theirs = other.keys.get(position).copied();
+                (ours != theirs).then_some(KeyDiff {
+                    position,
+                    ours,
+                    theirs,
+                })
+            })
+            .collect()
+    }
+}
+
+/// The pad's characters, in order.
+impl fmt::Display for Pad {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.keys.iter().try_for_each(|key| write!(f, "{}", key.ch))
+    }
+}
+
+/// Generate a onetime pad using the specified hash-maker.
+pub fn generate_onetime_pad(make_hash: impl Fn(usize) -> String) -> Pad {
+    generate_onetime_pad_with(make_hash, true)
+}
+
+/// Generate a onetime pad using the specified hash-maker.
+///
+/// Unless `strict`, stop as soon as 64 keys have activated. This skips scanning the final
+/// 1000 hashes, but may miss keys whose triplets precede the 64th key's.
+pub fn generate_onetime_pad_with(make_hash: impl Fn(usize) -> String, strict: bool) -> Pad {
+    generate_onetime_pad_reporting(make_hash, strict, &Silent)
+}
+
+/// Generate a onetime pad as `generate_onetime_pad_with`, reporting the number of keys
+/// activated so far to `progress`.
+pub fn generate_onetime_pad_reporting(
+    make_hash: impl Fn(usize) -> String,
+    strict: bool,
+    progress: &dyn Progress,
+) -> Pad {
+    let keys = generate_keys(|idx| Some(make_hash(idx)), strict, progress)
+        .expect("hash functions never run out of hashes");
+    Pad::from_keys(keys)
+}
+
+/// The length of an MD5 digest, in bytes.
+const DIGEST_LEN: usize = 16;
+
+/// Identifies a hash log, and guards against replaying some other file.
+const LOG_MAGIC: &str = "day14-hash-log";
+
+/// The hashes consumed by a strict search for a salt's pad, so that the search can be replayed
+/// without computing any MD5.
+///
+/// The hash of each index is stored in order from 0, so the index is implicit. Only the hashes
+/// up to the index at which the search stopped are stored, as raw digests rather than hex, which
+/// keeps a stretched log to a few hundred kilobytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashLog {
+    pub salt: String,
+    pub stretched: bool,
+    digests: Vec<[u8; DIGEST_LEN]>,
+}
+
+fn to_digest(hash: &str) -> [u8; DIGEST_LEN] {
+    let mut digest = [0; DIGEST_LEN];
+    for (idx, byte) in digest.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hash[2 * idx..2 * idx + 2], 16)
+            .expect("md5 produces hexadecimal hashes");
+    }
+    digest
+}
+
+fn to_hash(digest: &[u8; DIGEST_LEN]) -> String {
+    digest.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+impl HashLog {
+    /// Search for the pad of `salt`, recording each hash consumed.
+    pub fn record(salt: &str, stretched: bool, progress: &dyn Progress) -> (HashLog, Pad) {
+        let make_hash: Box<dyn Fn(usize) -> String> = if stretched {
+            Box::new(make_stretched_hash_for(salt))
+        } else {
+            Box::new(make_hash_for(salt))
+        };
+        let mut digests = Vec::new();
+        let keys = generate_keys(
+            |idx| {
+                let hash = make_hash(idx);
+                digests.push(to_digest(&hash));
+                Some(hash)
+            },
+            true,
+            progress,
+        )
+        .expect("hash functions never run out of hashes");
+        let log = HashLog {
+            salt: salt.to_owned(),
+            stretched,
+            digests,
+        };
+        (log, Pad::from_keys(keys))
+    }
+
+    /// The number of hashes recorded.
+    pub fn len(&self) -> usize {
+        self.digests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.digests.is_empty()
+    }
+
+    /// Search for the pad again, using the recorded hashes instead of computing them.
+    ///
+    /// Fails if the search needs more hashes than were recorded, for example if it was changed
+    /// to scan further.
+    pub fn replay(&self, strict: bool) -> Result<Pad, Error> {
+        let keys = generate_keys(|idx| self.digests.get(idx).map(to_hash), strict, &Silent)
+            .ok_or(Error::LogExhausted(self.len()))?;
+        Ok(Pad::from_keys(keys))
+    }
+
+    /// Write this log: a header line, then each raw digest.
+    pub fn write(&self, writer: &mut impl Write) -> Result<(), Error> {
+        writeln!(
+            writer,
+            "{} {} {} {}",
+            LOG_MAGIC,
+            self.stretched,
+            self.digests.len(),
+            self.salt
+        )?;
+        for digest in &self.digests {
+            writer.write_all(digest)?;
+        }
+        Ok(())
+    }
+
+    /// Read a log written by `write`, or `None` at the end of the input.
+    pub fn read(reader: &mut impl BufRead) -> Result<Option<HashLog>, Error> {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Ok(None);
+        }
+        let malformed = || Error::MalformedLog(header.trim_end().to_owned());
+        let mut fields = header.trim_end_matches('\n').splitn(4, ' ');
+        if fields.next() != Some(LOG_MAGIC) {
+            return Err(malformed());
+        }
+        let stretched = fields
+            .next()
+            .and_then(|field| field.parse().ok())
+            .ok_or_else(malformed)?;
+        let len: usize = fields
+            .next()
+            .and_then(|field| field.parse().ok())
+            .ok_or_else(malformed)?;
+        let salt = fields.next().ok_or_else(malformed)?.to_owned();
+
+        // the header's length isn't trusted for allocation: digests are only kept as they are read
+        let mut digests = Vec::new();
+        for _ in 0..len {
+            let mut digest = [0; DIGEST_LEN];
+            reader.read_exact(&mut digest).map_err(|err| {
+                if err.kind() == std::io::ErrorKind::UnexpectedEof {
+                    Error::TruncatedLog {
+                        expected: len,
+                        found: digests.len(),
+                    }
+                } else {
+                    err.into()
+                }
+            })?;
+            digests.push(digest);
+        }
+        Ok(Some(HashLog {
+            salt,
+            stretched,
+            digests,
+        }))
+    }
+}
+
+/// Record the hashes searched for each salt in the input, stretched or not, to `path`.
+pub fn record_hashes(input: &Path, stretched: bool, path: &Path) -> Result<(), Error> {
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
     for salt in parse::<String>(input)? {
-        let (pad, idx) = generate_onetime_pad(make_hash_for(&salt));
-        println!("salt {}: generates at idx {}", salt, idx);
-        if show_pad {
-            println!("  pad: {}", pad);
+        let (log, pad) = HashLog::record(&salt, stretched, &Silent);
+        log.write(&mut writer)?;
+        println!(
+            "salt {}: recorded {} hashes; final key at idx {}",
+            salt,
+            log.len(),
+            pad.final_index().ok_or(Error::EmptyPad)?
+        );
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Replay each log recorded to `path`, without computing any hashes.
+pub fn replay_hashes(path: &Path, strict: bool) -> Result<(), Error> {
+    let mut reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    while let Some(log) = HashLog::read(&mut reader)? {
+        let pad = log.replay(strict)?;
+        println!(
+            "salt {}{}: replayed {} hashes; generates at idx {}",
+            log.salt,
+            if log.stretched { " (stretched)" } else { "" },
+            log.len(),
+            pad.final_index().ok_or(Error::EmptyPad)?
+        );
+    }
+    Ok(())
+}
+
+/// Statistics about the repeated characters produced by a hash function.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HashStats {
+    /// Number of hashes examined.
+    pub hashes: usize,
+    /// Per hex digit, the number of hashes whose first triplet is of that digit.
+    pub triples: [usize; 16],
+    /// Per hex digit, the number of hashes containing at least one quintuplet of that digit.
+    pub quintuples: [usize; 16],
+    /// The differences between the indices of consecutive keys of the generated pad.
+    pub key_gaps: Vec<usize>,
+}
+
+const HEX_DIGITS: &str = "0123456789abcdef";
+
+impl HashStats {
+    /// Render the triple and quintuple counts as a histogram, one row per hex digit.
+    pub fn histogram(&self) -> String {
+        const BAR_WIDTH: usize = 50;
+        let scale =
+            |count: usize, max: usize| (count * BAR_WIDTH).checked_div(max).unwrap_or_default();
+
+        let mut out = String::new();
+        for (title, counts) in &[("triples", &self.triples), ("quintuples", &self.quintuples)] {
+            let max = counts.iter().copied().max().unwrap_or_default();
+            out.push_str(&format!("{} over {} hashes:\n", title, self.hashes));
+            for (digit, &count) in HEX_DIGITS.chars().zip(counts.iter()) {
+                out.push_str(&format!(
+                    "  {} {:>6} {}\n",
+                    digit,
+                    count,
+                    "#".repeat(scale(count, max))
+                ));
+            }
+        }
+
+        let min_gap = self.key_gaps.iter().min().copied().unwrap_or_default();
+        let max_gap = self.key_gaps.iter().max().copied().unwrap_or_default();
+        let mean_gap =
+            self.key_gaps.iter().sum::<usize>() as f64 / self.key_gaps.len().max(1) as f64;
+        out.push_str(&format!(
+            "key gaps: min {}, mean {:.1}, max {}\n",
+            min_gap, mean_gap, max_gap
+        ));
+        out
+    }
+}
+
+/// Analyze the first `n` hashes produced by `make_hash`, and the pad it generates.
+pub fn analyze(make_hash: impl Fn(usize) -> String, n: usize) -> HashStats {
+    let mut stats = HashStats {
+        hashes: n,
+        ..HashStats::default()
+    };
+
+    for idx in 0..n {
+        let hash = make_hash(idx);
+        if let Some(triplet) = first_triplet_in(&hash) {
+            stats.triples[State::idx_for(triplet)] += 1;
+        }
+        let mut seen = [false; 16];
+        for quintuplet in quintuplets_in(&hash) {
+            seen[State::idx_for(quintuplet)] = true;
+        }
+        for (count, seen) in stats.quintuples.iter_mut().zip(seen.iter()) {
+            *count += *seen as usize;
         }
     }
+
+    stats.key_gaps = generate_keys(|idx| Some(make_hash(idx)), true, &Silent)
+        .expect("hash functions never run out of hashes")
+        .windows(2)
+        .map(|window| window[1].0 - window[0].0)
+        .collect();
+
+    stats
+}
+
+/// Analyze the first `n` hashes for a given salt, and the pad it generates.
+pub fn analyze_salt(salt: &str, n: usize) -> HashStats {
+    analyze(make_hash_for(salt), n)
+}
+
+/// Print statistics about the hashes for each salt in the input.
+pub fn print_analysis(input: &Path, n: usize, stretched: bool) -> Result<(), Error> {
+    for salt in parse::<String>(input)? {
+        let stats = if stretched {
+            analyze(make_stretched_hash_for(&salt), n)
+        } else {
+            analyze_salt(&salt, n)
+        };
+        println!("salt {}:\n{}", salt, stats.histogram());
+    }
+    Ok(())
+}
+
+fn make_pad(salt: &str, stretched: bool, strict: bool, progress: &dyn Progress) -> Pad {
+    if stretched {
+        generate_onetime_pad_reporting(make_stretched_hash_for(salt), strict, progress)
+    } else {
+        generate_onetime_pad_reporting(make_hash_for(salt), strict, progress)
+    }
+}
+
+/// The onetime pad generated by each salt in the input.
+pub fn part1(input: &Path, strict: bool, progress: &dyn Progress) -> Result<Vec<Pad>, Error> {
+    part1_from_str(&std::fs::read_to_string(input)?, strict, progress)
+}
+
+/// The onetime pad generated by each salt in `input`.
+pub fn part1_from_str(
+    input: &str,
+    strict: bool,
+    progress: &dyn Progress,
+) -> Result<Vec<Pad>, Error> {
+    Ok(aoclib::input::parse_str::<String>(input)?
+        .map(|salt| make_pad(&salt, false, strict, progress))
+        .collect())
+}
+
+/// The onetime pad generated by each salt in the input, with stretched hashes.
+pub fn part2(input: &Path, strict: bool, progress: &dyn Progress) -> Result<Vec<Pad>, Error> {
+    part2_from_str(&std::fs::read_to_string(input)?, strict, progress)
+}
+
+/// The onetime pad generated by each salt in `input`, with stretched hashes.
+pub fn part2_from_str(
+    input: &str,
+    strict: bool,
+    progress: &dyn Progress,
+) -> Result<Vec<Pad>, Error> {
+    Ok(aoclib::input::parse_str::<String>(input)?
+        .map(|salt| make_pad(&salt, true, strict, progress))
+        .collect())
+}
+
+/// Write the pad for each salt in the input to `path`, as a JSON object keyed by salt.
+pub fn export_pads(input: &Path, stretched: bool, path: &Path) -> Result<(), Error> {
+    let pads: BTreeMap<_, _> = parse::<String>(input)?
+        .map(|salt| {
+            let pad = make_pad(&salt, stretched, true, &Silent);
+            (salt, pad)
+        })
+        .collect();
+    let writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    serde_json::to_writer_pretty(writer, &pads)?;
+    println!("exported {} pad(s) to {}", pads.len(), path.display());
     Ok(())
 }
 
-pub fn part2(input: &Path, show_pad: bool) -> Result<(), Error> {
+/// Compare the pad for each salt in the input with those previously exported to `path`.
+pub fn compare_pads(input: &Path, stretched: bool, path: &Path) -> Result<(), Error> {
+    let reader = std::io::BufReader::new(std::fs::File::open(path)?);
+    let exported: BTreeMap<String, Pad> = serde_json::from_reader(reader)?;
     for salt in parse::<String>(input)? {
-        let (pad, idx) = generate_onetime_pad(make_stretched_hash_for(&salt));
-        println!("salt {}: generates (stretched) at idx {}", salt, idx);
-        if show_pad {
-            println!("  pad: {}", pad);
+        let theirs = match exported.get(&salt) {
+            Some(pad) => pad,
+            None => {
+                println!("salt {}: no exported pad", salt);
+                continue;
+            }
+        };
+        let diff = make_pad(&salt, stretched, true, &Silent).diff(theirs);
+        if diff.is_empty() {
+            println!("salt {}: pads agree", salt);
+        } else {
+            println!("salt {}: pads disagree at {} key(s):", salt, diff.len());
+            for key_diff in diff {
+                println!("  {}", key_diff);
+            }
         }
     }
     Ok(())
 }
 
+const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: "abc",
+        answer: "22728",
+    },
+    Example {
+        part: 2,
+        input: "abc",
+        answer: "22551",
+    },
+];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        self.solve_reporting(1, input, &Silent)
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        self.solve_reporting(2, input, &Silent)
+    }
+
+    fn solve_reporting(
+        &self,
+        part: u8,
+        input: &str,
+        progress: &dyn Progress,
+    ) -> aoc2016_solver::Result<Answer> {
+        let pads = match part {
+            1 => part1_from_str(input, true, progress)?,
+            2 => part2_from_str(input, true, progress)?,
+            _ => return self.solve(part, input),
+        };
+        each_line(pads, |pad| Ok(pad.final_index().ok_or(Error::EmptyPad)?))
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(14, Solution);
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error("pad has no keys")]
+    EmptyPad,
+    #[error("reading or writing pads")]
+    Json(#[from] serde_json::Error),
+    #[error("malformed hash log header: {0:?}")]
+    MalformedLog(String),
+    #[error("hash log ran out after {0} hashes")]
+    LogExhausted(usize),
+    #[error("hash log header promises {expected} hashes, but only {found} follow")]
+    TruncatedLog { expected: usize, found: usize },
 }
 
 #[cfg(test)]
@@ -328,17 +851,140 @@ mod tests {
         assert!(has_e(quintuplets_in(&hash)));
     }
 
+    #[test]
+    fn analyze_example() {
+        let stats = analyze_salt("abc", 40);
+        assert_eq!(stats.hashes, 40);
+        assert_eq!(stats.triples.iter().sum::<usize>(), 2);
+        assert_eq!(stats.triples[State::idx_for('8')], 1);
+        assert_eq!(stats.triples[State::idx_for('e')], 1);
+        assert_eq!(stats.key_gaps.len(), 63);
+        assert_eq!(stats.key_gaps.iter().sum::<usize>(), 22728 - 39);
+    }
+
     #[test]
     fn full_example() {
-        let (pad, idx) = generate_onetime_pad(make_hash_for("abc"));
-        dbg!(pad);
-        assert_eq!(idx, 22728);
+        let pad = generate_onetime_pad(make_hash_for("abc"));
+        dbg!(pad.to_string());
+        assert_eq!(pad.final_index(), Some(22728));
+    }
+
+    #[test]
+    fn hash_log_round_trip() {
+        let (log, pad) = HashLog::record("abc", false, &Silent);
+        assert_eq!(pad.final_index(), Some(22728));
+        // strict searches scan one window beyond the final key
+        assert_eq!(log.len(), 22728 + WINDOW + 1);
+
+        let mut buffer = Vec::new();
+        log.write(&mut buffer).unwrap();
+        log.write(&mut buffer).unwrap();
+        let mut reader = std::io::Cursor::new(buffer);
+        let read = HashLog::read(&mut reader).unwrap().unwrap();
+        assert_eq!(read, log);
+        assert_eq!(HashLog::read(&mut reader).unwrap().unwrap(), log);
+        assert!(HashLog::read(&mut reader).unwrap().is_none());
+
+        assert_eq!(read.replay(true).unwrap(), pad);
+        assert_eq!(read.replay(false).unwrap().final_index(), Some(22728));
+    }
+
+    #[test]
+    fn hash_log_exhausted() {
+        let (mut log, _) = HashLog::record("abc", false, &Silent);
+        log.digests.truncate(1000);
+        assert!(matches!(log.replay(true), Err(Error::LogExhausted(1000))));
+
+        let mut reader = std::io::Cursor::new(b"not a log\n".to_vec());
+        assert!(matches!(
+            HashLog::read(&mut reader),
+            Err(Error::MalformedLog(_))
+        ));
+
+        // a header may not make the reader allocate more than the file holds
+        let mut buffer = format!("{} false {} abc\n", LOG_MAGIC, usize::MAX).into_bytes();
+        buffer.extend_from_slice(&[0; 3 * DIGEST_LEN + 1]);
+        let mut reader = std::io::Cursor::new(buffer);
+        assert!(matches!(
+            HashLog::read(&mut reader),
+            Err(Error::TruncatedLog {
+                expected: usize::MAX,
+                found: 3,
+            })
+        ));
+    }
+
+    /// The hashes of a strict stretched search for the example salt, recorded by
+    /// `day14 --part2 --record-hashes`.
+    const STRETCHED_LOG: &[u8] = include_bytes!("../fixtures/abc-stretched.log");
+
+    #[test]
+    fn stretched_hash_log_fixture() {
+        let mut reader = std::io::Cursor::new(STRETCHED_LOG);
+        let log = HashLog::read(&mut reader).unwrap().unwrap();
+        assert!(HashLog::read(&mut reader).unwrap().is_none());
+        assert_eq!(log.salt, "abc");
+        assert!(log.stretched);
+        assert_eq!(log.len(), 22551 + WINDOW + 1);
+
+        assert_eq!(log.replay(true).unwrap().final_index(), Some(22551));
+        assert_eq!(log.replay(false).unwrap().final_index(), Some(22551));
+        // the log holds the stretched hashes themselves
+        assert_eq!(to_hash(&log.digests[0]), make_stretched_hash_for("abc")(0));
     }
 
     #[test]
     fn full_stretched_example() {
-        let (pad, idx) = generate_onetime_pad(make_stretched_hash_for("abc"));
-        dbg!(pad);
-        assert_eq!(idx, 22551);
+        let pad = generate_onetime_pad(make_stretched_hash_for("abc"));
+        dbg!(pad.to_string());
+        assert_eq!(pad.final_index(), Some(22551));
+    }
+
+    /// A key whose quintuplet comes late must still be found, even once 64 later keys have
+    /// activated.
+    #[test]
+    fn late_activation() {
+        let make_hash = |idx: usize| {
+            match idx {
+                0 => "111",
+                1..=64 => "aaa",
+                65 => "aaaaa",
+                999 => "11111",
+                _ => "",
+            }
+            .to_owned()
+                + "0123456789abcdef"
+        };
+
+        let lax = generate_onetime_pad_with(make_hash, false);
+        assert_eq!(lax.keys().len(), 64);
+        assert_eq!(lax.keys()[0], Key { index: 1, ch: 'a' });
+        assert_eq!(lax.final_index(), Some(64));
+
+        let strict = generate_onetime_pad_with(make_hash, true);
+        assert_eq!(strict.keys().len(), 64);
+        assert_eq!(strict.keys()[0], Key { index: 0, ch: '1' });
+        assert_eq!(strict.final_index(), Some(63));
+    }
+
+    #[test]
+    fn pad_roundtrip_and_diff() {
+        let pad = generate_onetime_pad(make_hash_for("abc"));
+        assert_eq!(pad.keys().len(), 64);
+        assert_eq!(pad.keys()[0], Key { index: 39, ch: 'e' });
+
+        let json = serde_json::to_string(&pad).unwrap();
+        let restored: Pad = serde_json::from_str(&json).unwrap();
+        assert!(pad.diff(&restored).is_empty());
+
+        let mut altered = restored;
+        altered.keys[3].ch = 'x';
+        altered.keys.pop();
+        let diff = pad.diff(&altered);
+        assert_eq!(
+            diff.iter().map(|d| d.position).collect::<Vec<_>>(),
+            vec![3, 63]
+        );
+        assert_eq!(diff[1].theirs, None);
     }
 }
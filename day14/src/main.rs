@@ -1,19 +1,25 @@
-use aoclib::{config::Config, website::get_input};
-use day14::{part1, part2};
+use aoc2016_solver::{Input, TerminalProgress};
+use day14::{
+    compare_pads, export_pads, part1, part2, print_analysis, record_hashes, replay_hashes, Error,
+    Pad,
+};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 14;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -24,33 +30,80 @@ struct RunArgs {
 
     #[structopt(long)]
     show_pad: bool,
+
+    /// stop as soon as 64 keys activate, without checking for earlier keys which activate later
+    #[structopt(long)]
+    lax: bool,
+
+    /// print statistics about the distribution of triples and quintuples over this many hashes
+    #[structopt(long)]
+    analyze: Option<usize>,
+
+    /// write the pads to this path as JSON; stretched with --part2
+    #[structopt(long, parse(from_os_str))]
+    export_pad: Option<PathBuf>,
+
+    /// compare the pads with those previously exported to this path; stretched with --part2
+    #[structopt(long, parse(from_os_str))]
+    compare_pad: Option<PathBuf>,
+
+    /// record the hashes each search consumes to this path; stretched with --part2
+    #[structopt(long, parse(from_os_str))]
+    record_hashes: Option<PathBuf>,
+
+    /// replay hashes previously recorded to this path, instead of computing them
+    #[structopt(long, parse(from_os_str))]
+    replay_hashes: Option<PathBuf>,
 }
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
+fn show(pad: &Pad, stretched: bool, show_pad: bool) -> Result<()> {
+    let idx = pad.final_index().ok_or(Error::EmptyPad)?;
+    println!(
+        "generates{} at idx {}",
+        if stretched { " (stretched)" } else { "" },
+        idx
+    );
+    if show_pad {
+        println!("  pad: {}", pad);
     }
+    Ok(())
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
 
+    if let Some(n) = args.analyze {
+        print_analysis(&input_path, n, args.part2)?;
+    }
+    if let Some(ref path) = args.export_pad {
+        export_pads(&input_path, args.part2, path)?;
+    }
+    if let Some(ref path) = args.compare_pad {
+        compare_pads(&input_path, args.part2, path)?;
+    }
+    if let Some(ref path) = args.record_hashes {
+        record_hashes(&input_path, args.part2, path)?;
+    }
+    if let Some(ref path) = args.replay_hashes {
+        replay_hashes(path, !args.lax)?;
+    }
     if !args.no_part1 {
-        part1(&input_path, args.show_pad)?;
+        let progress = TerminalProgress::new("keys");
+        let pads = part1(&input_path, !args.lax, &progress)?;
+        progress.finish();
+        for pad in pads {
+            show(&pad, false, args.show_pad)?;
+        }
     }
     if args.part2 {
-        part2(&input_path, args.show_pad)?;
+        let progress = TerminalProgress::new("keys (stretched)");
+        let pads = part2(&input_path, !args.lax, &progress)?;
+        progress.finish();
+        for pad in pads {
+            show(&pad, true, args.show_pad)?;
+        }
     }
     Ok(())
 }
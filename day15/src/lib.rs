@@ -1,6 +1,7 @@
+use aoc2016_solver::{Answer, Example, Registry, Solver};
 use aoclib::{
+    input::parse_str,
     numbers::chinese_remainder::{chinese_remainder, Constraint},
-    parse,
 };
 
 use std::path::Path;
@@ -44,46 +45,88 @@ impl Disc {
     }
 }
 
-fn when_discs_line_up(discs: &[Disc]) -> Option<i32> {
-    let constraints: Vec<_> = discs
+/// The first time at which a capsule dropped then falls through every disc.
+///
+/// The solution is computed modulo the product of the discs' sizes, so fails with
+/// `Error::Overflow` if that product doesn't fit in an `i32`.
+fn when_discs_line_up(discs: &[Disc]) -> Result<i32, Error> {
+    let product = discs
+        .iter()
+        .try_fold(1_i32, |product, disc| product.checked_mul(disc.positions))
+        .ok_or(Error::Overflow)?;
+    let constraints = discs
         .iter()
         .enumerate()
         .map(|(idx, disc)| {
-            let time = idx as i32;
-            Constraint::new(disc.positions, -disc.initial - time)
+            let offset = (idx as i32)
+                .checked_add(disc.initial)
+                .ok_or(Error::Overflow)?;
+            Ok(Constraint::new(disc.positions, -offset))
         })
-        .collect();
-    let product: i32 = discs.iter().map(|disc| disc.positions).product();
-    chinese_remainder(&constraints).map(|mut solution| {
-        // subtract 1 for initial fall time
-        solution -= 1;
-        while solution < 0 {
-            solution += product;
-        }
-        solution
-    })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let mut solution = chinese_remainder(&constraints).ok_or(Error::NoSolution)?;
+    // subtract 1 for initial fall time
+    solution -= 1;
+    while solution < 0 {
+        solution += product;
+    }
+    Ok(solution)
+}
+
+/// The first time at which a capsule falls through every disc.
+pub fn part1(input: &Path) -> Result<i32, Error> {
+    part1_from_str(&std::fs::read_to_string(input)?)
+}
+
+/// The first time at which a capsule falls through every disc described in `input`.
+pub fn part1_from_str(input: &str) -> Result<i32, Error> {
+    let discs: Vec<Disc> = parse_str(input)?.collect();
+    when_discs_line_up(&discs)
 }
 
-pub fn part1(input: &Path) -> Result<(), Error> {
-    let discs: Vec<Disc> = parse(input)?.collect();
-    println!(
-        "discs first line up at time {}",
-        when_discs_line_up(&discs).ok_or(Error::NoSolution)?
-    );
-    Ok(())
+/// The first time at which a capsule falls through every disc, including the extra one.
+pub fn part2(input: &Path) -> Result<i32, Error> {
+    part2_from_str(&std::fs::read_to_string(input)?)
 }
 
-pub fn part2(input: &Path) -> Result<(), Error> {
-    let mut discs: Vec<Disc> = parse(input)?.collect();
+/// The first time at which a capsule falls through every disc described in `input`, including
+/// the extra one.
+pub fn part2_from_str(input: &str) -> Result<i32, Error> {
+    let mut discs: Vec<Disc> = parse_str(input)?.collect();
     discs.push(Disc {
         positions: 11,
         initial: 0,
     });
-    println!(
-        "discs first line up at time {}",
-        when_discs_line_up(&discs).ok_or(Error::NoSolution)?
-    );
-    Ok(())
+    when_discs_line_up(&discs)
+}
+
+/// The discs from the puzzle text.
+const EXAMPLE: &str = "Disc #1 has 5 positions; at time=0, it is at position 4.\nDisc #2 has 2 positions; at time=0, it is at position 1.\n";
+
+const EXAMPLES: &[Example] = &[Example {
+    part: 1,
+    input: EXAMPLE,
+    answer: "5",
+}];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part1_from_str(input)?.into())
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part2_from_str(input)?.into())
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(15, Solution);
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -92,17 +135,16 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("no solution found")]
     NoSolution,
+    #[error("disc arithmetic overflows i32")]
+    Overflow,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const EXAMPLE: &str = "Disc #1 has 5 positions; at time=0, it is at position 4.
-    Disc #2 has 2 positions; at time=0, it is at position 1.";
-
     fn example() -> Vec<Disc> {
-        aoclib::input::parse_str(EXAMPLE).unwrap().collect()
+        parse_str(EXAMPLE).unwrap().collect()
     }
 
     #[test]
@@ -116,6 +158,21 @@ mod tests {
         assert_eq!(when_discs_line_up(&discs).unwrap(), 5);
     }
 
+    #[test]
+    fn test_overflow() {
+        let discs = vec![
+            Disc {
+                positions: 65_537,
+                initial: 0,
+            },
+            Disc {
+                positions: 65_539,
+                initial: 0,
+            },
+        ];
+        assert!(matches!(when_discs_line_up(&discs), Err(Error::Overflow)));
+    }
+
     #[test]
     fn test_at() {
         let discs = example();
@@ -1,7 +1,11 @@
+use aoc2016_core::dragon;
+use aoc2016_solver::{each_line, Answer, Registry, Solver};
 use aoclib::parse;
 
 use std::{
     fmt,
+    fs::File,
+    io::{self, BufWriter, Read, Write},
     ops::{Deref, DerefMut},
     path::Path,
     str::FromStr,
@@ -54,74 +58,147 @@ impl DerefMut for Data {
 }
 
 impl Data {
-    fn dragon(&self) -> Self {
-        let mut next = self.clone();
-        next.reserve(1 + self.len());
-        next.push(false);
-        for bit in self.iter().rev() {
-            next.push(!*bit);
-        }
-        next
-    }
-
     fn dragon_fill(&self, want_bits: usize) -> Self {
-        let mut dragon = self.clone();
-        while dragon.len() < want_bits {
-            dragon = dragon.dragon();
-        }
-        dragon.truncate(want_bits);
-        dragon
+        Self(dragon::dragon_fill(self, want_bits))
     }
 
     fn checksum(&self) -> Self {
-        let mut data = self.0.clone();
-        let mut next = Vec::with_capacity(data.len());
-
-        while data.len() % 2 == 0 {
-            next.clear();
-            for pair in data.chunks(2) {
-                next.push(pair[0] == pair[1]);
-            }
-            std::mem::swap(&mut data, &mut next);
-        }
+        Self(dragon::checksum(self))
+    }
 
-        Self(data)
+    fn checksum_with_block(&self, block: usize) -> Result<Self, Error> {
+        dragon::checksum_with_block(self, block)
+            .map(Self)
+            .ok_or(Error::BlockSize {
+                block,
+                len: self.len(),
+            })
     }
 }
 
 const PART1_SIZE: usize = 272;
 const PART2_SIZE: usize = 35651584;
 
-pub fn part1(input: &Path) -> Result<(), Error> {
+/// Files of disk data are read and written in chunks of this many bytes.
+const FILE_CHUNK: usize = 64 * 1024;
+
+/// The checksum of the data filling the first disk, for each initial state in the input.
+pub fn part1(input: &Path) -> Result<Vec<String>, Error> {
+    part1_from_str(&std::fs::read_to_string(input)?)
+}
+
+/// The checksum of the data filling the first disk, for each initial state in `input`.
+pub fn part1_from_str(input: &str) -> Result<Vec<String>, Error> {
+    Ok(aoclib::input::parse_str::<Data>(input)?
+        .map(|initial_state| initial_state.dragon_fill(PART1_SIZE).checksum().to_string())
+        .collect())
+}
+
+/// The checksum of the data filling the second disk, for each initial state in the input.
+pub fn part2(input: &Path) -> Result<Vec<String>, Error> {
+    part2_from_str(&std::fs::read_to_string(input)?)
+}
+
+/// The checksum of the data filling the second disk, for each initial state in `input`.
+pub fn part2_from_str(input: &str) -> Result<Vec<String>, Error> {
+    Ok(aoclib::input::parse_str::<Data>(input)?
+        .map(|initial_state| initial_state.dragon_fill(PART2_SIZE).checksum().to_string())
+        .collect())
+}
+
+/// Fill the disk for part 1, then checksum it by reducing blocks of `block` bits.
+///
+/// The standard checksum uses the largest power of two dividing the disk size; other block
+/// sizes are available for experimentation, but must divide the disk size.
+pub fn checksum_with_block(input: &Path, block: usize) -> Result<(), Error> {
     for initial_state in parse::<Data>(input)? {
         let filled = initial_state.dragon_fill(PART1_SIZE);
-        let checksum = filled.checksum();
+        let checksum = filled.checksum_with_block(block)?;
         println!(
-            "Given {}, size {}, checksum is {}",
-            initial_state, PART1_SIZE, checksum
+            "Given {}, size {}, block {}, checksum is {}",
+            initial_state, PART1_SIZE, block, checksum
         );
     }
     Ok(())
 }
 
-pub fn part2(input: &Path) -> Result<(), Error> {
-    for initial_state in parse::<Data>(input)? {
-        let filled = initial_state.dragon_fill(PART2_SIZE);
-        let checksum = filled.checksum();
-        println!(
-            "Given {}, size {}, checksum is {}",
-            initial_state, PART2_SIZE, checksum
-        );
+/// Write the dragon fill of `size` bits, from the first initial state in `input`, to `path` as
+/// ASCII `0`s and `1`s.
+///
+/// The fill is generated as it is written, so `size` may far exceed the available memory.
+pub fn write_fill(input: &Path, size: usize, path: &Path) -> Result<(), Error> {
+    let initial_state = parse::<Data>(input)?.next().ok_or(Error::NoInput)?;
+    let mut writer = BufWriter::with_capacity(FILE_CHUNK, File::create(path)?);
+    for bit in dragon::dragon_bits(&initial_state, size) {
+        writer.write_all(if bit { b"1" } else { b"0" })?;
     }
+    writer.flush()?;
     Ok(())
 }
 
+/// Call `f` with each bit of a file of ASCII `0`s and `1`s, ignoring whitespace.
+fn for_each_bit(path: &Path, mut f: impl FnMut(bool)) -> Result<(), Error> {
+    let mut file = File::open(path)?;
+    let mut buffer = vec![0; FILE_CHUNK];
+    loop {
+        let n = match file.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err.into()),
+        };
+        for &byte in &buffer[..n] {
+            match byte {
+                b'0' => f(false),
+                b'1' => f(true),
+                _ if byte.is_ascii_whitespace() => {}
+                _ => return Err(Error::UnexpectedChar(byte as char)),
+            }
+        }
+    }
+}
+
+/// The checksum of a file of ASCII `0`s and `1`s, such as one written by `write_fill`.
+///
+/// The file is read twice, a chunk at a time: once to learn its length, and again to compute the
+/// checksum. Only the checksum itself is held in memory.
+pub fn checksum_file(path: &Path) -> Result<String, Error> {
+    let mut len = 0;
+    for_each_bit(path, |_| len += 1)?;
+    let mut checksummer = dragon::Checksummer::new(len);
+    for_each_bit(path, |bit| checksummer.push(bit))?;
+    let checksum = checksummer.finish().ok_or(Error::FileChanged)?;
+    Ok(Data(checksum).to_string())
+}
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        each_line(part1_from_str(input)?, Ok)
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        each_line(part2_from_str(input)?, Ok)
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(16, Solution);
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
     #[error("unexpected char '{0}'")]
     UnexpectedChar(char),
+    #[error("block size {block} does not divide data length {len}")]
+    BlockSize { block: usize, len: usize },
+    #[error("no initial state in input")]
+    NoInput,
+    #[error("file changed while computing its checksum")]
+    FileChanged,
 }
 
 #[cfg(test)]
@@ -137,8 +214,72 @@ mod test {
             ("111100001010", "1111000010100101011110000"),
         ]) {
             let data = Data::from_str(init).unwrap();
-            let data = data.dragon();
+            let data = Data(dragon::dragon(&data));
             assert_eq!(data.to_string(), want);
         }
     }
+
+    #[test]
+    fn test_checksum_with_block() {
+        let data = Data::from_str("110010110100").unwrap();
+        assert_eq!(data.checksum().to_string(), "100");
+        let standard = dragon::standard_block(data.len());
+        assert_eq!(standard, 4);
+        assert_eq!(
+            data.checksum_with_block(standard).unwrap().to_string(),
+            "100"
+        );
+        assert_eq!(data.checksum_with_block(2).unwrap().to_string(), "110101");
+        assert_eq!(data.checksum_with_block(12).unwrap().to_string(), "1");
+        assert!(matches!(
+            data.checksum_with_block(5),
+            Err(Error::BlockSize { block: 5, len: 12 })
+        ));
+        assert!(data.checksum_with_block(0).is_err());
+    }
+
+    #[test]
+    fn test_standard_block_matches_checksum() {
+        let data = Data::from_str("10000").unwrap().dragon_fill(PART1_SIZE);
+        let block = dragon::standard_block(PART1_SIZE);
+        assert_eq!(
+            data.checksum_with_block(block).unwrap().to_string(),
+            data.checksum().to_string()
+        );
+    }
+
+    #[test]
+    fn test_dragon_bits() {
+        for (init, want_bits) in [("1", 40), ("10000", 20), ("110", 300), ("", 17)] {
+            let data = Data::from_str(init).unwrap();
+            let streamed: Vec<bool> = dragon::dragon_bits(&data, want_bits).collect();
+            assert_eq!(streamed, dragon::dragon_fill(&data, want_bits), "{}", init);
+        }
+    }
+
+    #[test]
+    fn test_checksum_file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("day16-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let input = dir.join("input.txt");
+        let disk = dir.join("disk.txt");
+        std::fs::write(&input, "10000\n").unwrap();
+
+        for size in [20, 272, 1 << 16, 3 * 17] {
+            write_fill(&input, size, &disk).unwrap();
+            let expect = Data::from_str("10000")
+                .unwrap()
+                .dragon_fill(size)
+                .checksum();
+            assert_eq!(
+                checksum_file(&disk).unwrap(),
+                expect.to_string(),
+                "size {}",
+                size
+            );
+        }
+        assert_eq!(checksum_file(&disk).unwrap().len(), 51);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -1,19 +1,22 @@
-use aoclib::{config::Config, website::get_input};
-use day16::{part1, part2};
+use aoc2016_solver::Input;
+use day16::{checksum_file, checksum_with_block, part1, part2, write_fill};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 16;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -21,33 +24,48 @@ struct RunArgs {
     /// run part 2
     #[structopt(long)]
     part2: bool,
-}
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
-    }
+    /// checksum the part 1 disk by reducing blocks of this many bits
+    #[structopt(long)]
+    block: Option<usize>,
+
+    /// write the disk filled from the input to this path as ASCII '0's and '1's
+    #[structopt(long, parse(from_os_str))]
+    write_fill: Option<PathBuf>,
+
+    /// number of bits to write with --write-fill
+    #[structopt(long, default_value = "35651584")]
+    fill_size: usize,
+
+    /// print the checksum of a file of ASCII '0's and '1's, then exit
+    #[structopt(long, parse(from_os_str))]
+    checksum_file: Option<PathBuf>,
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    if let Some(ref path) = args.checksum_file {
+        println!("checksum: {}", checksum_file(path)?);
+        return Ok(());
+    }
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
 
     if !args.no_part1 {
-        part1(&input_path)?;
+        for checksum in part1(&input_path)? {
+            println!("checksum: {}", checksum);
+        }
     }
     if args.part2 {
-        part2(&input_path)?;
+        for checksum in part2(&input_path)? {
+            println!("checksum: {}", checksum);
+        }
+    }
+    if let Some(block) = args.block {
+        checksum_with_block(&input_path, block)?;
+    }
+    if let Some(ref path) = args.write_fill {
+        write_fill(&input_path, args.fill_size, path)?;
     }
     Ok(())
 }
@@ -1,3 +1,4 @@
+use aoc2016_solver::{each_line, Answer, Example, Registry, Solver};
 use aoclib::{
     geometry::{Direction, Point},
     parse,
@@ -5,7 +6,7 @@ use aoclib::{
 use crypto::{digest::Digest, md5::Md5};
 
 use std::{
-    collections::VecDeque,
+    collections::{BTreeMap, VecDeque},
     ops::{Index, IndexMut},
     path::Path,
     rc::Rc,
@@ -202,30 +203,251 @@ fn find_longest_path_to(
     max_path_len
 }
 
-pub fn part1(input: &Path) -> Result<(), Error> {
+/// Maximum number of states `path_stats` will explore before giving up.
+pub const PATH_STATS_CUTOFF: usize = 10_000_000;
+
+/// Statistics over every valid path to the vault.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PathStats {
+    /// Number of distinct paths reaching the vault.
+    pub count: usize,
+    pub min_len: Option<usize>,
+    pub max_len: Option<usize>,
+    /// Map of path length to the number of paths of that length.
+    pub histogram: BTreeMap<usize, usize>,
+}
+
+impl PathStats {
+    fn record(&mut self, len: usize) {
+        self.count += 1;
+        self.min_len = Some(self.min_len.map_or(len, |min| min.min(len)));
+        self.max_len = Some(self.max_len.map_or(len, |max| max.max(len)));
+        *self.histogram.entry(len).or_default() += 1;
+    }
+}
+
+/// Compute statistics over every valid path to the vault for this passcode.
+///
+/// The number of paths can grow exponentially, so this gives up with `Error::Cutoff` after
+/// exploring `PATH_STATS_CUTOFF` states.
+pub fn path_stats(passcode: &str) -> Result<PathStats, Error> {
+    path_stats_with_cutoff(passcode, PATH_STATS_CUTOFF)
+}
+
+/// Compute statistics over every valid path to the vault, exploring at most `cutoff` states.
+pub fn path_stats_with_cutoff(passcode: &str, cutoff: usize) -> Result<PathStats, Error> {
+    let get_room_status = make_get_room_status(passcode);
+    let goal = MAP.bottom_right();
+
+    let mut queue = VecDeque::new();
+    queue.push_front(State::new(MAP.top_left()));
+    let mut explored = 0;
+    let mut stats = PathStats::default();
+
+    while let Some(state) = queue.pop_front() {
+        explored += 1;
+        if explored > cutoff {
+            return Err(Error::Cutoff(cutoff));
+        }
+
+        // as in part 2, reaching the vault ends a path
+        if state.position == goal {
+            stats.record(state.path_to().len());
+            continue;
+        }
+
+        queue.extend(state.children(&get_room_status));
+    }
+
+    Ok(stats)
+}
+
+/// Print statistics over all paths to the vault for each passcode in the input.
+pub fn print_path_stats(input: &Path) -> Result<(), Error> {
     for passcode in parse::<String>(input)? {
-        let get_room_status = make_get_room_status(&passcode);
-        let path = breadth_first_search(MAP.top_left(), MAP.bottom_right(), get_room_status)
-            .ok_or(Error::NotFound)?;
-        println!("shortest path to goal: {}", path);
+        let stats = path_stats(&passcode)?;
+        println!("{}: {} paths to goal", passcode, stats.count);
+        if let (Some(min), Some(max)) = (stats.min_len, stats.max_len) {
+            println!("  lengths: {}..={}", min, max);
+        }
+        for (len, count) in &stats.histogram {
+            println!("  {:>5}: {}", len, count);
+        }
     }
     Ok(())
 }
 
-pub fn part2(input: &Path) -> Result<(), Error> {
+/// Everything the searches can tell about a passcode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasscodeReport {
+    pub passcode: String,
+    /// The shortest path to the vault, as for part 1.
+    pub shortest: Option<String>,
+    /// The length of the longest path to the vault, as for part 2.
+    pub longest: Option<usize>,
+    /// Number of distinct paths reaching the vault.
+    pub paths: usize,
+}
+
+impl PasscodeReport {
+    /// Whether any path reaches the vault at all.
+    pub fn reachable(&self) -> bool {
+        self.shortest.is_some()
+    }
+}
+
+/// Analyze a passcode: its shortest and longest paths, and how many paths reach the vault.
+///
+/// Like `path_stats`, this gives up with `Error::Cutoff` for passcodes with too many paths.
+pub fn analyze_passcode(passcode: &str) -> Result<PasscodeReport, Error> {
+    let stats = path_stats(passcode)?;
+    let shortest = breadth_first_search(
+        MAP.top_left(),
+        MAP.bottom_right(),
+        make_get_room_status(passcode),
+    );
+    debug_assert_eq!(shortest.as_ref().map(String::len), stats.min_len);
+    Ok(PasscodeReport {
+        passcode: passcode.to_string(),
+        shortest,
+        longest: stats.max_len,
+        paths: stats.count,
+    })
+}
+
+/// Print an analysis of each passcode in the input.
+pub fn print_analysis(input: &Path) -> Result<(), Error> {
     for passcode in parse::<String>(input)? {
-        let get_room_status = make_get_room_status(&passcode);
-        let path_len = find_longest_path_to(MAP.top_left(), MAP.bottom_right(), get_room_status)
-            .ok_or(Error::NotFound)?;
-        println!("longest path to goal: {}", path_len);
+        let report = analyze_passcode(&passcode)?;
+        if !report.reachable() {
+            println!("{}: vault unreachable", passcode);
+            continue;
+        }
+        println!("{}: {} paths to goal", passcode, report.paths);
+        if let Some(shortest) = &report.shortest {
+            println!("  shortest: {} ({} steps)", shortest, shortest.len());
+        }
+        if let Some(longest) = report.longest {
+            println!("  longest:  {} steps", longest);
+        }
     }
     Ok(())
 }
 
+/// The shortest path to the vault, for each passcode in the input.
+pub fn part1(input: &Path) -> Result<Vec<String>, Error> {
+    part1_from_str(&std::fs::read_to_string(input)?)
+}
+
+/// The shortest path to the vault, for each passcode in `input`.
+pub fn part1_from_str(input: &str) -> Result<Vec<String>, Error> {
+    aoclib::input::parse_str::<String>(input)?
+        .map(|passcode| {
+            let get_room_status = make_get_room_status(&passcode);
+            breadth_first_search(MAP.top_left(), MAP.bottom_right(), get_room_status)
+                .ok_or(Error::NotFound)
+        })
+        .collect()
+}
+
+/// The length of the longest path to the vault, for each passcode in the input.
+pub fn part2(input: &Path) -> Result<Vec<usize>, Error> {
+    part2_from_str(&std::fs::read_to_string(input)?)
+}
+
+/// The length of the longest path to the vault, for each passcode in `input`.
+pub fn part2_from_str(input: &str) -> Result<Vec<usize>, Error> {
+    aoclib::input::parse_str::<String>(input)?
+        .map(|passcode| {
+            let get_room_status = make_get_room_status(&passcode);
+            find_longest_path_to(MAP.top_left(), MAP.bottom_right(), get_room_status)
+                .ok_or(Error::NotFound)
+        })
+        .collect()
+}
+
+const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: "ihgpwlah\nkglvqrro\nulqzkmiv\n",
+        answer: "DDRRRD\nDDUDRLRRUDRD\nDRURDRUDDLLDLUURRDULRLDUUDDDRR",
+    },
+    Example {
+        part: 2,
+        input: "ihgpwlah\nkglvqrro\nulqzkmiv\n",
+        answer: "370\n492\n830",
+    },
+];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        each_line(part1_from_str(input)?, Ok)
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        each_line(part2_from_str(input)?, Ok)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(17, Solution);
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
     #[error("could not find a path to the goal")]
     NotFound,
+    #[error("gave up after exploring {0} states")]
+    Cutoff(usize),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_stats_example() {
+        let stats = path_stats("ihgpwlah").unwrap();
+        assert_eq!(stats.min_len, Some(6));
+        assert_eq!(stats.max_len, Some(370));
+        assert_eq!(stats.histogram.values().sum::<usize>(), stats.count);
+        assert_eq!(stats.histogram[&6], 1);
+    }
+
+    #[test]
+    fn test_path_stats_cutoff() {
+        assert!(matches!(
+            path_stats_with_cutoff("ihgpwlah", 100),
+            Err(Error::Cutoff(100))
+        ));
+    }
+
+    #[test]
+    fn test_analyze_passcode() {
+        let report = analyze_passcode("kglvqrro").unwrap();
+        assert!(report.reachable());
+        assert_eq!(report.shortest.as_deref(), Some("DDUDRLRRUDRD"));
+        assert_eq!(report.longest, Some(492));
+        assert_eq!(report.paths, path_stats("kglvqrro").unwrap().count);
+
+        let report = analyze_passcode("hijkl").unwrap();
+        assert!(!report.reachable());
+        assert_eq!(report.longest, None);
+        assert_eq!(report.paths, 0);
+    }
+
+    #[test]
+    fn test_path_stats_no_path() {
+        // the example passcode with no way out of the first room
+        let stats = path_stats("hijkl").unwrap();
+        assert_eq!(stats, PathStats::default());
+    }
 }
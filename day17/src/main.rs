@@ -1,19 +1,22 @@
-use aoclib::{config::Config, website::get_input};
-use day17::{part1, part2};
+use aoc2016_solver::Input;
+use day17::{part1, part2, print_analysis, print_path_stats};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 17;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -21,33 +24,36 @@ struct RunArgs {
     /// run part 2
     #[structopt(long)]
     part2: bool,
-}
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
-    }
+    /// print statistics over all paths to the goal
+    #[structopt(long)]
+    stats: bool,
+
+    /// print an analysis of each passcode: its shortest and longest paths and how many there are
+    #[structopt(short, long)]
+    verbose: bool,
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
 
     if !args.no_part1 {
-        part1(&input_path)?;
+        for path in part1(&input_path)? {
+            println!("shortest path to goal: {}", path);
+        }
     }
     if args.part2 {
-        part2(&input_path)?;
+        for path_len in part2(&input_path)? {
+            println!("longest path to goal: {}", path_len);
+        }
+    }
+    if args.stats {
+        print_path_stats(&input_path)?;
+    }
+    if args.verbose {
+        print_analysis(&input_path)?;
     }
     Ok(())
 }
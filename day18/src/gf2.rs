@@ -0,0 +1,234 @@
+//! Jump straight to any row of the room, by matrix exponentiation over GF(2).
+//!
+//! A tile is a trap exactly when the tiles to its upper left and upper right differ, so each
+//! row is a linear function of the previous one over GF(2): `next[i] = prev[i-1] ^ prev[i+1]`,
+//! where tiles beyond the walls are safe. Raising the matrix of that function to the `k`th
+//! power computes row `k` in `O(w³ log k)` time, instead of the `O(wk)` of generating every row
+//! in between.
+//!
+//! This is experimental: it makes queries like row 10¹² practical, and cross-checks the
+//! row-by-row generator.
+
+use super::{tiles_from_str, Error, Tile};
+
+/// A set of column indices, one bit per column.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BitRow(Vec<u64>);
+
+impl BitRow {
+    fn zero(width: usize) -> BitRow {
+        BitRow(vec![0; width.div_ceil(64)])
+    }
+
+    fn get(&self, idx: usize) -> bool {
+        self.0[idx / 64] & (1 << (idx % 64)) != 0
+    }
+
+    fn set(&mut self, idx: usize) {
+        self.0[idx / 64] |= 1 << (idx % 64);
+    }
+
+    fn xor_assign(&mut self, other: &BitRow) {
+        for (word, other) in self.0.iter_mut().zip(&other.0) {
+            *word ^= other;
+        }
+    }
+
+    /// The dot product of two rows over GF(2): the parity of their intersection.
+    fn dot(&self, other: &BitRow) -> bool {
+        let ones: u32 = self
+            .0
+            .iter()
+            .zip(&other.0)
+            .map(|(a, b)| (a & b).count_ones())
+            .sum();
+        ones % 2 == 1
+    }
+}
+
+/// A square matrix over GF(2), stored as one `BitRow` per row.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Matrix(Vec<BitRow>);
+
+impl Matrix {
+    fn identity(width: usize) -> Matrix {
+        Matrix(
+            (0..width)
+                .map(|idx| {
+                    let mut row = BitRow::zero(width);
+                    row.set(idx);
+                    row
+                })
+                .collect(),
+        )
+    }
+
+    /// The matrix which computes one row of the room from the row above it.
+    fn transition(width: usize) -> Matrix {
+        Matrix(
+            (0..width)
+                .map(|idx| {
+                    let mut row = BitRow::zero(width);
+                    if idx > 0 {
+                        row.set(idx - 1);
+                    }
+                    if idx + 1 < width {
+                        row.set(idx + 1);
+                    }
+                    row
+                })
+                .collect(),
+        )
+    }
+
+    fn width(&self) -> usize {
+        self.0.len()
+    }
+
+    fn mul(&self, other: &Matrix) -> Matrix {
+        let width = self.width();
+        Matrix(
+            self.0
+                .iter()
+                .map(|row| {
+                    // each row of the product is the sum of the rows of `other` it selects
+                    let mut product = BitRow::zero(width);
+                    for (idx, other_row) in other.0.iter().enumerate() {
+                        if row.get(idx) {
+                            product.xor_assign(other_row);
+                        }
+                    }
+                    product
+                })
+                .collect(),
+        )
+    }
+
+    fn pow(&self, mut exponent: u64) -> Matrix {
+        let mut result = Matrix::identity(self.width());
+        let mut base = self.clone();
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base);
+            }
+            base = base.mul(&base);
+            exponent >>= 1;
+        }
+        result
+    }
+
+    fn apply(&self, vector: &BitRow) -> BitRow {
+        let mut result = BitRow::zero(self.width());
+        for (idx, row) in self.0.iter().enumerate() {
+            if row.dot(vector) {
+                result.set(idx);
+            }
+        }
+        result
+    }
+}
+
+fn to_bits(tiles: &[Tile]) -> BitRow {
+    let mut bits = BitRow::zero(tiles.len());
+    for (idx, tile) in tiles.iter().enumerate() {
+        if *tile == Tile::Trap {
+            bits.set(idx);
+        }
+    }
+    bits
+}
+
+fn from_bits(bits: &BitRow, width: usize) -> Vec<Tile> {
+    (0..width)
+        .map(|idx| {
+            if bits.get(idx) {
+                Tile::Trap
+            } else {
+                Tile::Safe
+            }
+        })
+        .collect()
+}
+
+/// Row `k` of the room whose first row is `tiles`, where row 0 is `tiles` itself.
+fn nth_row(tiles: &[Tile], k: u64) -> Vec<Tile> {
+    let width = tiles.len();
+    let jump = Matrix::transition(width).pow(k);
+    from_bits(&jump.apply(&to_bits(tiles)), width)
+}
+
+/// Render row `k` of the room whose first row is `first_row`, where row 0 is `first_row`.
+pub fn row_at(first_row: &str, k: u64) -> Result<String, Error> {
+    let tiles = tiles_from_str(first_row)?;
+    Ok(nth_row(&tiles, k).iter().map(ToString::to_string).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::next_row;
+    use std::collections::HashMap;
+
+    /// Row `k` found by generating rows until one repeats, then skipping whole cycles.
+    fn row_by_cycle(tiles: &[Tile], k: u64) -> Vec<Tile> {
+        let mut seen = HashMap::new();
+        let mut rows = Vec::new();
+        let mut row = tiles.to_vec();
+        while !seen.contains_key(&row) {
+            if rows.len() as u64 == k {
+                return row;
+            }
+            seen.insert(row.clone(), rows.len() as u64);
+            rows.push(row.clone());
+            row = next_row(&row);
+        }
+        let cycle_start = seen[&row];
+        let cycle_len = rows.len() as u64 - cycle_start;
+        rows[(cycle_start + (k - cycle_start) % cycle_len) as usize].clone()
+    }
+
+    #[test]
+    fn test_matches_generator() {
+        let first_row = tiles_from_str(".^^.^.^^^^").unwrap();
+        let mut row = first_row.clone();
+        for k in 0..50 {
+            assert_eq!(nth_row(&first_row, k), row, "row {}", k);
+            row = next_row(&row);
+        }
+    }
+
+    #[test]
+    fn test_matches_cycle_extrapolation() {
+        for first_row in [
+            "..^^.",
+            ".^^.^.^^^^",
+            "^.^^^..^^...^.^^",
+            "^^^^^^^^^^^^^^^^^^^^^^^^",
+        ] {
+            let tiles = tiles_from_str(first_row).unwrap();
+            for k in [0, 1, 63, 64, 1000, 1_000_003, 1_000_000_000_000] {
+                assert_eq!(
+                    nth_row(&tiles, k),
+                    row_by_cycle(&tiles, k),
+                    "row {} of {}",
+                    k,
+                    first_row
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_wide_rows() {
+        // wider than one word, so rows span several
+        let first_row: String = (0..150)
+            .map(|idx| if idx % 7 < 3 { '^' } else { '.' })
+            .collect();
+        let mut row = tiles_from_str(&first_row).unwrap();
+        for _ in 0..200 {
+            row = next_row(&row);
+        }
+        let expect: String = row.iter().map(ToString::to_string).collect();
+        assert_eq!(row_at(&first_row, 200).unwrap(), expect);
+    }
+}
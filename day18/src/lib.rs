@@ -1,15 +1,31 @@
-use aoclib::parse;
-
-use std::{convert::TryInto, iter, path::Path, str::FromStr};
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq, parse_display::FromStr, parse_display::Display)]
-enum Tile {
+use aoc2016_solver::{each_line, Answer, Registry, Solver};
+use aoclib::{
+    geometry::{tile::DisplayWidth, Map as GenericMap},
+    input::parse_str,
+};
+
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::{convert::TryInto, iter, str::FromStr};
+
+pub mod gf2;
+
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, Hash, parse_display::FromStr, parse_display::Display,
+)]
+pub enum Tile {
     #[display(".")]
     Safe,
     #[display("^")]
     Trap,
 }
 
+impl DisplayWidth for Tile {
+    const DISPLAY_WIDTH: usize = 1;
+}
+
+pub type Map = GenericMap<Tile>;
+
 fn tiles_from_str(s: &str) -> Result<Vec<Tile>, Error> {
     s.as_bytes()
         .windows(1)
@@ -59,20 +75,112 @@ fn count_safe_in_n_rows(tiles: &[Tile], n: usize) -> usize {
     safe
 }
 
-pub fn part1(input: &Path) -> Result<(), Error> {
-    for initial_row in parse::<String>(input)?.map(|row| tiles_from_str(&row)) {
-        let safe_tiles = count_safe_in_n_rows(&initial_row?, 40);
-        println!("safe tiles: {}", safe_tiles);
+/// Render the first `n` rows of the room whose first row is `first_row`.
+pub fn render_rows(first_row: &str, n: usize) -> Result<Vec<String>, Error> {
+    let mut row = tiles_from_str(first_row)?;
+    let mut rows = Vec::with_capacity(n);
+    for _ in 0..n {
+        rows.push(row.iter().map(ToString::to_string).collect());
+        row = next_row(&row);
+    }
+    Ok(rows)
+}
+
+/// Count the safe tiles in the first `n` rows of the room whose first row is `first_row`.
+pub fn count_safe(first_row: &str, n: usize) -> Result<usize, Error> {
+    Ok(count_safe_in_n_rows(&tiles_from_str(first_row)?, n))
+}
+
+/// Build a map of the first `n` rows of the room whose first row is `first_row`.
+///
+/// The first row is at the top of the map, so the map displays the same as `render_rows`.
+pub fn to_map(first_row: &str, n: usize) -> Result<Map, Error> {
+    let mut row = tiles_from_str(first_row)?;
+    let width = row.len();
+    let mut rows = Vec::with_capacity(n);
+    for _ in 0..n {
+        let next = next_row(&row);
+        rows.push(row);
+        row = next;
+    }
+    Ok(Map::procedural(width, n, |point| {
+        rows[n - 1 - point.y as usize][point.x as usize]
+    }))
+}
+
+/// Find the size of the largest orthogonally connected region of safe tiles in `map`.
+pub fn largest_safe_region_in(map: &Map) -> usize {
+    let mut visited = GenericMap::procedural(map.width(), map.height(), |_| false);
+    let mut largest = 0;
+
+    for start in map.points() {
+        if visited[start] || map[start] != Tile::Safe {
+            continue;
+        }
+        visited[start] = true;
+        let mut size = 0;
+        let mut stack = vec![start];
+        while let Some(point) = stack.pop() {
+            size += 1;
+            for adj in map.orthogonal_adjacencies(point) {
+                if !visited[adj] && map[adj] == Tile::Safe {
+                    visited[adj] = true;
+                    stack.push(adj);
+                }
+            }
+        }
+        largest = largest.max(size);
     }
-    Ok(())
+
+    largest
+}
+
+/// Find the size of the largest connected region of safe tiles in the first `n` rows of the
+/// room whose first row is `first_row`.
+pub fn largest_safe_region(first_row: &str, n: usize) -> Result<usize, Error> {
+    Ok(largest_safe_region_in(&to_map(first_row, n)?))
+}
+
+/// The number of safe tiles in 40 rows, for each first row in the input.
+#[cfg(feature = "fs")]
+pub fn part1(input: &Path) -> Result<Vec<usize>, Error> {
+    part1_from_str(&std::fs::read_to_string(input)?)
 }
 
-pub fn part2(input: &Path) -> Result<(), Error> {
-    for initial_row in parse::<String>(input)?.map(|row| tiles_from_str(&row)) {
-        let safe_tiles = count_safe_in_n_rows(&initial_row?, 400_000);
-        println!("safe tiles 400k: {}", safe_tiles);
+/// The number of safe tiles in 40 rows, for each first row in `input`.
+pub fn part1_from_str(input: &str) -> Result<Vec<usize>, Error> {
+    parse_str::<String>(input)?
+        .map(|initial_row| count_safe(&initial_row, 40))
+        .collect()
+}
+
+/// The number of safe tiles in 400,000 rows, for each first row in the input.
+#[cfg(feature = "fs")]
+pub fn part2(input: &Path) -> Result<Vec<usize>, Error> {
+    part2_from_str(&std::fs::read_to_string(input)?)
+}
+
+/// The number of safe tiles in 400,000 rows, for each first row in `input`.
+pub fn part2_from_str(input: &str) -> Result<Vec<usize>, Error> {
+    parse_str::<String>(input)?
+        .map(|initial_row| count_safe(&initial_row, 400_000))
+        .collect()
+}
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        each_line(part1_from_str(input)?, Ok)
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        each_line(part2_from_str(input)?, Ok)
     }
-    Ok(())
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(18, Solution);
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -120,4 +228,24 @@ mod tests {
         let tiles = tiles_from_str(".^^.^.^^^^").unwrap();
         assert_eq!(count_safe_in_n_rows(&tiles, 10), 38);
     }
+
+    #[test]
+    fn test_render_rows() {
+        let rows = render_rows("..^^.", 3).unwrap();
+        assert_eq!(rows, &["..^^.", ".^^^^", "^^..^"]);
+    }
+
+    #[test]
+    fn test_to_map() {
+        let map = to_map("..^^.", 3).unwrap();
+        assert_eq!(map.to_string(), "..^^.\n.^^^^\n^^..^\n");
+        assert_eq!(map[map.top_left()], Tile::Safe);
+        assert_eq!(map[map.bottom_left()], Tile::Trap);
+    }
+
+    #[test]
+    fn test_largest_safe_region() {
+        assert_eq!(largest_safe_region("..^^.", 3).unwrap(), 3);
+        assert_eq!(largest_safe_region("..^^.", 0).unwrap(), 0);
+    }
 }
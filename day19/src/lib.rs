@@ -1,121 +1,220 @@
+use aoc2016_solver::{each_line, Answer, Example, Registry, Solver};
 use aoclib::parse;
 
-use std::{cell::Cell, collections::VecDeque, path::Path, rc::Rc};
-
-fn clear_leading_one(mut n: u32) -> u32 {
-    if n != 0 {
-        let mask = !(1 << (31 - n.leading_zeros()));
-        n &= mask;
-    }
-    n
-}
+pub use aoc2016_core::josephus::{elimination_order, Variant};
+use aoc2016_core::josephus::{eliminations, josephus, josephus_across, josephus_across_from_iter};
+use std::{fmt, path::Path};
 
 // Oh man! I get the pun in the title ("An Elephant Named Joseph"), because this is the
 // Josephus problem, and I did some work on that as an undergrad. I think this is literally
 // the first time that an AoC problem has been a re-statement of an obscure math thing,
 // and I immediately recognized the obscure math thing. I love it!
-fn josephus(n: u32) -> u32 {
-    (clear_leading_one(n) << 1) | 1
+//
+// The algorithms themselves live in `aoc2016_core::josephus`.
+
+/// The elf who gets all the presents, stealing from the left, for each elf count in the input.
+pub fn part1(input: &Path) -> Result<Vec<u32>, Error> {
+    part1_from_str(&std::fs::read_to_string(input)?)
 }
 
-pub fn part1(input: &Path) -> Result<(), Error> {
-    for input in parse(input)? {
-        println!("solution for {}: {}", input, josephus(input));
-    }
-    Ok(())
+/// The elf who gets all the presents, stealing from the left, for each elf count in `input`.
+pub fn part1_from_str(input: &str) -> Result<Vec<u32>, Error> {
+    Ok(aoclib::input::parse_str(input)?.map(josephus).collect())
 }
 
-// This is pretty bad: `O(n**2)`: VecDeque rotation requires `O(n)`
-fn josephus_across(n: u32) -> u32 {
-    let mut players: VecDeque<_> = (1..=n).collect();
-    while players.len() > 1 {
-        let shift = players.len() / 2;
-        players.rotate_left(shift);
-        players.pop_front();
-        players.rotate_right(shift - 1);
-    }
-    players[0]
+// oh well, I was hoping this would be super simple, but I guess I can actually implement
+// this problem.
+/// The elf who gets all the presents, stealing from across, for each elf count in the input.
+pub fn part2(input: &Path) -> Result<Vec<u32>, Error> {
+    part2_from_str(&std::fs::read_to_string(input)?)
 }
 
-// getting a solution still requires `O(n)`, but that's acceptable, where
-// the naive implementation isn't.
-fn josephus_across_from_iter(n: u32) -> u32 {
-    josephus_across_iter()
-        .nth((n - 1) as usize)
-        .expect("josephus_across_iter never terminates")
+/// The elf who gets all the presents, stealing from across, for each elf count in `input`.
+pub fn part2_from_str(input: &str) -> Result<Vec<u32>, Error> {
+    Ok(aoclib::input::parse_str(input)?
+        .map(josephus_across_from_iter)
+        .collect())
 }
 
-fn partial_josephus_across_iter(n: u32) -> impl Iterator<Item = u32> {
-    (1..=n).chain((1..=n).map(move |m| 2 * m + n))
+/// The intermediate quantities of the closed-form solutions for `n` elves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Explanation {
+    pub n: u32,
+    /// The largest power of two not exceeding `n`.
+    pub power_of_two: u32,
+    /// `n - power_of_two`: the eliminations needed before the circle size is a power of two.
+    pub offset_two: u32,
+    /// The survivor when each elf takes from the next: `2 * offset_two + 1`.
+    pub survivor: u32,
+    /// The largest power of three not exceeding `n`.
+    pub power_of_three: u32,
+    /// `n - power_of_three`.
+    pub offset_three: u32,
+    /// The survivor when each elf takes from across the circle.
+    ///
+    /// This is `n` when `n` is a power of three, `offset_three` while that doesn't exceed
+    /// `power_of_three`, and `2 * n - 3 * power_of_three` otherwise.
+    pub survivor_across: u32,
 }
 
-fn josephus_across_iter() -> impl Iterator<Item = u32> {
-    let mut sub_iter: Box<dyn Iterator<Item = u32>> = Box::new(partial_josephus_across_iter(3));
-
-    // this is a bit ugly, but it's forced on us: we're creating two references
-    // to a single `Cell`, which gives us interior mutability. That means that
-    // we can hand one of them to the `from_fn` closure, which can read it, and
-    // another to the `inspect` closure, which can update it.
-    //
-    // We could work around this by implementing Iterator manually on a struct, but
-    // I wanted to do this the lazy way instead.
-    let prev1 = Rc::new(Cell::new(0));
-    let prev2 = prev1.clone();
-
-    std::array::IntoIter::new([1, 1, 3]).chain(
-        std::iter::from_fn(move || {
-            Some(match sub_iter.next() {
-                Some(v) => v,
-                None => {
-                    // set the sub-iterator, but skip its first value; we know that it always
-                    // equals 1
-                    sub_iter = Box::new(partial_josephus_across_iter(prev1.get()).skip(1));
-                    1
-                }
-            })
-        })
-        .inspect(move |v| {
-            prev2.set(*v);
-        }),
-    )
+/// Derive both puzzle answers for `n` elves from their closed forms, without simulation.
+///
+/// Panics if `n` is 0.
+pub fn explain(n: u32) -> Explanation {
+    assert!(n > 0, "there must be at least one elf");
+
+    let power_of_two = 1 << (31 - n.leading_zeros());
+    let offset_two = n - power_of_two;
+
+    let mut power_of_three = 1_u32;
+    while let Some(next) = power_of_three.checked_mul(3).filter(|next| *next <= n) {
+        power_of_three = next;
+    }
+    let offset_three = n - power_of_three;
+    let survivor_across = if offset_three == 0 {
+        n
+    } else if offset_three <= power_of_three {
+        offset_three
+    } else {
+        n - (3 * power_of_three - n)
+    };
+
+    Explanation {
+        n,
+        power_of_two,
+        offset_two,
+        survivor: 2 * offset_two + 1,
+        power_of_three,
+        offset_three,
+        survivor_across,
+    }
 }
 
-// oh well, I was hoping this would be super simple, but I guess I can actually implement
-// this problem.
-pub fn part2(input: &Path) -> Result<(), Error> {
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} elves:", self.n)?;
+        writeln!(
+            f,
+            "  taking from the next elf: {} = {} + {}; survivor is 2 * {} + 1 = {}",
+            self.n, self.power_of_two, self.offset_two, self.offset_two, self.survivor
+        )?;
+        write!(
+            f,
+            "  taking from across: {} = {} + {}; ",
+            self.n, self.power_of_three, self.offset_three
+        )?;
+        if self.offset_three == 0 {
+            write!(f, "a power of three, so the survivor is {}", self.n)
+        } else if self.offset_three <= self.power_of_three {
+            write!(
+                f,
+                "{} <= {}, so the survivor is {}",
+                self.offset_three, self.power_of_three, self.survivor_across
+            )
+        } else {
+            write!(
+                f,
+                "{} > {}, so the survivor is 2 * {} - 3 * {} = {}",
+                self.offset_three,
+                self.power_of_three,
+                self.n,
+                self.power_of_three,
+                self.survivor_across
+            )
+        }
+    }
+}
+
+/// Explain the closed-form derivation of both answers for each input.
+pub fn print_explanations(input: &Path) -> Result<(), Error> {
     for input in parse(input)? {
+        println!("{}", explain(input));
+    }
+    Ok(())
+}
+
+/// Step-by-step printing is limited to games with at most this many elves.
+pub const MAX_STEPS_ELVES: u32 = 100;
+
+/// Print each step of the game with `n` elves, in the style of the puzzle's worked example.
+pub fn print_steps(n: u32, variant: Variant) -> Result<(), Error> {
+    if n == 0 || n > MAX_STEPS_ELVES {
+        return Err(Error::StepsOutOfRange(n));
+    }
+    match variant {
+        Variant::Next => println!("{} elves, taking from the next elf:", n),
+        Variant::Across => println!("{} elves, taking from across the circle:", n),
+    }
+    let eliminations = eliminations(n, variant);
+    for elimination in &eliminations {
         println!(
-            "solution across for {}: {}",
-            input,
-            josephus_across_from_iter(input)
+            "  Elf {} takes Elf {}'s presents.",
+            elimination.thief, elimination.victim
         );
     }
+    let survivor = eliminations.last().map_or(1, |last| last.thief);
+    println!("  Elf {} has all the presents.", survivor);
     Ok(())
 }
 
+/// Log, at debug level, the survivor taking from across for each of the first 100 games, and
+/// the games after which the survivor resets to elf 1.
 pub fn first_100_across() {
+    let _span = tracing::debug_span!("first_100_across").entered();
     for n in 1..=100 {
-        println!("josephus_across({}) -> {}", n, josephus_across(n));
+        tracing::debug!(elves = n, survivor = josephus_across(n));
     }
-    println!("resets:");
-    print!("1");
-    for n in 2..=100 {
-        if josephus_across(n) == 1 {
-            print!(", {}", n);
-        }
+    let resets: Vec<u32> = (1..=100).filter(|&n| josephus_across(n) == 1).collect();
+    tracing::debug!(?resets);
+}
+
+const EXAMPLES: &[Example] = &[
+    Example {
+        part: 1,
+        input: "5",
+        answer: "3",
+    },
+    Example {
+        part: 2,
+        input: "5",
+        answer: "2",
+    },
+];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        each_line(part1_from_str(input)?, Ok)
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        each_line(part2_from_str(input)?, Ok)
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
     }
-    println!();
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(19, Solution);
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error("can only print steps for 1 to {} elves, not {0}", MAX_STEPS_ELVES)]
+    StepsOutOfRange(u32),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use aoc2016_core::josephus::{
+        clear_leading_one, josephus_across_iter, partial_josephus_across_iter,
+    };
 
     #[test]
     fn test_clear_leading_one() {
@@ -139,6 +238,27 @@ mod tests {
         assert_eq!(josephus_across(5), 2);
     }
 
+    #[test]
+    fn test_elimination_order_examples() {
+        assert_eq!(elimination_order(5, Variant::Next), vec![2, 4, 1, 5]);
+        assert_eq!(elimination_order(5, Variant::Across), vec![3, 5, 1, 4]);
+        assert!(elimination_order(1, Variant::Across).is_empty());
+    }
+
+    #[test]
+    fn test_simulations_agree_with_closed_forms() {
+        for n in 2..300 {
+            let survivor = |variant| eliminations(n, variant).last().unwrap().thief;
+            assert_eq!(survivor(Variant::Next), josephus(n), "n = {}", n);
+            assert_eq!(
+                survivor(Variant::Across),
+                josephus_across_from_iter(n),
+                "n = {}",
+                n
+            );
+        }
+    }
+
     #[test]
     fn test_partial_josephus_across_iter() {
         let expect = [
@@ -170,4 +290,29 @@ mod tests {
             assert_eq!(josephus_across(n), josephus_across_from_iter(n));
         }
     }
+
+    #[test]
+    fn test_explain_matches_simulation() {
+        for n in 1..=200 {
+            let explanation = explain(n);
+            assert_eq!(explanation.survivor, josephus(n), "n = {}", n);
+            assert_eq!(explanation.survivor_across, josephus_across(n), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn test_explain_example() {
+        let explanation = explain(5);
+        assert_eq!(explanation.power_of_two, 4);
+        assert_eq!(explanation.offset_two, 1);
+        assert_eq!(explanation.survivor, 3);
+        assert_eq!(explanation.power_of_three, 3);
+        assert_eq!(explanation.offset_three, 2);
+        assert_eq!(explanation.survivor_across, 2);
+        assert_eq!(explain(u32::MAX).power_of_three, 3_u32.pow(20));
+        assert_eq!(
+            explain(3_u32.pow(20) - 1).survivor_across,
+            3_u32.pow(20) - 2
+        );
+    }
 }
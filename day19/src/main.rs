@@ -1,19 +1,22 @@
-use aoclib::{config::Config, website::get_input};
-use day19::{part1, part2};
+use aoc2016_solver::Input;
+use day19::{part1, part2, print_steps, Variant};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 19;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -22,38 +25,55 @@ struct RunArgs {
     #[structopt(long)]
     part2: bool,
 
+    /// log the survivor taking from across for the first 100 games; implies `-vv`
     #[structopt(long)]
     first_100_across: bool,
-}
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
-    }
+    /// show diagnostics; repeat for more
+    #[structopt(short, long, parse(from_occurrences))]
+    verbose: u8,
+
+    /// explain how the closed forms derive each answer
+    #[structopt(long)]
+    explain: bool,
+
+    /// print each step of the game with this many elves, for both variants, then exit
+    #[structopt(long)]
+    steps: Option<u32>,
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    let verbosity = if args.first_100_across {
+        args.verbose.max(2)
+    } else {
+        args.verbose
+    };
+    aoc2016_solver::logging::init(verbosity);
+    if let Some(n) = args.steps {
+        print_steps(n, Variant::Next)?;
+        println!();
+        print_steps(n, Variant::Across)?;
+        return Ok(());
+    }
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
 
     if !args.no_part1 {
-        part1(&input_path)?;
+        for elf in part1(&input_path)? {
+            println!("solution: {}", elf);
+        }
     }
     if args.first_100_across {
         day19::first_100_across();
     }
     if args.part2 {
-        part2(&input_path)?;
+        for elf in part2(&input_path)? {
+            println!("solution across: {}", elf);
+        }
+    }
+    if args.explain {
+        day19::print_explanations(&input_path)?;
     }
     Ok(())
 }
@@ -1,9 +1,7 @@
-use aoclib::parse;
-use itertools::Itertools;
-use std::{
-    ops::{Bound, RangeBounds},
-    path::Path,
-};
+use aoc2016_core::intervals;
+use aoc2016_solver::{Answer, Example, Registry, Solver};
+use rand::Rng;
+use std::{ops::RangeBounds, path::Path};
 
 #[derive(
     Default,
@@ -20,91 +18,136 @@ use std::{
 #[display("{0}-{1}")]
 struct Rule(u32, u32);
 
-fn ordered_rules_iter_from(rules: impl Iterator<Item = Rule>) -> impl Iterator<Item = Rule> {
-    let mut rules: Vec<_> = rules.collect();
-    debug_assert!(rules.iter().all(|Rule(low, high)| low <= high));
-    rules.sort_unstable();
-    rules
-        .into_iter()
-        .coalesce(|Rule(prev_low, prev_high), Rule(next_low, next_high)| {
-            // coalesce adjacent blacklist ranges into a combined range with a unified lower, upper
-            if next_low <= prev_high {
-                Ok(Rule(prev_low, prev_high.max(next_high)))
-            } else {
-                Err((Rule(prev_low, prev_high), Rule(next_low, next_high)))
-            }
+/// Parse the rules in `input`, rejecting any whose range is reversed.
+///
+/// The interval arithmetic assumes `low <= high`; a reversed rule would otherwise underflow it.
+fn rules_from_str(input: &str) -> Result<Vec<Rule>, Error> {
+    let rules: Vec<Rule> = aoclib::input::parse_str(input)?.collect();
+    match rules.iter().find(|Rule(low, high)| low > high) {
+        Some(&Rule(low, high)) => Err(Error::InvalidRule(low, high)),
+        None => Ok(rules),
+    }
+}
+
+fn bounds(rules: impl Iterator<Item = Rule>) -> impl Iterator<Item = (u32, u32)> {
+    rules.map(|Rule(low, high)| (low, high))
+}
+
+fn lowest_legal_value(
+    rules: impl Iterator<Item = Rule>,
+    range: impl RangeBounds<u32>,
+) -> Option<u32> {
+    intervals::lowest_uncovered_in(bounds(rules), range)
+}
+
+fn num_legal_values_in(rules: impl Iterator<Item = Rule>, range: impl RangeBounds<u32>) -> u64 {
+    intervals::count_uncovered_in(bounds(rules), range)
+}
+
+/// Draw `k` legal values within `range` independently and uniformly at random.
+///
+/// Each gap between rules is weighted by its length, so the legal values are never enumerated.
+fn sample_legal(
+    rules: impl Iterator<Item = Rule>,
+    range: impl RangeBounds<u32>,
+    k: usize,
+    rng: &mut impl Rng,
+) -> Option<Vec<u32>> {
+    let gaps = intervals::uncovered_in(bounds(rules), range);
+    // `cumulative[i]` is the number of legal values in `gaps[..=i]`
+    let cumulative: Vec<u64> = gaps
+        .iter()
+        .scan(0, |total, &(low, high)| {
+            *total += (high - low) as u64 + 1;
+            Some(*total)
         })
+        .collect();
+    let total = *cumulative.last()?;
+
+    let samples = (0..k)
+        .map(|_| {
+            let nth = rng.gen_range(0..total);
+            let gap = cumulative.partition_point(|&seen| seen <= nth);
+            let preceding = gap.checked_sub(1).map_or(0, |prev| cumulative[prev]);
+            gaps[gap].0 + (nth - preceding) as u32
+        })
+        .collect();
+    Some(samples)
 }
 
-fn lowest_legal_value(rules: impl Iterator<Item = Rule>) -> Option<u32> {
-    let mut iter = ordered_rules_iter_from(rules).peekable();
-    if let Some(Rule(low, _)) = iter.peek() {
-        if *low > 0 {
-            return Some(0);
-        }
-    }
-    while let Some(Rule(_, prev_high)) = iter.next() {
-        match iter.peek() {
-            None if prev_high < u32::MAX - 1 => return Some(prev_high + 1),
-            Some(Rule(next_low, _)) if *next_low > prev_high + 1 => return Some(prev_high + 1),
-            _ => {}
-        }
-    }
+/// The lowest IP in `min_ip..=max_ip` which no rule blocks.
+pub fn part1(input: &Path, min_ip: u32, max_ip: u32) -> Result<u32, Error> {
+    part1_from_str(&std::fs::read_to_string(input)?, min_ip, max_ip)
+}
 
-    None
+/// The lowest IP in `min_ip..=max_ip` which no rule in `input` blocks.
+pub fn part1_from_str(input: &str, min_ip: u32, max_ip: u32) -> Result<u32, Error> {
+    lowest_legal_value(rules_from_str(input)?.into_iter(), min_ip..=max_ip).ok_or(Error::NoSolution)
 }
 
-fn num_legal_values(rules: impl Iterator<Item = Rule>) -> u32 {
-    num_legal_values_in(rules, ..)
+/// The number of IPs in `min_ip..=max_ip` which no rule blocks.
+pub fn part2(input: &Path, min_ip: u32, max_ip: u32) -> Result<u64, Error> {
+    part2_from_str(&std::fs::read_to_string(input)?, min_ip, max_ip)
 }
 
-fn num_legal_values_in(rules: impl Iterator<Item = Rule>, bounds: impl RangeBounds<u32>) -> u32 {
-    let lower_bound_inclusive = match bounds.start_bound() {
-        Bound::Included(v) => *v,
-        Bound::Excluded(v) => *v + 1,
-        Bound::Unbounded => 0,
-    };
-    let upper_bound_inclusive = match bounds.end_bound() {
-        Bound::Included(v) => *v,
-        Bound::Excluded(v) => *v - 1,
-        Bound::Unbounded => u32::MAX,
-    };
-    let mut count = 0;
-    let mut iter = ordered_rules_iter_from(rules).peekable();
-    if let Some(Rule(low, _)) = iter.peek() {
-        if low.checked_sub(lower_bound_inclusive).unwrap_or_default() > 0 {
-            count += low;
-        }
-    }
-    while let Some(Rule(_, prev_high)) = iter.next() {
-        count += match iter.peek() {
-            None => upper_bound_inclusive - prev_high,
-            Some(Rule(next_low, _)) if next_low.checked_sub(prev_high).unwrap_or_default() > 1 => {
-                next_low - prev_high - 1
-            }
-            _ => 0,
-        }
-    }
+/// The number of IPs in `min_ip..=max_ip` which no rule in `input` blocks.
+pub fn part2_from_str(input: &str, min_ip: u32, max_ip: u32) -> Result<u64, Error> {
+    Ok(num_legal_values_in(
+        rules_from_str(input)?.into_iter(),
+        min_ip..=max_ip,
+    ))
+}
 
-    count
+/// `k` IPs in `min_ip..=max_ip` which no rule blocks, drawn uniformly at random with replacement.
+///
+/// Useful for generating traffic which the firewall lets through.
+pub fn sample(
+    input: &Path,
+    min_ip: u32,
+    max_ip: u32,
+    k: usize,
+    rng: &mut impl Rng,
+) -> Result<Vec<u32>, Error> {
+    let rules = rules_from_str(&std::fs::read_to_string(input)?)?;
+    sample_legal(rules.into_iter(), min_ip..=max_ip, k, rng).ok_or(Error::NoSolution)
 }
 
-pub fn part1(input: &Path) -> Result<(), Error> {
-    let llv = lowest_legal_value(parse(input)?).ok_or(Error::NoSolution)?;
-    println!("lowest legal value: {}", llv);
-    Ok(())
+/// The blacklist from the puzzle text.
+const EXAMPLE: &str = "5-8\n0-2\n4-7\n";
+
+// the puzzle's example allows only `0..=9`, which changes part 2's answer but not part 1's
+const EXAMPLES: &[Example] = &[Example {
+    part: 1,
+    input: EXAMPLE,
+    answer: "3",
+}];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part1_from_str(input, 0, u32::MAX)?.into())
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part2_from_str(input, 0, u32::MAX)?.into())
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
 }
 
-pub fn part2(input: &Path) -> Result<(), Error> {
-    let legal_values = num_legal_values(parse(input)?);
-    println!("num legal values: {}", legal_values);
-    Ok(())
+pub fn register(registry: &mut Registry) {
+    registry.register(20, Solution);
 }
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error("rule {0}-{1} ends before it starts")]
+    InvalidRule(u32, u32),
     #[error("no value is legal")]
     NoSolution,
 }
@@ -114,10 +157,6 @@ mod tests {
     use super::*;
     use aoclib::input::parse_str;
 
-    const EXAMPLE: &str = "5-8
-    0-2
-    4-7";
-
     #[test]
     fn test_example_part2() {
         assert_eq!(num_legal_values_in(parse_str(EXAMPLE).unwrap(), 0..10), 2);
@@ -126,14 +165,14 @@ mod tests {
     #[test]
     fn test_open_low() {
         let rules = || parse_str("2-9").unwrap();
-        assert_eq!(lowest_legal_value(rules()).unwrap(), 0);
+        assert_eq!(lowest_legal_value(rules(), ..).unwrap(), 0);
         assert_eq!(num_legal_values_in(rules(), 0..10), 2);
     }
 
     #[test]
     fn test_open_high() {
         let rules = || parse_str("0-7").unwrap();
-        assert_eq!(lowest_legal_value(rules()).unwrap(), 8);
+        assert_eq!(lowest_legal_value(rules(), ..).unwrap(), 8);
         assert_eq!(num_legal_values_in(rules(), 0..10), 2);
     }
 
@@ -148,7 +187,7 @@ mod tests {
             )
             .unwrap()
         };
-        assert_eq!(lowest_legal_value(rules()).unwrap(), 9);
+        assert_eq!(lowest_legal_value(rules(), ..).unwrap(), 9);
         assert_eq!(num_legal_values_in(rules(), ..10), 1);
     }
 
@@ -163,7 +202,7 @@ mod tests {
             )
             .unwrap()
         };
-        assert_eq!(lowest_legal_value(rules()).unwrap(), 9);
+        assert_eq!(lowest_legal_value(rules(), ..).unwrap(), 9);
         assert_eq!(num_legal_values_in(rules(), ..10), 1);
     }
 
@@ -177,7 +216,7 @@ mod tests {
             )
             .unwrap()
         };
-        assert_eq!(lowest_legal_value(rules()).unwrap(), 1);
+        assert_eq!(lowest_legal_value(rules(), ..).unwrap(), 1);
         assert_eq!(num_legal_values_in(rules(), ..10), 3);
     }
 
@@ -192,7 +231,7 @@ mod tests {
             )
             .unwrap()
         };
-        assert_eq!(lowest_legal_value(rules()).unwrap(), 9);
+        assert_eq!(lowest_legal_value(rules(), ..).unwrap(), 9);
         assert_eq!(num_legal_values_in(rules(), ..10), 1);
     }
 
@@ -206,7 +245,61 @@ mod tests {
             )
             .unwrap()
         };
-        assert_eq!(lowest_legal_value(rules()).unwrap(), 7);
+        assert_eq!(lowest_legal_value(rules(), ..).unwrap(), 7);
         assert_eq!(num_legal_values_in(rules(), ..10), 1);
     }
+
+    #[test]
+    fn test_bounds() {
+        let rules = || parse_str(EXAMPLE).unwrap();
+        assert_eq!(lowest_legal_value(rules(), 0..=9), Some(3));
+        assert_eq!(lowest_legal_value(rules(), 4..=9), Some(9));
+        assert_eq!(lowest_legal_value(rules(), 4..=8), None);
+        assert_eq!(num_legal_values_in(rules(), 4..=9), 1);
+        assert_eq!(num_legal_values_in(rules(), 3..3), 0);
+        assert_eq!(num_legal_values_in(rules(), ..), (1 << 32) - 8);
+    }
+
+    #[test]
+    fn test_unbounded_extremes() {
+        assert_eq!(
+            lowest_legal_value(parse_str("0-4294967294").unwrap(), ..),
+            Some(u32::MAX)
+        );
+        assert_eq!(
+            lowest_legal_value(parse_str("0-4294967295").unwrap(), ..),
+            None
+        );
+        assert_eq!(num_legal_values_in(std::iter::empty(), ..), 1 << 32);
+    }
+
+    #[test]
+    fn test_sample_legal() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let mut rng = StdRng::seed_from_u64(20);
+        let rules = || parse_str(EXAMPLE).unwrap();
+        let samples = sample_legal(rules(), 0..=9, 1000, &mut rng).unwrap();
+        assert_eq!(samples.len(), 1000);
+        for legal in [3, 9] {
+            let hits = samples.iter().filter(|&&ip| ip == legal).count();
+            assert!((400..600).contains(&hits), "{} drawn {} times", legal, hits);
+        }
+        assert!(samples.iter().all(|&ip| ip == 3 || ip == 9));
+
+        assert_eq!(sample_legal(rules(), 4..=8, 5, &mut rng), None);
+        assert_eq!(sample_legal(rules(), .., 0, &mut rng), Some(Vec::new()));
+
+        let samples = sample_legal(parse_str("1-4294967294").unwrap(), .., 100, &mut rng).unwrap();
+        assert!(samples.iter().all(|&ip| ip == 0 || ip == u32::MAX));
+    }
+
+    #[test]
+    fn test_reversed_rule() {
+        assert!(matches!(
+            part2_from_str("0-2\n8-5", 0, 9),
+            Err(Error::InvalidRule(8, 5))
+        ));
+        assert_eq!(part2_from_str("0-2\n5-8", 0, 9).unwrap(), 3);
+    }
 }
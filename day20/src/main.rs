@@ -1,19 +1,22 @@
-use aoclib::{config::Config, website::get_input};
-use day20::{part1, part2};
+use aoc2016_solver::Input;
+use day20::{part1, part2, sample};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 20;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -21,33 +24,48 @@ struct RunArgs {
     /// run part 2
     #[structopt(long)]
     part2: bool,
-}
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
-    }
+    /// lowest legal IP address
+    #[structopt(long, default_value = "0")]
+    min_ip: u32,
+
+    /// highest legal IP address
+    #[structopt(long, default_value = "4294967295")]
+    max_ip: u32,
+
+    /// print this many legal IPs, drawn uniformly at random
+    #[structopt(long)]
+    sample: Option<usize>,
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
 
     if !args.no_part1 {
-        part1(&input_path)?;
+        println!(
+            "lowest legal value: {}",
+            part1(&input_path, args.min_ip, args.max_ip)?
+        );
     }
     if args.part2 {
-        part2(&input_path)?;
+        println!(
+            "num legal values: {}",
+            part2(&input_path, args.min_ip, args.max_ip)?
+        );
+    }
+    if let Some(k) = args.sample {
+        let ips = sample(
+            &input_path,
+            args.min_ip,
+            args.max_ip,
+            k,
+            &mut rand::thread_rng(),
+        )?;
+        for ip in ips {
+            println!("{}", std::net::Ipv4Addr::from(ip));
+        }
     }
     Ok(())
 }
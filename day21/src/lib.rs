@@ -1,13 +1,17 @@
-use aoclib::parse;
+use aoc2016_solver::{Answer, Registry, Solver};
 
-use std::{collections::VecDeque, path::Path};
+use std::{
+    collections::{BTreeSet, VecDeque},
+    path::Path,
+};
 
 const INPUT_PART1: &str = "abcdefgh";
 const INPUT_PART2: &str = "fbgdceah";
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, parse_display::Display, parse_display::FromStr)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[display(style = "lowercase")]
-enum Direction {
+pub enum Direction {
     Left,
     Right,
 }
@@ -32,7 +36,8 @@ fn reverse_rotate(pos: usize) -> usize {
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, parse_display::Display, parse_display::FromStr)]
-enum Operation {
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub enum Operation {
     #[display("swap position {0} with position {1}")]
     SwapPosition(usize, usize),
     #[display("swap letter {0} with letter {1}")]
@@ -49,6 +54,22 @@ enum Operation {
 }
 
 impl Operation {
+    /// Whether this operation can be applied to `password` without panicking: each position
+    /// must be within it, each letter ASCII, and each letter to rotate on present in it.
+    ///
+    /// Operations only ever rearrange a password's letters, so this holds at every step of a
+    /// scramble if it holds for the initial password.
+    pub fn applies_to(self, password: &str) -> bool {
+        let len = password.len();
+        match self {
+            Self::SwapPosition(a, b) | Self::Move(a, b) => a < len && b < len,
+            Self::Reverse(a, b) => a <= b && b < len,
+            Self::SwapLetter(a, b) => a.is_ascii() && b.is_ascii(),
+            Self::RotateOn(c) => c.is_ascii() && password.contains(c),
+            Self::Rotate(..) => true,
+        }
+    }
+
     fn apply(self, buffer: &mut VecDeque<u8>) {
         match self {
             Self::SwapPosition(a, b) => buffer.swap(a, b),
@@ -63,8 +84,8 @@ impl Operation {
                 })
             }
             Self::Rotate(direction, by) => match direction {
-                Direction::Left => buffer.rotate_left(by),
-                Direction::Right => buffer.rotate_right(by),
+                Direction::Left => buffer.rotate_left(by % buffer.len().max(1)),
+                Direction::Right => buffer.rotate_right(by % buffer.len().max(1)),
             },
             Self::RotateOn(c) => {
                 let c = c as u8;
@@ -75,7 +96,7 @@ impl Operation {
                     )
                 });
                 let rot = 1 + pos + if pos >= 4 { 1 } else { 0 };
-                buffer.rotate_right(rot);
+                buffer.rotate_right(rot % buffer.len());
             }
             Self::Reverse(a, b) => {
                 buffer.make_contiguous()[a..=b].reverse();
@@ -103,8 +124,8 @@ impl Operation {
                 })
             }
             Self::Rotate(direction, by) => match direction {
-                Direction::Left => buffer.rotate_right(by),
-                Direction::Right => buffer.rotate_left(by),
+                Direction::Left => buffer.rotate_right(by % buffer.len().max(1)),
+                Direction::Right => buffer.rotate_left(by % buffer.len().max(1)),
             },
             Self::RotateOn(c) => {
                 let c = c as u8;
@@ -129,6 +150,68 @@ impl Operation {
             }
         }
     }
+
+    /// Compute every buffer which `apply` would transform into `buffer`.
+    ///
+    /// Most operations are bijections, so there is exactly one pre-image. `RotateOn` is only
+    /// a bijection for certain buffer lengths, so for it we test each possible rotation.
+    fn preimages(self, mut buffer: VecDeque<u8>) -> Vec<VecDeque<u8>> {
+        match self {
+            Self::RotateOn(c) => {
+                if !buffer.contains(&(c as u8)) {
+                    return Vec::new();
+                }
+                (0..buffer.len())
+                    .filter_map(|rot| {
+                        let mut candidate = buffer.clone();
+                        candidate.rotate_left(rot);
+                        let mut check = candidate.clone();
+                        self.apply(&mut check);
+                        (check == buffer).then_some(candidate)
+                    })
+                    .collect()
+            }
+            _ => {
+                self.unapply(&mut buffer);
+                vec![buffer]
+            }
+        }
+    }
+}
+
+/// Parse `input` as one operation per line, ignoring blank lines.
+///
+/// Unlike parsing with `aoclib`, a malformed line is an error which identifies it.
+pub fn parse_operations(input: &str) -> Result<Vec<Operation>, Error> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| (idx + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(line_number, line)| {
+            line.parse().map_err(|_| Error::Parse {
+                line_number,
+                line: line.to_owned(),
+            })
+        })
+        .collect()
+}
+
+/// Check that every one of `operations` can be applied to `password`.
+pub fn check_applicable(operations: &[Operation], password: &str) -> Result<(), Error> {
+    if !password.is_ascii() {
+        return Err(Error::NonAscii(password.to_owned()));
+    }
+    match operations
+        .iter()
+        .find(|operation| !operation.applies_to(password))
+    {
+        Some(&operation) => Err(Error::Inapplicable {
+            operation,
+            password: password.to_owned(),
+        }),
+        None => Ok(()),
+    }
 }
 
 fn scramble(input: &str, operations: impl Iterator<Item = Operation>) -> String {
@@ -153,20 +236,393 @@ fn unscramble(input: &str, operations: impl Iterator<Item = Operation>) -> Strin
         .expect("scramble operations shouldn't remove utf8-ness")
 }
 
-pub fn part1(input: &Path) -> Result<(), Error> {
-    let scrambled = scramble(INPUT_PART1, parse(input)?);
-    println!("scrambled password: {}", scrambled);
+/// Find every input which scrambles to `scrambled` under `operations`.
+///
+/// For 8-character passwords this is equivalent to `unscramble`, but for other lengths the
+/// `RotateOn` operation can be lossy: several inputs may scramble to the same output, or none
+/// at all. Rather than enumerating every permutation of the input, we work backwards through
+/// the operations, branching only where a `RotateOn` has more than one pre-image.
+///
+/// The results are sorted and deduplicated.
+pub fn candidates(scrambled: &str, operations: &[Operation]) -> Vec<String> {
+    let mut buffers = BTreeSet::new();
+    buffers.insert(
+        scrambled
+            .as_bytes()
+            .iter()
+            .copied()
+            .collect::<VecDeque<u8>>(),
+    );
+
+    for operation in operations.iter().rev() {
+        buffers = buffers
+            .into_iter()
+            .flat_map(|buffer| operation.preimages(buffer))
+            .collect();
+    }
+
+    buffers
+        .into_iter()
+        .map(|buffer| {
+            String::from_utf8(buffer.into_iter().collect())
+                .expect("scramble operations shouldn't remove utf8-ness")
+        })
+        .collect()
+}
+
+fn to_string(buffer: &VecDeque<u8>) -> String {
+    String::from_utf8(buffer.iter().copied().collect())
+        .expect("scramble operations shouldn't remove utf8-ness")
+}
+
+/// Check that `operations` round-trip in both directions.
+///
+/// Scrambling `plain` and then unscrambling the result must restore `plain`, and unscrambling
+/// `scrambled` and then scrambling the result must restore `scrambled`. While scrambling `plain`,
+/// each individual operation is also checked, so that a failure identifies the responsible
+/// operation.
+pub fn verify(operations: &[Operation], plain: &str, scrambled: &str) -> Result<(), Error> {
+    let mut buffer: VecDeque<u8> = plain.bytes().collect();
+    for operation in operations {
+        let before = buffer.clone();
+        operation.apply(&mut buffer);
+        let mut check = buffer.clone();
+        operation.unapply(&mut check);
+        if check != before {
+            return Err(Error::OperationRoundTrip {
+                operation: *operation,
+                before: to_string(&before),
+                after: to_string(&buffer),
+                restored: to_string(&check),
+            });
+        }
+    }
+
+    let forward = scramble(plain, operations.iter().copied());
+    let restored = unscramble(&forward, operations.iter().copied());
+    if restored != plain {
+        return Err(Error::RoundTrip {
+            input: plain.to_owned(),
+            intermediate: forward,
+            restored,
+        });
+    }
+
+    let backward = unscramble(scrambled, operations.iter().copied());
+    let restored = scramble(&backward, operations.iter().copied());
+    if restored != scrambled {
+        return Err(Error::RoundTrip {
+            input: scrambled.to_owned(),
+            intermediate: backward,
+            restored,
+        });
+    }
+
     Ok(())
 }
 
-pub fn part2(input: &Path) -> Result<(), Error> {
-    let unscrambled = unscramble(INPUT_PART2, parse(input)?);
-    println!("scrambled password: {}", unscrambled);
+/// Whether `second`, applied directly after `first`, always undoes it.
+fn cancels(first: Operation, second: Operation) -> bool {
+    use Operation::*;
+    match (first, second) {
+        (SwapPosition(a, b), SwapPosition(c, d)) => (a, b) == (c, d) || (a, b) == (d, c),
+        (SwapLetter(a, b), SwapLetter(c, d)) => (a, b) == (c, d) || (a, b) == (d, c),
+        (Rotate(first_direction, a), Rotate(second_direction, b)) => {
+            first_direction != second_direction && a == b
+        }
+        (Reverse(a, b), Reverse(c, d)) => (a, b) == (c, d),
+        _ => false,
+    }
+}
+
+/// Remove operations which provably have no effect, scrambling `canary` to check the result.
+///
+/// Adjacent pairs of operations which undo each other are removed: opposite rotations by the
+/// same amount, and repeated swaps or reversals of the same pair. Removing a pair can bring two
+/// more operations together, so this repeats until no pair cancels.
+///
+/// `canary` must be long enough for every position in `operations`, and contain every letter
+/// they mention.
+pub fn minimize_with(operations: &[Operation], canary: &str) -> Result<Vec<Operation>, Error> {
+    let mut minimized: Vec<Operation> = Vec::with_capacity(operations.len());
+    for &operation in operations {
+        match minimized.last() {
+            Some(&previous) if cancels(previous, operation) => {
+                minimized.pop();
+            }
+            _ => minimized.push(operation),
+        }
+    }
+
+    let expect = scramble(canary, operations.iter().copied());
+    let actual = scramble(canary, minimized.iter().copied());
+    if expect != actual {
+        return Err(Error::Minimize {
+            canary: canary.to_owned(),
+            expect,
+            actual,
+        });
+    }
+    Ok(minimized)
+}
+
+/// Remove operations which provably have no effect, checking the result against `abcdefgh`.
+pub fn minimize(operations: &[Operation]) -> Result<Vec<Operation>, Error> {
+    minimize_with(operations, INPUT_PART1)
+}
+
+/// Render `operations` in the puzzle's format, one per line.
+pub fn to_lines(operations: &[Operation]) -> String {
+    operations
+        .iter()
+        .map(|operation| match operation {
+            // the puzzle's text doesn't pluralize a single step
+            Operation::Rotate(direction, 1) => format!("rotate {} 1 step\n", direction),
+            _ => format!("{}\n", operation),
+        })
+        .collect()
+}
+
+/// The result of scrambling `abcdefgh`.
+pub fn part1(input: &Path) -> Result<String, Error> {
+    part1_from_str(&std::fs::read_to_string(input)?)
+}
+
+/// The result of scrambling `abcdefgh` with the operations in `input`.
+pub fn part1_from_str(input: &str) -> Result<String, Error> {
+    let operations = parse_operations(input)?;
+    check_applicable(&operations, INPUT_PART1)?;
+    Ok(scramble(INPUT_PART1, operations.into_iter()))
+}
+
+/// The password which scrambles to `fbgdceah`.
+pub fn part2(input: &Path) -> Result<String, Error> {
+    part2_from_str(&std::fs::read_to_string(input)?)
+}
+
+/// The password which the operations in `input` scramble to `fbgdceah`.
+pub fn part2_from_str(input: &str) -> Result<String, Error> {
+    let operations = parse_operations(input)?;
+    check_applicable(&operations, INPUT_PART2)?;
+    Ok(unscramble(INPUT_PART2, operations.into_iter()))
+}
+
+/// Verify that the input's operations round-trip on the puzzle's passwords.
+pub fn verify_input(input: &Path) -> Result<(), Error> {
+    let operations = parse_operations(&std::fs::read_to_string(input)?)?;
+    check_applicable(&operations, INPUT_PART1)?;
+    verify(&operations, INPUT_PART1, INPUT_PART2)?;
+    println!("round trip verified");
     Ok(())
 }
 
+/// The input's operations, minimized and rendered in the puzzle's format.
+pub fn minimize_input(input: &Path) -> Result<String, Error> {
+    let operations = parse_operations(&std::fs::read_to_string(input)?)?;
+    check_applicable(&operations, INPUT_PART1)?;
+    Ok(to_lines(&minimize(&operations)?))
+}
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part1_from_str(input)?.into())
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part2_from_str(input)?.into())
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(21, Solution);
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error("line {line_number}: not an operation: {line:?}")]
+    Parse { line_number: usize, line: String },
+    #[error("password {0:?} is not ASCII")]
+    NonAscii(String),
+    #[error("`{operation}` cannot be applied to {password}")]
+    Inapplicable {
+        operation: Operation,
+        password: String,
+    },
+    #[error("`{operation}` does not round-trip: {before} -> {after} -> {restored}")]
+    OperationRoundTrip {
+        operation: Operation,
+        before: String,
+        after: String,
+        restored: String,
+    },
+    #[error("operations do not round-trip: {input} -> {intermediate} -> {restored}")]
+    RoundTrip {
+        input: String,
+        intermediate: String,
+        restored: String,
+    },
+    #[error("minimized operations scramble {canary} to {actual} instead of {expect}")]
+    Minimize {
+        canary: String,
+        expect: String,
+        actual: String,
+    },
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aoclib::input::parse_str;
+
+    const EXAMPLE: &str = "swap position 4 with position 0
+    swap letter d with letter b
+    reverse positions 0 through 4
+    rotate left 1 step
+    move position 1 to position 4
+    move position 3 to position 0
+    rotate based on position of letter b
+    rotate based on position of letter d";
+
+    fn example() -> Vec<Operation> {
+        parse_str(EXAMPLE).unwrap().collect()
+    }
+
+    #[test]
+    fn test_example_scramble() {
+        assert_eq!(scramble("abcde", example().into_iter()), "decab");
+    }
+
+    #[test]
+    fn test_candidates_example() {
+        let operations = example();
+        let candidates = candidates("decab", &operations);
+        assert!(candidates.contains(&"abcde".to_string()));
+        for candidate in candidates {
+            assert_eq!(scramble(&candidate, operations.iter().copied()), "decab");
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let operations = example();
+        assert!(verify(&operations, INPUT_PART1, INPUT_PART2).is_ok());
+
+        // on five letters, `RotateOn` isn't invertible
+        assert!(matches!(
+            verify(&[Operation::RotateOn('a')], "bcade", "abcde"),
+            Err(Error::OperationRoundTrip {
+                operation: Operation::RotateOn(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_to_lines() {
+        let expect: String = EXAMPLE
+            .lines()
+            .map(|line| format!("{}\n", line.trim()))
+            .collect();
+        assert_eq!(to_lines(&example()), expect);
+        assert_eq!(
+            parse_str::<Operation>(&expect).unwrap().collect::<Vec<_>>(),
+            example()
+        );
+    }
+
+    #[test]
+    fn test_minimize() {
+        use Operation::*;
+
+        let operations = [
+            SwapLetter('a', 'b'),
+            Rotate(Direction::Left, 3),
+            SwapPosition(1, 4),
+            SwapPosition(4, 1),
+            Rotate(Direction::Right, 3),
+            Move(2, 5),
+            Reverse(0, 3),
+            Reverse(0, 3),
+            SwapLetter('c', 'd'),
+            SwapLetter('d', 'c'),
+            RotateOn('e'),
+            Rotate(Direction::Left, 2),
+            Rotate(Direction::Right, 3),
+        ];
+        assert_eq!(
+            minimize(&operations).unwrap(),
+            vec![
+                SwapLetter('a', 'b'),
+                Move(2, 5),
+                RotateOn('e'),
+                Rotate(Direction::Left, 2),
+                Rotate(Direction::Right, 3),
+            ]
+        );
+
+        // nothing in the example cancels
+        assert_eq!(minimize_with(&example(), "abcde").unwrap(), example());
+    }
+
+    #[test]
+    fn test_parse_operations() {
+        assert_eq!(parse_operations(EXAMPLE).unwrap(), example());
+        assert!(matches!(
+            parse_operations("rotate left 1 step\n\nswap position x with position 2\n"),
+            Err(Error::Parse { line_number: 3, .. })
+        ));
+        // too large for a usize
+        assert!(parse_operations("move position 99999999999999999999999 to position 1").is_err());
+    }
+
+    #[test]
+    fn test_check_applicable() {
+        use Operation::*;
+
+        assert!(check_applicable(&example(), INPUT_PART1).is_ok());
+        for &operation in &[
+            SwapPosition(0, 8),
+            Move(8, 0),
+            Reverse(5, 2),
+            Reverse(0, 8),
+            RotateOn('z'),
+            RotateOn('é'),
+            SwapLetter('a', 'é'),
+        ] {
+            assert!(
+                matches!(
+                    part1_from_str(&to_lines(&[operation])),
+                    Err(Error::Inapplicable { .. })
+                ),
+                "{}",
+                operation
+            );
+        }
+        assert!(matches!(
+            check_applicable(&[], "abcdé"),
+            Err(Error::NonAscii(_))
+        ));
+
+        // rotating further than the password's length wraps around
+        let operations = [Rotate(Direction::Left, 17)];
+        assert_eq!(
+            scramble(INPUT_PART1, operations.iter().copied()),
+            "bcdefgha"
+        );
+        assert_eq!(
+            unscramble("bcdefgha", operations.iter().copied()),
+            INPUT_PART1
+        );
+    }
+
+    #[test]
+    fn test_candidates_len_8_unique() {
+        let operations = example();
+        let scrambled = scramble(INPUT_PART1, operations.iter().copied());
+        assert_eq!(candidates(&scrambled, &operations), vec![INPUT_PART1]);
+    }
 }
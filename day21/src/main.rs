@@ -1,19 +1,22 @@
-use aoclib::{config::Config, website::get_input};
-use day21::{part1, part2};
+use aoc2016_solver::Input;
+use day21::{minimize_input, part1, part2, verify_input};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 21;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -21,33 +24,32 @@ struct RunArgs {
     /// run part 2
     #[structopt(long)]
     part2: bool,
-}
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
-    }
+    /// after part 2, check that the input's operations round-trip in both directions
+    #[structopt(long)]
+    verify: bool,
+
+    /// print the input's operations with redundant pairs removed
+    #[structopt(long)]
+    minimize: bool,
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
 
     if !args.no_part1 {
-        part1(&input_path)?;
+        println!("scrambled password: {}", part1(&input_path)?);
     }
     if args.part2 {
-        part2(&input_path)?;
+        println!("unscrambled password: {}", part2(&input_path)?);
+        if args.verify {
+            verify_input(&input_path)?;
+        }
+    }
+    if args.minimize {
+        print!("{}", minimize_input(&input_path)?);
     }
     Ok(())
 }
@@ -1,15 +1,19 @@
+use aoc2016_solver::{Answer, Example, Registry, Solver};
 use aoclib::geometry::{
     map::{ContextInto, Map as GenericMap, Traversable},
     tile::DisplayWidth,
-    Direction, Point,
+    Point,
 };
 use regex::Regex;
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     convert::{TryFrom, TryInto},
-    io::BufRead,
+    fmt,
+    io::{BufRead, Write},
     path::Path,
     str::FromStr,
+    time::Duration,
 };
 
 lazy_static::lazy_static! {
@@ -28,7 +32,8 @@ pub struct RawNode {
 
 impl RawNode {
     fn is_valid(&self) -> bool {
-        self.size == self.used + self.avail
+        i32::try_from(self.x.max(self.y)).is_ok()
+            && self.used.checked_add(self.avail) == Some(self.size)
             && self.use_pct == (self.used as f64 / self.size as f64 * 100.0).floor() as u32
     }
 }
@@ -38,13 +43,14 @@ impl FromStr for RawNode {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let captures = RAW_NODE_RE.captures(s).ok_or(Error::ParseErr)?;
-        // these unwraps should be safe given a properly constructed regex and input
-        let x = captures.name("x").unwrap().as_str().parse().unwrap();
-        let y = captures.name("y").unwrap().as_str().parse().unwrap();
-        let size = captures.name("size").unwrap().as_str().parse().unwrap();
-        let used = captures.name("used").unwrap().as_str().parse().unwrap();
-        let avail = captures.name("avail").unwrap().as_str().parse().unwrap();
-        let use_pct = captures.name("use_pct").unwrap().as_str().parse().unwrap();
+        // every group matches digits, but they may be too many for a u32
+        let field = |name: &str| captures[name].parse::<u32>().map_err(|_| Error::ParseErr);
+        let x = field("x")?;
+        let y = field("y")?;
+        let size = field("size")?;
+        let used = field("used")?;
+        let avail = field("avail")?;
+        let use_pct = field("use_pct")?;
 
         Ok(RawNode {
             x,
@@ -58,30 +64,32 @@ impl FromStr for RawNode {
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-struct Node {
-    position: Point,
-    size: u32,
-    used: u32,
+pub struct Node {
+    pub position: Point,
+    pub size: u32,
+    pub used: u32,
 }
 
 impl Node {
-    fn avail(&self) -> u32 {
+    pub fn avail(&self) -> u32 {
         self.size - self.used
     }
 }
 
-fn parse(input: &Path) -> Result<impl '_ + Iterator<Item = Node>, Error> {
+fn parse(input: &Path) -> Result<Vec<Node>, Error> {
     let file = std::fs::File::open(input)?;
-    let mut reader = std::io::BufReader::new(file);
-    // skip the first two lines
-    let mut buffer = String::new();
-    for _ in 0..2 {
-        let _valid = reader.read_line(&mut buffer).is_ok();
-        debug_assert!(_valid);
-    }
-    aoclib::input::parse_reader::<RawNode, _, _>(reader, input.display())
-        .map(|iter| iter.map(|raw_node| raw_node.try_into().unwrap()))
-        .map_err(Into::into)
+    parse_reader(std::io::BufReader::new(file))
+}
+
+/// Parse the output of `df`, failing on the first line which doesn't describe a valid node.
+fn parse_reader(reader: impl BufRead) -> Result<Vec<Node>, Error> {
+    reader
+        .lines()
+        // skip the command and the column headers
+        .skip(2)
+        .filter(|line| line.as_ref().map_or(true, |line| !line.trim().is_empty()))
+        .map(|line| line?.trim().parse::<RawNode>()?.try_into())
+        .collect()
 }
 
 impl TryFrom<RawNode> for Node {
@@ -130,9 +138,28 @@ impl Default for MapNode {
 
 type Map = GenericMap<MapNode>;
 
-// return a complete map, plus a list of empties
-fn make_map(input: &Path) -> Result<(Map, Vec<Point>), Error> {
-    let nodes: HashMap<_, Node> = parse(input)?.map(|node| (node.position, node)).collect();
+/// The storage cluster: every node, by position.
+pub type Grid = GenericMap<Node>;
+
+/// Read the storage cluster described by `input`.
+pub fn read_grid(input: &Path) -> Result<Grid, Error> {
+    read_nodes(input)
+}
+
+/// Read the storage cluster listed by `reader`, which produces the output of `df`.
+pub fn read_grid_from_reader(reader: impl BufRead) -> Result<Grid, Error> {
+    to_grid(parse_reader(reader)?)
+}
+
+fn read_nodes(input: &Path) -> Result<GenericMap<Node>, Error> {
+    to_grid(parse(input)?)
+}
+
+fn to_grid(nodes: Vec<Node>) -> Result<GenericMap<Node>, Error> {
+    let nodes: HashMap<_, Node> = nodes
+        .into_iter()
+        .map(|node| (node.position, node))
+        .collect();
     let max_x = nodes
         .keys()
         .map(|position| position.x)
@@ -143,89 +170,613 @@ fn make_map(input: &Path) -> Result<(Map, Vec<Point>), Error> {
         .map(|position| position.y)
         .max()
         .ok_or(Error::NoInput)?;
-    let raw_map = GenericMap::procedural(max_x as usize + 1, max_y as usize + 1, |position| {
+    let (width, height) = (max_x as usize + 1, max_y as usize + 1);
+    // positions are never negative, so if there are as many as the grid has points, every
+    // point is covered
+    if width.checked_mul(height) != Some(nodes.len()) {
+        return Err(Error::Incomplete {
+            width,
+            height,
+            nodes: nodes.len(),
+        });
+    }
+    Ok(GenericMap::procedural(width, height, |position| {
         *nodes
             .get(&position)
             .expect("input covers all points in map")
-    });
+    }))
+}
+
+/// An overview of the storage cluster, as a sysadmin would want from `df`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ClusterStats {
+    pub nodes: usize,
+    /// Total capacity of every node, in terabytes.
+    pub size: u64,
+    /// Total data stored, in terabytes.
+    pub used: u64,
+    /// Total free space, in terabytes.
+    pub avail: u64,
+    /// Map of node size to the number of nodes of that size.
+    pub size_histogram: BTreeMap<u32, usize>,
+    /// The most free space on any single node: no data larger than this can ever move.
+    pub max_avail: u32,
+    /// Number of nodes holding more data than `max_avail`, which act as walls in part 2.
+    pub oversized: usize,
+    /// Number of nodes holding no data.
+    pub empty: usize,
+}
+
+impl ClusterStats {
+    /// Whether the data on `node` is too large to move anywhere.
+    pub fn is_oversized(&self, node: &Node) -> bool {
+        node.used > self.max_avail
+    }
+}
+
+/// Compute an overview of the storage cluster made up of `nodes`.
+pub fn cluster_stats(nodes: &[Node]) -> ClusterStats {
+    let mut stats = ClusterStats {
+        nodes: nodes.len(),
+        max_avail: nodes.iter().map(Node::avail).max().unwrap_or_default(),
+        ..ClusterStats::default()
+    };
+    for node in nodes {
+        stats.size += node.size as u64;
+        stats.used += node.used as u64;
+        stats.avail += node.avail() as u64;
+        *stats.size_histogram.entry(node.size).or_default() += 1;
+        if node.used == 0 {
+            stats.empty += 1;
+        }
+    }
+    stats.oversized = nodes.iter().filter(|node| stats.is_oversized(node)).count();
+    stats
+}
+
+impl fmt::Display for ClusterStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} nodes", self.nodes)?;
+        writeln!(
+            f,
+            "  size: {}T, used: {}T, avail: {}T",
+            self.size, self.used, self.avail
+        )?;
+        writeln!(f, "  empty: {}", self.empty)?;
+        writeln!(
+            f,
+            "  oversized: {} (holding more than {}T)",
+            self.oversized, self.max_avail
+        )?;
+        write!(f, "  sizes:")?;
+        for (size, count) in &self.size_histogram {
+            write!(f, "\n  {:>5}T: {}", size, count)?;
+        }
+        Ok(())
+    }
+}
+
+/// Compute an overview of the storage cluster in `input`.
+pub fn read_cluster_stats(input: &Path) -> Result<ClusterStats, Error> {
+    Ok(cluster_stats(&parse(input)?))
+}
+
+// return a complete map, plus a list of empties
+fn make_map(raw_map: &GenericMap<Node>) -> (Map, Vec<Point>) {
+    let nodes: Vec<_> = raw_map.points().map(|position| raw_map[position]).collect();
+    let stats = cluster_stats(&nodes);
     let empties = nodes
         .iter()
-        .filter_map(|(position, node)| (node.used == 0).then(move || *position))
+        .filter(|node| node.used == 0)
+        .map(|node| node.position)
         .collect();
-    Ok((
+    (
+        // data which fits on no node can never move, so the nodes holding it are walls
         Map::procedural(raw_map.width(), raw_map.height(), |position| {
-            if raw_map
-                .orthogonal_adjacencies(position)
-                .all(|neighbor_pos| raw_map[neighbor_pos].size >= raw_map[position].used)
-            {
-                MapNode::Blank
-            } else {
+            if stats.is_oversized(&raw_map[position]) {
                 MapNode::Wall
+            } else {
+                MapNode::Blank
             }
         }),
         empties,
-    ))
+    )
+}
+
+/// Serialize points as `[x, y]`.
+mod point_serde {
+    use aoclib::geometry::Point;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(point: &Point, serializer: S) -> Result<S::Ok, S::Error> {
+        (point.x, point.y).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Point, D::Error> {
+        let (x, y) = Deserialize::deserialize(deserializer)?;
+        Ok(Point::new(x, y))
+    }
+}
+
+/// A single data move: all data on the `from` node is copied onto the `to` node,
+/// after which `from` is empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Move {
+    #[serde(with = "point_serde")]
+    pub from: Point,
+    #[serde(with = "point_serde")]
+    pub to: Point,
+}
+
+/// A reason that a plan cannot be carried out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+pub enum Violation {
+    #[error("{0:?} is outside the grid")]
+    OutOfBounds(Point),
+    #[error("{0:?} and {1:?} are not adjacent")]
+    NotAdjacent(Point, Point),
+    #[error("{0:?} holds no data to move")]
+    NothingToMove(Point),
+    #[error("{needed}T needed on {to:?}, but only {avail}T available")]
+    Overflow { to: Point, needed: u32, avail: u32 },
+    #[error("goal data ends at {0:?}, not the origin")]
+    GoalNotDelivered(Point),
+}
+
+/// A solution to part 2: a sequence of moves which brings the goal data to the origin.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Plan {
+    pub moves: Vec<Move>,
+}
+
+impl Plan {
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Replay this plan on `grid`, checking that every move is between adjacent nodes and fits
+    /// within the capacity of its destination, and that the goal data ends at the origin.
+    pub fn validate(&self, grid: &Grid) -> Result<(), Error> {
+        let mut grid = grid.clone();
+        let mut goal = grid.bottom_right();
+        let (width, height) = (grid.width() as i32, grid.height() as i32);
+        let in_bounds =
+            |point: Point| point.x >= 0 && point.y >= 0 && point.x < width && point.y < height;
+        let invalid = |step, violation| Error::InvalidPlan { step, violation };
+
+        for (step, &Move { from, to }) in self.moves.iter().enumerate() {
+            for &point in &[from, to] {
+                if !in_bounds(point) {
+                    return Err(invalid(step, Violation::OutOfBounds(point)));
+                }
+            }
+            if (to - from).manhattan() != 1 {
+                return Err(invalid(step, Violation::NotAdjacent(from, to)));
+            }
+            let needed = grid[from].used;
+            if needed == 0 {
+                return Err(invalid(step, Violation::NothingToMove(from)));
+            }
+            let avail = grid[to].avail();
+            if needed > avail {
+                return Err(invalid(step, Violation::Overflow { to, needed, avail }));
+            }
+            grid[to].used += needed;
+            grid[from].used = 0;
+            if from == goal {
+                goal = to;
+            }
+        }
+
+        if goal != Point::new(0, 0) {
+            return Err(invalid(self.len(), Violation::GoalNotDelivered(goal)));
+        }
+        Ok(())
+    }
+}
+
+/// Plan the sequence of moves which brings the goal data to the origin, given a starting empty node.
+///
+/// The goal data follows the shortest route to the origin around any walls. Before each step
+/// of that route, the empty node is navigated to the goal data's next position without passing
+/// through the goal data itself; the two are then swapped.
+///
+/// Both routes are shortest on their own, so on an open cluster this is the optimal plan: the
+/// empty node walks around the goal data through the next row. Around walls it takes a detour,
+/// but it doesn't search for a better route for the goal data, so it can miss a plan which
+/// exists.
+fn plan(map: &Map, empty: Point) -> Option<Vec<Move>> {
+    let mut goal = map.bottom_right();
+    let goal_route = map.navigate(goal, Point::new(0, 0))?;
+
+    let mut map = map.clone();
+    let mut moves = Vec::new();
+    let mut empty_at = empty;
+    for step in goal_route {
+        let next_goal = goal + step;
+
+        let under_goal = std::mem::replace(&mut map[goal], MapNode::Wall);
+        let approach = map.navigate(empty_at, next_goal);
+        map[goal] = under_goal;
+
+        for direction in approach? {
+            let to = empty_at + direction;
+            moves.push(Move {
+                from: to,
+                to: empty_at,
+            });
+            empty_at = to;
+        }
+        moves.push(Move {
+            from: goal,
+            to: empty_at,
+        });
+        empty_at = goal;
+        goal = next_goal;
+    }
+
+    Some(moves)
+}
+
+/// Find the shortest plan over all initially-empty nodes.
+pub fn best_plan(grid: &Grid) -> Option<Plan> {
+    let (map, empties) = make_map(grid);
+    tracing::debug!("map:\n{}", map);
+    tracing::debug!(?empties, "empty nodes");
+    empties
+        .into_iter()
+        .filter_map(|empty| {
+            let moves = plan(&map, empty);
+            tracing::trace!(?empty, moves = ?moves.as_ref().map(Vec::len), "planned from empty node");
+            moves
+        })
+        .min_by_key(|moves| moves.len())
+        .map(|moves| Plan { moves })
+}
+
+/// Replan part 2 as though the node at `failed` had failed, treating it as a wall.
+///
+/// The planner routes both the goal data and the empty node around the failed node, so the
+/// plan may be longer than the [`best_plan`]. Returns `None` if the planner finds no way around.
+pub fn simulate_failure(grid: &Grid, failed: Point) -> Option<Plan> {
+    if failed == grid.bottom_right() {
+        // the goal data is lost with its node
+        return None;
+    }
+    let (mut map, empties) = make_map(grid);
+    map[failed] = MapNode::Wall;
+    empties
+        .into_iter()
+        .filter(|&empty| empty != failed)
+        .filter_map(|empty| plan(&map, empty))
+        .min_by_key(|moves| moves.len())
+        .map(|moves| Plan { moves })
+}
+
+/// A node whose failure disrupts part 2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CriticalNode {
+    pub position: Point,
+    /// The length of the best plan avoiding this node, or `None` if there is none.
+    pub moves: Option<usize>,
+}
+
+/// Find every node whose failure makes the goal unreachable, or requires more than `threshold`
+/// moves beyond the best plan.
+///
+/// Only nodes touched by the best plan are considered: the failure of any other node leaves
+/// that plan intact. Returns nothing if there is no plan to begin with.
+pub fn critical_nodes(grid: &Grid, threshold: usize) -> Vec<CriticalNode> {
+    let best = match best_plan(grid) {
+        Some(plan) => plan,
+        None => return Vec::new(),
+    };
+    let mut touched: Vec<_> = best
+        .moves
+        .iter()
+        .flat_map(|m| std::iter::once(m.from).chain(std::iter::once(m.to)))
+        .collect();
+    touched.sort_unstable_by_key(|point| (point.y, point.x));
+    touched.dedup();
+
+    touched
+        .into_iter()
+        .map(|position| CriticalNode {
+            position,
+            moves: simulate_failure(grid, position).map(|plan| plan.len()),
+        })
+        .filter(|node| !matches!(node.moves, Some(moves) if moves <= best.len() + threshold))
+        .collect()
+}
+
+/// Print every node whose failure makes the goal unreachable or costs more than `threshold`
+/// extra moves.
+pub fn print_critical_nodes(input: &Path, threshold: usize) -> Result<(), Error> {
+    let grid = read_grid(input)?;
+    for node in critical_nodes(&grid, threshold) {
+        match node.moves {
+            Some(moves) => println!(
+                "({}, {}): {} moves",
+                node.position.x, node.position.y, moves
+            ),
+            None => println!("({}, {}): unreachable", node.position.x, node.position.y),
+        }
+    }
+    Ok(())
 }
 
-pub fn print_map(input: &Path) -> Result<(), Error> {
-    let (map, empties) = make_map(input)?;
-    println!("map:\n{}", map);
-    println!("empties: {:?}", empties);
+/// Render the cluster in the puzzle's notation: `_` is empty, `G` is the goal data,
+/// `#` is a node too large to move, and the target node is wrapped in parentheses.
+fn render(raw_map: &GenericMap<Node>, walls: &Map, goal: Point) -> String {
+    let mut out = String::new();
+    for y in 0..raw_map.height() as i32 {
+        for x in 0..raw_map.width() as i32 {
+            let position = Point::new(x, y);
+            let symbol = if position == goal {
+                'G'
+            } else if raw_map[position].used == 0 {
+                '_'
+            } else if walls[position] == MapNode::Wall {
+                '#'
+            } else {
+                '.'
+            };
+            if position == Point::new(0, 0) {
+                out.push('(');
+                out.push(symbol);
+                out.push(')');
+            } else {
+                out.push(' ');
+                out.push(symbol);
+                out.push(' ');
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// Render the initial state of the cluster in `input`.
+pub fn render_cluster(input: &Path) -> Result<String, Error> {
+    let raw_map = read_nodes(input)?;
+    let (walls, _) = make_map(&raw_map);
+    let goal = raw_map.bottom_right();
+    Ok(render(&raw_map, &walls, goal))
+}
+
+/// Each state of the cluster as `moves` are carried out, with the position of the goal data,
+/// starting from the initial state.
+///
+/// Moves are not checked; see [`Plan::validate`].
+fn replay<'a>(grid: &Grid, moves: &'a [Move]) -> impl 'a + Iterator<Item = (Grid, Point)> {
+    let mut grid = grid.clone();
+    let mut goal = grid.bottom_right();
+    let initial = (grid.clone(), goal);
+    std::iter::once(initial).chain(moves.iter().map(move |&Move { from, to }| {
+        grid[to].used += grid[from].used;
+        grid[from].used = 0;
+        if from == goal {
+            goal = to;
+        }
+        (grid.clone(), goal)
+    }))
+}
+
+/// Animate the part 2 solution in the terminal, one frame per move.
+pub fn animate(input: &Path, frame_delay: Duration) -> Result<(), Error> {
+    let grid = read_nodes(input)?;
+    let (walls, _) = make_map(&grid);
+    let moves = best_plan(&grid).ok_or(Error::NoSolution)?.moves;
+
+    for (idx, (state, goal)) in replay(&grid, &moves).enumerate() {
+        // clear the terminal and return the cursor home before each frame
+        print!("\x1b[2J\x1b[H");
+        println!("{}", render(&state, &walls, goal));
+        match idx.checked_sub(1).map(|idx| moves[idx]) {
+            Some(Move { from, to }) => {
+                println!("move {}/{}: {:?} -> {:?}", idx, moves.len(), from, to)
+            }
+            None => println!("move 0/{}", moves.len()),
+        }
+        std::thread::sleep(frame_delay);
+    }
     Ok(())
 }
 
-pub fn part1(input: &Path) -> Result<(), Error> {
-    let mut nodes: Vec<_> = parse(input)?.collect();
-    nodes.sort_unstable_by_key(|node| node.avail());
+/// Each node of an animated GIF is a square this many pixels wide, including a one-pixel gap.
+pub const GIF_CELL_SIZE: usize = 8;
+
+// palette indices of the GIF frames
+const GIF_GAP: u8 = 0;
+const GIF_DATA: u8 = 1;
+const GIF_EMPTY: u8 = 2;
+const GIF_GOAL: u8 = 3;
+const GIF_WALL: u8 = 4;
+const GIF_TARGET: u8 = 5;
+
+const GIF_PALETTE: &[u8] = &[
+    0x20, 0x20, 0x20, // gap
+    0x80, 0x80, 0x80, // data
+    0xff, 0xff, 0xff, // empty
+    0xff, 0xc0, 0x00, // goal
+    0x00, 0x00, 0x00, // wall
+    0x00, 0xc0, 0x40, // target
+];
 
-    let mut viable_pairs = 0;
-    for (idx, node) in nodes.iter().enumerate() {
-        for (partner_idx, potential_partner) in nodes.iter().enumerate() {
-            if idx != partner_idx && node.used != 0 && node.used <= potential_partner.avail() {
-                viable_pairs += 1;
+/// Draw a single frame of the GIF animation as palette indices, in the symbols of [`render`].
+///
+/// The target node at the origin is outlined.
+fn gif_frame(grid: &Grid, walls: &Map, goal: Point) -> Vec<u8> {
+    let width = grid.width() * GIF_CELL_SIZE;
+    let mut pixels = vec![GIF_GAP; width * grid.height() * GIF_CELL_SIZE];
+    for position in grid.points() {
+        let color = if position == goal {
+            GIF_GOAL
+        } else if grid[position].used == 0 {
+            GIF_EMPTY
+        } else if walls[position] == MapNode::Wall {
+            GIF_WALL
+        } else {
+            GIF_DATA
+        };
+        let (left, top) = (
+            position.x as usize * GIF_CELL_SIZE,
+            position.y as usize * GIF_CELL_SIZE,
+        );
+        // the last row and column of each cell are left as the gap
+        for y in 0..GIF_CELL_SIZE - 1 {
+            for x in 0..GIF_CELL_SIZE - 1 {
+                let edge = x == 0 || y == 0 || x == GIF_CELL_SIZE - 2 || y == GIF_CELL_SIZE - 2;
+                pixels[(top + y) * width + left + x] = if edge && position == Point::new(0, 0) {
+                    GIF_TARGET
+                } else {
+                    color
+                };
             }
         }
     }
+    pixels
+}
+
+/// Write `plan`, carried out on `grid`, to `writer` as a looping animated GIF with one frame
+/// per move.
+pub fn write_gif(
+    grid: &Grid,
+    plan: &Plan,
+    writer: impl Write,
+    frame_delay: Duration,
+) -> Result<(), Error> {
+    let too_large = || Error::GifTooLarge(grid.width(), grid.height());
+    let width = u16::try_from(grid.width() * GIF_CELL_SIZE).map_err(|_| too_large())?;
+    let height = u16::try_from(grid.height() * GIF_CELL_SIZE).map_err(|_| too_large())?;
+    // GIF delays are in hundredths of a second
+    let delay = u16::try_from(frame_delay.as_millis() / 10).unwrap_or(u16::MAX);
 
-    println!("viable pairs: {}", viable_pairs);
+    let (walls, _) = make_map(grid);
+    let mut encoder = gif::Encoder::new(writer, width, height, GIF_PALETTE)?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+    for (state, goal) in replay(grid, &plan.moves) {
+        let mut frame =
+            gif::Frame::from_indexed_pixels(width, height, &gif_frame(&state, &walls, goal), None);
+        frame.delay = delay;
+        encoder.write_frame(&frame)?;
+    }
     Ok(())
 }
 
-pub fn part2(input: &Path) -> Result<(), Error> {
-    let (map, empties) = make_map(input)?;
-    let (min_steps, starting_position) = empties
-        .into_iter()
-        .filter_map(|starting_position| {
-            // first move the blank tile to the left of the goal tile
-            let goal_tile = map.bottom_right() + Direction::Left;
-            debug_assert_eq!(goal_tile.y, 0);
-            let path_to_goal = map.navigate(starting_position, goal_tile)?;
-
-            // dumb optimization: we can print the map and know that there are no obstacles
-            // between here and the goal, so just use straight math instead of actually
-            // calculating a path
-            Some((
-                // how this formula works:
-                //
-                // - move the empty tile to the immediate left of the goal
-                //   tile in the most direct route possible
-                // - to move the node tile 1 space left and then reset the
-                //   state that the empty is directly to its left, we need
-                //   5 moves, multiplied until the empty tile is at the left edge
-                // - 1 more to move the node tile into the final empty space
-                path_to_goal.len() as i32 + (5 * goal_tile.x) + 1,
-                starting_position,
-            ))
+/// Write the part 2 solution for `input` to `path` as an animated GIF, one frame per move.
+///
+/// Returns the number of frames written.
+pub fn export_gif(input: &Path, path: &Path, frame_delay: Duration) -> Result<usize, Error> {
+    let grid = read_grid(input)?;
+    let plan = best_plan(&grid).ok_or(Error::NoSolution)?;
+    let writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write_gif(&grid, &plan, writer, frame_delay)?;
+    Ok(plan.len() + 1)
+}
+
+/// Compute every viable pair of nodes `(a, b)`: `a` is not empty, and its data would fit on `b`.
+pub fn viable_pairs(nodes: &[Node]) -> impl '_ + Iterator<Item = (Point, Point)> {
+    nodes
+        .iter()
+        .filter(|node| node.used != 0)
+        .flat_map(move |node| {
+            nodes
+                .iter()
+                .filter(move |partner| {
+                    partner.position != node.position && node.used <= partner.avail()
+                })
+                .map(move |partner| (node.position, partner.position))
         })
-        .min()
-        .ok_or(Error::NoSolution)?;
-    println!(
-        "min steps to solution (starting from {:?}): {}",
-        starting_position, min_steps
-    );
+}
+
+pub fn print_viable_pairs(input: &Path) -> Result<(), Error> {
+    let nodes = parse(input)?;
+    for (a, b) in viable_pairs(&nodes) {
+        println!("({}, {}) -> ({}, {})", a.x, a.y, b.x, b.y);
+    }
     Ok(())
 }
 
+/// The number of viable pairs of nodes.
+pub fn part1(input: &Path) -> Result<usize, Error> {
+    let nodes = parse(input)?;
+    Ok(viable_pairs(&nodes).count())
+}
+
+/// The number of viable pairs of the nodes listed by `reader`, which produces the output of `df`.
+pub fn part1_from_reader(reader: impl BufRead) -> Result<usize, Error> {
+    let nodes = parse_reader(reader)?;
+    Ok(viable_pairs(&nodes).count())
+}
+
+/// The shortest plan which brings the goal data to the origin.
+pub fn part2(input: &Path) -> Result<Plan, Error> {
+    best_plan(&read_nodes(input)?).ok_or(Error::NoSolution)
+}
+
+/// The shortest plan which brings the goal data to the origin, for the nodes listed by
+/// `reader`, which produces the output of `df`.
+pub fn part2_from_reader(reader: impl BufRead) -> Result<Plan, Error> {
+    best_plan(&read_grid_from_reader(reader)?).ok_or(Error::NoSolution)
+}
+
+/// Validate the part 2 plan and write it to `path` as JSON.
+///
+/// Returns the number of moves in the plan.
+pub fn export_plan(input: &Path, path: &Path) -> Result<usize, Error> {
+    let grid = read_grid(input)?;
+    let plan = best_plan(&grid).ok_or(Error::NoSolution)?;
+    plan.validate(&grid)?;
+    let writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    serde_json::to_writer_pretty(writer, &plan)?;
+    Ok(plan.len())
+}
+
+/// The example cluster from the puzzle text, as listed by `df`.
+const EXAMPLE: &str = "root@ebhq-gridcenter# df -h
+Filesystem            Size  Used  Avail  Use%
+/dev/grid/node-x0-y0   10T    8T     2T   80%
+/dev/grid/node-x0-y1   11T    6T     5T   54%
+/dev/grid/node-x0-y2   32T   28T     4T   87%
+/dev/grid/node-x1-y0    9T    7T     2T   77%
+/dev/grid/node-x1-y1    8T    0T     8T    0%
+/dev/grid/node-x1-y2   11T    7T     4T   63%
+/dev/grid/node-x2-y0   10T    6T     4T   60%
+/dev/grid/node-x2-y1    9T    8T     1T   88%
+/dev/grid/node-x2-y2    9T    6T     3T   66%
+";
+
+const EXAMPLES: &[Example] = &[Example {
+    part: 2,
+    input: EXAMPLE,
+    answer: "7",
+}];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part1_from_reader(input.as_bytes())?.into())
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        Ok(part2_from_reader(input.as_bytes())?.len().into())
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(22, Solution);
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
@@ -236,6 +787,315 @@ pub enum Error {
     Invalid(RawNode),
     #[error("no input")]
     NoInput,
+    #[error("{nodes} nodes listed for a grid of {width}x{height}")]
+    Incomplete {
+        width: usize,
+        height: usize,
+        nodes: usize,
+    },
+    #[error("plan is invalid at step {step}: {violation}")]
+    InvalidPlan { step: usize, violation: Violation },
+    #[error("writing plan")]
+    Json(#[from] serde_json::Error),
+    #[error("writing animation")]
+    Gif(#[from] gif::EncodingError),
+    #[error("a {0}x{1} cluster is too large to animate")]
+    GifTooLarge(usize, usize),
     #[error("could not find path to get goal data to origin node")]
     NoSolution,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(x, y, size, used)` for each node of the example cluster.
+    const EXAMPLE: &[(i32, i32, u32, u32)] = &[
+        (0, 0, 10, 8),
+        (0, 1, 11, 6),
+        (0, 2, 32, 28),
+        (1, 0, 9, 7),
+        (1, 1, 8, 0),
+        (1, 2, 11, 7),
+        (2, 0, 10, 6),
+        (2, 1, 9, 8),
+        (2, 2, 9, 6),
+    ];
+
+    fn example() -> Grid {
+        let nodes: HashMap<_, _> = EXAMPLE
+            .iter()
+            .map(|&(x, y, size, used)| {
+                let position = Point::new(x, y);
+                (
+                    position,
+                    Node {
+                        position,
+                        size,
+                        used,
+                    },
+                )
+            })
+            .collect();
+        Grid::procedural(3, 3, |position| nodes[&position])
+    }
+
+    #[test]
+    fn test_parse_df() {
+        let grid = read_grid_from_reader(super::EXAMPLE.as_bytes()).unwrap();
+        assert_eq!(grid, example());
+
+        let header = "root@ebhq-gridcenter# df -h\nFilesystem Size Used Avail Use%\n";
+        let parse = |lines: &str| read_grid_from_reader(format!("{}{}", header, lines).as_bytes());
+        assert!(matches!(parse(""), Err(Error::NoInput)));
+        assert!(matches!(
+            parse("/dev/grid/node-x0-y0 10T 8T 2T 80%\nnot a node\n"),
+            Err(Error::ParseErr)
+        ));
+        // too large for a u32
+        assert!(matches!(
+            parse("/dev/grid/node-x0-y0 99999999999T 8T 2T 80%\n"),
+            Err(Error::ParseErr)
+        ));
+        // used and avail overflow rather than summing to size
+        assert!(matches!(
+            parse("/dev/grid/node-x0-y0 10T 4294967295T 11T 0%\n"),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            parse("/dev/grid/node-x3000000000-y0 10T 8T 2T 80%\n"),
+            Err(Error::Invalid(_))
+        ));
+        assert!(matches!(
+            parse("/dev/grid/node-x1-y1 10T 8T 2T 80%\n"),
+            Err(Error::Incomplete {
+                width: 2,
+                height: 2,
+                nodes: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_cluster_stats() {
+        let grid = example();
+        let nodes: Vec<_> = grid.points().map(|position| grid[position]).collect();
+        let stats = cluster_stats(&nodes);
+        assert_eq!(stats.nodes, 9);
+        assert_eq!(stats.size, 109);
+        assert_eq!(stats.used, 76);
+        assert_eq!(stats.avail, 33);
+        assert_eq!(stats.max_avail, 8);
+        assert_eq!(stats.oversized, 1);
+        assert_eq!(stats.empty, 1);
+        assert_eq!(
+            stats.size_histogram.into_iter().collect::<Vec<_>>(),
+            vec![(8, 1), (9, 3), (10, 2), (11, 2), (32, 1)]
+        );
+        assert_eq!(
+            cluster_stats(&nodes[..2]).to_string(),
+            "2 nodes
+  size: 19T, used: 15T, avail: 4T
+  empty: 0
+  oversized: 2 (holding more than 2T)
+  sizes:
+      9T: 1
+     10T: 1"
+        );
+
+        let (map, _) = make_map(&grid);
+        assert_eq!(map[Point::new(0, 2)], MapNode::Wall);
+        assert_eq!(
+            grid.points()
+                .filter(|&position| map[position] == MapNode::Wall)
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_example_plan() {
+        let grid = example();
+        let plan = best_plan(&grid).unwrap();
+        assert_eq!(plan.len(), 7);
+        plan.validate(&grid).unwrap();
+
+        let json = serde_json::to_string(&plan).unwrap();
+        let restored: Plan = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, plan);
+    }
+
+    #[test]
+    fn test_example_moves() {
+        let (map, empties) = make_map(&example());
+        assert_eq!(empties, vec![Point::new(1, 1)]);
+        let moves: Vec<_> = plan(&map, Point::new(1, 1))
+            .unwrap()
+            .into_iter()
+            .map(|Move { from, to }| ((from.x, from.y), (to.x, to.y)))
+            .collect();
+        // as in the puzzle text: bring the empty node next to the goal data, swap them, then
+        // walk the empty node around through the row below to swap them again
+        assert_eq!(
+            moves,
+            vec![
+                ((1, 0), (1, 1)),
+                ((2, 0), (1, 0)),
+                ((2, 1), (2, 0)),
+                ((1, 1), (2, 1)),
+                ((0, 1), (1, 1)),
+                ((0, 0), (0, 1)),
+                ((1, 0), (0, 0)),
+            ]
+        );
+        assert_eq!(best_plan(&example()).unwrap().moves.len(), moves.len());
+    }
+
+    #[test]
+    fn test_replay() {
+        let grid = example();
+        let plan = best_plan(&grid).unwrap();
+        let states: Vec<_> = replay(&grid, &plan.moves).collect();
+        assert_eq!(states.len(), plan.len() + 1);
+        assert_eq!(states[0], (grid.clone(), Point::new(2, 0)));
+        let (last, goal) = states.last().unwrap();
+        assert_eq!(*goal, Point::new(0, 0));
+        assert_eq!(last[Point::new(0, 0)].used, 6);
+        assert_eq!(last[Point::new(0, 1)].used, 8);
+    }
+
+    #[test]
+    fn test_write_gif() {
+        let grid = example();
+        let plan = best_plan(&grid).unwrap();
+        let mut buffer = Vec::new();
+        write_gif(&grid, &plan, &mut buffer, Duration::from_millis(250)).unwrap();
+
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::Indexed);
+        let mut decoder = options.read_info(buffer.as_slice()).unwrap();
+        let edge = 3 * GIF_CELL_SIZE;
+        assert_eq!(
+            (decoder.width() as usize, decoder.height() as usize),
+            (edge, edge)
+        );
+        let pixel = |buffer: &[u8], position: Point| {
+            let center = GIF_CELL_SIZE / 2;
+            buffer[(position.y as usize * GIF_CELL_SIZE + center) * edge
+                + position.x as usize * GIF_CELL_SIZE
+                + center]
+        };
+
+        let mut frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 25);
+            let buffer = &frame.buffer;
+            if frames == 0 {
+                assert_eq!(pixel(buffer, Point::new(2, 0)), GIF_GOAL);
+                assert_eq!(pixel(buffer, Point::new(1, 1)), GIF_EMPTY);
+                assert_eq!(pixel(buffer, Point::new(0, 2)), GIF_WALL);
+                assert_eq!(buffer[0], GIF_TARGET);
+            }
+            if frames == plan.len() {
+                assert_eq!(pixel(buffer, Point::new(0, 0)), GIF_GOAL);
+            }
+            frames += 1;
+        }
+        assert_eq!(frames, plan.len() + 1);
+    }
+
+    #[test]
+    fn test_simulate_failure() {
+        let grid = example();
+
+        // the bottom-left node isn't part of the plan
+        let plan = simulate_failure(&grid, Point::new(0, 2)).unwrap();
+        assert_eq!(plan.len(), 7);
+        plan.validate(&grid).unwrap();
+
+        assert_eq!(simulate_failure(&grid, Point::new(0, 0)), None);
+        assert_eq!(simulate_failure(&grid, Point::new(1, 1)), None);
+
+        // the 3x3 grid has no room for a detour, so every node on the plan is critical
+        let critical = critical_nodes(&grid, 0);
+        assert_eq!(critical.len(), 6);
+        assert!(critical.iter().all(|node| node.moves.is_none()));
+        assert!(!critical
+            .iter()
+            .any(|node| node.position == Point::new(0, 2)));
+    }
+
+    /// A `width` by `height` cluster of identical nodes, of which only `empty` is empty.
+    fn open_cluster(width: usize, height: usize, empty: Point) -> Grid {
+        Grid::procedural(width, height, |position| Node {
+            position,
+            size: 10,
+            used: if position == empty { 0 } else { 6 },
+        })
+    }
+
+    #[test]
+    fn test_failure_reroute() {
+        let grid = open_cluster(4, 3, Point::new(0, 2));
+        let best = best_plan(&grid).unwrap();
+        assert_eq!(best.len(), 15);
+
+        // every walk around the goal data passes through this node, so the empty node must
+        // detour through the bottom row instead
+        let failed = Point::new(2, 1);
+        assert!(best
+            .moves
+            .iter()
+            .any(|m| m.from == failed || m.to == failed));
+        let plan = simulate_failure(&grid, failed).unwrap();
+        assert_eq!(plan.len(), 21);
+        plan.validate(&grid).unwrap();
+        assert!(plan
+            .moves
+            .iter()
+            .all(|m| m.from != failed && m.to != failed));
+
+        let critical = critical_nodes(&grid, 5);
+        assert!(critical.contains(&CriticalNode {
+            position: failed,
+            moves: Some(21),
+        }));
+        assert!(critical_nodes(&grid, 6)
+            .iter()
+            .all(|node| node.position != failed));
+
+        // the goal data can't get past a failure next to the origin
+        assert_eq!(simulate_failure(&grid, Point::new(1, 0)), None);
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_plans() {
+        let grid = example();
+        let plan = best_plan(&grid).unwrap();
+
+        let mut truncated = plan.clone();
+        truncated.moves.pop();
+        assert!(matches!(
+            truncated.validate(&grid),
+            Err(Error::InvalidPlan {
+                violation: Violation::GoalNotDelivered(_),
+                ..
+            })
+        ));
+
+        // the largest node's data fits nowhere
+        let overflow = Plan {
+            moves: vec![Move {
+                from: Point::new(0, 2),
+                to: Point::new(1, 2),
+            }],
+        };
+        assert!(matches!(
+            overflow.validate(&grid),
+            Err(Error::InvalidPlan {
+                step: 0,
+                violation: Violation::Overflow { .. },
+            })
+        ));
+    }
+}
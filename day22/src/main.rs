@@ -1,22 +1,56 @@
-use aoclib::{config::Config, website::get_input};
-use day22::{part1, part2, print_map};
+use aoc2016_solver::Input;
+use day22::{
+    animate, export_gif, export_plan, part1, part2, print_critical_nodes, print_viable_pairs,
+    read_cluster_stats, Error,
+};
 
 use color_eyre::eyre::Result;
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 22;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
-    /// print the input map and list of empties
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
+    /// show diagnostics; repeat for more. `-vv` shows the input map and list of empties
+    #[structopt(short, long, parse(from_occurrences))]
+    verbose: u8,
+
+    /// list every viable pair of nodes
+    #[structopt(long)]
+    viable_pairs: bool,
+
+    /// print an overview of the cluster: capacity, node sizes, and empty and oversized nodes
+    #[structopt(long)]
+    stats: bool,
+
+    /// animate the part 2 solution in the terminal
     #[structopt(long)]
-    print_map: bool,
+    animate: bool,
+
+    /// write the part 2 solution to this path as an animated GIF
+    #[structopt(long, parse(from_os_str))]
+    gif: Option<PathBuf>,
+
+    /// delay between animation frames, in milliseconds
+    #[structopt(long, default_value = "100")]
+    frame_delay: u64,
+
+    /// write the validated part 2 plan to this path as JSON
+    #[structopt(long, parse(from_os_str))]
+    export_plan: Option<PathBuf>,
+
+    /// list nodes whose failure makes part 2 impossible or costs more than this many extra moves
+    #[structopt(long)]
+    critical_nodes: Option<usize>,
 
     /// skip part 1
     #[structopt(long)]
@@ -27,34 +61,43 @@ struct RunArgs {
     part2: bool,
 }
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
-    }
-}
-
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    aoc2016_solver::logging::init(args.verbose);
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
 
-    if args.print_map {
-        print_map(&input_path)?;
+    if args.viable_pairs {
+        print_viable_pairs(&input_path)?;
+    }
+    if args.stats {
+        println!("{}", read_cluster_stats(&input_path)?);
+    }
+    if args.animate {
+        animate(&input_path, Duration::from_millis(args.frame_delay))?;
+    }
+    if let Some(ref path) = args.gif {
+        let frames = export_gif(&input_path, path, Duration::from_millis(args.frame_delay))?;
+        println!("wrote {} frames to {}", frames, path.display());
     }
     if !args.no_part1 {
-        part1(&input_path)?;
+        println!("viable pairs: {}", part1(&input_path)?);
     }
     if args.part2 {
-        part2(&input_path)?;
+        let plan = part2(&input_path)?;
+        let starting_position = plan.moves.first().ok_or(Error::NoSolution)?.to;
+        println!(
+            "min steps to solution (starting from {:?}): {}",
+            starting_position,
+            plan.len()
+        );
+    }
+    if let Some(ref path) = args.export_plan {
+        let moves = export_plan(&input_path, path)?;
+        println!("wrote plan of {} moves to {}", moves, path.display());
+    }
+    if let Some(threshold) = args.critical_nodes {
+        print_critical_nodes(&input_path, threshold)?;
     }
     Ok(())
 }
@@ -1,28 +1,309 @@
-use aoclib::parse;
-use assembunny::{Computer, Instruction, Register};
+use aoc2016_solver::{Answer, Example, Registry, Solver};
+use assembunny::{
+    Computer, Condition, Debugger, ExecutionProfile, Instruction, Integer, Register, Stop,
+    ToggleEvent,
+};
 
 use std::path::Path;
 
-pub fn part1(input: &Path) -> Result<(), Error> {
-    let program: Vec<Instruction> = parse(input)?.collect();
+/// The number of eggs in register `a` for part 1.
+pub const PART1_EGGS: Integer = 7;
+
+/// The number of eggs in register `a` for part 2.
+pub const PART2_EGGS: Integer = 12;
+
+/// Run `program` to termination, with `initial_a` in register `a`.
+fn execute(program: Vec<Instruction>, initial_a: Integer) -> Result<Computer, Error> {
+    let mut computer = Computer::from_program(program);
+    computer[Register::A] = initial_a;
+    computer.try_run_compiled()?;
+    Ok(computer)
+}
+
+/// The value in register `a` after running with 7 eggs, and the work done to get there.
+pub fn part1(input: &Path) -> Result<(Integer, ExecutionProfile), Error> {
+    part1_from_str(&std::fs::read_to_string(input)?)
+}
+
+/// The value in register `a` after running the program `input` with 7 eggs, and the work done
+/// to get there.
+pub fn part1_from_str(input: &str) -> Result<(Integer, ExecutionProfile), Error> {
+    let computer = execute(assembunny::parse_instructions(input)?, PART1_EGGS)?;
+    Ok((computer[Register::A], computer.profile()))
+}
+
+/// The value in register `a` after running with 12 eggs, and the work done to get there.
+pub fn part2(input: &Path) -> Result<(Integer, ExecutionProfile), Error> {
+    part2_from_str(&std::fs::read_to_string(input)?)
+}
+
+/// The value in register `a` after running the program `input` with 12 eggs, and the work done
+/// to get there.
+pub fn part2_from_str(input: &str) -> Result<(Integer, ExecutionProfile), Error> {
+    let computer = execute(assembunny::parse_instructions(input)?, PART2_EGGS)?;
+    Ok((computer[Register::A], computer.profile()))
+}
+
+/// Run `program` to termination with `initial_a` in register `a`, recording every toggle in
+/// the order it happened.
+///
+/// Returns the toggles and the final value in register `a`.
+pub fn toggle_events(
+    program: Vec<Instruction>,
+    initial_a: Integer,
+) -> Result<(Vec<ToggleEvent>, Integer), Error> {
     let mut computer = Computer::from_program(program);
-    computer[Register::A] = 7;
-    computer.run();
-    println!("value in a after termination: {}", computer[Register::A]);
+    computer[Register::A] = initial_a;
+    let mut events = Vec::new();
+    for event in computer.steps() {
+        events.extend(event?.toggle);
+    }
+    Ok((events, computer[Register::A]))
+}
+
+/// Run the program with the given initial value in register `a`, logging every toggle.
+///
+/// This shows how a particular input mutates itself as it runs.
+pub fn audit_toggles(input: &Path, initial_a: Integer) -> Result<(), Error> {
+    let (events, a) = toggle_events(assembunny::read_program(input)?, initial_a)?;
+    for event in events {
+        match event.change {
+            Some((before, after)) => println!(
+                "ip {:>2}: tgl -> {:>2}: `{}` => `{}`",
+                event.ip, event.target, before, after
+            ),
+            None => println!(
+                "ip {:>2}: tgl -> {:>2}: out of range; no effect",
+                event.ip, event.target
+            ),
+        }
+    }
+    println!("value in a after termination: {}", a);
     Ok(())
 }
 
-pub fn part2(input: &Path) -> Result<(), Error> {
-    let program: Vec<Instruction> = parse(input)?.collect();
+/// The registers at the moment execution reached a breakpoint, before the instruction there ran.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BreakpointHit {
+    pub ip: usize,
+    /// How many times execution has reached this breakpoint, including this time.
+    pub hit: usize,
+    pub registers: [Integer; 4],
+}
+
+/// Run `program` with `initial_a` in register `a`, recording the registers each time execution
+/// reaches one of `breakpoints`, or any instruction while one of `conditions` holds.
+///
+/// Each breakpoint is recorded at most `max_hits` times. Once every breakpoint has reached that
+/// limit, execution stops rather than running to termination. Execution also stops after
+/// `max_steps` instructions, as a program may never terminate.
+pub fn breakpoint_hits(
+    program: Vec<Instruction>,
+    initial_a: Integer,
+    breakpoints: &[usize],
+    conditions: &[Condition],
+    max_hits: usize,
+    max_steps: u64,
+) -> Result<Vec<BreakpointHit>, Error> {
     let mut computer = Computer::from_program(program);
-    computer[Register::A] = 12;
-    computer.run();
-    println!("value in a after termination: {}", computer[Register::A]);
+    computer[Register::A] = initial_a;
+    let mut debugger = Debugger::new(computer);
+    for &ip in breakpoints {
+        debugger.break_at(ip);
+    }
+    for &condition in conditions {
+        debugger.break_when(condition);
+    }
+    let mut hits = Vec::new();
+
+    while max_hits > 0
+        && debugger
+            .breakpoints()
+            .iter()
+            .any(|breakpoint| breakpoint.enabled)
+    {
+        let executed = debugger.computer().profile().instructions;
+        let triggered = match debugger.resume_for(max_steps.saturating_sub(executed))? {
+            Stop::Breakpoint(triggered) => triggered,
+            Stop::Halted | Stop::StepLimit => break,
+        };
+        for index in triggered {
+            let hit = debugger.breakpoints()[index].hits;
+            hits.push(BreakpointHit {
+                ip: debugger.ip(),
+                hit,
+                registers: debugger.registers(),
+            });
+            if hit >= max_hits {
+                debugger.set_enabled(index, false);
+            }
+        }
+    }
+
+    Ok(hits)
+}
+
+/// Run the program with the given initial value in register `a`, dumping the registers each
+/// time execution reaches one of `breakpoints` or meets one of `conditions`, at most
+/// `max_hits` times apiece, within the first `max_steps` instructions.
+pub fn debug(
+    input: &Path,
+    initial_a: Integer,
+    breakpoints: &[usize],
+    conditions: &[Condition],
+    max_hits: usize,
+    max_steps: u64,
+) -> Result<(), Error> {
+    let program = assembunny::read_program(input)?;
+    let hits = breakpoint_hits(
+        program,
+        initial_a,
+        breakpoints,
+        conditions,
+        max_hits,
+        max_steps,
+    )?;
+    for hit in hits {
+        let [a, b, c, d] = hit.registers;
+        println!(
+            "ip {:>2} (hit {:>3}): a={} b={} c={} d={}",
+            hit.ip, hit.hit, a, b, c, d
+        );
+    }
     Ok(())
 }
 
+/// The program from the puzzle text.
+const EXAMPLE: &str = "cpy 2 a\ntgl a\ntgl a\ntgl a\ncpy 1 a\ndec a\ndec a\n";
+
+const EXAMPLES: &[Example] = &[Example {
+    part: 1,
+    input: EXAMPLE,
+    answer: "3",
+}];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        let (a, _) = part1_from_str(input)?;
+        Ok(a.into())
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        let (a, _) = part2_from_str(input)?;
+        Ok(a.into())
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(23, Solution);
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Program(#[from] assembunny::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(ip: usize, hit: usize, registers: [Integer; 4]) -> BreakpointHit {
+        BreakpointHit { ip, hit, registers }
+    }
+
+    #[test]
+    fn test_toggle_events() {
+        let program = assembunny::parse_instructions(EXAMPLE).unwrap();
+        let (events, a) = toggle_events(program, PART1_EGGS).unwrap();
+        assert_eq!(a, 3);
+        let summary: Vec<_> = events
+            .iter()
+            .map(|event| {
+                let (before, after) = event.change.unwrap();
+                (
+                    event.ip,
+                    event.target,
+                    before.to_string(),
+                    after.to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (1, 3, "tgl a".to_string(), "inc a".to_string()),
+                (2, 4, "cpy 1 a".to_string(), "jnz 1 a".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_breakpoint_hits() {
+        let program = assembunny::parse_instructions(EXAMPLE).unwrap();
+        // the toggles turn ip 3 into `inc a` and ip 4 into a jump past the end
+        assert_eq!(
+            breakpoint_hits(program.clone(), 0, &[1, 3, 4, 5], &[], 10, u64::MAX).unwrap(),
+            vec![
+                hit(1, 1, [2, 0, 0, 0]),
+                hit(3, 1, [2, 0, 0, 0]),
+                hit(4, 1, [3, 0, 0, 0]),
+            ]
+        );
+        // ip 3 is reached after three instructions, so only ip 1's hit precedes the limit
+        assert_eq!(
+            breakpoint_hits(program, 0, &[1, 3, 4, 5], &[], 10, 2).unwrap(),
+            vec![hit(1, 1, [2, 0, 0, 0])]
+        );
+    }
+
+    #[test]
+    fn test_breakpoint_hits_step_limit() {
+        // this loops forever, and its breakpoint never triggers
+        let program = assembunny::parse_instructions("inc a\njnz 1 -1\ndec a").unwrap();
+        assert_eq!(
+            breakpoint_hits(program, 0, &[2], &[], 10, 1000).unwrap(),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_breakpoint_hit_limit() {
+        let program = assembunny::parse_instructions(
+            "cpy 4 b
+            dec b
+            jnz b -1",
+        )
+        .unwrap();
+        assert_eq!(
+            breakpoint_hits(program.clone(), 0, &[1], &[], 2, u64::MAX).unwrap(),
+            vec![hit(1, 1, [0, 4, 0, 0]), hit(1, 2, [0, 3, 0, 0])]
+        );
+        assert!(breakpoint_hits(program, 0, &[], &[], 2, u64::MAX)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_conditional_breakpoints() {
+        let program = assembunny::parse_instructions(
+            "cpy 4 b
+            dec b
+            jnz b -1",
+        )
+        .unwrap();
+        let conditions = ["b == 1".parse().unwrap()];
+        assert_eq!(
+            breakpoint_hits(program, 0, &[], &conditions, 10, u64::MAX).unwrap(),
+            vec![hit(2, 1, [0, 1, 0, 0]), hit(1, 2, [0, 1, 0, 0])]
+        );
+    }
 }
@@ -1,19 +1,56 @@
-use aoclib::{config::Config, website::get_input};
-use day23::{part1, part2};
+use aoc2016_solver::Input;
+use assembunny::{Condition, ExecutionProfile, Instruction, Integer};
+use day23::{audit_toggles, debug, part1, part2};
 
 use color_eyre::eyre::Result;
-use structopt::StructOpt;
 use std::path::PathBuf;
+use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 23;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
+    /// log every toggle while running with this initial value in register a
+    #[structopt(long)]
+    audit_toggles: Option<Integer>,
+
+    /// dump the registers each time execution reaches this instruction index; may be repeated
+    #[structopt(long = "break")]
+    breakpoints: Vec<usize>,
+
+    /// dump the registers before each instruction while this condition holds, e.g. "c == 0";
+    /// may be repeated
+    #[structopt(long = "break-if")]
+    conditions: Vec<Condition>,
+
+    /// initial value in register a while running to breakpoints
+    #[structopt(long, default_value = "7")]
+    break_a: Integer,
+
+    /// stop reporting a breakpoint after it has been hit this many times
+    #[structopt(long, default_value = "10")]
+    max_hits: usize,
+
+    /// stop running to breakpoints after this many instructions
+    #[structopt(long, default_value = "100000000")]
+    max_steps: u64,
+
+    /// report the instructions executed and cycles consumed by each part
+    #[structopt(long)]
+    profile: bool,
+
+    /// after each part, list this many of its most executed instructions
+    #[structopt(long)]
+    hot: Option<usize>,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -23,31 +60,53 @@ struct RunArgs {
     part2: bool,
 }
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
+fn show(
+    (a, profile): (Integer, ExecutionProfile),
+    show_profile: bool,
+    hot: Option<(&[Instruction], usize)>,
+) {
+    println!("value in a after termination: {}", a);
+    if show_profile {
+        println!(
+            "  executed {} instructions in {} cycles",
+            profile.instructions, profile.cycles
+        );
+    }
+    if let Some((program, top)) = hot {
+        // the program is listed as written: toggles may have changed these instructions by the
+        // time they executed
+        print!("{}", profile.report(program, top));
     }
 }
 
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
 
+    if let Some(initial_a) = args.audit_toggles {
+        audit_toggles(&input_path, initial_a)?;
+    }
+    if !args.breakpoints.is_empty() || !args.conditions.is_empty() {
+        debug(
+            &input_path,
+            args.break_a,
+            &args.breakpoints,
+            &args.conditions,
+            args.max_hits,
+            args.max_steps,
+        )?;
+    }
+    let program = match args.hot {
+        Some(_) => assembunny::read_program(&input_path)?,
+        None => Vec::new(),
+    };
+    let hot = args.hot.map(|top| (program.as_slice(), top));
     if !args.no_part1 {
-        part1(&input_path)?;
+        show(part1(&input_path)?, args.profile, hot);
     }
     if args.part2 {
-        part2(&input_path)?;
+        show(part2(&input_path)?, args.profile, hot);
     }
     Ok(())
 }
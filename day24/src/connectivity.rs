@@ -0,0 +1,152 @@
+use crate::{poi_positions, Error, Map, Tile};
+use aoclib::geometry::Point;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+/// The structure of the ducts reachable from POI 0.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectivityReport {
+    /// POIs which can't be reached from POI 0, in ascending order.
+    pub unreachable: Vec<u8>,
+    /// Reachable tiles with exactly one open neighbor: the closed ends of dead-end corridors.
+    pub dead_ends: Vec<Point>,
+    /// Reachable tiles whose removal would split the reachable ducts in two.
+    pub articulation_points: Vec<Point>,
+}
+
+impl ConnectivityReport {
+    /// `true` when every POI is reachable from POI 0.
+    pub fn is_connected(&self) -> bool {
+        self.unreachable.is_empty()
+    }
+}
+
+impl fmt::Display for ConnectivityReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let show = |points: &[Point]| {
+            points
+                .iter()
+                .map(|point| format!("({}, {})", point.x, point.y))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        writeln!(f, "unreachable POIs: {:?}", self.unreachable)?;
+        writeln!(
+            f,
+            "dead ends ({}): {}",
+            self.dead_ends.len(),
+            show(&self.dead_ends)
+        )?;
+        write!(
+            f,
+            "articulation points ({}): {}",
+            self.articulation_points.len(),
+            show(&self.articulation_points)
+        )
+    }
+}
+
+/// The open tiles reachable from `start`, in map order, and for each its reachable neighbors.
+fn reachable_graph(map: &Map, start: Point) -> (Vec<Point>, Vec<Vec<usize>>) {
+    let mut seen = HashSet::new();
+    seen.insert(start);
+    let mut queue = vec![start];
+    while let Some(point) = queue.pop() {
+        for neighbor in map.orthogonal_adjacencies(point) {
+            if map[neighbor] != Tile::Wall && seen.insert(neighbor) {
+                queue.push(neighbor);
+            }
+        }
+    }
+
+    let nodes: Vec<_> = map.points().filter(|point| seen.contains(point)).collect();
+    let ids: HashMap<_, _> = nodes.iter().enumerate().map(|(id, &p)| (p, id)).collect();
+    let adjacencies = nodes
+        .iter()
+        .map(|&point| {
+            map.orthogonal_adjacencies(point)
+                .filter_map(|neighbor| ids.get(&neighbor).copied())
+                .collect()
+        })
+        .collect();
+    (nodes, adjacencies)
+}
+
+/// Find the articulation points of a connected graph, by Tarjan's algorithm.
+///
+/// The depth-first search is iterative, as duct maps are large enough to overflow the stack.
+fn articulation_points(adjacencies: &[Vec<usize>], root: usize) -> Vec<bool> {
+    const UNSEEN: usize = !0;
+    let n = adjacencies.len();
+    let mut discovered = vec![UNSEEN; n];
+    let mut low = vec![0; n];
+    let mut is_articulation = vec![false; n];
+    let mut root_children = 0;
+
+    discovered[root] = 0;
+    let mut timer = 1;
+    // (node, parent, index of next neighbor to visit)
+    let mut stack = vec![(root, UNSEEN, 0)];
+    while let Some(&(node, parent, next)) = stack.last() {
+        if let Some(&neighbor) = adjacencies[node].get(next) {
+            stack.last_mut().expect("stack is not empty").2 += 1;
+            if discovered[neighbor] == UNSEEN {
+                discovered[neighbor] = timer;
+                low[neighbor] = timer;
+                timer += 1;
+                stack.push((neighbor, node, 0));
+            } else if neighbor != parent {
+                low[node] = low[node].min(discovered[neighbor]);
+            }
+        } else {
+            stack.pop();
+            if parent == root {
+                root_children += 1;
+            } else if parent != UNSEEN {
+                low[parent] = low[parent].min(low[node]);
+                if low[node] >= discovered[parent] {
+                    is_articulation[parent] = true;
+                }
+            }
+        }
+    }
+    is_articulation[root] = root_children > 1;
+    is_articulation
+}
+
+/// Analyze the ducts reachable from POI 0.
+pub fn connectivity(map: &Map) -> Result<ConnectivityReport, Error> {
+    let positions = poi_positions(map)?;
+    let (nodes, adjacencies) = reachable_graph(map, positions[0]);
+    let root = nodes
+        .iter()
+        .position(|&point| point == positions[0])
+        .expect("POI 0 reaches itself");
+
+    let unreachable = positions
+        .iter()
+        .enumerate()
+        .filter(|(_, position)| !nodes.contains(position))
+        .map(|(poi, _)| poi as u8)
+        .collect();
+    let dead_ends = nodes
+        .iter()
+        .zip(&adjacencies)
+        .filter(|(_, neighbors)| neighbors.len() == 1)
+        .map(|(&point, _)| point)
+        .collect();
+    let articulation_points = nodes
+        .iter()
+        .zip(articulation_points(&adjacencies, root))
+        .filter(|(_, is_articulation)| *is_articulation)
+        .map(|(&point, _)| point)
+        .collect();
+
+    Ok(ConnectivityReport {
+        unreachable,
+        dead_ends,
+        articulation_points,
+    })
+}
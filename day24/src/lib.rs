@@ -1,13 +1,31 @@
+use aoc2016_solver::{Answer, Example, Registry, Solver};
 use aoclib::geometry::{
     map::{ContextFrom, Traversable},
     tile::DisplayWidth,
-    Map as GenericMap,
+    Map as GenericMap, Point,
 };
 
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    io::BufRead,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+#[cfg(feature = "parallelism")]
+use rayon::prelude::*;
+
+mod connectivity;
+mod maze_builder;
+mod path_tree;
+pub use connectivity::{connectivity, ConnectivityReport};
+pub use maze_builder::MazeBuilder;
+pub use path_tree::{
+    geodesics, render_geodesics, shortest_path_tree, Geodesic, PathTree, TreeNode,
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, parse_display::Display, parse_display::FromStr)]
-enum Tile {
+pub enum Tile {
     #[display("#")]
     Wall,
     #[display(".")]
@@ -40,48 +58,119 @@ impl ContextFrom<Tile> for Traversable {
     }
 }
 
-type Map = GenericMap<Tile>;
+pub type Map = GenericMap<Tile>;
 
-pub fn traveling_salesman(input: &Path, return_to_start: bool) -> Result<usize, Error> {
+fn read_map(input: &Path) -> Result<Map, Error> {
     let file = std::fs::File::open(input)?;
     let reader = std::io::BufReader::new(file);
-    let map = Map::try_from(reader)?;
+    Map::try_from(reader).map_err(Into::into)
+}
+
+pub fn traveling_salesman(input: &Path, return_to_start: bool) -> Result<usize, Error> {
+    shortest_tour(&read_map(input)?, return_to_start)
+}
+
+/// The position of each POI on the map, indexed by POI.
+fn poi_positions(map: &Map) -> Result<Vec<Point>, Error> {
     let pois: HashMap<_, _> = map
         .points()
         .filter_map(|point| map[point].as_poi().map(|poi| (poi, point)))
         .collect();
-    let mut distances = HashMap::new();
-    let mut distance_between = |mut a: u8, mut b: u8| {
-        // enforce: a is always less than b
-        if a > b {
-            std::mem::swap(&mut a, &mut b);
-        }
-        *distances.entry((a, b)).or_insert_with(|| {
-            let a = pois[&a];
-            let b = pois[&b];
-            map.navigate(a, b)
-                .map(|directions| directions.len())
-                .unwrap_or(!0)
-        })
-    };
     let max_poi = *pois.keys().max().ok_or(Error::NoPois)?;
-    let mut ordering: Vec<_> = (1..=max_poi).collect();
-    let mut min_path_len = !0;
-
-    permutohedron::heap_recursive(&mut ordering, |ordering| {
-        let mut path_len = distance_between(0, ordering[0]);
-        for window in ordering.windows(2) {
-            if path_len > min_path_len {
-                return;
+    (0..=max_poi)
+        .map(|poi| pois.get(&poi).copied().ok_or(Error::MissingPoi(poi)))
+        .collect()
+}
+
+/// Compute the distances between each pair of POIs on the map.
+///
+/// Fails if any POI is unreachable from POI 0.
+fn distances(map: &Map) -> Result<Vec<Vec<usize>>, Error> {
+    let report = connectivity(map)?;
+    if !report.is_connected() {
+        return Err(Error::Unreachable(report.unreachable));
+    }
+    let positions = poi_positions(map)?;
+
+    let mut distances = vec![vec![0; positions.len()]; positions.len()];
+    for a in 0..positions.len() {
+        for b in a + 1..positions.len() {
+            let distance = map
+                .navigate(positions[a], positions[b])
+                .map(|directions| directions.len())
+                .expect("all POIs are reachable from POI 0, so from each other");
+            distances[a][b] = distance;
+            distances[b][a] = distance;
+        }
+    }
+    Ok(distances)
+}
+
+/// Search every tour which visits `first` immediately after POI 0, lowering `best` to the
+/// length of the shortest found.
+///
+/// Partial tours are abandoned as soon as they're longer than `best`, which may be lowered
+/// concurrently by searches from other first POIs.
+///
+/// Fails with `Error::Overflow` if the length of a tour exceeds `usize`.
+fn search_tours_from(
+    distances: &[Vec<usize>],
+    first: usize,
+    return_to_start: bool,
+    best: &AtomicUsize,
+) -> Result<(), Error> {
+    let mut rest: Vec<_> = (1..distances.len()).filter(|&poi| poi != first).collect();
+
+    // the length of the tour visiting `rest` after `first`, or `None` if it is abandoned
+    let tour_len = |rest: &[usize]| {
+        let mut path_len = distances[0][first];
+        let mut prev = first;
+        for &poi in rest {
+            if path_len > best.load(Ordering::Relaxed) {
+                return Ok(None);
             }
-            path_len += distance_between(window[0], window[1]);
+            path_len = checked_add(path_len, distances[prev][poi])?;
+            prev = poi;
         }
         if return_to_start {
-            path_len += distance_between(ordering.last().copied().unwrap_or_default(), 0);
+            path_len = checked_add(path_len, distances[prev][0])?;
+        }
+        Ok(Some(path_len))
+    };
+
+    let mut result = Ok(());
+    permutohedron::heap_recursive(&mut rest, |rest| {
+        if result.is_ok() {
+            match tour_len(rest) {
+                Ok(Some(path_len)) => {
+                    best.fetch_min(path_len, Ordering::Relaxed);
+                }
+                Ok(None) => {}
+                Err(err) => result = Err(err),
+            }
         }
-        min_path_len = min_path_len.min(path_len);
     });
+    result
+}
+
+/// Find the length of the shortest tour visiting every POI on the map, starting at POI 0.
+///
+/// With the `parallelism` feature, the tours are partitioned by the first POI visited after 0,
+/// and each partition is searched on its own thread.
+pub fn shortest_tour(map: &Map, return_to_start: bool) -> Result<usize, Error> {
+    let distances = distances(map)?;
+    if distances.len() == 1 {
+        return Ok(0);
+    }
+
+    let best = AtomicUsize::new(!0);
+    #[cfg(feature = "parallelism")]
+    let firsts = (1..distances.len()).into_par_iter();
+    #[cfg(not(feature = "parallelism"))]
+    let mut firsts = 1..distances.len();
+    firsts.try_for_each(|first| search_tours_from(&distances, first, return_to_start, &best))?;
 
+    let min_path_len = best.into_inner();
     if min_path_len == !0 {
         return Err(Error::NoSolution);
     }
@@ -89,16 +178,157 @@ pub fn traveling_salesman(input: &Path, return_to_start: bool) -> Result<usize,
     Ok(min_path_len)
 }
 
-pub fn part1(input: &Path) -> Result<(), Error> {
-    let min_path_len = traveling_salesman(input, false)?;
-    println!("min path len: {}", min_path_len);
-    Ok(())
+/// Bounds on the length of the shortest tour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TourBounds {
+    /// The weight of the minimum spanning tree of the POIs.
+    ///
+    /// Every tour contains a spanning tree, so no tour can be shorter than this.
+    pub lower: usize,
+    /// The length of the tour which visits the POIs in depth-first order over the minimum
+    /// spanning tree, starting at POI 0.
+    ///
+    /// This tour exists, so the shortest tour can't be longer. Because distances on the map
+    /// obey the triangle inequality, it is also never more than twice the lower bound.
+    pub upper: usize,
 }
 
-pub fn part2(input: &Path) -> Result<(), Error> {
-    let min_path_len = traveling_salesman(input, true)?;
-    println!("min path len (return to start): {}", min_path_len);
-    Ok(())
+impl TourBounds {
+    pub fn contains(&self, tour_len: usize) -> bool {
+        self.lower <= tour_len && tour_len <= self.upper
+    }
+}
+
+/// Compute bounds on the shortest tour visiting every POI on the map, starting at POI 0.
+///
+/// This takes polynomial time, so unlike `shortest_tour` it remains practical for large maps.
+pub fn tour_bounds(map: &Map, return_to_start: bool) -> Result<TourBounds, Error> {
+    bounds_from_distances(&distances(map)?, return_to_start)
+}
+
+/// Compute tour bounds from the pairwise distances between POIs.
+///
+/// Fails with `Error::Overflow` rather than wrapping if either bound exceeds `usize`.
+fn bounds_from_distances(
+    distances: &[Vec<usize>],
+    return_to_start: bool,
+) -> Result<TourBounds, Error> {
+    let n = distances.len();
+
+    // Prim's algorithm, rooted at POI 0
+    let mut in_tree = vec![false; n];
+    let mut best = vec![(!0, 0); n]; // (distance to tree, nearest tree node)
+    let mut children = vec![Vec::new(); n];
+    best[0] = (0, 0);
+    let mut lower = 0;
+    for _ in 0..n {
+        let (node, &(distance, parent)) = best
+            .iter()
+            .enumerate()
+            .filter(|(node, _)| !in_tree[*node])
+            .min_by_key(|(_, (distance, _))| *distance)
+            .expect("a node remains outside the tree each iteration");
+        if distance == !0 {
+            return Err(Error::NoSolution);
+        }
+        in_tree[node] = true;
+        lower = checked_add(lower, distance)?;
+        if node != 0 {
+            children[parent].push(node);
+        }
+        for other in 0..n {
+            if !in_tree[other] && distances[node][other] < best[other].0 {
+                best[other] = (distances[node][other], node);
+            }
+        }
+    }
+
+    // walk the tree depth-first, skipping nodes already visited
+    let mut order = Vec::with_capacity(n);
+    let mut stack = vec![0];
+    while let Some(node) = stack.pop() {
+        order.push(node);
+        stack.extend(children[node].iter().rev());
+    }
+    if return_to_start {
+        order.push(0);
+    }
+    let upper = order
+        .windows(2)
+        .map(|window| distances[window[0]][window[1]])
+        .try_fold(0, checked_add)?;
+
+    Ok(TourBounds { lower, upper })
+}
+
+fn checked_add(a: usize, b: usize) -> Result<usize, Error> {
+    a.checked_add(b).ok_or(Error::Overflow)
+}
+
+fn solve(map: &Map, return_to_start: bool) -> Result<(usize, TourBounds), Error> {
+    Ok((
+        shortest_tour(map, return_to_start)?,
+        tour_bounds(map, return_to_start)?,
+    ))
+}
+
+/// The length of the shortest route visiting every POI, and bounds on it.
+pub fn part1(input: &Path) -> Result<(usize, TourBounds), Error> {
+    solve(&read_map(input)?, false)
+}
+
+/// The length of the shortest route visiting every POI on the map read from `reader`, and
+/// bounds on it.
+pub fn part1_from_reader(reader: impl BufRead) -> Result<(usize, TourBounds), Error> {
+    solve(&Map::try_from(reader)?, false)
+}
+
+/// The length of the shortest route visiting every POI and returning to the start, and bounds
+/// on it.
+pub fn part2(input: &Path) -> Result<(usize, TourBounds), Error> {
+    solve(&read_map(input)?, true)
+}
+
+/// The length of the shortest route visiting every POI on the map read from `reader` and
+/// returning to the start, and bounds on it.
+pub fn part2_from_reader(reader: impl BufRead) -> Result<(usize, TourBounds), Error> {
+    solve(&Map::try_from(reader)?, true)
+}
+
+/// The example map from the puzzle text.
+const EXAMPLE: &str = "###########
+#0.1.....2#
+#.#######.#
+#4.......3#
+###########
+";
+
+const EXAMPLES: &[Example] = &[Example {
+    part: 1,
+    input: EXAMPLE,
+    answer: "14",
+}];
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        let map = Map::try_from(input.as_bytes())?;
+        Ok(shortest_tour(&map, false)?.into())
+    }
+
+    fn part2(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        let map = Map::try_from(input.as_bytes())?;
+        Ok(shortest_tour(&map, true)?.into())
+    }
+
+    fn examples(&self) -> &'static [Example] {
+        EXAMPLES
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(24, Solution);
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -109,6 +339,240 @@ pub enum Error {
     MapRead(#[from] aoclib::geometry::map::MapConversionErr),
     #[error("no points of interest found in the input map")]
     NoPois,
+    #[error("point of interest {0} is missing from the map")]
+    MissingPoi(u8),
+    #[error("points of interest {0:?} are unreachable from point of interest 0")]
+    Unreachable(Vec<u8>),
     #[error("no solution found")]
     NoSolution,
+    #[error("tour length overflows usize")]
+    Overflow,
+    #[error("a {width}x{height} maze has no room inside its border")]
+    MazeTooSmall { width: usize, height: usize },
+    #[error("point of interest {0} is not a single digit")]
+    PoiNotADigit(u8),
+    #[error("a maze with {open} open tiles has no room for {pois} points of interest")]
+    NoRoomForPois { pois: u8, open: usize },
+    #[error("writing path tree")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Print the connectivity report for the input map.
+pub fn print_connectivity(input: &Path) -> Result<(), Error> {
+    println!("{}", connectivity(&read_map(input)?)?);
+    Ok(())
+}
+
+/// Write the shortest-path tree from POI 0 of the input map to `path` as JSON.
+pub fn export_path_tree(input: &Path, path: &Path) -> Result<(), Error> {
+    let tree = shortest_path_tree(&read_map(input)?)?;
+    let writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    serde_json::to_writer_pretty(writer, &tree)?;
+    println!(
+        "wrote tree of {} tiles to {}",
+        tree.nodes.len(),
+        path.display()
+    );
+    Ok(())
+}
+
+/// Print the input map with the geodesics between every pair of POIs marked.
+pub fn print_geodesics(input: &Path) -> Result<(), Error> {
+    print!("{}", render_geodesics(&read_map(input)?)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn example() -> MazeBuilder {
+        let mut builder = MazeBuilder::new(11, 5).unwrap();
+        builder
+            .poi(1, 1, 0)
+            .poi(3, 1, 1)
+            .poi(9, 1, 2)
+            .poi(9, 3, 3)
+            .poi(1, 3, 4);
+        for x in 2..9 {
+            builder.wall(x, 2);
+        }
+        builder
+    }
+
+    #[test]
+    fn test_builder_text_format() {
+        assert_eq!(example().to_string(), EXAMPLE);
+    }
+
+    #[test]
+    fn test_example() {
+        let map = example().build().unwrap();
+        assert_eq!(shortest_tour(&map, false).unwrap(), 14);
+    }
+
+    #[test]
+    fn test_connectivity() {
+        let report = connectivity(&example().build().unwrap()).unwrap();
+        assert!(report.is_connected());
+        assert!(report.dead_ends.is_empty());
+        assert!(report.articulation_points.is_empty());
+
+        // closing the west end of the loop turns it into one long corridor
+        let mut builder = example();
+        builder.wall(1, 2);
+        let map = builder.build().unwrap();
+        let report = connectivity(&map).unwrap();
+        let positions = poi_positions(&map).unwrap();
+        let mut dead_ends = report.dead_ends.clone();
+        dead_ends.sort_unstable_by_key(|point| (point.x, point.y));
+        let mut expect = vec![positions[0], positions[4]];
+        expect.sort_unstable_by_key(|point| (point.x, point.y));
+        assert_eq!(dead_ends, expect);
+        // every other open tile is on the only path between them
+        assert_eq!(report.articulation_points.len(), 17);
+        assert!(!report.articulation_points.contains(&positions[0]));
+
+        // wall off POI 1; the others remain reachable the long way around
+        let mut builder = example();
+        builder.wall(2, 1).wall(4, 1);
+        let map = builder.build().unwrap();
+        assert_eq!(connectivity(&map).unwrap().unreachable, vec![1]);
+        assert!(matches!(
+            shortest_tour(&map, false),
+            Err(Error::Unreachable(pois)) if pois == vec![1]
+        ));
+    }
+
+    #[test]
+    fn test_maze_too_small() {
+        assert!(matches!(
+            MazeBuilder::new(2, 5),
+            Err(Error::MazeTooSmall {
+                width: 2,
+                height: 5
+            })
+        ));
+        assert!(MazeBuilder::new(3, 0).is_err());
+        assert!(MazeBuilder::new(3, 3).is_ok());
+    }
+
+    #[test]
+    fn test_few_pois() {
+        let mut builder = MazeBuilder::new(5, 3).unwrap();
+        builder.poi(1, 1, 0);
+        assert_eq!(shortest_tour(&builder.build().unwrap(), true).unwrap(), 0);
+        builder.poi(3, 1, 1);
+        let map = builder.build().unwrap();
+        assert_eq!(shortest_tour(&map, false).unwrap(), 2);
+        assert_eq!(shortest_tour(&map, true).unwrap(), 4);
+    }
+
+    #[test]
+    fn test_example_bounds() {
+        let map = example().build().unwrap();
+        let bounds = tour_bounds(&map, false).unwrap();
+        // the tree is 0-1-2-3 plus 0-4; walking it as 0 1 2 3 4 costs 8 to return from 3 to 4
+        assert_eq!(
+            bounds,
+            TourBounds {
+                lower: 12,
+                upper: 18
+            }
+        );
+        assert!(bounds.contains(14));
+    }
+
+    #[test]
+    fn test_tour_overflow() {
+        let far = usize::MAX / 2 + 1;
+        let distances = vec![vec![0, far, far], vec![far, 0, far], vec![far, far, 0]];
+        let best = AtomicUsize::new(!0);
+        assert!(matches!(
+            search_tours_from(&distances, 1, false, &best),
+            Err(Error::Overflow)
+        ));
+        assert_eq!(best.into_inner(), !0);
+    }
+
+    #[test]
+    fn test_bounds_overflow() {
+        let far = usize::MAX / 2 + 1;
+        let distances = vec![vec![0, far, far], vec![far, 0, far], vec![far, far, 0]];
+        assert!(matches!(
+            bounds_from_distances(&distances, false),
+            Err(Error::Overflow)
+        ));
+
+        let distances = vec![vec![0, far], vec![far, 0]];
+        assert!(matches!(
+            bounds_from_distances(&distances, true),
+            Err(Error::Overflow)
+        ));
+        assert_eq!(
+            bounds_from_distances(&distances, false).unwrap(),
+            TourBounds {
+                lower: far,
+                upper: far
+            }
+        );
+    }
+
+    #[test]
+    fn test_random_maze_bounds() {
+        let mut rng = StdRng::seed_from_u64(2016);
+        for _ in 0..10 {
+            let builder = MazeBuilder::random(21, 11, 6, &mut rng).unwrap();
+            let map = builder.build().unwrap();
+            for &return_to_start in &[false, true] {
+                let tour = shortest_tour(&map, return_to_start).unwrap();
+                let bounds = tour_bounds(&map, return_to_start).unwrap();
+                assert!(
+                    bounds.contains(tour),
+                    "{} not within {:?}:\n{}",
+                    tour,
+                    bounds,
+                    builder
+                );
+                assert!(bounds.upper <= 2 * bounds.lower);
+            }
+        }
+    }
+
+    #[test]
+    fn test_random_maze_errors() {
+        let mut rng = StdRng::seed_from_u64(2016);
+        assert!(matches!(
+            MazeBuilder::random(2, 11, 1, &mut rng),
+            Err(Error::MazeTooSmall {
+                width: 2,
+                height: 11
+            })
+        ));
+        assert!(matches!(
+            MazeBuilder::random(21, 11, 11, &mut rng),
+            Err(Error::PoiNotADigit(10))
+        ));
+        // a 3x3 maze has a single open tile
+        assert!(matches!(
+            MazeBuilder::random(3, 3, 2, &mut rng),
+            Err(Error::NoRoomForPois { pois: 2, open: 1 })
+        ));
+        assert!(MazeBuilder::random(3, 3, 1, &mut rng).is_ok());
+    }
+
+    #[test]
+    fn test_random_mazes_are_solvable() {
+        let mut rng = StdRng::seed_from_u64(2016);
+        for _ in 0..10 {
+            let builder = MazeBuilder::random(21, 11, 5, &mut rng).unwrap();
+            let map = builder.build().unwrap();
+            assert!(
+                shortest_tour(&map, true).is_ok(),
+                "unsolvable maze:\n{}",
+                builder
+            );
+        }
+    }
 }
@@ -1,19 +1,34 @@
-use aoclib::{config::Config, website::get_input};
-use day24::{part1, part2};
+use aoc2016_solver::Input;
+use day24::{export_path_tree, part1, part2, print_connectivity, print_geodesics};
 
 use color_eyre::eyre::Result;
-use structopt::StructOpt;
 use std::path::PathBuf;
+use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 24;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
+    /// report unreachable POIs, dead ends, and articulation points of the ducts
+    #[structopt(long)]
+    connectivity: bool,
+
+    /// show the maze with the shortest paths between every pair of POIs marked
+    #[structopt(long)]
+    geodesics: bool,
+
+    /// write the shortest-path tree from POI 0 to this path as JSON
+    #[structopt(long, parse(from_os_str))]
+    export_tree: Option<PathBuf>,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
@@ -23,31 +38,34 @@ struct RunArgs {
     part2: bool,
 }
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
-    }
-}
-
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
+
+    if args.connectivity {
+        print_connectivity(&input_path)?;
+    }
+    if args.geodesics {
+        print_geodesics(&input_path)?;
+    }
+    if let Some(ref path) = args.export_tree {
+        export_path_tree(&input_path, path)?;
+    }
 
     if !args.no_part1 {
-        part1(&input_path)?;
+        let (min_path_len, bounds) = part1(&input_path)?;
+        println!(
+            "min path len: {} (bounds: {}..={})",
+            min_path_len, bounds.lower, bounds.upper
+        );
     }
     if args.part2 {
-        part2(&input_path)?;
+        let (min_path_len, bounds) = part2(&input_path)?;
+        println!(
+            "min path len (return to start): {} (bounds: {}..={})",
+            min_path_len, bounds.lower, bounds.upper
+        );
     }
     Ok(())
 }
@@ -0,0 +1,178 @@
+use crate::{Error, Map, Tile};
+use rand::{seq::SliceRandom, Rng};
+use std::fmt;
+
+/// Programmatically construct duct mazes in the puzzle's text format.
+///
+/// Coordinates are `(x, y)` with the origin at the top left, as in the text format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MazeBuilder {
+    width: usize,
+    height: usize,
+    tiles: Vec<Tile>,
+}
+
+impl MazeBuilder {
+    /// Create a maze of the given dimensions: open, except for a wall around the border.
+    ///
+    /// Fails with `Error::MazeTooSmall` unless both dimensions are at least 3, leaving room for
+    /// something inside the border.
+    pub fn new(width: usize, height: usize) -> Result<MazeBuilder, Error> {
+        if width < 3 || height < 3 {
+            return Err(Error::MazeTooSmall { width, height });
+        }
+        let mut builder = MazeBuilder {
+            width,
+            height,
+            tiles: vec![Tile::Empty; width * height],
+        };
+        for x in 0..width {
+            builder.wall(x, 0).wall(x, height - 1);
+        }
+        for y in 0..height {
+            builder.wall(0, y).wall(width - 1, y);
+        }
+        Ok(builder)
+    }
+
+    /// Generate a random maze with `pois` points of interest.
+    ///
+    /// Every open tile in the generated maze is reachable from every other, so all POIs
+    /// are always mutually reachable.
+    ///
+    /// Fails with `Error::MazeTooSmall` just as [`MazeBuilder::new`] does, with
+    /// `Error::PoiNotADigit` if there are more than 10 POIs, and with `Error::NoRoomForPois` if
+    /// the maze has too few open tiles to contain them all.
+    pub fn random<R: Rng>(
+        width: usize,
+        height: usize,
+        pois: u8,
+        rng: &mut R,
+    ) -> Result<MazeBuilder, Error> {
+        if width < 3 || height < 3 {
+            return Err(Error::MazeTooSmall { width, height });
+        }
+        if pois > 10 {
+            return Err(Error::PoiNotADigit(pois - 1));
+        }
+
+        let mut builder = MazeBuilder {
+            width,
+            height,
+            tiles: vec![Tile::Wall; width * height],
+        };
+
+        // carve a perfect maze through the odd-coordinate cells with a randomized depth-first search
+        let mut stack = vec![(1, 1)];
+        builder.open(1, 1);
+        while let Some(&(x, y)) = stack.last() {
+            let mut neighbors = Vec::with_capacity(4);
+            if x >= 3 {
+                neighbors.push((x - 2, y));
+            }
+            if y >= 3 {
+                neighbors.push((x, y - 2));
+            }
+            if x + 2 < width - 1 {
+                neighbors.push((x + 2, y));
+            }
+            if y + 2 < height - 1 {
+                neighbors.push((x, y + 2));
+            }
+            neighbors.retain(|&(nx, ny)| builder[(nx, ny)] == Tile::Wall);
+
+            match neighbors.choose(rng) {
+                Some(&(nx, ny)) => {
+                    builder.open((x + nx) / 2, (y + ny) / 2).open(nx, ny);
+                    stack.push((nx, ny));
+                }
+                None => {
+                    stack.pop();
+                }
+            }
+        }
+
+        // a perfect maze has exactly one route between any two points, which makes for a
+        // boring tour; knock out some interior walls between open tiles to create loops.
+        // Opening a tile can never disconnect anything, so connectivity is preserved.
+        for _ in 0..(width * height) / 20 {
+            let x = rng.gen_range(1..width - 1);
+            let y = rng.gen_range(1..height - 1);
+            let joins_horizontal =
+                builder[(x - 1, y)] != Tile::Wall && builder[(x + 1, y)] != Tile::Wall;
+            let joins_vertical =
+                builder[(x, y - 1)] != Tile::Wall && builder[(x, y + 1)] != Tile::Wall;
+            if joins_horizontal || joins_vertical {
+                builder.open(x, y);
+            }
+        }
+
+        let open_tiles: Vec<_> = builder.open_tiles().collect();
+        if open_tiles.len() < pois as usize {
+            return Err(Error::NoRoomForPois {
+                pois,
+                open: open_tiles.len(),
+            });
+        }
+        for (poi, &(x, y)) in open_tiles.choose_multiple(rng, pois as usize).enumerate() {
+            builder.poi(x, y, poi as u8);
+        }
+
+        Ok(builder)
+    }
+
+    fn set(&mut self, x: usize, y: usize, tile: Tile) -> &mut Self {
+        assert!(x < self.width && y < self.height, "position out of bounds");
+        self.tiles[y * self.width + x] = tile;
+        self
+    }
+
+    /// Place a wall at the given position.
+    pub fn wall(&mut self, x: usize, y: usize) -> &mut Self {
+        self.set(x, y, Tile::Wall)
+    }
+
+    /// Clear the given position.
+    pub fn open(&mut self, x: usize, y: usize) -> &mut Self {
+        self.set(x, y, Tile::Empty)
+    }
+
+    /// Place a point of interest at the given position.
+    ///
+    /// Panics if `poi` is not a single digit.
+    pub fn poi(&mut self, x: usize, y: usize, poi: u8) -> &mut Self {
+        assert!(poi < 10, "POIs must be representable as a single digit");
+        self.set(x, y, Tile::Poi(poi))
+    }
+
+    fn open_tiles(&self) -> impl '_ + Iterator<Item = (usize, usize)> {
+        (0..self.height)
+            .flat_map(move |y| (0..self.width).map(move |x| (x, y)))
+            .filter(move |&position| self[position] == Tile::Empty)
+    }
+
+    /// Convert this maze into a map, exactly as if it had been read from an input file.
+    pub fn build(&self) -> Result<Map, Error> {
+        Map::try_from(self.to_string().as_bytes()).map_err(Into::into)
+    }
+}
+
+impl std::ops::Index<(usize, usize)> for MazeBuilder {
+    type Output = Tile;
+
+    fn index(&self, (x, y): (usize, usize)) -> &Self::Output {
+        &self.tiles[y * self.width + x]
+    }
+}
+
+impl fmt::Display for MazeBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.tiles.chunks(self.width) {
+            for tile in row {
+                write!(f, "{}", tile)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
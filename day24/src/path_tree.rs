@@ -0,0 +1,206 @@
+use crate::{poi_positions, Error, Map, Tile};
+use aoclib::geometry::{tile::DisplayWidth, Map as GenericMap, Point};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// A tile reached by a breadth-first search, and the tile it was reached from.
+///
+/// Positions are `(x, y)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TreeNode {
+    pub position: (i32, i32),
+    /// `None` only for the root.
+    pub parent: Option<(i32, i32)>,
+    /// Steps from the root.
+    pub distance: usize,
+}
+
+/// The shortest-path tree of a breadth-first search over the ducts: following parents from any
+/// reached tile walks a shortest path back to the root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathTree {
+    pub root: (i32, i32),
+    /// Every tile reachable from the root, in the order the search reached them.
+    pub nodes: Vec<TreeNode>,
+}
+
+fn to_pair(point: Point) -> (i32, i32) {
+    (point.x, point.y)
+}
+
+impl PathTree {
+    /// Search outwards from `root`.
+    fn search(map: &Map, root: Point) -> PathTree {
+        let mut nodes = vec![TreeNode {
+            position: to_pair(root),
+            parent: None,
+            distance: 0,
+        }];
+        let mut seen = HashSet::new();
+        seen.insert(root);
+        let mut queue = VecDeque::new();
+        queue.push_back((root, 0));
+        while let Some((point, distance)) = queue.pop_front() {
+            for neighbor in map.orthogonal_adjacencies(point) {
+                if map[neighbor] != Tile::Wall && seen.insert(neighbor) {
+                    nodes.push(TreeNode {
+                        position: to_pair(neighbor),
+                        parent: Some(to_pair(point)),
+                        distance: distance + 1,
+                    });
+                    queue.push_back((neighbor, distance + 1));
+                }
+            }
+        }
+        PathTree {
+            root: to_pair(root),
+            nodes,
+        }
+    }
+
+    /// The path from the root to `goal`, including both ends, or `None` if `goal` is
+    /// unreachable.
+    pub fn path_to(&self, goal: Point) -> Option<Vec<Point>> {
+        let parents: HashMap<_, _> = self
+            .nodes
+            .iter()
+            .map(|node| (node.position, node.parent))
+            .collect();
+        let mut path = vec![goal];
+        let mut parent = *parents.get(&to_pair(goal))?;
+        while let Some((x, y)) = parent {
+            path.push(Point::new(x, y));
+            parent = parents[&(x, y)];
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+/// The shortest-path tree from POI 0.
+pub fn shortest_path_tree(map: &Map) -> Result<PathTree, Error> {
+    let positions = poi_positions(map)?;
+    Ok(PathTree::search(map, positions[0]))
+}
+
+/// A shortest path between two POIs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Geodesic {
+    pub from: u8,
+    pub to: u8,
+    /// Every tile along the way, including both POIs.
+    pub path: Vec<Point>,
+}
+
+/// A shortest path between each pair of POIs, from the lower POI to the higher.
+///
+/// Where several paths are equally short, the one the breadth-first search finds first is used.
+/// Fails if any POI is unreachable from POI 0.
+pub fn geodesics(map: &Map) -> Result<Vec<Geodesic>, Error> {
+    let positions = poi_positions(map)?;
+    let mut geodesics = Vec::new();
+    for (a, &from) in positions.iter().enumerate() {
+        let tree = PathTree::search(map, from);
+        for (b, &to) in positions.iter().enumerate().skip(a + 1) {
+            let path = tree.path_to(to).ok_or_else(|| {
+                let unreachable = positions
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &poi)| tree.path_to(poi).is_none())
+                    .map(|(poi, _)| poi as u8)
+                    .collect();
+                Error::Unreachable(unreachable)
+            })?;
+            geodesics.push(Geodesic {
+                from: a as u8,
+                to: b as u8,
+                path,
+            });
+        }
+    }
+    Ok(geodesics)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, parse_display::Display)]
+enum Overlay {
+    #[display("{0}")]
+    Tile(Tile),
+    #[display("o")]
+    Geodesic,
+}
+
+impl DisplayWidth for Overlay {
+    const DISPLAY_WIDTH: usize = 1;
+}
+
+/// Render the map with every empty tile on a POI-to-POI geodesic marked `o`.
+///
+/// The optimal tour is made of these geodesics, so it only ever runs along marked tiles.
+pub fn render_geodesics(map: &Map) -> Result<String, Error> {
+    let on_geodesic: HashSet<Point> = geodesics(map)?
+        .into_iter()
+        .flat_map(|geodesic| geodesic.path)
+        .collect();
+    let overlay =
+        GenericMap::procedural(map.width(), map.height(), |position| match map[position] {
+            Tile::Empty if on_geodesic.contains(&position) => Overlay::Geodesic,
+            tile => Overlay::Tile(tile),
+        });
+    Ok(overlay.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MazeBuilder;
+
+    /// The example maze, with the bottom corridor blocked in the middle, and a dead end leading
+    /// south from the middle of the top corridor.
+    fn example() -> Map {
+        let mut builder = MazeBuilder::new(11, 5).unwrap();
+        builder
+            .poi(1, 1, 0)
+            .poi(3, 1, 1)
+            .poi(9, 1, 2)
+            .poi(9, 3, 3)
+            .poi(1, 3, 4);
+        for x in (2..9).filter(|&x| x != 5) {
+            builder.wall(x, 2);
+        }
+        builder.wall(5, 3).build().unwrap()
+    }
+
+    #[test]
+    fn test_shortest_path_tree() {
+        let map = example();
+        let positions = poi_positions(&map).unwrap();
+        let tree = shortest_path_tree(&map).unwrap();
+        assert_eq!(tree.root, to_pair(positions[0]));
+        // every open tile is reachable
+        assert_eq!(tree.nodes.len(), 20);
+
+        let path = tree.path_to(positions[3]).unwrap();
+        assert_eq!(path.first(), Some(&positions[0]));
+        assert_eq!(path.last(), Some(&positions[3]));
+        assert_eq!(path.len() - 1, 10);
+        let node = tree
+            .nodes
+            .iter()
+            .find(|node| node.position == to_pair(positions[3]))
+            .unwrap();
+        assert_eq!(node.distance, 10);
+
+        let json = serde_json::to_string(&tree).unwrap();
+        assert_eq!(serde_json::from_str::<PathTree>(&json).unwrap(), tree);
+    }
+
+    #[test]
+    fn test_render_geodesics() {
+        let map = example();
+        assert_eq!(geodesics(&map).unwrap().len(), 10);
+        assert_eq!(
+            render_geodesics(&map).unwrap(),
+            "###########\n#0o1ooooo2#\n#o###.###o#\n#4...#...3#\n###########\n"
+        );
+    }
+}
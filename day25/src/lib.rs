@@ -1,37 +1,621 @@
-use aoclib::parse;
-use assembunny::{Computer, Instruction, Register};
+use aoc2016_solver::{Answer, Progress, Registry, Silent, Solver};
+use assembunny::{Computer, Instruction, Integer, Register, Value};
 
-use std::path::Path;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, iter::successors, ops::Range, path::Path};
+
+#[cfg(feature = "parallelism")]
+use rayon::prelude::*;
 
 /// if this many values match, assume all of them will
 const LENGTH_ASSUMPTION: usize = 64;
 
-pub fn part1(input: &Path) -> Result<(), Error> {
-    let want_signal = std::array::IntoIter::new([0, 1]).cycle();
-    let program: Vec<Instruction> = parse(input)?.collect();
-    for a in 0_i32.. {
-        let mut computer = Computer::from_program(program.clone());
-        let (sender, receiver) = std::sync::mpsc::sync_channel(0);
+/// number of outputs recorded in a proof
+const PROOF_OUTPUTS: usize = 256;
+
+/// give up looking for a state cycle after this many instructions
+const MAX_PROOF_STEPS: u64 = 100_000_000;
+
+/// `true` when the program, run with `a` as its initial value in register `a`, produces a clock signal.
+///
+/// The program runs compiled in its own thread; it halts once the receiver has seen enough and is
+/// dropped. Fails if a register overflows first.
+fn produces_clock_signal(program: &[Instruction], a: Integer) -> Result<bool, Error> {
+    let want_signal = [0, 1].iter().copied().cycle();
+    let mut computer = Computer::from_program(program.to_vec());
+    let (sender, receiver) = std::sync::mpsc::sync_channel(0);
+    computer.set_sender(sender);
+    computer[Register::A] = a;
+
+    let handle = computer.launch();
+    let is_clock_signal = want_signal
+        .take(LENGTH_ASSUMPTION)
+        .eq(receiver.iter().take(LENGTH_ASSUMPTION));
+    drop(receiver);
+    handle
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))?;
+    Ok(is_clock_signal)
+}
+
+/// Find every initial value of `a` within `range` which produces a clock signal.
+///
+/// The results are in ascending order.
+#[cfg(feature = "parallelism")]
+pub fn verify_range(program: &[Instruction], range: Range<Integer>) -> Result<Vec<Integer>, Error> {
+    range
+        .into_par_iter()
+        .filter_map(|a| {
+            produces_clock_signal(program, a)
+                .map(|ok| ok.then_some(a))
+                .transpose()
+        })
+        .collect()
+}
+
+/// Find every initial value of `a` within `range` which produces a clock signal.
+///
+/// The results are in ascending order.
+#[cfg(not(feature = "parallelism"))]
+pub fn verify_range(program: &[Instruction], range: Range<Integer>) -> Result<Vec<Integer>, Error> {
+    range
+        .filter_map(|a| {
+            produces_clock_signal(program, a)
+                .map(|ok| ok.then_some(a))
+                .transpose()
+        })
+        .collect()
+}
+
+/// A repetition in the machine's state.
+///
+/// The machine state (registers, instruction pointer, and program) immediately after emitting
+/// output `start` is identical to its state immediately after emitting output `start + length`.
+/// The machine is deterministic, so from `start` onwards the outputs repeat with period `length`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+/// Register values of a machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Registers {
+    pub a: Integer,
+    pub b: Integer,
+    pub c: Integer,
+    pub d: Integer,
+}
+
+impl Registers {
+    /// The registers as the puzzle starts them: `a` as given, and the rest zero.
+    pub fn with_a(a: Integer) -> Registers {
+        Registers {
+            a,
+            b: 0,
+            c: 0,
+            d: 0,
+        }
+    }
+
+    fn load_into(&self, computer: &mut Computer) {
+        computer[Register::A] = self.a;
+        computer[Register::B] = self.b;
+        computer[Register::C] = self.c;
+        computer[Register::D] = self.d;
+    }
+}
+
+/// Evidence that some initial register values produce a clock signal forever.
+///
+/// Checking a proof requires running the program only until the cycle closes, rather than
+/// searching for the answer.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Proof {
+    pub initial_registers: Registers,
+    /// The first outputs of the program; at least enough to cover one repetition of the cycle.
+    pub outputs: Vec<Integer>,
+    pub cycle: Cycle,
+}
+
+impl Proof {
+    /// Run `program` from `initial_registers` until it has emitted at least `PROOF_OUTPUTS`
+    /// values and its state has repeated.
+    ///
+    /// Fails with `Error::EmptyProgram` if there is nothing to run.
+    pub fn generate(program: &[Instruction], initial_registers: Registers) -> Result<Proof, Error> {
+        if program.is_empty() {
+            return Err(Error::EmptyProgram);
+        }
+        let mut computer = Computer::from_program(program.to_vec());
+        let (sender, receiver) = std::sync::mpsc::sync_channel(1);
         computer.set_sender(sender);
-        computer[Register::A] = a;
+        initial_registers.load_into(&mut computer);
+
+        let mut outputs = Vec::new();
+        let mut seen = HashMap::new();
+        let mut cycle = None;
+        while outputs.len() < PROOF_OUTPUTS || cycle.is_none() {
+            if computer.instructions() >= MAX_PROOF_STEPS {
+                return Err(Error::NoCycle);
+            }
+            let running = computer.try_step()?;
+            if let Ok(output) = receiver.try_recv() {
+                outputs.push(output);
+                if cycle.is_none() {
+                    let state = (
+                        computer.registers(),
+                        computer.ip(),
+                        computer.program().to_vec(),
+                    );
+                    let idx = outputs.len() - 1;
+                    if let Some(&start) = seen.get(&state) {
+                        cycle = Some(Cycle {
+                            start,
+                            length: idx - start,
+                        });
+                        seen.clear();
+                    } else {
+                        seen.insert(state, idx);
+                    }
+                }
+            }
+            if !running {
+                return Err(Error::NoCycle);
+            }
+        }
+
+        Ok(Proof {
+            initial_registers,
+            outputs,
+            cycle: cycle.expect("loop exits only once a cycle is found"),
+        })
+    }
+
+    /// `true` when this proof's outputs form a clock signal which repeats forever.
+    ///
+    /// This only checks the proof's internal consistency; use `verify` to check it against a program.
+    pub fn is_clock_signal(&self) -> bool {
+        let Cycle { start, length } = self.cycle;
+        length > 0
+            && length % 2 == 0
+            && self.outputs.len() >= start + length
+            && self.outputs.iter().copied().eq([0, 1]
+                .iter()
+                .copied()
+                .cycle()
+                .take(self.outputs.len()))
+    }
+
+    /// Re-run `program` to check that this proof is accurate, and that it proves a clock signal.
+    pub fn verify(&self, program: &[Instruction]) -> Result<(), Error> {
+        let actual = Proof::generate(program, self.initial_registers)?;
+        if actual.cycle != self.cycle {
+            return Err(Error::InvalidProof(format!(
+                "expected cycle {:?}; found {:?}",
+                self.cycle, actual.cycle
+            )));
+        }
+        if !actual.outputs.starts_with(&self.outputs) || self.outputs.len() < PROOF_OUTPUTS {
+            return Err(Error::InvalidProof("outputs differ".into()));
+        }
+        if !self.is_clock_signal() {
+            return Err(Error::InvalidProof("outputs are not a clock signal".into()));
+        }
+        Ok(())
+    }
+}
+
+/// Everything in a puzzle input after the prologue: emit the bits of `d`, least significant first,
+/// then start over. Puzzle inputs differ only in their prologue's constants.
+const EMITTER: &str = "cpy d a
+jnz 0 0
+cpy a b
+cpy 0 a
+cpy 2 c
+jnz b 2
+jnz 1 6
+dec b
+dec c
+jnz c -4
+inc a
+jnz 1 -7
+cpy 2 b
+jnz c 2
+jnz 1 4
+dec b
+dec c
+jnz 1 -4
+jnz 0 0
+out b
+jnz a -19
+jnz 1 -21";
+
+/// The constant `C` which the program adds to `a` before emitting its bits, if it can be derived.
+///
+/// Puzzle inputs begin by computing `d = a + C1 * C2` in a pair of nested loops:
+///
+/// ```text
+/// cpy a d
+/// cpy C1 c
+/// cpy C2 b
+/// inc d
+/// dec b
+/// jnz b -2
+/// dec c
+/// jnz c -5
+/// ```
+///
+/// and then emit the bits of `d` forever. Programs which aren't exactly so aren't analyzed, so
+/// for any program which is, `a` produces a clock signal exactly when `a + C` alternates.
+pub fn clock_constant(program: &[Instruction]) -> Option<Integer> {
+    // take the constants from wherever they'd be, then check the whole prologue against them
+    let literal = |idx: usize| match program.get(idx)? {
+        Instruction::Copy(Value::Value(n), _) => Some(*n),
+        _ => None,
+    };
+    let (c1, c2) = (literal(1)?, literal(2)?);
+    if c1 <= 0 || c2 <= 0 {
+        // the loops only multiply when both counts are positive
+        return None;
+    }
+    let expected = assembunny::parse_instructions(&format!(
+        "cpy a d\ncpy {} c\ncpy {} b\ninc d\ndec b\njnz b -2\ndec c\njnz c -5\n{}",
+        c1, c2, EMITTER
+    ))
+    .ok()?;
+    if program != expected.as_slice() {
+        return None;
+    }
+    c1.checked_mul(c2)
+}
+
+/// `true` when the binary representation of `n` alternates `1010…10`.
+///
+/// The program emits the bits of `a + C` from least significant upwards, then starts over, so
+/// it produces a clock signal only when `a + C` alternates like this.
+pub fn alternates(n: Integer) -> bool {
+    let edges = n ^ (n >> 1);
+    n > 0 && n % 2 == 0 && edges & edges.wrapping_add(1) == 0
+}
 
-        computer.launch();
-        if want_signal
-            .clone()
-            .take(LENGTH_ASSUMPTION)
-            .eq(receiver.into_iter().take(LENGTH_ASSUMPTION))
-        {
-            println!("value in a producing clock signal: {}", a);
-            return Ok(());
+/// `true` when the program, run with `a` in register `a`, provably repeats a clock signal forever.
+fn proves_clock_signal(program: &[Instruction], a: Integer) -> Result<bool, Error> {
+    match Proof::generate(program, Registers::with_a(a)) {
+        Ok(proof) => Ok(proof.is_clock_signal()),
+        Err(Error::NoCycle) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+/// Find the answer, reporting each candidate to `progress` before trying it.
+///
+/// When `clock_constant` can analyze the program, only values of `a` for which `a + C`
+/// alternates are tried. The analysis recognizes the whole program, so no value skipped can
+/// work; the first candidate which does, proven by simulation, is the smallest. Otherwise, or if
+/// no candidate is proven, search upwards from 0.
+fn find_answer(program: &[Instruction], progress: &dyn Progress) -> Result<Integer, Error> {
+    // an empty program emits nothing for any value, so the search would never end
+    if program.is_empty() {
+        return Err(Error::EmptyProgram);
+    }
+    if let Some(constant) = clock_constant(program) {
+        // every alternating number, in ascending order: 0b10, 0b1010, 0b101010, ...
+        let candidates = successors(Some(2), |&n: &Integer| n.checked_mul(4)?.checked_add(2))
+            .map(|n| n - constant)
+            .filter(|&a| a >= 0);
+        for a in candidates {
+            progress.update(a as u64);
+            if produces_clock_signal(program, a)? {
+                // every smaller value is either a rejected candidate or excluded by the analysis
+                if !proves_clock_signal(program, a)? {
+                    break;
+                }
+                return Ok(a);
+            }
+        }
+    }
+
+    for a in 0.. {
+        progress.update(a as u64);
+        if produces_clock_signal(program, a)? {
+            return Ok(a);
         }
     }
     Err(Error::NoSolution)
 }
 
+/// The lowest initial value of register `a` which produces a clock signal.
+pub fn part1(input: &Path, progress: &dyn Progress) -> Result<Integer, Error> {
+    part1_from_str(&std::fs::read_to_string(input)?, progress)
+}
+
+/// The lowest initial value of register `a` which makes the program `input` produce a clock
+/// signal.
+pub fn part1_from_str(input: &str, progress: &dyn Progress) -> Result<Integer, Error> {
+    find_answer(&assembunny::parse_instructions(input)?, progress)
+}
+
+/// Find the answer, then run the program with it until it has emitted as many values as a proof
+/// records, listing the `top` most executed instructions.
+pub fn print_profile(input: &Path, top: usize) -> Result<(), Error> {
+    let program: Vec<Instruction> = assembunny::read_program(input)?;
+    let a = find_answer(&program, &Silent)?;
+    let mut computer = Computer::from_program(program);
+    let (sender, receiver) = std::sync::mpsc::sync_channel(1);
+    computer.set_sender(sender);
+    computer[Register::A] = a;
+
+    let mut outputs = 0;
+    while outputs < PROOF_OUTPUTS && computer.instructions() < MAX_PROOF_STEPS {
+        let running = computer.try_step()?;
+        if receiver.try_recv().is_ok() {
+            outputs += 1;
+        }
+        if !running {
+            break;
+        }
+    }
+
+    println!("profile of the first {} outputs with a = {}:", outputs, a);
+    print!("{}", computer.profile_report(top));
+    Ok(())
+}
+
+/// Find the answer and write a JSON proof of it to `proof_path`.
+pub fn write_proof(input: &Path, proof_path: &Path) -> Result<(), Error> {
+    let program: Vec<Instruction> = assembunny::read_program(input)?;
+    let a = find_answer(&program, &Silent)?;
+    let proof = Proof::generate(&program, Registers::with_a(a))?;
+    let writer = std::io::BufWriter::new(std::fs::File::create(proof_path)?);
+    serde_json::to_writer_pretty(writer, &proof)?;
+    println!(
+        "wrote proof for a = {} (cycle of {} outputs starting at output {}) to {}",
+        a,
+        proof.cycle.length,
+        proof.cycle.start,
+        proof_path.display()
+    );
+    Ok(())
+}
+
+/// Check the JSON proof at `proof_path` against the program.
+pub fn check_proof(input: &Path, proof_path: &Path) -> Result<(), Error> {
+    let program: Vec<Instruction> = assembunny::read_program(input)?;
+    let reader = std::io::BufReader::new(std::fs::File::open(proof_path)?);
+    let proof: Proof = serde_json::from_reader(reader)?;
+    proof.verify(&program)?;
+    println!(
+        "proof valid: a = {} produces a clock signal",
+        proof.initial_registers.a
+    );
+    Ok(())
+}
+
+/// Report every value in `0..limit` which produces a clock signal, not just the first.
+pub fn verify(input: &Path, limit: Integer) -> Result<(), Error> {
+    let program: Vec<Instruction> = assembunny::read_program(input)?;
+    let valid = verify_range(&program, 0..limit)?;
+    println!(
+        "values in a producing clock signal below {}: {:?}",
+        limit, valid
+    );
+    Ok(())
+}
+
+pub struct Solution;
+
+impl Solver for Solution {
+    fn part1(&self, input: &str) -> aoc2016_solver::Result<Answer> {
+        self.solve_reporting(1, input, &Silent)
+    }
+
+    fn has_part2(&self) -> bool {
+        false
+    }
+
+    fn solve_reporting(
+        &self,
+        part: u8,
+        input: &str,
+        progress: &dyn Progress,
+    ) -> aoc2016_solver::Result<Answer> {
+        match part {
+            1 => Ok(part1_from_str(input, progress)?.into()),
+            _ => self.solve(part, input),
+        }
+    }
+}
+
+pub fn register(registry: &mut Registry) {
+    registry.register(25, Solution);
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
     #[error(transparent)]
     Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Program(#[from] assembunny::Error),
     #[error("no solution found")]
     NoSolution,
+    #[error("the program is empty")]
+    EmptyProgram,
+    #[error("program halted or did not repeat its state")]
+    NoCycle,
+    #[error("invalid proof: {0}")]
+    InvalidProof(String),
+    #[error("serializing proof")]
+    Json(#[from] serde_json::Error),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Emit the bits of `a + 2`, least significant first, forever.
+    const PROGRAM: &str = "
+cpy a d
+inc d
+inc d
+cpy d a
+cpy a b
+cpy 0 a
+cpy 2 c
+jnz b 2
+jnz 1 6
+dec b
+dec c
+jnz c -4
+inc a
+jnz 1 -7
+cpy 2 b
+jnz c 2
+jnz 1 4
+dec b
+dec c
+jnz 1 -4
+out b
+jnz a -17
+jnz 1 -19
+";
+
+    fn program() -> Vec<Instruction> {
+        PROGRAM
+            .trim()
+            .lines()
+            .map(|line| line.parse().unwrap())
+            .collect()
+    }
+
+    /// The shape of the puzzle inputs, with a small constant `C = 4 * 3`.
+    const ANALYZABLE: &str = "
+cpy a d
+cpy 4 c
+cpy 3 b
+inc d
+dec b
+jnz b -2
+dec c
+jnz c -5
+cpy d a
+jnz 0 0
+cpy a b
+cpy 0 a
+cpy 2 c
+jnz b 2
+jnz 1 6
+dec b
+dec c
+jnz c -4
+inc a
+jnz 1 -7
+cpy 2 b
+jnz c 2
+jnz 1 4
+dec b
+dec c
+jnz 1 -4
+jnz 0 0
+out b
+jnz a -19
+jnz 1 -21
+";
+
+    #[test]
+    fn test_proof_roundtrip() {
+        let program = program();
+        let a = find_answer(&program, &Silent).unwrap();
+        let proof = Proof::generate(&program, Registers::with_a(a)).unwrap();
+        assert!(proof.is_clock_signal());
+        assert_eq!(proof.outputs.len(), PROOF_OUTPUTS);
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let proof: Proof = serde_json::from_str(&json).unwrap();
+        proof.verify(&program).unwrap();
+    }
+
+    #[test]
+    fn test_proof_rejects_wrong_answer() {
+        let program = program();
+        let a = find_answer(&program, &Silent).unwrap();
+        let proof = Proof::generate(&program, Registers::with_a(a + 1)).unwrap();
+        assert!(!proof.is_clock_signal());
+
+        let mut forged = proof.clone();
+        forged.outputs = [0, 1].iter().copied().cycle().take(PROOF_OUTPUTS).collect();
+        assert!(forged.verify(&program).is_err());
+    }
+
+    #[test]
+    fn test_alternates() {
+        let alternating: Vec<Integer> = (0..200).filter(|&n| alternates(n)).collect();
+        assert_eq!(alternating, vec![0b10, 0b1010, 0b101010, 0b10101010]);
+        assert!(!alternates(-2));
+        assert!(!alternates(Integer::MAX));
+    }
+
+    #[test]
+    fn test_clock_constant() {
+        let analyzable = assembunny::parse_instructions(ANALYZABLE).unwrap();
+        assert_eq!(clock_constant(&analyzable), Some(12));
+        assert_eq!(clock_constant(&program()), None);
+
+        let mut modified = analyzable.clone();
+        modified[5] = "jnz b -3".parse().unwrap();
+        assert_eq!(clock_constant(&modified), None);
+
+        let mut modified = analyzable.clone();
+        modified[27] = "out a".parse().unwrap();
+        assert_eq!(clock_constant(&modified), None);
+        assert_eq!(clock_constant(&analyzable[..analyzable.len() - 1]), None);
+    }
+
+    #[test]
+    fn test_restricted_search() {
+        let program = assembunny::parse_instructions(ANALYZABLE).unwrap();
+        // 0b101010 is the first alternating number no less than 12
+        let a = find_answer(&program, &Silent).unwrap();
+        assert_eq!(a, 0b101010 - 12);
+        assert_eq!(
+            (0..)
+                .find(|&a| produces_clock_signal(&program, a).unwrap())
+                .unwrap(),
+            a
+        );
+    }
+
+    #[test]
+    fn test_unrecognized_body_is_searched() {
+        // the prologue matches, but the program ignores it and always emits a clock signal
+        let program = assembunny::parse_instructions(
+            "cpy a d\ncpy 4 c\ncpy 3 b\ninc d\ndec b\njnz b -2\ndec c\njnz c -5\n\
+             out 0\nout 1\njnz 1 -2",
+        )
+        .unwrap();
+        assert_eq!(clock_constant(&program), None);
+        assert_eq!(find_answer(&program, &Silent).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_empty_program() {
+        assert!(matches!(
+            Proof::generate(&[], Registers::with_a(0)),
+            Err(Error::EmptyProgram)
+        ));
+        assert!(matches!(
+            part1_from_str("", &Silent),
+            Err(Error::EmptyProgram)
+        ));
+    }
+
+    #[test]
+    fn test_overflow_is_an_error() {
+        let program = assembunny::parse_instructions("inc a\nout a\njnz 1 -2").unwrap();
+        assert!(matches!(
+            produces_clock_signal(&program, Integer::MAX),
+            Err(Error::Program(assembunny::Error::Overflow { ip: 0, .. }))
+        ));
+        assert!(verify_range(&program, Integer::MAX - 1..Integer::MAX).is_err());
+    }
 }
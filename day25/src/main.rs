@@ -1,46 +1,66 @@
-use aoclib::{config::Config, website::get_input};
-use day25::part1;
+use aoc2016_solver::{Input, TerminalProgress};
+use assembunny::Integer;
+use day25::{check_proof, part1, print_profile, verify, write_proof};
 
 use color_eyre::eyre::Result;
 use std::path::PathBuf;
 use structopt::StructOpt;
 
-const YEAR: u32 = 2016;
 const DAY: u8 = 25;
 
 #[derive(StructOpt, Debug)]
 struct RunArgs {
-    /// input file
+    /// input file, or `-` for stdin
     #[structopt(long, parse(from_os_str))]
     input: Option<PathBuf>,
 
+    /// read input from stdin
+    #[structopt(long, conflicts_with = "input")]
+    stdin: bool,
+
+    /// report every value of a below this limit which produces a clock signal
+    #[structopt(long)]
+    verify_range: Option<Integer>,
+
+    /// write a JSON proof of the answer to this path
+    #[structopt(long, parse(from_os_str))]
+    proof: Option<PathBuf>,
+
+    /// check the JSON proof at this path against the input
+    #[structopt(long, parse(from_os_str))]
+    check_proof: Option<PathBuf>,
+
+    /// list this many of the most executed instructions while the answer produces its signal
+    #[structopt(long)]
+    hot: Option<usize>,
+
     /// skip part 1
     #[structopt(long)]
     no_part1: bool,
 }
 
-impl RunArgs {
-    fn input(&self) -> Result<PathBuf> {
-        match self.input {
-            None => {
-                let config = Config::load()?;
-                // this does nothing if the input file already exists, but
-                // simplifies the workflow after cloning the repo on a new computer
-                get_input(&config, YEAR, DAY)?;
-                Ok(config.input_for(YEAR, DAY))
-            }
-            Some(ref path) => Ok(path.clone()),
-        }
-    }
-}
-
 fn main() -> Result<()> {
     color_eyre::install()?;
     let args = RunArgs::from_args();
-    let input_path = args.input()?;
+    let input_path = Input::for_day(DAY, args.stdin, args.input.as_deref())?;
 
     if !args.no_part1 {
-        part1(&input_path)?;
+        let progress = TerminalProgress::new("values of a tried");
+        let a = part1(&input_path, &progress)?;
+        progress.finish();
+        println!("value in a producing clock signal: {}", a);
+    }
+    if let Some(limit) = args.verify_range {
+        verify(&input_path, limit)?;
+    }
+    if let Some(ref proof_path) = args.proof {
+        write_proof(&input_path, proof_path)?;
+    }
+    if let Some(ref proof_path) = args.check_proof {
+        check_proof(&input_path, proof_path)?;
+    }
+    if let Some(top) = args.hot {
+        print_profile(&input_path, top)?;
     }
     Ok(())
 }
@@ -0,0 +1,39 @@
+/* C bindings for the Advent of Code 2016 solutions; see ffi/src/lib.rs. */
+#ifndef AOC2016_H
+#define AOC2016_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define AOC2016_OK 0
+#define AOC2016_ERR_NULL_INPUT 1
+#define AOC2016_ERR_INVALID_UTF8 2
+#define AOC2016_ERR_NO_SUCH_DAY 3
+#define AOC2016_ERR_NO_SUCH_PART 4
+#define AOC2016_ERR_SOLVER 5
+#define AOC2016_ERR_ANSWER_NUL 6
+#define AOC2016_ERR_PANIC 7
+
+/* Solve `part` of `day` for the `len` bytes of UTF-8 text at `input`.
+ * Returns the answer, to be released with aoc2016_free_string, or NULL on failure. */
+char *aoc2016_solve(uint8_t day, uint8_t part, const uint8_t *input, size_t len);
+
+/* The AOC2016_ERR_* code for the last call on this thread, or AOC2016_OK. */
+int aoc2016_last_error(void);
+
+/* Why the last call on this thread failed, to be released with aoc2016_free_string,
+ * or NULL if it succeeded. */
+char *aoc2016_last_error_message(void);
+
+/* Release a string returned by this library. NULL is ignored. */
+void aoc2016_free_string(char *s);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* AOC2016_H */
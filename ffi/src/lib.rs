@@ -0,0 +1,246 @@
+//! C bindings for every day's solutions, so that they can be embedded in other languages'
+//! benchmark harnesses.
+//!
+//! Build with `cargo build --release -p aoc2016-ffi`, and declare the functions with
+//! `include/aoc2016.h`. Every string returned by this library is owned by the caller, who must
+//! release it with `aoc2016_free_string`.
+//!
+//! When a call fails, it returns a null pointer; `aoc2016_last_error` then gives one of the
+//! `AOC2016_ERR_*` codes, and `aoc2016_last_error_message` describes what went wrong. Both
+//! describe the most recent call on the calling thread.
+
+use aoc2016_solver::Registry;
+use std::{
+    any::Any,
+    cell::RefCell,
+    ffi::CString,
+    os::raw::{c_char, c_int},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+};
+
+/// The last call succeeded.
+pub const AOC2016_OK: c_int = 0;
+/// The input pointer was null, but its length was not zero.
+pub const AOC2016_ERR_NULL_INPUT: c_int = 1;
+/// The input was not valid UTF-8.
+pub const AOC2016_ERR_INVALID_UTF8: c_int = 2;
+/// There is no such day.
+pub const AOC2016_ERR_NO_SUCH_DAY: c_int = 3;
+/// The day has no such part.
+pub const AOC2016_ERR_NO_SUCH_PART: c_int = 4;
+/// The solver failed on this input.
+pub const AOC2016_ERR_SOLVER: c_int = 5;
+/// The answer contained a NUL byte, so cannot be returned as a C string.
+pub const AOC2016_ERR_ANSWER_NUL: c_int = 6;
+/// The call panicked, most likely in the solver.
+pub const AOC2016_ERR_PANIC: c_int = 7;
+
+lazy_static::lazy_static! {
+    static ref REGISTRY: Registry = aoc2016::run::registry();
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<Error>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("input pointer is null")]
+    NullInput,
+    #[error("input is not valid UTF-8")]
+    InvalidUtf8(#[from] std::str::Utf8Error),
+    #[error("no such day: {0}")]
+    NoSuchDay(u8),
+    #[error("day {day} has no part {part}")]
+    NoSuchPart { day: u8, part: u8 },
+    #[error("day {day} part {part}: {message}")]
+    Solver { day: u8, part: u8, message: String },
+    #[error("answer contains a NUL byte")]
+    AnswerNul(#[from] std::ffi::NulError),
+    #[error("panicked: {0}")]
+    Panic(String),
+}
+
+impl Error {
+    /// The `AOC2016_ERR_*` code for this error.
+    pub fn code(&self) -> c_int {
+        match self {
+            Error::NullInput => AOC2016_ERR_NULL_INPUT,
+            Error::InvalidUtf8(_) => AOC2016_ERR_INVALID_UTF8,
+            Error::NoSuchDay(_) => AOC2016_ERR_NO_SUCH_DAY,
+            Error::NoSuchPart { .. } => AOC2016_ERR_NO_SUCH_PART,
+            Error::Solver { .. } => AOC2016_ERR_SOLVER,
+            Error::AnswerNul(_) => AOC2016_ERR_ANSWER_NUL,
+            Error::Panic(_) => AOC2016_ERR_PANIC,
+        }
+    }
+}
+
+/// Solve `part` of `day` for `input`, rendering the answer as the runner displays it.
+///
+/// Panics if the solver does; `aoc2016_solve` catches that.
+pub fn solve(day: u8, part: u8, input: &[u8]) -> Result<CString, Error> {
+    let input = std::str::from_utf8(input)?;
+    let solver = REGISTRY.get(day).ok_or(Error::NoSuchDay(day))?;
+    if !solver.parts().contains(&part) {
+        return Err(Error::NoSuchPart { day, part });
+    }
+    let answer = solver.solve(part, input).map_err(|err| Error::Solver {
+        day,
+        part,
+        message: err.to_string(),
+    })?;
+    Ok(CString::new(answer.to_string())?)
+}
+
+/// The message a panic was raised with, if it has one.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_default()
+}
+
+/// Solve `part` of `day` for the `len` bytes of UTF-8 text at `input`.
+///
+/// Returns the answer as a NUL-terminated string, which the caller must release with
+/// `aoc2016_free_string`, or null if solving failed. Multi-line answers have one line per
+/// line of input.
+///
+/// A panic anywhere in the call, including while loading the solvers, is caught rather than
+/// unwinding into the caller, and reported as `AOC2016_ERR_PANIC`.
+///
+/// # Safety
+///
+/// `input` must point to `len` readable bytes, or may be null if `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn aoc2016_solve(
+    day: u8,
+    part: u8,
+    input: *const u8,
+    len: usize,
+) -> *mut c_char {
+    catch_unwind(AssertUnwindSafe(|| {
+        let input = if len == 0 {
+            Ok(&[][..])
+        } else if input.is_null() {
+            Err(Error::NullInput)
+        } else {
+            Ok(std::slice::from_raw_parts(input, len))
+        };
+        let result = input.and_then(|input| solve(day, part, input));
+        match result {
+            Ok(answer) => {
+                LAST_ERROR.with(|last| *last.borrow_mut() = None);
+                answer.into_raw()
+            }
+            Err(err) => {
+                LAST_ERROR.with(|last| *last.borrow_mut() = Some(err));
+                ptr::null_mut()
+            }
+        }
+    }))
+    .unwrap_or_else(|payload| {
+        let err = Error::Panic(panic_message(&*payload));
+        // `try_with`, as the panic may have come from the thread-local being torn down
+        let _ = LAST_ERROR.try_with(|last| *last.borrow_mut() = Some(err));
+        ptr::null_mut()
+    })
+}
+
+/// The `AOC2016_ERR_*` code describing why the last call on this thread failed, or
+/// `AOC2016_OK` if it succeeded.
+#[no_mangle]
+pub extern "C" fn aoc2016_last_error() -> c_int {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(AOC2016_OK, Error::code))
+}
+
+/// A description of why the last call on this thread failed, or null if it succeeded.
+///
+/// The caller must release the description with `aoc2016_free_string`.
+#[no_mangle]
+pub extern "C" fn aoc2016_last_error_message() -> *mut c_char {
+    LAST_ERROR.with(|last| match last.borrow().as_ref() {
+        // error messages never contain NUL bytes, so this never returns null for an error
+        Some(err) => CString::new(err.to_string()).map_or(ptr::null_mut(), CString::into_raw),
+        None => ptr::null_mut(),
+    })
+}
+
+/// Release a string returned by this library. Does nothing if `s` is null.
+///
+/// # Safety
+///
+/// `s` must be null or have been returned by this library, and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn aoc2016_free_string(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    /// Call `aoc2016_solve` as a C caller would, taking ownership of what it returns.
+    fn call(day: u8, part: u8, input: &str) -> Result<String, (c_int, String)> {
+        unsafe {
+            let answer = aoc2016_solve(day, part, input.as_ptr(), input.len());
+            if answer.is_null() {
+                let message = aoc2016_last_error_message();
+                let result = Err((
+                    aoc2016_last_error(),
+                    CStr::from_ptr(message).to_string_lossy().into_owned(),
+                ));
+                aoc2016_free_string(message);
+                return result;
+            }
+            let result = CStr::from_ptr(answer).to_string_lossy().into_owned();
+            aoc2016_free_string(answer);
+            Ok(result)
+        }
+    }
+
+    #[test]
+    fn test_solve() {
+        assert_eq!(call(1, 1, "R5, L5, R5, R3"), Ok("12".into()));
+        assert_eq!(aoc2016_last_error(), AOC2016_OK);
+        assert!(aoc2016_last_error_message().is_null());
+    }
+
+    #[test]
+    fn test_error_codes() {
+        assert_eq!(call(26, 1, "").unwrap_err().0, AOC2016_ERR_NO_SUCH_DAY);
+        assert_eq!(call(25, 2, "").unwrap_err().0, AOC2016_ERR_NO_SUCH_PART);
+        // no door out of the first room is open for this passcode
+        let (code, message) = call(17, 1, "hijkl").unwrap_err();
+        assert_eq!(code, AOC2016_ERR_SOLVER);
+        assert!(message.starts_with("day 17 part 1: "), "{}", message);
+
+        let invalid = [0xff, 0xfe];
+        let answer = unsafe { aoc2016_solve(1, 1, invalid.as_ptr(), invalid.len()) };
+        assert!(answer.is_null());
+        assert_eq!(aoc2016_last_error(), AOC2016_ERR_INVALID_UTF8);
+
+        let answer = unsafe { aoc2016_solve(1, 1, ptr::null(), 3) };
+        assert!(answer.is_null());
+        assert_eq!(aoc2016_last_error(), AOC2016_ERR_NULL_INPUT);
+    }
+
+    #[test]
+    fn test_panic_message() {
+        let payload = catch_unwind(|| panic!("at the {}", "disco")).unwrap_err();
+        assert_eq!(panic_message(&*payload), "at the disco");
+        let payload = catch_unwind(|| std::panic::panic_any(7)).unwrap_err();
+        assert_eq!(panic_message(&*payload), "");
+    }
+
+    #[test]
+    fn test_free_null() {
+        unsafe { aoc2016_free_string(ptr::null_mut()) };
+    }
+}
@@ -0,0 +1,15 @@
+//! Malformed or enormous markers must be errors, and both ways of counting the improved format
+//! must agree.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let _ = day09::decompress(input);
+    let sequential = day09::count_decompressed_v2(&mut input.chars());
+    let segmented = day09::count_decompressed_v2_segmented(input);
+    match (sequential, segmented) {
+        (Ok(sequential), Ok(segmented)) => assert_eq!(sequential, segmented),
+        (Err(_), Err(_)) => {}
+        (sequential, segmented) => panic!("{:?} != {:?}", sequential, segmented),
+    }
+});
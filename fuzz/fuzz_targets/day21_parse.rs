@@ -0,0 +1,8 @@
+//! Arbitrary text must either fail to parse or scramble without panicking.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let _ = day21::part1_from_str(input);
+    let _ = day21::part2_from_str(input);
+});
@@ -0,0 +1,18 @@
+//! Rendering operations in the puzzle's format and parsing them again must restore them.
+#![no_main]
+use day21::{parse_operations, to_lines, Operation};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|operations: Vec<Operation>| {
+    // the puzzle's letters are never whitespace or punctuation
+    let letters_ok = operations.iter().all(|operation| match *operation {
+        Operation::SwapLetter(a, b) => a.is_alphanumeric() && b.is_alphanumeric(),
+        Operation::RotateOn(c) => c.is_alphanumeric(),
+        _ => true,
+    });
+    if !letters_ok {
+        return;
+    }
+    let lines = to_lines(&operations);
+    assert_eq!(parse_operations(&lines).unwrap(), operations, "{}", lines);
+});
@@ -0,0 +1,10 @@
+//! Arbitrary `df` output must either fail to parse or describe a complete grid.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(grid) = day22::read_grid_from_reader(data) {
+        assert!(grid.width() > 0 && grid.height() > 0);
+        let _ = day22::part1_from_reader(data);
+    }
+});
@@ -0,0 +1,60 @@
+use crate::{runner::Run, visual};
+use std::collections::HashMap;
+
+pub const DAYS: u8 = 25;
+
+/// Dashboard state.
+#[derive(Default)]
+pub struct App {
+    /// Index of the selected day, from 0.
+    selected: usize,
+    runs: HashMap<u8, Run>,
+    /// The most recent visualization: the day, and either its rendering or an error message.
+    visual: Option<(u8, Result<String, String>)>,
+}
+
+impl App {
+    pub fn selected_day(&self) -> u8 {
+        self.selected as u8 + 1
+    }
+
+    pub fn selected(&self) -> usize {
+        self.selected
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.checked_sub(1).unwrap_or(DAYS as usize - 1);
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % DAYS as usize;
+    }
+
+    pub fn run(&self, day: u8) -> Option<&Run> {
+        self.runs.get(&day)
+    }
+
+    pub fn visual(&self) -> Option<&(u8, Result<String, String>)> {
+        self.visual.as_ref()
+    }
+
+    /// Start running the selected day, unless it is already running.
+    pub fn run_selected(&mut self) {
+        let day = self.selected_day();
+        if !matches!(self.runs.get(&day), Some(run) if run.is_running()) {
+            self.runs.insert(day, Run::start(day));
+        }
+    }
+
+    pub fn visualize_selected(&mut self) {
+        let day = self.selected_day();
+        self.visual = Some((day, visual::render(day).map_err(|err| err.to_string())));
+    }
+
+    /// Collect the output of every running day.
+    pub fn poll_runs(&mut self) {
+        for run in self.runs.values_mut() {
+            run.poll();
+        }
+    }
+}
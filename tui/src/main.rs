@@ -0,0 +1,80 @@
+//! Terminal dashboard for running and visualizing the days of the workspace.
+//!
+//! Select a day with the arrow keys, then press `enter` to run it or `v` to visualize it.
+//! Press `q` to quit. Days run in-process through their `Solver`, so the slow ones can show
+//! their progress as they go.
+
+use color_eyre::eyre::Result;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{backend::CrosstermBackend, Terminal};
+use std::{io::stdout, thread, time::Duration};
+
+mod app;
+mod runner;
+mod ui;
+mod visual;
+
+use app::App;
+
+/// How often to redraw while nothing is happening, so that elapsed times stay current.
+const TICK: Duration = Duration::from_millis(100);
+
+/// Keep panics from writing over the dashboard.
+///
+/// Runs catch their solvers' panics and show the messages in their output, so panics on other
+/// threads are silent. A panic on the main thread restores the terminal, then is reported as
+/// usual.
+fn install_panic_hook() {
+    let report = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if thread::current().name() == Some("main") {
+            let _ = disable_raw_mode();
+            let _ = execute!(stdout(), LeaveAlternateScreen);
+            report(info);
+        }
+    }));
+}
+
+fn main() -> Result<()> {
+    color_eyre::install()?;
+    install_panic_hook();
+
+    enable_raw_mode()?;
+    execute!(stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+    let result = run(&mut terminal);
+
+    disable_raw_mode()?;
+    execute!(stdout(), LeaveAlternateScreen)?;
+    result
+}
+
+fn run(terminal: &mut Terminal<CrosstermBackend<std::io::Stdout>>) -> Result<()> {
+    let mut app = App::default();
+    loop {
+        app.poll_runs();
+        terminal.draw(|frame| ui::draw(frame, &app))?;
+
+        if !event::poll(TICK)? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()? {
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Up | KeyCode::Char('k') => app.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => app.select_next(),
+                KeyCode::Enter | KeyCode::Char('r') => app.run_selected(),
+                KeyCode::Char('v') => app.visualize_selected(),
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,197 @@
+//! Run days in-process on background threads, collecting their answers and progress as they go.
+
+use aoc2016::run::{read_input, registry};
+use aoc2016_solver::Progress;
+use std::{
+    any::Any,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{channel, Receiver, Sender, TryRecvError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+enum Message {
+    Line(String),
+    Exited(bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Running,
+    Succeeded(Duration),
+    Failed(Duration),
+}
+
+/// The latest progress reported by a running part, shared with the dashboard.
+#[derive(Debug, Default)]
+struct SharedProgress {
+    position: AtomicU64,
+    /// 0 when the total is unknown.
+    total: AtomicU64,
+}
+
+impl SharedProgress {
+    fn reset(&self) {
+        self.position.store(0, Ordering::Relaxed);
+        self.total.store(0, Ordering::Relaxed);
+    }
+}
+
+impl Progress for SharedProgress {
+    fn set_total(&self, total: u64) {
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    fn update(&self, position: u64) {
+        self.position.store(position, Ordering::Relaxed);
+    }
+}
+
+/// A single run of a day, with its output so far.
+pub struct Run {
+    started: Instant,
+    status: Status,
+    lines: Vec<String>,
+    receiver: Receiver<Message>,
+    progress: Arc<SharedProgress>,
+}
+
+/// Solve each part of `day` against its configured input, sending a line per answer.
+///
+/// Returns `false` if anything failed.
+fn solve(day: u8, progress: &SharedProgress, sender: &Sender<Message>) -> bool {
+    let line = |line: String| {
+        // the dashboard only stops listening when it exits
+        let _ = sender.send(Message::Line(line));
+    };
+    let registry = registry();
+    let solver = match registry.get(day) {
+        Some(solver) => solver,
+        None => {
+            line(format!("no such day: {}", day));
+            return false;
+        }
+    };
+    let input = match read_input(day, None) {
+        Ok(input) => input,
+        Err(err) => {
+            line(format!("reading input: {}", err));
+            return false;
+        }
+    };
+
+    let mut success = true;
+    for &part in solver.parts() {
+        progress.reset();
+        let started = Instant::now();
+        match solver.solve_reporting(part, &input, progress) {
+            Ok(answer) => {
+                line(format!("part {} ({:.3?}):", part, started.elapsed()));
+                for answer_line in answer.to_string().lines() {
+                    line(format!("  {}", answer_line));
+                }
+            }
+            Err(err) => {
+                line(format!("part {} failed: {}", part, err));
+                success = false;
+            }
+        }
+    }
+    success
+}
+
+/// The message a panic was raised with, if it has one.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_default()
+}
+
+impl Run {
+    /// Run every part of `day` on a background thread.
+    ///
+    /// A panic fails the run, with its message as the run's last line.
+    pub fn start(day: u8) -> Run {
+        let (sender, receiver) = channel();
+        let progress = Arc::new(SharedProgress::default());
+        {
+            let progress = progress.clone();
+            thread::spawn(move || {
+                let success = catch_unwind(AssertUnwindSafe(|| solve(day, &progress, &sender)))
+                    .unwrap_or_else(|payload| {
+                        let message = format!("panicked: {}", panic_message(&*payload));
+                        let _ = sender.send(Message::Line(message));
+                        false
+                    });
+                let _ = sender.send(Message::Exited(success));
+            });
+        }
+
+        Run {
+            started: Instant::now(),
+            status: Status::Running,
+            lines: Vec::new(),
+            receiver,
+            progress,
+        }
+    }
+
+    /// Collect any new output without blocking.
+    ///
+    /// A run whose thread went away without saying how it exited has failed.
+    pub fn poll(&mut self) {
+        loop {
+            match self.receiver.try_recv() {
+                Ok(Message::Line(line)) => self.lines.push(line),
+                Ok(Message::Exited(success)) => {
+                    let elapsed = self.started.elapsed();
+                    self.status = if success {
+                        Status::Succeeded(elapsed)
+                    } else {
+                        Status::Failed(elapsed)
+                    };
+                }
+                Err(TryRecvError::Disconnected) if self.is_running() => {
+                    self.status = Status::Failed(self.started.elapsed());
+                }
+                Err(_) => break,
+            }
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.status == Status::Running
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Time since the run started, or its total duration once it has finished.
+    pub fn elapsed(&self) -> Duration {
+        match self.status {
+            Status::Running => self.started.elapsed(),
+            Status::Succeeded(elapsed) | Status::Failed(elapsed) => elapsed,
+        }
+    }
+
+    /// The progress most recently reported by the running part, and its total if known.
+    ///
+    /// `None` while nothing has been reported, which is always the case for fast days.
+    pub fn progress(&self) -> Option<(u64, Option<u64>)> {
+        let position = self.progress.position.load(Ordering::Relaxed);
+        let total = self.progress.total.load(Ordering::Relaxed);
+        (self.is_running() && (position > 0 || total > 0))
+            .then(|| (position, (total > 0).then_some(total)))
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+}
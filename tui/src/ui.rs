@@ -0,0 +1,121 @@
+use crate::{
+    app::{App, DAYS},
+    runner::Status,
+    visual::VISUAL_DAYS,
+};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+const SPINNER: &[char] = &['|', '/', '-', '\\'];
+
+fn status_marker(app: &App, day: u8) -> (String, Style) {
+    let run = match app.run(day) {
+        Some(run) => run,
+        None => return (String::new(), Style::default()),
+    };
+    match run.status() {
+        Status::Running => {
+            let elapsed = run.elapsed();
+            let spin = SPINNER[(elapsed.as_millis() / 100) as usize % SPINNER.len()];
+            let progress = match run.progress() {
+                Some((position, Some(total))) => format!(" {}/{}", position, total),
+                Some((position, None)) => format!(" {}", position),
+                None => String::new(),
+            };
+            (
+                format!("{} {:>4}s{}", spin, elapsed.as_secs(), progress),
+                Style::default().fg(Color::Yellow),
+            )
+        }
+        Status::Succeeded(elapsed) => (
+            format!("ok {:.1}s", elapsed.as_secs_f64()),
+            Style::default().fg(Color::Green),
+        ),
+        Status::Failed(elapsed) => (
+            format!("err {:.1}s", elapsed.as_secs_f64()),
+            Style::default().fg(Color::Red),
+        ),
+    }
+}
+
+fn draw_days(frame: &mut Frame, app: &App, area: Rect) {
+    let items: Vec<_> = (1..=DAYS)
+        .map(|day| {
+            let (marker, style) = status_marker(app, day);
+            let visual = if VISUAL_DAYS.contains(&day) { '*' } else { ' ' };
+            ListItem::new(format!("day{:02}{} {}", day, visual, marker)).style(style)
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("days (* visual)"),
+        )
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+    let mut state = ListState::default();
+    state.select(Some(app.selected()));
+    frame.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_output(frame: &mut Frame, app: &App, area: Rect) {
+    let day = app.selected_day();
+    let lines: Vec<Line> = match app.run(day) {
+        Some(run) => run
+            .lines()
+            .iter()
+            .map(|line| Line::from(line.as_str()))
+            .collect(),
+        None => vec![Line::from("press enter to run")],
+    };
+    // keep the most recent output in view
+    let visible = area.height.saturating_sub(2) as usize;
+    let scroll = lines.len().saturating_sub(visible) as u16;
+    let output = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("day{:02} output", day)),
+        )
+        .scroll((scroll, 0));
+    frame.render_widget(output, area);
+}
+
+fn draw_visual(frame: &mut Frame, day: u8, rendering: &Result<String, String>, area: Rect) {
+    let text = match rendering {
+        Ok(rendering) => rendering.as_str(),
+        Err(err) => err.as_str(),
+    };
+    let visual = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("day{:02} visual", day)),
+    );
+    frame.render_widget(visual, area);
+}
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        // wide enough for a running day's progress
+        .constraints([Constraint::Length(34), Constraint::Min(0)])
+        .split(frame.size());
+    draw_days(frame, app, columns[0]);
+
+    match app.visual() {
+        Some((day, rendering)) => {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .split(columns[1]);
+            draw_output(frame, app, rows[0]);
+            draw_visual(frame, *day, rendering, rows[1]);
+        }
+        None => draw_output(frame, app, columns[1]),
+    }
+}
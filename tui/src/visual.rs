@@ -0,0 +1,34 @@
+//! Renderings of the days whose state is worth looking at.
+
+use aoc2016::run::{input_path, read_input};
+use color_eyre::eyre::{eyre, Result};
+
+/// Days which `render` supports.
+pub const VISUAL_DAYS: &[u8] = &[8, 13, 18, 22];
+
+/// Number of trap rows to render for day 18.
+const TRAP_ROWS: usize = 40;
+
+/// Edge length of the office to render for day 13; large enough to contain the part 1 goal.
+const OFFICE_EDGE: usize = 45;
+
+fn first_line(day: u8) -> Result<String> {
+    read_input(day, None)?
+        .lines()
+        .next()
+        .map(|line| line.trim().to_owned())
+        .ok_or_else(|| eyre!("input for day {} is empty", day))
+}
+
+/// Render the state of `day` for its input.
+pub fn render(day: u8) -> Result<String> {
+    Ok(match day {
+        8 => day08::render_screen(&input_path(day, None)?, day08::Dialect::Standard)?,
+        13 => day13::Office::new(first_line(day)?.parse()?)
+            .render_path(OFFICE_EDGE, day13::PART1_GOAL)
+            .ok_or_else(|| eyre!("no path to the goal"))?,
+        18 => day18::render_rows(&first_line(day)?, TRAP_ROWS)?.join("\n"),
+        22 => day22::render_cluster(&input_path(day, None)?)?,
+        _ => return Err(eyre!("day {} has no visualization", day)),
+    })
+}
@@ -0,0 +1,129 @@
+//! Browser bindings for a few of the more visual solvers.
+//!
+//! Build with `wasm-pack build --target web wasm-demo`. Each exported function takes plain
+//! text or numbers and returns the rendering as text, so the page only needs to put it in a
+//! `<pre>` element. Errors are returned to javascript as strings.
+//!
+//! The day crates are built without their `fs` feature, so nothing here can reach the file
+//! system, and none of the solvers used here spawn threads.
+
+use aoclib::geometry::Point;
+use day13::Office;
+use wasm_bindgen::prelude::*;
+
+/// Run a screen program on a `width` by `height` screen and render the result.
+pub fn render_screen(
+    program: &str,
+    width: usize,
+    height: usize,
+    extended: bool,
+) -> Result<String, Error> {
+    let dialect = if extended {
+        day08::Dialect::Extended
+    } else {
+        day08::Dialect::Standard
+    };
+    let mut screen = day08::Screen::new(width, height);
+    for instruction in day08::parse_instructions(program, dialect)? {
+        screen.apply(instruction);
+    }
+    Ok(screen.to_string())
+}
+
+/// Render the top-left `edge` by `edge` corner of the office, marking the shortest path from
+/// the initial position to `(goal_x, goal_y)` with `O`.
+///
+/// Walls are `#` and open space is `.`. As in the puzzle, `y` increases downwards.
+pub fn render_maze(
+    favorite_number: i32,
+    edge: usize,
+    goal_x: i32,
+    goal_y: i32,
+) -> Result<String, Error> {
+    Office::new(favorite_number)
+        .render_path(edge, Point::new(goal_x, goal_y))
+        .ok_or(Error::NoPath(goal_x, goal_y))
+}
+
+/// Render the first `rows` rows of the trap room whose first row is `first_row`.
+pub fn render_traps(first_row: &str, rows: usize) -> Result<String, Error> {
+    // the solver assumes at least two tiles, which real inputs always have
+    if first_row.len() < 2 {
+        return Err(Error::RowTooShort);
+    }
+    let mut out = day18::render_rows(first_row, rows)?.join("\n");
+    out.push('\n');
+    Ok(out)
+}
+
+fn to_js(err: Error) -> JsValue {
+    JsValue::from_str(&err.to_string())
+}
+
+#[wasm_bindgen]
+pub fn screen(
+    program: &str,
+    width: usize,
+    height: usize,
+    extended: bool,
+) -> Result<String, JsValue> {
+    render_screen(program, width, height, extended).map_err(to_js)
+}
+
+#[wasm_bindgen]
+pub fn maze(
+    favorite_number: i32,
+    edge: usize,
+    goal_x: i32,
+    goal_y: i32,
+) -> Result<String, JsValue> {
+    render_maze(favorite_number, edge, goal_x, goal_y).map_err(to_js)
+}
+
+#[wasm_bindgen]
+pub fn traps(first_row: &str, rows: usize) -> Result<String, JsValue> {
+    render_traps(first_row, rows).map_err(to_js)
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Screen(#[from] day08::Error),
+    #[error(transparent)]
+    Traps(#[from] day18::Error),
+    #[error("no path to ({0}, {1})")]
+    NoPath(i32, i32),
+    #[error("a row needs at least two tiles")]
+    RowTooShort,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screen() {
+        let program =
+            "rect 3x2\nrotate column x=1 by 1\nrotate row y=0 by 4\nrotate column x=1 by 1\n";
+        let rendering = render_screen(program, 7, 3, false).unwrap();
+        assert_eq!(rendering.lines().count(), 3);
+        assert_eq!(rendering.chars().filter(|&ch| ch == '#').count(), 6);
+        assert!(render_screen("clear 1x1", 7, 3, false).is_err());
+    }
+
+    #[test]
+    fn test_maze() {
+        // example from the puzzle: the shortest path to (7, 4) takes 11 steps
+        let rendering = render_maze(10, 10, 7, 4).unwrap();
+        assert_eq!(rendering.chars().filter(|&ch| ch == 'O').count(), 12);
+        assert!(rendering
+            .replace('O', ".")
+            .starts_with(".#.####.##\n..#..#...#\n#....##...\n"));
+    }
+
+    #[test]
+    fn test_traps() {
+        assert_eq!(render_traps("..^^.", 3).unwrap(), "..^^.\n.^^^^\n^^..^\n");
+        assert!(matches!(render_traps("^", 3), Err(Error::RowTooShort)));
+    }
+}